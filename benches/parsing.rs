@@ -0,0 +1,129 @@
+#[path = "../tests/mock_data.rs"]
+mod mock_data;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use mock_data::{generate_ethernet_mock_packets, DEFAULT_ETHER_TYPE, DEFAULT_Q_TAG};
+use net_sift::parsers::{
+    definitions::{DeepParser, LayeredData, LayeredDataRef},
+    ethernet_frame::EthernetFrame,
+    view::scan_ethernet,
+};
+
+use std::hint::black_box;
+
+const FRAME_COUNT: usize = 4096;
+
+fn generate_frames() -> Vec<Vec<u8>> {
+    (0..FRAME_COUNT)
+        .map(|i| match i % 2 {
+            0 => generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE),
+            _ => generate_ethernet_mock_packets(Some(DEFAULT_Q_TAG), DEFAULT_ETHER_TYPE),
+        })
+        .collect()
+}
+
+fn bench_parsing(c: &mut Criterion) {
+    let frames = generate_frames();
+
+    let mut group = c.benchmark_group("ethernet_frames");
+    group.throughput(Throughput::Elements(FRAME_COUNT as u64));
+
+    group.bench_function("shallow_parse", |b| {
+        b.iter(|| {
+            for frame in &frames {
+                black_box(EthernetFrame::from_bytes(black_box(frame), true).unwrap());
+            }
+        })
+    });
+
+    // Shares the payload with the capture buffer instead of copying it, leaving only the
+    // header allocations of a read-only parse.
+    #[cfg(feature = "bytes")]
+    {
+        let buffers: Vec<_> = frames.iter().cloned().map(bytes::Bytes::from).collect();
+        group.bench_function("shallow_parse_shared", |b| {
+            b.iter(|| {
+                for buf in &buffers {
+                    black_box(EthernetFrame::from_bytes_buf(black_box(buf), true).unwrap());
+                }
+            })
+        });
+    }
+
+    group.bench_function("deep_parse", |b| {
+        b.iter(|| {
+            for frame in &frames {
+                let frame = EthernetFrame::from_bytes(black_box(frame), true).unwrap();
+                black_box(frame.parse_next_layer().unwrap());
+            }
+        })
+    });
+
+    // Copies every payload out of the frame, as a parse kept past the capture buffer does.
+    group.bench_function("deep_parse_owned", |b| {
+        b.iter(|| {
+            for frame in &frames {
+                let frame = EthernetFrame::from_bytes(black_box(frame), true).unwrap();
+                black_box(frame.parse_next_layer().unwrap().into_owned());
+            }
+        })
+    });
+
+    // Shares every layer's payload with the capture buffer, down to the TCP payload.
+    #[cfg(feature = "bytes")]
+    {
+        let buffers: Vec<_> = frames.iter().cloned().map(bytes::Bytes::from).collect();
+        group.bench_function("deep_parse_shared", |b| {
+            b.iter(|| {
+                for buf in &buffers {
+                    let frame = EthernetFrame::from_bytes_buf(black_box(buf), true).unwrap();
+                    black_box(frame.parse_next_layer().unwrap());
+                }
+            })
+        });
+    }
+
+    // Walks the same layers borrowed from the frame, without allocating.
+    group.bench_function("deep_parse_borrowed", |b| {
+        b.iter(|| {
+            for frame in &frames {
+                let frame = EthernetFrame::from_bytes_ref(black_box(frame), true).unwrap();
+                for layer in LayeredDataRef::EthernetFrameData(frame).layers() {
+                    black_box(layer.unwrap());
+                }
+            }
+        })
+    });
+
+    // Counting SYNs is the kind of scan the allocation-free view is meant for.
+    group.bench_function("count_syn_full_parse", |b| {
+        b.iter(|| {
+            frames
+                .iter()
+                .filter(|frame| {
+                    let frame = EthernetFrame::from_bytes(black_box(frame), true).unwrap();
+                    frame.parse_next_layer().unwrap().layers().any(|layer| {
+                        matches!(layer, LayeredData::TcpData(segment) if segment.header.flags.syn)
+                    })
+                })
+                .count()
+        })
+    });
+
+    group.bench_function("count_syn_scan", |b| {
+        b.iter(|| {
+            frames
+                .iter()
+                .filter(|frame| {
+                    let view = scan_ethernet(black_box(frame)).unwrap();
+                    view.tcp_flags().is_some_and(|flags| flags.syn)
+                })
+                .count()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_parsing);
+criterion_main!(benches);
@@ -0,0 +1,97 @@
+// Flattens a parsed layer stack into the handful of fields most analyses look at, so
+// callers can read addresses, ports and lengths without matching on every layer.
+
+use super::{
+    definitions::{IPType, LayeredData},
+    ethernet_frame::MacAddress,
+};
+
+use core::net::IpAddr;
+
+/// The link, network and transport addresses of a packet, along with its payload length.
+///
+/// The addresses and protocol are those of the outermost IPv4 or IPv6 packet, so the inner
+/// packet of a tunnel is not summarized. The MAC addresses come from the outermost
+/// Ethernet frame, if the stack has one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PacketSummary {
+    pub source_mac: Option<MacAddress>,
+    pub destination_mac: Option<MacAddress>,
+    pub source_ip: IpAddr,
+    pub destination_ip: IpAddr,
+
+    /// The protocol carried by the IP packet, after any IPv6 extension headers.
+    pub protocol: IPType,
+
+    /// The ports of a TCP segment or UDP datagram directly beneath the IP packet.
+    pub source_port: Option<u16>,
+    pub destination_port: Option<u16>,
+
+    /// The number of bytes carried above the transport header for TCP and UDP, and of the
+    /// whole IP payload for any other protocol.
+    pub payload_len: usize,
+}
+
+impl PacketSummary {
+    /// Summarizes the outermost IP packet within `layered_data`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(PacketSummary)` - The summary of the outermost IPv4 or IPv6 packet.
+    /// * `None` - If `layered_data` contains no parsed IP packet.
+    pub fn from_layered(layered_data: &LayeredData) -> Option<Self> {
+        let (source_ip, destination_ip, protocol, payload) =
+            layered_data.layers().find_map(|layer| match layer {
+                LayeredData::Ipv4Data(packet) => Some((
+                    IpAddr::V4(packet.header.source_address),
+                    IpAddr::V4(packet.header.destination_address),
+                    packet.header.protocol.clone(),
+                    &*packet.data,
+                )),
+                LayeredData::Ipv6Data(packet) => Some((
+                    IpAddr::V6(packet.header.source_address),
+                    IpAddr::V6(packet.header.destination_address),
+                    packet.header.upper_layer_protocol(),
+                    &*packet.data,
+                )),
+                _ => None,
+            })?;
+
+        let (source_mac, destination_mac) = layered_data
+            .layers()
+            .find_map(|layer| match layer {
+                LayeredData::EthernetFrameData(frame) => Some((
+                    Some(frame.header.mac_source.clone()),
+                    Some(frame.header.mac_destination.clone()),
+                )),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        let (ports, payload_len) = match payload {
+            LayeredData::TcpData(segment) => (
+                Some((segment.header.source_port, segment.header.destination_port)),
+                segment.payload_len(),
+            ),
+            LayeredData::UdpData(datagram) => (
+                Some((
+                    datagram.header.source_port,
+                    datagram.header.destination_port,
+                )),
+                datagram.payload_len(),
+            ),
+            other => (None, other.byte_len()),
+        };
+
+        Some(PacketSummary {
+            source_mac,
+            destination_mac,
+            source_ip,
+            destination_ip,
+            protocol,
+            source_port: ports.map(|(source, _)| source),
+            destination_port: ports.map(|(_, destination)| destination),
+            payload_len,
+        })
+    }
+}
@@ -0,0 +1,226 @@
+// ARP Packet:
+//  0                   1                   2                   3
+//  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |         Hardware Type         |         Protocol Type         |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |  Hardware Len |  Protocol Len |           Operation           |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |           Sender Hardware Address (Hardware Len bytes)        |
+// |           Sender Protocol Address (Protocol Len bytes)        |
+// |           Target Hardware Address (Hardware Len bytes)        |
+// |           Target Protocol Address (Protocol Len bytes)        |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//
+// RARP (RFC 903) shares this layout, under its own EtherType and with opcodes 3 and 4.
+
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
+use super::{errors::ParserError, utils::ByteReader};
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{net::Ipv4Addr, ops::Range};
+
+const FIXED_HEADER_SIZE: usize = 8;
+const IPV4_PROTOCOL_TYPE: u16 = 0x0800;
+
+/// The operation an ARP or RARP packet performs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ArpOperation {
+    Request,        // ARP request, asking for the hardware address of a protocol address
+    Reply,          // ARP reply
+    RequestReverse, // RARP request, asking for the protocol address of a hardware address
+    ReplyReverse,   // RARP reply
+    Other(u16),     // Catch-all for other opcodes
+}
+
+impl From<u16> for ArpOperation {
+    fn from(raw: u16) -> Self {
+        match raw {
+            1 => Self::Request,
+            2 => Self::Reply,
+            3 => Self::RequestReverse,
+            4 => Self::ReplyReverse,
+            other => Self::Other(other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ArpPacket {
+    /// The link-layer protocol, e.g. 1 for Ethernet.
+    pub hardware_type: u16,
+
+    /// The EtherType of the protocol being resolved, e.g. 0x0800 for IPv4.
+    pub protocol_type: u16,
+
+    /// The length, in bytes, of each hardware address.
+    pub hardware_len: u8,
+
+    /// The length, in bytes, of each protocol address.
+    pub protocol_len: u8,
+
+    /// The raw opcode. See `opcode` for its meaning.
+    pub operation: u16,
+
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub sender_hardware_address: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub sender_protocol_address: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub target_hardware_address: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub target_protocol_address: Vec<u8>,
+
+    /// The position of the packet within the bytes it was parsed from. Trailing bytes,
+    /// e.g. Ethernet padding, are not included.
+    pub span: Range<usize>,
+}
+
+impl ArpPacket {
+    /// Constructs an `ArpPacket` from a slice of bytes holding an ARP or RARP packet.
+    ///
+    /// The address lengths are taken from the packet, so hardware and protocol types
+    /// other than Ethernet and IPv4 are supported. Bytes after the target protocol
+    /// address are ignored.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::InvalidLength` - If the slice is shorter than the 8 bytes of fixed
+    ///   fields plus the `2 * (hardware_len + protocol_len)` bytes of addresses they declare.
+    pub fn from_bytes(packets: &[u8]) -> Result<Self, ParserError> {
+        if packets.len() < FIXED_HEADER_SIZE {
+            return Err(ParserError::InvalidLength("ARP packet".to_string()));
+        }
+
+        let mut cursor = ByteReader::new(packets);
+
+        let hardware_type = cursor.read_u16_be("ARP_Hardware_Type")?;
+        let protocol_type = cursor.read_u16_be("ARP_Protocol_Type")?;
+        let hardware_len = cursor.read_u8("ARP_Hardware_Len")?;
+        let protocol_len = cursor.read_u8("ARP_Protocol_Len")?;
+        let operation = cursor.read_u16_be("ARP_Operation")?;
+
+        let (hardware_size, protocol_size) = (hardware_len as usize, protocol_len as usize);
+        let need = FIXED_HEADER_SIZE + 2 * (hardware_size + protocol_size);
+        if packets.len() < need {
+            return Err(ParserError::InvalidLength("ARP packet".to_string()));
+        }
+
+        let sender_hardware_address = cursor
+            .read_slice(hardware_size, "ARP_Sender_Hardware_Address")?
+            .to_vec();
+        let sender_protocol_address = cursor
+            .read_slice(protocol_size, "ARP_Sender_Protocol_Address")?
+            .to_vec();
+        let target_hardware_address = cursor
+            .read_slice(hardware_size, "ARP_Target_Hardware_Address")?
+            .to_vec();
+        let target_protocol_address = cursor
+            .read_slice(protocol_size, "ARP_Target_Protocol_Address")?
+            .to_vec();
+
+        Ok(ArpPacket {
+            hardware_type,
+            protocol_type,
+            hardware_len,
+            protocol_len,
+            operation,
+            sender_hardware_address,
+            sender_protocol_address,
+            target_hardware_address,
+            target_protocol_address,
+            span: 0..cursor.position(),
+        })
+    }
+
+    /// Serializes the packet back into its wire format. Trailing bytes ignored by
+    /// `from_bytes`, such as Ethernet padding, are not included.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.span.len());
+        bytes.extend_from_slice(&self.hardware_type.to_be_bytes());
+        bytes.extend_from_slice(&self.protocol_type.to_be_bytes());
+        bytes.push(self.hardware_len);
+        bytes.push(self.protocol_len);
+        bytes.extend_from_slice(&self.operation.to_be_bytes());
+        bytes.extend_from_slice(&self.sender_hardware_address);
+        bytes.extend_from_slice(&self.sender_protocol_address);
+        bytes.extend_from_slice(&self.target_hardware_address);
+        bytes.extend_from_slice(&self.target_protocol_address);
+        bytes
+    }
+
+    /// Returns the operation the packet performs.
+    pub fn opcode(&self) -> ArpOperation {
+        ArpOperation::from(self.operation)
+    }
+
+    /// Returns `true` for the RARP opcodes, Request Reverse and Reply Reverse.
+    pub fn is_reverse(&self) -> bool {
+        matches!(
+            self.opcode(),
+            ArpOperation::RequestReverse | ArpOperation::ReplyReverse
+        )
+    }
+
+    /// Returns the sender's protocol address if it is an IPv4 address.
+    pub fn sender_ipv4(&self) -> Option<Ipv4Addr> {
+        self.ipv4(&self.sender_protocol_address)
+    }
+
+    /// Returns the target's protocol address if it is an IPv4 address.
+    pub fn target_ipv4(&self) -> Option<Ipv4Addr> {
+        self.ipv4(&self.target_protocol_address)
+    }
+
+    /// Returns a short, tcpdump-style description of the packet, e.g.
+    /// `ARP who-has 192.168.1.20 tell 192.168.1.1`.
+    pub fn describe(&self) -> String {
+        let hardware = |address: &[u8]| {
+            address
+                .iter()
+                .map(|byte| format!("{byte:02X}"))
+                .collect::<Vec<_>>()
+                .join(":")
+        };
+        let protocol = |address: &[u8], ipv4: Option<Ipv4Addr>| match ipv4 {
+            Some(ipv4) => ipv4.to_string(),
+            None => hardware(address),
+        };
+        let sender_protocol = protocol(&self.sender_protocol_address, self.sender_ipv4());
+        let target_protocol = protocol(&self.target_protocol_address, self.target_ipv4());
+
+        match self.opcode() {
+            ArpOperation::Request => {
+                format!("ARP who-has {target_protocol} tell {sender_protocol}")
+            }
+            ArpOperation::Reply => format!(
+                "ARP {sender_protocol} is-at {}",
+                hardware(&self.sender_hardware_address)
+            ),
+            ArpOperation::RequestReverse => format!(
+                "RARP who-is {} tell {}",
+                hardware(&self.target_hardware_address),
+                hardware(&self.sender_hardware_address)
+            ),
+            ArpOperation::ReplyReverse => format!(
+                "RARP {} at {target_protocol}",
+                hardware(&self.target_hardware_address)
+            ),
+            ArpOperation::Other(operation) => format!("ARP opcode {operation}"),
+        }
+    }
+
+    fn ipv4(&self, address: &[u8]) -> Option<Ipv4Addr> {
+        if self.protocol_type != IPV4_PROTOCOL_TYPE {
+            return None;
+        }
+        <[u8; 4]>::try_from(address).ok().map(Ipv4Addr::from)
+    }
+}
@@ -0,0 +1,95 @@
+//! The one's-complement arithmetic behind the Internet checksum (RFC 1071), shared by the
+//! IPv4 header, TCP, UDP and ICMP checksums.
+//!
+//! Sums are accumulated in a `u32` and folded into 16 bits at the end, so partial sums,
+//! such as a pseudo-header and the segment it covers, can simply be added together.
+
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+/// Computes the one's-complement sum of `data` taken as big-endian 16-bit words (RFC 1071).
+///
+/// A trailing odd byte is padded with zero. The result is folded into 16 bits but not
+/// complemented, so sums of consecutive even-length slices can be combined with
+/// `fold_sum`. The checksum written into a header is the complement of the sum.
+///
+/// # Example
+///
+/// ```
+/// use net_sift::parsers::checksum::ones_complement_sum;
+///
+/// // The example from RFC 1071, section 3.
+/// let data = [0x00, 0x01, 0xF2, 0x03, 0xF4, 0xF5, 0xF6, 0xF7];
+/// assert_eq!(ones_complement_sum(&data), 0xDDF2);
+/// assert_eq!(!ones_complement_sum(&data), 0x220D);
+/// ```
+pub fn ones_complement_sum(data: &[u8]) -> u16 {
+    let mut sum: u32 = 0;
+
+    let mut words = data.chunks_exact(2);
+    for word in &mut words {
+        // Folding as we go keeps the accumulator from overflowing on large inputs.
+        sum = fold_carry(sum + u16::from_be_bytes([word[0], word[1]]) as u32);
+    }
+    if let [tail] = words.remainder() {
+        sum = fold_carry(sum + u16::from_be_bytes([*tail, 0]) as u32);
+    }
+
+    sum as u16
+}
+
+/// Folds the carries of a 32-bit partial sum back into the lower 16 bits, producing a
+/// 16-bit one's-complement sum.
+pub fn fold_sum(sum: u32) -> u16 {
+    let mut sum = sum;
+    while sum >> 16 != 0 {
+        sum = fold_carry(sum);
+    }
+    sum as u16
+}
+
+/// Sums the IPv4 pseudo-header covered by TCP and UDP checksums (RFC 793, RFC 768): the
+/// addresses, a zero byte, the protocol number and the length of the segment.
+///
+/// The result is an unfolded partial sum, to be added to the sum of the segment.
+pub fn pseudo_header_sum_v4(
+    source: Ipv4Addr,
+    destination: Ipv4Addr,
+    protocol: u8,
+    length: u16,
+) -> u32 {
+    address_sum(&source.octets())
+        + address_sum(&destination.octets())
+        + protocol as u32
+        + length as u32
+}
+
+/// Sums the IPv6 pseudo-header covered by upper-layer checksums (RFC 8200, section 8.1):
+/// the addresses, the 32-bit upper-layer packet length and the next header value.
+///
+/// The result is an unfolded partial sum, to be added to the sum of the segment.
+pub fn pseudo_header_sum_v6(
+    source: Ipv6Addr,
+    destination: Ipv6Addr,
+    next_header: u8,
+    length: u32,
+) -> u32 {
+    address_sum(&source.octets())
+        + address_sum(&destination.octets())
+        + (length >> 16)
+        + (length & 0xFFFF)
+        + next_header as u32
+}
+
+/// Adds the carry out of the lower 16 bits back in, once.
+fn fold_carry(sum: u32) -> u32 {
+    (sum & 0xFFFF) + (sum >> 16)
+}
+
+/// Sums the 16-bit words of an address without folding. At most eight words are summed,
+/// so the result cannot overflow.
+fn address_sum(octets: &[u8]) -> u32 {
+    octets
+        .chunks_exact(2)
+        .map(|word| u16::from_be_bytes([word[0], word[1]]) as u32)
+        .sum()
+}
@@ -1,8 +1,64 @@
-pub const ACCEPTED_ETHERTYPES: [[u8; 2]; 3] = [
+/// The EtherTypes net-sift decodes, or at least names. `EthernetFrame::from_bytes` accepts
+/// others too, leaving their payload raw.
+pub const ACCEPTED_ETHERTYPES: [[u8; 2]; 10] = [
     [8, 0],     // IPv4
     [134, 221], // IPv6
     [8, 6],     // ARP
+    [128, 53],  // RARP
+    [8, 66],    // Wake-on-LAN
+    [136, 204], // LLDP
+    [136, 71],  // MPLS unicast
+    [136, 72],  // MPLS multicast
+    [136, 99],  // PPPoE discovery
+    [136, 100], // PPPoE session
                 // ... Add others as needed
 ];
 
+/// The minimum size of an Ethernet frame on the wire, including its FCS (IEEE 802.3,
+/// clause 4.4.2). `EthernetFrame::from_bytes` does not enforce it, as captured frames are
+/// commonly shorter once their padding and FCS are stripped; it only requires the header.
 pub const MIN_FRAME_SIZE: usize = 64;
+
+pub const DNS_PORT: u16 = 53;
+pub const DHCP_SERVER_PORT: u16 = 67;
+pub const DHCP_CLIENT_PORT: u16 = 68;
+pub const HTTP_PORT: u16 = 80;
+pub const NTP_PORT: u16 = 123;
+pub const SNMP_PORT: u16 = 161;
+pub const SNMP_TRAP_PORT: u16 = 162;
+pub const HTTPS_PORT: u16 = 443;
+pub const GTP_U_PORT: u16 = 2152;
+pub const VXLAN_PORT: u16 = 4789;
+pub const SIP_PORT: u16 = 5060;
+/// The alternative HTTP port, commonly used by proxies and development servers. Not in
+/// `WELL_KNOWN_PORTS`, but WebSocket frames are looked for on it like on ports 80 and 443.
+pub const HTTP_ALT_PORT: u16 = 8080;
+
+/// The ports on which `TcpSegment` looks for WebSocket frames, those HTTP connections are
+/// upgraded from.
+pub const WEBSOCKET_PORTS: [u16; 3] = [HTTP_PORT, HTTPS_PORT, HTTP_ALT_PORT];
+
+/// Well-known TCP and UDP ports and the services conventionally found on them. The
+/// application-layer dissectors of `TcpSegment` and `UdpDatagram` dispatch on these ports.
+pub const WELL_KNOWN_PORTS: [(u16, &str); 11] = [
+    (DNS_PORT, "DNS"),
+    (DHCP_SERVER_PORT, "DHCP"),
+    (DHCP_CLIENT_PORT, "DHCP"),
+    (HTTP_PORT, "HTTP"),
+    (NTP_PORT, "NTP"),
+    (SNMP_PORT, "SNMP"),
+    (SNMP_TRAP_PORT, "SNMP"),
+    (HTTPS_PORT, "HTTPS"),
+    (GTP_U_PORT, "GTP-U"),
+    (VXLAN_PORT, "VXLAN"),
+    (SIP_PORT, "SIP"),
+];
+
+/// Returns the name of the service conventionally found on `port`, or `None` if the port
+/// is not in `WELL_KNOWN_PORTS`.
+pub fn service_name(port: u16) -> Option<&'static str> {
+    WELL_KNOWN_PORTS
+        .iter()
+        .find(|(known, _)| *known == port)
+        .map(|(_, name)| *name)
+}
@@ -1,36 +1,139 @@
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
 use super::{
-    errors::ParserError, ethernet_frame::EthernetFrame, icmp::IcmpPacket, ipv4::Ipv4Packet,
-    ipv6::Ipv6Packet, tcp::TcpSegment, udp::UdpDatagram,
+    arp::ArpPacket,
+    dns::{DnsMessage, DnsTcpMessage},
+    errors::ParserError,
+    ethernet_frame::{EthernetFrame, EthernetFrameRef},
+    gtp::GtpPacket,
+    icmp::IcmpPacket,
+    icmpv6::Icmpv6Packet,
+    ipv4::{Ipv4Packet, Ipv4PacketRef},
+    ipv6::{Ipv6Packet, Ipv6PacketRef},
+    lldp::LldpPacket,
+    mpls::MplsPacket,
+    pppoe::PppoePacket,
+    registry::{CustomLayer, ParserRegistry},
+    sip::SipMessage,
+    sll::SllPacket,
+    snmp::SnmpMessage,
+    tcp::{TcpSegment, TcpSegmentRef},
+    trace,
+    udp::{UdpDatagram, UdpDatagramRef},
+    utils::hexdump,
+    vxlan::VxlanPacket,
+    websocket::WebSocketFrame,
 };
 
+#[cfg(feature = "std")]
+use alloc::sync::Arc;
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use core::{fmt, time::Duration};
+use core::{net::IpAddr, ops::Range};
+
 /// Represents the various types of Internet Protocol (IP) that might be encountered.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum IPType {
     TCP,       // Transmission Control Protocol
     UDP,       // User Datagram Protocol
     ICMP,      // Internet Control Message Protocol
+    ICMPv6,    // Internet Control Message Protocol for IPv6
+    IPv4,      // IPv4 encapsulated in IP (IP-in-IP, RFC 2003)
+    IPv6,      // IPv6 encapsulated in IP (6in4, RFC 4213)
     Other(u8), // Placeholder for other types not explicitly handled
 }
 
+// Enables conversion back into the raw protocol number, used when serializing headers.
+impl From<&IPType> for u8 {
+    fn from(ip_type: &IPType) -> u8 {
+        match ip_type {
+            IPType::ICMP => 1,
+            IPType::IPv4 => 4,
+            IPType::TCP => 6,
+            IPType::UDP => 17,
+            IPType::IPv6 => 41,
+            IPType::ICMPv6 => 58,
+            IPType::Other(byte) => *byte,
+        }
+    }
+}
+
 // Enables conversion from a byte, making it easier to handle values from raw data.
 impl From<u8> for IPType {
     fn from(byte: u8) -> IPType {
         match byte {
             1 => IPType::ICMP,
+            4 => IPType::IPv4,
             6 => IPType::TCP,
             17 => IPType::UDP,
+            41 => IPType::IPv6,
+            58 => IPType::ICMPv6,
             _ => IPType::Other(byte), // Any other type is still preserved.
         }
     }
 }
 
+/// The Explicit Congestion Notification codepoint (RFC 3168), carried in the low 2 bits
+/// of the IPv4 Type of Service and IPv6 Traffic Class fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ecn {
+    NotEct, // Not ECN-Capable Transport
+    Ect1,   // ECN-Capable Transport, codepoint ECT(1)
+    Ect0,   // ECN-Capable Transport, codepoint ECT(0)
+    Ce,     // Congestion Experienced
+}
+
+// Decodes the low 2 bits of a Type of Service or Traffic Class byte.
+impl From<u8> for Ecn {
+    fn from(byte: u8) -> Ecn {
+        match byte & 0b11 {
+            0b00 => Ecn::NotEct,
+            0b01 => Ecn::Ect1,
+            0b10 => Ecn::Ect0,
+            _ => Ecn::Ce,
+        }
+    }
+}
+
 /// Defines the types of protocols expected in the Ethernet frame's EtherType field.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum EtherType {
-    IPv4,       // Internet Protocol version 4
-    IPv6,       // Internet Protocol version 6
-    ARP,        // Address Resolution Protocol
-    Other(u16), // Catch-all for other EtherTypes
+    IPv4,           // Internet Protocol version 4
+    IPv6,           // Internet Protocol version 6
+    ARP,            // Address Resolution Protocol
+    RARP,           // Reverse Address Resolution Protocol
+    LLDP,           // Link Layer Discovery Protocol
+    MPLSUnicast,    // Multiprotocol Label Switching, unicast
+    MPLSMulticast,  // Multiprotocol Label Switching, multicast
+    PPPoEDiscovery, // PPP over Ethernet, discovery stage
+    PPPoESession,   // PPP over Ethernet, session stage
+    Other(u16),     // Catch-all for other EtherTypes
+}
+
+// Enables conversion back into the raw EtherType, used when serializing headers.
+impl From<&EtherType> for u16 {
+    fn from(ether_type: &EtherType) -> u16 {
+        match ether_type {
+            EtherType::IPv4 => 0x0800,
+            EtherType::IPv6 => 0x86DD,
+            EtherType::ARP => 0x0806,
+            EtherType::RARP => 0x8035,
+            EtherType::LLDP => 0x88CC,
+            EtherType::MPLSUnicast => 0x8847,
+            EtherType::MPLSMulticast => 0x8848,
+            EtherType::PPPoEDiscovery => 0x8863,
+            EtherType::PPPoESession => 0x8864,
+            EtherType::Other(raw) => *raw,
+        }
+    }
 }
 
 // Simplifies the creation of `EtherType` instances from raw numerical values.
@@ -40,31 +143,878 @@ impl From<u16> for EtherType {
             0x0800 => Self::IPv4,
             0x86DD => Self::IPv6,
             0x0806 => Self::ARP,
+            0x8035 => Self::RARP,
+            0x88CC => Self::LLDP,
+            0x8847 => Self::MPLSUnicast,
+            0x8848 => Self::MPLSMulticast,
+            0x8863 => Self::PPPoEDiscovery,
+            0x8864 => Self::PPPoESession,
             other => Self::Other(other), // Other values are still retained.
         }
     }
 }
 
+/// Identifies the protocol of a parsed layer, for classification without matching on
+/// the full `LayeredData`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LayerKind {
+    Ethernet,
+    Arp,
+    Rarp,
+    Lldp,
+    Mpls,
+    Pppoe,
+    Sll,
+    Ipv4,
+    Ipv6,
+    Tcp,
+    Udp,
+    Icmp,
+    Icmpv6,
+    Vxlan,
+    Gtp,
+    Snmp,
+    Sip,
+    Dns,
+    WebSocket,
+    Custom,
+}
+
+/// The number of layers a deep parse may descend through when no limit is given.
+pub const DEFAULT_MAX_DEPTH: usize = 16;
+
+/// Options that control how far, and how, a packet is deep-parsed.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// The maximum number of layers parsed from a single packet, counting the outermost.
+    /// Bounds the recursion on crafted packets that nest encapsulations many times over.
+    pub max_depth: usize,
+
+    /// Custom parsers offered TCP and UDP payloads before the built-in dissectors.
+    pub parsers: ParserRegistry,
+
+    /// Rejects packets with anomalies that are normally ignored, such as a wrong IP
+    /// version or nonzero reserved bits, with `ParserError::ProtocolViolation`.
+    pub strict: bool,
+
+    /// Leaves a nested layer that fails to parse as raw payload, rather than failing the
+    /// packet as a whole. Errors in the layer parsing starts from are still returned.
+    pub best_effort: bool,
+
+    /// With `best_effort`, replaces the payload of a nested layer that fails to parse with
+    /// `LayeredData::Unparsed`, recording why it failed alongside its bytes.
+    pub annotate_failures: bool,
+
+    /// Verifies IPv4 header checksums and the checksums of TCP segments and UDP datagrams
+    /// within IP packets, failing with `ParserError::ChecksumMismatch` if one is wrong.
+    pub verify_checksums: bool,
+
+    /// Accepts IP packets, TCP segments and UDP datagrams whose declared length runs past
+    /// the bytes available, as in captures taken with a short snaplen. Their payloads are
+    /// cut short at the end of the data and they are marked `truncated`, rather than failing
+    /// with `ParserError::InconsistentLength`. Checksums of truncated layers are not verified.
+    pub allow_truncated: bool,
+
+    /// Called with the time taken to decode each nested layer, excluding the layers nested
+    /// within it. Layers are timed with `std::time::Instant`, and only while a hook is set.
+    #[cfg(feature = "std")]
+    pub on_layer: Option<LayerHook>,
+}
+
+/// A callback receiving the kind of each nested layer parsed and the time it took to
+/// decode, e.g. to find which protocols dominate parse time on a large capture.
+///
+/// Cloning a hook is cheap, as the callback is shared rather than copied.
+///
+/// # Example
+///
+/// ```
+/// use net_sift::parsers::definitions::{LayerHook, ParseOptions};
+///
+/// let options = ParseOptions {
+///     on_layer: Some(LayerHook::new(|kind, elapsed| println!("{kind:?}: {elapsed:?}"))),
+///     ..Default::default()
+/// };
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct LayerHook(Arc<dyn Fn(LayerKind, Duration) + Send + Sync>);
+
+#[cfg(feature = "std")]
+impl LayerHook {
+    pub fn new<F>(hook: F) -> Self
+    where
+        F: Fn(LayerKind, Duration) + Send + Sync + 'static,
+    {
+        LayerHook(Arc::new(hook))
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Debug for LayerHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LayerHook").finish_non_exhaustive()
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            max_depth: DEFAULT_MAX_DEPTH,
+            parsers: ParserRegistry::new(),
+            strict: false,
+            best_effort: false,
+            annotate_failures: false,
+            verify_checksums: false,
+            allow_truncated: false,
+            #[cfg(feature = "std")]
+            on_layer: None,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Checks that a layer found `depth` layers down may be parsed, returning the depth
+    /// of the layers nested within it.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::MaxDepthExceeded` - If `depth` has already reached `max_depth`.
+    pub(crate) fn descend(&self, depth: usize) -> Result<usize, ParserError> {
+        if depth >= self.max_depth {
+            trace::debug!(max_depth = self.max_depth, "maximum parse depth reached");
+            return Err(ParserError::MaxDepthExceeded(self.max_depth));
+        }
+
+        Ok(depth + 1)
+    }
+
+    /// Decodes a nested layer of the given kind with `decode`, reporting the time it took
+    /// to `on_layer` if a hook is set.
+    pub(crate) fn timed<T>(&self, kind: LayerKind, decode: impl FnOnce() -> T) -> T {
+        #[cfg(feature = "std")]
+        if let Some(hook) = &self.on_layer {
+            let start = std::time::Instant::now();
+            let decoded = decode();
+            (hook.0)(kind, start.elapsed());
+            return decoded;
+        }
+
+        #[cfg(not(feature = "std"))]
+        let _ = kind;
+
+        decode()
+    }
+
+    /// Settles the result of parsing the nested layer held raw in `data`, replacing `data`
+    /// with the layer if it parsed successfully.
+    ///
+    /// In best-effort mode a failure leaves `data` raw, or with `annotate_failures` set,
+    /// replaces it with `LayeredData::Unparsed`.
+    ///
+    /// # Errors
+    ///
+    /// The error the nested layer failed with, if best-effort mode is off.
+    pub(crate) fn recover<'a>(
+        &self,
+        result: Result<LayeredData<'a>, ParserError>,
+        data: &mut LayeredData<'a>,
+    ) -> Result<(), ParserError> {
+        match result {
+            Ok(layered_data) => *data = layered_data,
+            Err(err) if self.best_effort => {
+                trace::debug!(error = %err, "nested layer failed, payload left raw");
+                if let (true, Some(bytes)) = (self.annotate_failures, data.payload()) {
+                    *data = LayeredData::Unparsed {
+                        reason: err.to_string(),
+                        bytes: bytes.to_vec(),
+                    };
+                }
+            }
+            Err(err) => {
+                trace::trace!(error = %err, "nested layer failed");
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+}
+
 /// A trait that defines the functionality for deep packet inspection, ensuring a consistent interface.
-pub trait DeepParser {
+pub trait DeepParser<'a>: Sized {
     /// Analyzes the encapsulated data within the packet, returning a more structured form.
     ///
+    /// Parsing uses the default `ParseOptions`.
+    ///
     /// # Returns
     ///
     /// * `Ok(LayeredData)` - Parsed packet data encapsulated in a structured type.
     /// * `Err(ParserError)` - An error occurred during parsing, encapsulated in a `ParserError`.
-    fn parse_next_layer(self) -> Result<LayeredData, ParserError>;
+    fn parse_next_layer(self) -> Result<LayeredData<'a>, ParserError> {
+        self.parse_next_layer_with(&ParseOptions::default(), 0)
+    }
+
+    /// Parses as many nested layers as possible, leaving the first layer that fails to
+    /// parse as raw payload instead of discarding the layers around it.
+    ///
+    /// Parsing uses the default `ParseOptions`, with `best_effort` set.
+    ///
+    /// # Errors
+    ///
+    /// Only errors in this layer are returned, e.g. `ParserError::InvalidPayload` if its
+    /// payload has already been parsed.
+    fn parse_best_effort(self) -> Result<LayeredData<'a>, ParserError> {
+        let options = ParseOptions {
+            best_effort: true,
+            ..Default::default()
+        };
+        self.parse_next_layer_with(&options, 0)
+    }
+
+    /// Parses as many nested layers as possible, like `parse_best_effort`, but leaves the
+    /// first layer that fails to parse as `LayeredData::Unparsed`, so the error that
+    /// stopped parsing is kept along with the bytes it could not decode.
+    ///
+    /// Parsing uses the default `ParseOptions`, with `best_effort` and `annotate_failures`
+    /// set.
+    ///
+    /// # Errors
+    ///
+    /// Only errors in this layer are returned, as with `parse_best_effort`.
+    fn parse_next_layer_lossy(self) -> Result<LayeredData<'a>, ParserError> {
+        let options = ParseOptions {
+            best_effort: true,
+            annotate_failures: true,
+            ..Default::default()
+        };
+        self.parse_next_layer_with(&options, 0)
+    }
+
+    /// Analyzes the encapsulated data within the packet according to `options`.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The options to parse with.
+    /// * `depth` - The number of layers already parsed above this one; `0` for the
+    ///   outermost layer.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::MaxDepthExceeded` - If the packet nests more layers than
+    ///   `options.max_depth`.
+    fn parse_next_layer_with(
+        self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError>;
 }
 
 /// Represents the various forms of data that can be parsed from the network layers.
-#[derive(Debug, PartialEq)]
-pub enum LayeredData {
-    Payload(Vec<u8>),                 // Raw data payload
-    IcmpData(IcmpPacket),             // Data from an ICMP packet
-    UdpData(UdpDatagram),             // Data from a UDP datagram
-    TcpData(TcpSegment),              // Data from a TCP segment
-    Ipv4Data(Ipv4Packet),             // Data from an IPv4 packet
-    Ipv6Data(Ipv6Packet),             // Data from an IPv6 packet
-    EthernetFrameData(EthernetFrame), // Data from a complete Ethernet frame
-    Empty,                            // Represents a lack of data or an empty packet
+///
+/// A raw `Payload` borrows from the bytes it was parsed from where the lifetime allows,
+/// and owns its bytes otherwise, e.g. once the layer is made independent of its buffer
+/// with `into_owned`.
+///
+/// Raw payloads compare equal when their bytes do, whether they are held as `Payload` or
+/// share a buffer as `Bytes`.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum LayeredData<'a> {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    Payload(Cow<'a, [u8]>), // Raw data payload
+    #[cfg(feature = "bytes")]
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    Bytes(bytes::Bytes), // Raw data payload sharing the buffer it was parsed from
+    IcmpData(IcmpPacket<'a>),             // Data from an ICMP packet
+    Icmpv6Data(Icmpv6Packet<'a>),         // Data from an ICMPv6 message
+    UdpData(UdpDatagram<'a>),             // Data from a UDP datagram
+    TcpData(TcpSegment<'a>),              // Data from a TCP segment
+    Ipv4Data(Ipv4Packet<'a>),             // Data from an IPv4 packet
+    Ipv6Data(Ipv6Packet<'a>),             // Data from an IPv6 packet
+    MplsData(MplsPacket<'a>),             // Data from an MPLS labelled packet
+    PppoeData(PppoePacket<'a>),           // Data from a PPPoE packet
+    SllData(SllPacket<'a>),               // Data from a Linux cooked capture (SLL) header
+    LldpData(LldpPacket),                 // Data from an LLDPDU
+    SnmpData(SnmpMessage),                // Data from an SNMP message
+    SipData(SipMessage),                  // Data from a SIP message
+    DnsData(DnsMessage),                  // Data from a DNS message
+    DnsTcpData(Vec<DnsTcpMessage>),       // Length-prefixed DNS messages from a TCP segment
+    WebSocketData(Vec<WebSocketFrame>),   // WebSocket frames from a TCP segment
+    ArpData(ArpPacket),                   // Data from an ARP packet
+    RarpData(ArpPacket),                  // Data from a RARP packet
+    VxlanData(VxlanPacket<'a>), // Data from a VXLAN packet, wrapping an inner Ethernet frame
+    GtpData(GtpPacket<'a>),     // Data from a GTP-U packet, wrapping an inner IP packet
+    EthernetFrameData(EthernetFrame<'a>), // Data from a complete Ethernet frame
+    Custom(CustomLayer),        // Data decoded by a custom parser
+    /// A nested layer that failed to parse in best-effort mode with `annotate_failures`
+    /// set, holding the error message and the bytes that could not be decoded.
+    Unparsed {
+        reason: String,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+        bytes: Vec<u8>,
+    },
+    #[default]
+    Empty, // Represents a lack of data or an empty packet
+}
+
+impl PartialEq for LayeredData<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        if let (Some(a), Some(b)) = (self.payload(), other.payload()) {
+            return a == b;
+        }
+
+        match (self, other) {
+            (LayeredData::IcmpData(a), LayeredData::IcmpData(b)) => a == b,
+            (LayeredData::Icmpv6Data(a), LayeredData::Icmpv6Data(b)) => a == b,
+            (LayeredData::UdpData(a), LayeredData::UdpData(b)) => a == b,
+            (LayeredData::TcpData(a), LayeredData::TcpData(b)) => a == b,
+            (LayeredData::Ipv4Data(a), LayeredData::Ipv4Data(b)) => a == b,
+            (LayeredData::Ipv6Data(a), LayeredData::Ipv6Data(b)) => a == b,
+            (LayeredData::MplsData(a), LayeredData::MplsData(b)) => a == b,
+            (LayeredData::PppoeData(a), LayeredData::PppoeData(b)) => a == b,
+            (LayeredData::SllData(a), LayeredData::SllData(b)) => a == b,
+            (LayeredData::LldpData(a), LayeredData::LldpData(b)) => a == b,
+            (LayeredData::SnmpData(a), LayeredData::SnmpData(b)) => a == b,
+            (LayeredData::SipData(a), LayeredData::SipData(b)) => a == b,
+            (LayeredData::DnsData(a), LayeredData::DnsData(b)) => a == b,
+            (LayeredData::DnsTcpData(a), LayeredData::DnsTcpData(b)) => a == b,
+            (LayeredData::WebSocketData(a), LayeredData::WebSocketData(b)) => a == b,
+            (LayeredData::ArpData(a), LayeredData::ArpData(b)) => a == b,
+            (LayeredData::RarpData(a), LayeredData::RarpData(b)) => a == b,
+            (LayeredData::VxlanData(a), LayeredData::VxlanData(b)) => a == b,
+            (LayeredData::GtpData(a), LayeredData::GtpData(b)) => a == b,
+            (LayeredData::EthernetFrameData(a), LayeredData::EthernetFrameData(b)) => a == b,
+            (LayeredData::Custom(a), LayeredData::Custom(b)) => a == b,
+            (
+                LayeredData::Unparsed { reason, bytes },
+                LayeredData::Unparsed {
+                    reason: other_reason,
+                    bytes: other_bytes,
+                },
+            ) => reason == other_reason && bytes == other_bytes,
+            (LayeredData::Empty, LayeredData::Empty) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<'a> LayeredData<'a> {
+    /// Serializes the layer, and everything nested within it, back into its wire format.
+    ///
+    /// Raw payloads are emitted unchanged, and custom layers as the payload they were
+    /// decoded from.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            LayeredData::Payload(data) => data.to_vec(),
+            LayeredData::Unparsed { bytes, .. } => bytes.clone(),
+            #[cfg(feature = "bytes")]
+            LayeredData::Bytes(data) => data.to_vec(),
+            LayeredData::Ipv4Data(packet) => packet.to_bytes(),
+            LayeredData::Ipv6Data(packet) => packet.to_bytes(),
+            LayeredData::EthernetFrameData(frame) => frame.to_bytes(),
+            LayeredData::TcpData(segment) => segment.to_bytes(),
+            LayeredData::UdpData(datagram) => datagram.to_bytes(),
+            LayeredData::Icmpv6Data(packet) => packet.to_bytes(),
+            LayeredData::SipData(message) => message.to_bytes(),
+            LayeredData::DnsData(message) => message.to_bytes(),
+            LayeredData::DnsTcpData(messages) => {
+                messages.iter().flat_map(DnsTcpMessage::to_bytes).collect()
+            }
+            LayeredData::WebSocketData(frames) => {
+                frames.iter().flat_map(WebSocketFrame::to_bytes).collect()
+            }
+            LayeredData::IcmpData(packet) => packet.to_bytes(),
+            LayeredData::ArpData(packet) | LayeredData::RarpData(packet) => packet.to_bytes(),
+            LayeredData::MplsData(packet) => packet.to_bytes(),
+            LayeredData::PppoeData(packet) => packet.to_bytes(),
+            LayeredData::SllData(packet) => packet.to_bytes(),
+            LayeredData::VxlanData(packet) => packet.to_bytes(),
+            LayeredData::GtpData(packet) => packet.to_bytes(),
+            LayeredData::SnmpData(message) => message.to_bytes(),
+            LayeredData::LldpData(packet) => packet.to_bytes(),
+            LayeredData::Custom(layer) => layer.bytes().to_vec(),
+            LayeredData::Empty => Vec::new(),
+        }
+    }
+
+    /// Returns the bytes of a raw payload, whether owned or shared, or `None` for parsed
+    /// layers.
+    pub fn payload(&self) -> Option<&[u8]> {
+        match self {
+            LayeredData::Payload(data) => Some(data),
+            #[cfg(feature = "bytes")]
+            LayeredData::Bytes(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Detaches the layer from the bytes it was parsed from, copying any borrowed payload
+    /// so the layer can outlive them.
+    pub fn into_owned(self) -> LayeredData<'static> {
+        match self {
+            LayeredData::Payload(data) => LayeredData::Payload(Cow::Owned(data.into_owned())),
+            #[cfg(feature = "bytes")]
+            LayeredData::Bytes(data) => LayeredData::Bytes(data),
+            LayeredData::IcmpData(packet) => LayeredData::IcmpData(packet.into_owned()),
+            LayeredData::Icmpv6Data(packet) => LayeredData::Icmpv6Data(packet.into_owned()),
+            LayeredData::UdpData(datagram) => LayeredData::UdpData(datagram.into_owned()),
+            LayeredData::TcpData(segment) => LayeredData::TcpData(segment.into_owned()),
+            LayeredData::Ipv4Data(packet) => LayeredData::Ipv4Data(packet.into_owned()),
+            LayeredData::Ipv6Data(packet) => LayeredData::Ipv6Data(packet.into_owned()),
+            LayeredData::MplsData(packet) => LayeredData::MplsData(packet.into_owned()),
+            LayeredData::PppoeData(packet) => LayeredData::PppoeData(packet.into_owned()),
+            LayeredData::SllData(packet) => LayeredData::SllData(packet.into_owned()),
+            LayeredData::LldpData(packet) => LayeredData::LldpData(packet),
+            LayeredData::SnmpData(message) => LayeredData::SnmpData(message),
+            LayeredData::SipData(message) => LayeredData::SipData(message),
+            LayeredData::DnsData(message) => LayeredData::DnsData(message),
+            LayeredData::DnsTcpData(messages) => LayeredData::DnsTcpData(messages),
+            LayeredData::WebSocketData(frames) => LayeredData::WebSocketData(frames),
+            LayeredData::ArpData(packet) => LayeredData::ArpData(packet),
+            LayeredData::RarpData(packet) => LayeredData::RarpData(packet),
+            LayeredData::VxlanData(packet) => LayeredData::VxlanData(packet.into_owned()),
+            LayeredData::GtpData(packet) => LayeredData::GtpData(packet.into_owned()),
+            LayeredData::EthernetFrameData(frame) => {
+                LayeredData::EthernetFrameData(frame.into_owned())
+            }
+            LayeredData::Custom(layer) => LayeredData::Custom(layer),
+            LayeredData::Unparsed { reason, bytes } => LayeredData::Unparsed { reason, bytes },
+            LayeredData::Empty => LayeredData::Empty,
+        }
+    }
+
+    /// Returns the layer nested directly within this one, if any.
+    pub fn inner(&self) -> Option<&LayeredData<'a>> {
+        match self {
+            LayeredData::IcmpData(packet) => Some(&packet.data),
+            LayeredData::Icmpv6Data(packet) => Some(&packet.data),
+            LayeredData::UdpData(datagram) => Some(&datagram.data),
+            LayeredData::TcpData(segment) => Some(&segment.data),
+            LayeredData::Ipv4Data(packet) => Some(&packet.data),
+            LayeredData::Ipv6Data(packet) => Some(&packet.data),
+            LayeredData::MplsData(packet) => Some(&packet.data),
+            LayeredData::PppoeData(packet) => Some(&packet.data),
+            LayeredData::SllData(packet) => Some(&packet.data),
+            LayeredData::VxlanData(packet) => Some(&packet.data),
+            LayeredData::GtpData(packet) => Some(&packet.data),
+            LayeredData::EthernetFrameData(frame) => Some(&frame.data),
+            LayeredData::LldpData(_)
+            | LayeredData::SnmpData(_)
+            | LayeredData::SipData(_)
+            | LayeredData::DnsData(_)
+            | LayeredData::DnsTcpData(_)
+            | LayeredData::WebSocketData(_)
+            | LayeredData::ArpData(_)
+            | LayeredData::RarpData(_)
+            | LayeredData::Custom(_)
+            | LayeredData::Payload(_)
+            | LayeredData::Unparsed { .. }
+            | LayeredData::Empty => None,
+            #[cfg(feature = "bytes")]
+            LayeredData::Bytes(_) => None,
+        }
+    }
+
+    /// Returns the layer nested directly within this one mutably, if any.
+    fn inner_mut(&mut self) -> Option<&mut LayeredData<'a>> {
+        match self {
+            LayeredData::IcmpData(packet) => Some(&mut packet.data),
+            LayeredData::Icmpv6Data(packet) => Some(&mut packet.data),
+            LayeredData::UdpData(datagram) => Some(&mut datagram.data),
+            LayeredData::TcpData(segment) => Some(&mut segment.data),
+            LayeredData::Ipv4Data(packet) => Some(&mut packet.data),
+            LayeredData::Ipv6Data(packet) => Some(&mut packet.data),
+            LayeredData::MplsData(packet) => Some(&mut packet.data),
+            LayeredData::PppoeData(packet) => Some(&mut packet.data),
+            LayeredData::SllData(packet) => Some(&mut packet.data),
+            LayeredData::VxlanData(packet) => Some(&mut packet.data),
+            LayeredData::GtpData(packet) => Some(&mut packet.data),
+            LayeredData::EthernetFrameData(frame) => Some(&mut frame.data),
+            _ => None,
+        }
+    }
+
+    /// Computes every checksum in the stack and writes it into the headers, so that the
+    /// packet serialized by `to_bytes` verifies.
+    ///
+    /// IPv4 header checksums are filled in, as are the checksums of TCP segments and UDP
+    /// datagrams carried by an IPv4 or IPv6 packet, whose pseudo-header is built from the
+    /// addresses of that packet. Nested layers are finalized before the layers enclosing
+    /// them, since their checksums are part of the enclosing payload.
+    pub fn finalize_checksums(&mut self) {
+        self.finalize_checksums_within(None);
+    }
+
+    /// Finalizes the checksums of this layer, found within an IP packet sent from and to
+    /// `addresses`, if any.
+    fn finalize_checksums_within(&mut self, addresses: Option<(IpAddr, IpAddr)>) {
+        let inner_addresses = match self {
+            LayeredData::Ipv4Data(packet) => Some((
+                packet.header.source_address.into(),
+                packet.header.destination_address.into(),
+            )),
+            LayeredData::Ipv6Data(packet) => Some((
+                packet.header.source_address.into(),
+                packet.header.destination_address.into(),
+            )),
+            _ => None,
+        };
+        if let Some(inner) = self.inner_mut() {
+            inner.finalize_checksums_within(inner_addresses);
+        }
+
+        match (self, addresses) {
+            (LayeredData::Ipv4Data(packet), _) => packet.finalize_checksum(),
+            (LayeredData::TcpData(segment), Some((source, destination))) => {
+                if let Some(checksum) =
+                    TcpSegment::compute_checksum(&segment.to_bytes(), source, destination)
+                {
+                    segment.header.checksum = checksum;
+                }
+            }
+            (LayeredData::UdpData(datagram), Some((source, destination))) => {
+                if let Some(checksum) =
+                    UdpDatagram::compute_checksum(&datagram.to_bytes(), source, destination)
+                {
+                    datagram.header.checksum = checksum;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns an iterator over this layer and every layer nested within it, from the
+    /// outermost layer inwards.
+    pub fn layers(&self) -> impl Iterator<Item = &LayeredData<'a>> {
+        core::iter::successors(Some(self), |layer| layer.inner())
+    }
+
+    /// Consumes the stack and returns its innermost parsed layer, e.g. the `TcpData` of an
+    /// Ethernet frame carrying IPv6 and TCP, along with everything it still holds.
+    ///
+    /// Descends until the next layer is a raw payload or there is none. A raw payload or
+    /// empty data is returned unchanged.
+    pub fn deepest(mut self) -> LayeredData<'a> {
+        loop {
+            match self.inner_mut() {
+                Some(inner) if inner.layer_kind().is_some() => self = core::mem::take(inner),
+                _ => return self,
+            }
+        }
+    }
+
+    /// Returns the protocol of this layer, or `None` for raw payloads and empty data.
+    pub fn layer_kind(&self) -> Option<LayerKind> {
+        match self {
+            LayeredData::IcmpData(packet) => Some(packet.layer_kind()),
+            LayeredData::Icmpv6Data(packet) => Some(packet.layer_kind()),
+            LayeredData::UdpData(datagram) => Some(datagram.layer_kind()),
+            LayeredData::TcpData(segment) => Some(segment.layer_kind()),
+            LayeredData::Ipv4Data(packet) => Some(packet.layer_kind()),
+            LayeredData::Ipv6Data(packet) => Some(packet.layer_kind()),
+            LayeredData::MplsData(packet) => Some(packet.layer_kind()),
+            LayeredData::PppoeData(packet) => Some(packet.layer_kind()),
+            LayeredData::SllData(packet) => Some(packet.layer_kind()),
+            LayeredData::VxlanData(packet) => Some(packet.layer_kind()),
+            LayeredData::GtpData(packet) => Some(packet.layer_kind()),
+            LayeredData::LldpData(packet) => Some(packet.layer_kind()),
+            LayeredData::SnmpData(message) => Some(message.layer_kind()),
+            LayeredData::SipData(message) => Some(message.layer_kind()),
+            LayeredData::DnsData(message) => Some(message.layer_kind()),
+            LayeredData::DnsTcpData(_) => Some(LayerKind::Dns),
+            LayeredData::WebSocketData(_) => Some(LayerKind::WebSocket),
+            LayeredData::ArpData(_) => Some(LayerKind::Arp),
+            LayeredData::RarpData(_) => Some(LayerKind::Rarp),
+            LayeredData::EthernetFrameData(frame) => Some(frame.layer_kind()),
+            LayeredData::Custom(_) => Some(LayerKind::Custom),
+            LayeredData::Payload(_) | LayeredData::Unparsed { .. } | LayeredData::Empty => None,
+            #[cfg(feature = "bytes")]
+            LayeredData::Bytes(_) => None,
+        }
+    }
+
+    /// Returns `true` if this layer, or any layer nested within it, is of the given kind.
+    pub fn contains(&self, kind: LayerKind) -> bool {
+        self.layers().any(|layer| layer.layer_kind() == Some(kind))
+    }
+
+    /// Returns `true` if any IPv4 or IPv6 layer in the stack has `addr` as its source or
+    /// destination, including the inner packets of tunnels.
+    pub fn involves_ip(&self, addr: IpAddr) -> bool {
+        self.layers().any(|layer| match (layer, addr) {
+            (LayeredData::Ipv4Data(packet), IpAddr::V4(addr)) => packet.matches_host(addr),
+            (LayeredData::Ipv6Data(packet), IpAddr::V6(addr)) => packet.matches_host(addr),
+            _ => false,
+        })
+    }
+
+    /// Returns the source address of the outermost IPv4 or IPv6 packet in the stack.
+    pub fn source_ip(&self) -> Option<IpAddr> {
+        self.layers().find_map(|layer| match layer {
+            LayeredData::Ipv4Data(packet) => Some(IpAddr::V4(packet.header.source_address)),
+            LayeredData::Ipv6Data(packet) => Some(IpAddr::V6(packet.header.source_address)),
+            _ => None,
+        })
+    }
+
+    /// Returns the destination address of the outermost IPv4 or IPv6 packet in the stack.
+    pub fn destination_ip(&self) -> Option<IpAddr> {
+        self.layers().find_map(|layer| match layer {
+            LayeredData::Ipv4Data(packet) => Some(IpAddr::V4(packet.header.destination_address)),
+            LayeredData::Ipv6Data(packet) => Some(IpAddr::V6(packet.header.destination_address)),
+            _ => None,
+        })
+    }
+
+    /// Returns the source port of the outermost TCP or UDP layer in the stack.
+    pub fn source_port(&self) -> Option<u16> {
+        self.layers().find_map(|layer| match layer {
+            LayeredData::TcpData(segment) => Some(segment.header.source_port),
+            LayeredData::UdpData(datagram) => Some(datagram.header.source_port),
+            _ => None,
+        })
+    }
+
+    /// Returns the destination port of the outermost TCP or UDP layer in the stack.
+    pub fn destination_port(&self) -> Option<u16> {
+        self.layers().find_map(|layer| match layer {
+            LayeredData::TcpData(segment) => Some(segment.header.destination_port),
+            LayeredData::UdpData(datagram) => Some(datagram.header.destination_port),
+            _ => None,
+        })
+    }
+
+    /// Returns a one-line description of the packet for log output, e.g.
+    /// `100.127.60.5:53145 -> 30.44.8.50:80 TCP SYN 8B`.
+    ///
+    /// The addresses come from the outermost IP packet, and the ports and protocol from the
+    /// outermost TCP, UDP, ICMP or ICMPv6 layer. Stacks without a transport layer are described by
+    /// their IP packet, ARP or RARP operation, or Ethernet addresses, in that order, and
+    /// otherwise by the kind of their innermost layer.
+    pub fn summary(&self) -> String {
+        let transport = self.layers().find_map(|layer| match layer {
+            LayeredData::TcpData(segment) => Some((
+                Some((segment.header.source_port, segment.header.destination_port)),
+                format!("TCP {} {}B", segment.header.flags, segment.payload_len()),
+            )),
+            LayeredData::UdpData(datagram) => Some((
+                Some((
+                    datagram.header.source_port,
+                    datagram.header.destination_port,
+                )),
+                format!("UDP {}B", datagram.payload_len()),
+            )),
+            LayeredData::IcmpData(packet) => Some((None, format!("ICMP {}", packet.describe()))),
+            LayeredData::Icmpv6Data(packet) => {
+                Some((None, format!("ICMPv6 {}", packet.describe())))
+            }
+            _ => None,
+        });
+
+        let endpoint = |ip: Option<IpAddr>, port: Option<u16>| match (ip, port) {
+            (Some(IpAddr::V6(ip)), Some(port)) => format!("[{ip}]:{port}"),
+            (Some(ip), Some(port)) => format!("{ip}:{port}"),
+            (Some(ip), None) => ip.to_string(),
+            (None, Some(port)) => port.to_string(),
+            (None, None) => "?".to_string(),
+        };
+        let flow = |ports: Option<(u16, u16)>, description: String| {
+            format!(
+                "{} -> {} {description}",
+                endpoint(self.source_ip(), ports.map(|(source, _)| source)),
+                endpoint(
+                    self.destination_ip(),
+                    ports.map(|(_, destination)| destination)
+                ),
+            )
+        };
+
+        if let Some((ports, description)) = transport {
+            return flow(ports, description);
+        }
+
+        let network = self.layers().find_map(|layer| match layer {
+            LayeredData::Ipv4Data(packet) => Some(format!(
+                "IPv4 proto {} {}B",
+                u8::from(&packet.header.protocol),
+                packet.data.byte_len()
+            )),
+            LayeredData::Ipv6Data(packet) => Some(format!(
+                "IPv6 next {} {}B",
+                u8::from(&packet.header.upper_layer_protocol()),
+                packet.data.byte_len()
+            )),
+            _ => None,
+        });
+        if let Some(description) = network {
+            return flow(None, description);
+        }
+
+        self.layers()
+            .find_map(|layer| match layer {
+                LayeredData::ArpData(packet) | LayeredData::RarpData(packet) => {
+                    Some(packet.describe())
+                }
+                _ => None,
+            })
+            .or_else(|| {
+                self.layers().find_map(|layer| match layer {
+                    LayeredData::EthernetFrameData(frame) => Some(frame.describe()),
+                    _ => None,
+                })
+            })
+            .or_else(|| {
+                let kind = self.layers().filter_map(LayeredData::layer_kind).last();
+                kind.map(|kind| format!("{kind:?}"))
+            })
+            .unwrap_or_else(|| format!("{}B", self.byte_len()))
+    }
+
+    /// Returns the number of bytes the layer holds: the length of a raw payload, or of the
+    /// serialized form of a parsed layer.
+    pub(crate) fn byte_len(&self) -> usize {
+        match self.payload() {
+            Some(data) => data.len(),
+            None => self.to_bytes().len(),
+        }
+    }
+
+    /// Returns a `hexdump` of the raw payload left at the bottom of the stack, i.e. the
+    /// bytes no parser could decode further.
+    ///
+    /// Returns `None` if the innermost layer was fully parsed (e.g. an LLDPDU).
+    pub fn hexdump_innermost(&self) -> Option<String> {
+        match self.layers().last()? {
+            LayeredData::Unparsed { bytes, .. } => Some(hexdump(bytes)),
+            innermost => innermost.payload().map(hexdump),
+        }
+    }
+
+    /// Returns the header span of this layer, relative to the bytes it was parsed from.
+    fn span(&self) -> Option<&Range<usize>> {
+        match self {
+            LayeredData::IcmpData(packet) => Some(&packet.header.span),
+            LayeredData::Icmpv6Data(packet) => Some(&packet.header.span),
+            LayeredData::UdpData(datagram) => Some(&datagram.header.span),
+            LayeredData::TcpData(segment) => Some(&segment.header.span),
+            LayeredData::Ipv4Data(packet) => Some(&packet.header.span),
+            LayeredData::Ipv6Data(packet) => Some(&packet.header.span),
+            LayeredData::MplsData(packet) => Some(&packet.header.span),
+            LayeredData::PppoeData(packet) => Some(&packet.header.span),
+            LayeredData::SllData(packet) => Some(&packet.header.span),
+            LayeredData::VxlanData(packet) => Some(&packet.header.span),
+            LayeredData::GtpData(packet) => Some(&packet.header.span),
+            LayeredData::LldpData(packet) => Some(&packet.span),
+            LayeredData::SnmpData(message) => Some(&message.span),
+            LayeredData::SipData(message) => Some(&message.span),
+            LayeredData::DnsData(message) => Some(&message.header.span),
+            LayeredData::ArpData(packet) | LayeredData::RarpData(packet) => Some(&packet.span),
+            LayeredData::EthernetFrameData(frame) => Some(&frame.header.span),
+            // Several DNS messages or WebSocket frames can share a segment, so no single
+            // span describes them.
+            LayeredData::DnsTcpData(_)
+            | LayeredData::WebSocketData(_)
+            | LayeredData::Custom(_)
+            | LayeredData::Payload(_)
+            | LayeredData::Unparsed { .. }
+            | LayeredData::Empty => None,
+            #[cfg(feature = "bytes")]
+            LayeredData::Bytes(_) => None,
+        }
+    }
+
+    /// Returns the absolute position of each parsed header, from the outermost layer inwards.
+    ///
+    /// Each layer records its span relative to the slice handed to its own parser, which
+    /// begins right after the enclosing header. The spans are therefore offset by the end
+    /// of every enclosing header while descending, so the results index directly into the
+    /// buffer the outermost layer was parsed from. Raw payloads carry no header and are
+    /// not included.
+    pub fn spans(&self) -> Vec<Range<usize>> {
+        let mut offset = 0;
+
+        self.layers()
+            .filter_map(LayeredData::span)
+            .map(|span| {
+                let absolute = offset + span.start..offset + span.end;
+                offset += span.end;
+                absolute
+            })
+            .collect()
+    }
+}
+
+/// A layer borrowed from the bytes it was parsed from, as returned by the `from_bytes_ref`
+/// constructors and by `next_layer`.
+///
+/// Nothing is copied into a borrowed layer, so a stack can be walked without allocating.
+/// Only Ethernet, IPv4, IPv6, TCP and UDP have borrowed forms: any other payload, including
+/// that of a TCP segment or UDP datagram, is left as `Payload`. To decode it further, parse
+/// the layer holding it with `DeepParser`, which builds the full layers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayeredDataRef<'a> {
+    Payload(&'a [u8]),                       // Raw data payload
+    UdpData(UdpDatagramRef<'a>),             // A UDP datagram
+    TcpData(TcpSegmentRef<'a>),              // A TCP segment
+    Ipv4Data(Ipv4PacketRef<'a>),             // An IPv4 packet
+    Ipv6Data(Ipv6PacketRef<'a>),             // An IPv6 packet
+    EthernetFrameData(EthernetFrameRef<'a>), // An Ethernet frame
+}
+
+impl<'a> LayeredDataRef<'a> {
+    /// Parses the layer carried by this one, borrowing from the same bytes. Returns `None`
+    /// for a raw payload, and the payload of a TCP segment or UDP datagram.
+    pub fn next_layer(&self) -> Option<Result<LayeredDataRef<'a>, ParserError>> {
+        match self {
+            LayeredDataRef::Payload(_) => None,
+            LayeredDataRef::UdpData(datagram) => Some(Ok(LayeredDataRef::Payload(datagram.data()))),
+            LayeredDataRef::TcpData(segment) => Some(Ok(LayeredDataRef::Payload(segment.data()))),
+            LayeredDataRef::Ipv4Data(packet) => Some(packet.next_layer()),
+            LayeredDataRef::Ipv6Data(packet) => Some(packet.next_layer()),
+            LayeredDataRef::EthernetFrameData(frame) => Some(frame.next_layer()),
+        }
+    }
+
+    /// Returns an iterator over this layer and every layer parsed from it by `next_layer`,
+    /// from the outermost layer inwards. A layer that fails to parse is yielded as an error
+    /// and ends the iteration.
+    pub fn layers(self) -> impl Iterator<Item = Result<LayeredDataRef<'a>, ParserError>> {
+        core::iter::successors(Some(Ok(self)), |layer| layer.as_ref().ok()?.next_layer())
+    }
+
+    /// Returns the bytes of a raw payload, or `None` for parsed layers.
+    pub fn payload(&self) -> Option<&'a [u8]> {
+        match self {
+            LayeredDataRef::Payload(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Returns the kind of this layer, or `None` for a raw payload.
+    pub fn layer_kind(&self) -> Option<LayerKind> {
+        match self {
+            LayeredDataRef::Payload(_) => None,
+            LayeredDataRef::UdpData(_) => Some(LayerKind::Udp),
+            LayeredDataRef::TcpData(_) => Some(LayerKind::Tcp),
+            LayeredDataRef::Ipv4Data(_) => Some(LayerKind::Ipv4),
+            LayeredDataRef::Ipv6Data(_) => Some(LayerKind::Ipv6),
+            LayeredDataRef::EthernetFrameData(_) => Some(LayerKind::Ethernet),
+        }
+    }
+}
+
+impl<'a> From<LayeredDataRef<'a>> for LayeredData<'a> {
+    /// Converts the layer into its full form, with its payload left raw and still borrowed.
+    fn from(layer: LayeredDataRef<'a>) -> Self {
+        match layer {
+            LayeredDataRef::Payload(data) => LayeredData::Payload(Cow::Borrowed(data)),
+            LayeredDataRef::UdpData(datagram) => LayeredData::UdpData(datagram.into()),
+            LayeredDataRef::TcpData(segment) => LayeredData::TcpData(segment.into()),
+            LayeredDataRef::Ipv4Data(packet) => LayeredData::Ipv4Data(packet.into()),
+            LayeredDataRef::Ipv6Data(packet) => LayeredData::Ipv6Data(packet.into()),
+            LayeredDataRef::EthernetFrameData(frame) => {
+                LayeredData::EthernetFrameData(frame.into())
+            }
+        }
+    }
 }
@@ -0,0 +1,327 @@
+// DNS Header:
+//  0  1  2  3  4  5  6  7  8  9 10 11 12 13 14 15
+// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+// |                      ID                       |
+// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+// |QR|   Opcode  |AA|TC|RD|RA|   Z    |   RCODE   |
+// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+// |                    QDCOUNT                    |
+// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+// |                    ANCOUNT                    |
+// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+// |                    NSCOUNT                    |
+// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+// |                    ARCOUNT                    |
+// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+//
+// Question:
+// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+// /                     QNAME                     /
+// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+// |                     QTYPE                     |
+// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+// |                     QCLASS                    |
+// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+//
+// A name is a sequence of length-prefixed labels ending with a zero length, or with a
+// pointer to a name elsewhere in the message: two bytes whose top two bits are set,
+// holding a 14-bit offset from the start of the message (RFC 1035, section 4.1.4).
+//
+// Over TCP, every message is preceded by a 2-byte length (RFC 1035, section 4.2.2),
+// and a single segment may carry several messages, or only part of one.
+
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
+use super::{
+    definitions::{DeepParser, LayerKind, LayeredData, ParseOptions},
+    errors::ParserError,
+    trace,
+    utils::ByteReader,
+};
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ops::Range;
+
+const HEADER_SIZE: usize = 12;
+const LENGTH_PREFIX_SIZE: usize = 2;
+const POINTER_MASK: u8 = 0xC0;
+const MAX_NAME_LENGTH: usize = 255;
+// More jumps than a name can hold labels means the pointers form a loop.
+const MAX_POINTER_JUMPS: usize = MAX_NAME_LENGTH / 2;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DnsHeader {
+    /// Matches a response to the query that prompted it.
+    pub id: u16,
+
+    /// The QR, Opcode, AA, TC, RD, RA, Z and RCODE fields.
+    pub flags: u16,
+
+    pub question_count: u16,
+    pub answer_count: u16,
+    pub authority_count: u16,
+    pub additional_count: u16,
+
+    /// The position of the header within the bytes the message was parsed from.
+    pub span: Range<usize>,
+
+    /// The 12 header bytes as received, which `to_bytes` writes back ahead of `body`.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub raw_header: Vec<u8>,
+}
+
+/// An entry of the question section, naming the records a query asks for.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DnsQuestion {
+    /// The queried domain name with compression pointers followed, e.g.
+    /// `example.com`. The root domain is written as `.`.
+    pub name: String,
+
+    /// The type of record asked for, e.g. 1 for A or 28 for AAAA.
+    pub qtype: u16,
+
+    /// The class of record asked for, usually 1 for IN.
+    pub qclass: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DnsMessage {
+    pub header: DnsHeader,
+
+    /// The decoded question section.
+    pub questions: Vec<DnsQuestion>,
+
+    /// The undecoded question, answer, authority and additional sections.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub body: Vec<u8>,
+}
+
+impl DnsMessage {
+    /// Constructs a `DnsMessage` from a slice of bytes holding exactly one message.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::HeaderTooShort` - If the slice is shorter than the DNS header.
+    /// * `ParserError::ExtractionError` - If the question section runs past the slice.
+    /// * `ParserError::MalformedMessage` - If a name uses a reserved label type, points
+    ///   outside the message, loops, or is longer than 255 bytes.
+    pub fn from_bytes(message: &[u8]) -> Result<Self, ParserError> {
+        if message.len() < HEADER_SIZE {
+            return Err(ParserError::HeaderTooShort {
+                layer: "DNS message".to_string(),
+                got: message.len(),
+                need: HEADER_SIZE,
+            });
+        }
+
+        let mut cursor = ByteReader::new(message);
+
+        let id = cursor.read_u16_be("DNS_Id")?;
+        let flags = cursor.read_u16_be("DNS_Flags")?;
+        let question_count = cursor.read_u16_be("DNS_QDCount")?;
+        let answer_count = cursor.read_u16_be("DNS_ANCount")?;
+        let authority_count = cursor.read_u16_be("DNS_NSCount")?;
+        let additional_count = cursor.read_u16_be("DNS_ARCount")?;
+
+        let mut questions = Vec::with_capacity((question_count as usize).min(message.len()));
+        for _ in 0..question_count {
+            let name = Self::read_name(message, &mut cursor)?;
+            let qtype = cursor.read_u16_be("DNS_QType")?;
+            let qclass = cursor.read_u16_be("DNS_QClass")?;
+            questions.push(DnsQuestion {
+                name,
+                qtype,
+                qclass,
+            });
+        }
+
+        Ok(DnsMessage {
+            header: DnsHeader {
+                id,
+                flags,
+                question_count,
+                answer_count,
+                authority_count,
+                additional_count,
+                span: 0..HEADER_SIZE,
+                raw_header: message[..HEADER_SIZE].to_vec(),
+            },
+            questions,
+            body: message[HEADER_SIZE..].to_vec(),
+        })
+    }
+
+    /// Reads the domain name at the cursor, following compression pointers within
+    /// `message`, which the cursor must be reading.
+    ///
+    /// The cursor is left after the name as it appears at its position, i.e. after the
+    /// first pointer if there is one.
+    fn read_name(message: &[u8], cursor: &mut ByteReader) -> Result<String, ParserError> {
+        let mut name = String::new();
+        let mut reader = ByteReader::new(message);
+        reader.set_position(cursor.position());
+        // Where the name ends at its own position, once a pointer has been followed.
+        let mut end = None;
+        let mut jumps = 0;
+
+        loop {
+            let length = reader.read_u8("DNS_Label_Length")?;
+            match length & POINTER_MASK {
+                0 if length == 0 => break,
+                0 => {
+                    let label = reader.read_slice(length as usize, "DNS_Label")?;
+                    if !name.is_empty() {
+                        name.push('.');
+                    }
+                    name.extend(label.iter().map(|&byte| byte as char));
+                    if name.len() > MAX_NAME_LENGTH {
+                        return Err(malformed("name is longer than 255 bytes"));
+                    }
+                }
+                POINTER_MASK => {
+                    let low = reader.read_u8("DNS_Pointer")?;
+                    let offset = u16::from_be_bytes([length & !POINTER_MASK, low]) as usize;
+                    end.get_or_insert(reader.position());
+
+                    jumps += 1;
+                    if jumps > MAX_POINTER_JUMPS {
+                        return Err(malformed("name compression pointers form a loop"));
+                    }
+                    if offset >= message.len() {
+                        return Err(malformed("name compression pointer is out of bounds"));
+                    }
+                    reader.set_position(offset);
+                }
+                _ => return Err(malformed("name uses a reserved label type")),
+            }
+        }
+
+        cursor.set_position(end.unwrap_or(reader.position()));
+        if name.is_empty() {
+            name.push('.');
+        }
+        Ok(name)
+    }
+
+    /// Returns `true` if the message is a response rather than a query.
+    pub fn is_response(&self) -> bool {
+        self.header.flags & 0x8000 != 0
+    }
+
+    /// Serializes the message back into its wire format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut message = self.header.raw_header.clone();
+        message.extend_from_slice(&self.body);
+        message
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Dns
+    }
+}
+
+impl<'a> DeepParser<'a> for DnsMessage {
+    fn parse_next_layer_with(
+        self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Dns, depth);
+
+        Ok(LayeredData::DnsData(self))
+    }
+}
+
+/// A length-prefixed DNS message found in a TCP segment.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DnsTcpMessage {
+    /// A message that fits within the segment.
+    Complete(DnsMessage),
+
+    /// The start of a message that continues in later segments, left undecoded. Bytes that
+    /// do not decode as a message, e.g. the end of one begun in an earlier segment, are
+    /// reported the same way.
+    Partial {
+        /// The length declared by the prefix, or `None` if the prefix itself is cut off.
+        length: Option<u16>,
+        /// The bytes of the message present in this segment.
+        #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+        data: Vec<u8>,
+    },
+}
+
+impl DnsTcpMessage {
+    /// Splits a TCP payload into the length-prefixed DNS messages it carries.
+    ///
+    /// The last message may be cut off by the end of the segment, in which case it is
+    /// returned as `DnsTcpMessage::Partial`. So is everything from the first message that
+    /// fails to decode, as the payload cannot be split any further.
+    pub fn parse_all(payload: &[u8]) -> Vec<Self> {
+        let mut messages = Vec::new();
+        let mut rest = payload;
+
+        while !rest.is_empty() {
+            let Some((prefix, data)) = rest.split_first_chunk::<LENGTH_PREFIX_SIZE>() else {
+                messages.push(DnsTcpMessage::Partial {
+                    length: None,
+                    data: rest.to_vec(),
+                });
+                break;
+            };
+
+            let length = u16::from_be_bytes(*prefix);
+            let message = data
+                .get(..length as usize)
+                .and_then(|message| DnsMessage::from_bytes(message).ok());
+            let Some(message) = message else {
+                messages.push(DnsTcpMessage::Partial {
+                    length: Some(length),
+                    data: data.to_vec(),
+                });
+                break;
+            };
+
+            messages.push(DnsTcpMessage::Complete(message));
+            rest = &data[length as usize..];
+        }
+
+        messages
+    }
+
+    /// Serializes the message back into its wire format, including the length prefix.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            DnsTcpMessage::Complete(message) => {
+                let message = message.to_bytes();
+                let mut bytes = (message.len() as u16).to_be_bytes().to_vec();
+                bytes.extend_from_slice(&message);
+                bytes
+            }
+            DnsTcpMessage::Partial { length, data } => {
+                // Without a length, `data` already holds the single byte of the prefix.
+                let mut bytes = match length {
+                    Some(length) => length.to_be_bytes().to_vec(),
+                    None => Vec::new(),
+                };
+                bytes.extend_from_slice(data);
+                bytes
+            }
+        }
+    }
+}
+
+fn malformed(detail: &str) -> ParserError {
+    ParserError::MalformedMessage {
+        layer: "DNS message".to_string(),
+        detail: detail.to_string(),
+    }
+}
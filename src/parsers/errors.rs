@@ -1,24 +1,40 @@
 use thiserror::Error;
 
-use std::fmt;
+use alloc::string::String;
+use core::fmt;
 
 #[derive(Debug)]
 pub enum ErrorSource {
+    #[cfg(feature = "std")]
     Io(std::io::Error),
-    TryFromSlice(std::array::TryFromSliceError),
+    /// The input ended before a field could be read in full. With the `std` feature
+    /// enabled this is reported as an `Io` error of kind `UnexpectedEof` instead.
+    UnexpectedEof,
 }
 
-// Implementing the std::fmt::Display trait to enable printing the error.
+impl ErrorSource {
+    /// Returns the error used to report reading past the end of the input.
+    pub(crate) fn unexpected_eof() -> Self {
+        #[cfg(feature = "std")]
+        return ErrorSource::Io(std::io::ErrorKind::UnexpectedEof.into());
+
+        #[cfg(not(feature = "std"))]
+        return ErrorSource::UnexpectedEof;
+    }
+}
+
+// Implementing the core::fmt::Display trait to enable printing the error.
 impl fmt::Display for ErrorSource {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            #[cfg(feature = "std")]
             ErrorSource::Io(e) => write!(f, "IO error: {}", e),
-            ErrorSource::TryFromSlice(e) => write!(f, "Try from slice error: {}", e),
+            ErrorSource::UnexpectedEof => write!(f, "Unexpected end of input"),
         }
     }
 }
 
-impl std::error::Error for ErrorSource {}
+impl core::error::Error for ErrorSource {}
 
 #[derive(Error, Debug)]
 pub enum ParserError {
@@ -29,31 +45,83 @@ pub enum ParserError {
         source: ErrorSource,
     },
 
-    #[error("Failed to seek in cursor for `{string}`")]
-    CursorError {
-        string: String,
-        #[source]
-        source: std::io::Error,
-    },
-
     #[error("Invalid IHL value got `{0}`, expected >=`{1}` or <= `{2}`")]
     InvalidIHLValue(u32, u8, u8),
 
     #[error("Invalid EtherType")]
     InvalidEtherType,
 
+    /// A length or size field holds a value the protocol does not allow.
     #[error("Invalid packet/segment length `{0}`")]
     InvalidLength(String),
 
+    /// The input ends before the fixed part of a header does.
+    #[error("`{layer}` is too short: got {got} bytes, need at least {need}")]
+    HeaderTooShort {
+        layer: String,
+        got: usize,
+        need: usize,
+    },
+
     #[error("Expected payload data")]
     InvalidPayload,
 
     #[error("Unknown IP type `{0}`")]
     UnknownIPType(u8),
 
-    #[error("Unknown ether type type")]
-    UnSupportedEtherType,
+    #[error("Unknown IP version `{0}`")]
+    UnknownIpVersion(u8),
+
+    #[error("Invalid filter expression: {0}")]
+    InvalidFilter(String),
+
+    #[error("Invalid MAC address `{0}`, expected six colon-separated hex octets")]
+    InvalidMacString(String),
+
+    #[error("MPLS label stack ended without a bottom-of-stack label")]
+    UnterminatedLabelStack,
+
+    #[error("Unrecognised pcap magic number `{0:#010x}`")]
+    InvalidPcapMagic(u32),
+
+    /// A pcap capture holds frames of a link type other than Ethernet.
+    #[error("Unsupported pcap link type `{0}`, expected Ethernet (1)")]
+    UnsupportedLinkType(u32),
+
+    /// A length field declares more bytes than the input holds.
+    #[error("`{layer}` declares {declared} bytes but only {available} are available")]
+    InconsistentLength {
+        layer: String,
+        declared: usize,
+        available: usize,
+    },
+
+    /// A header's options, or a single option, run past the end of the header or input.
+    #[error("`{layer}` declares {declared} bytes of options but only {available} remain")]
+    OptionsOverrun {
+        layer: String,
+        declared: usize,
+        available: usize,
+    },
+
+    #[error("Unexpected BER tag `{0:#04x}`")]
+    UnexpectedBerTag(u8),
+
+    #[error("Unsupported SNMP version `{0}`")]
+    UnsupportedSnmpVersion(i64),
+
+    #[error("LLDPDU is missing the mandatory `{0}` TLV")]
+    MissingLldpTlv(String),
+
+    #[error("Malformed `{layer}`: {detail}")]
+    MalformedMessage { layer: String, detail: String },
+
+    #[error("Packet nests more than the maximum of {0} layers")]
+    MaxDepthExceeded(usize),
+
+    #[error("Protocol violation in `{field}`: {detail}")]
+    ProtocolViolation { field: String, detail: String },
 
-    #[error("Exceeded MAC range `{0}`")]
-    MacRangeExceeded(u128),
+    #[error("Checksum mismatch: expected `{expected:#06x}`, computed `{computed:#06x}`")]
+    ChecksumMismatch { expected: u16, computed: u16 },
 }
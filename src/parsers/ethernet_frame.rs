@@ -14,25 +14,47 @@
 // +---------------------------+
 // | FCS (4 bytes)             |
 // +---------------------------+
+//
+// In IEEE 802.3 frames the EtherType field instead holds the length of an 802.2 LLC
+// header and its payload, optionally extended by SNAP to carry an EtherType:
+// +------+------+---------+---------+--------------+---------+
+// | DSAP | SSAP | Control | OUI (3) | Protocol (2) | Payload |
+// +------+------+---------+---------+--------------+---------+
+// |    LLC (3-4 bytes)    |   SNAP (5 bytes)       |
 
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
 use super::{
-    constants,
-    definitions::{DeepParser, EtherType, LayeredData},
+    arp::ArpPacket,
+    definitions::{DeepParser, EtherType, LayerKind, LayeredData, LayeredDataRef, ParseOptions},
     errors::ParserError,
-    ipv4::Ipv4Packet,
-    ipv6::Ipv6Packet,
-    utils::{read_arbitrary_length, read_u128, read_u16},
+    ipv4::{Ipv4Packet, Ipv4PacketRef},
+    ipv6::{Ipv6Packet, Ipv6PacketRef},
+    lldp::LldpPacket,
+    mpls::MplsPacket,
+    pppoe::PppoePacket,
+    trace,
+    utils::{be_u16, be_u32, ByteReader, PayloadSource},
 };
 
-use std::fmt;
-use std::io::Cursor;
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    fmt,
+    ops::{Deref, Range},
+    str::FromStr,
+};
 
 const MAC_ADDRESS_BYTES: usize = 6;
 
 /// A struct representing a Media Access Control (MAC) address, used for identifying network hardware.
 ///
 /// It contains a single field, a 6-byte array, as MAC addresses are 6 bytes in length.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct MacAddress(pub [u8; MAC_ADDRESS_BYTES]);
 
 impl MacAddress {
@@ -50,6 +72,37 @@ impl MacAddress {
     }
 }
 
+impl From<[u8; MAC_ADDRESS_BYTES]> for MacAddress {
+    fn from(bytes: [u8; MAC_ADDRESS_BYTES]) -> Self {
+        MacAddress(bytes)
+    }
+}
+
+impl TryFrom<&[u8]> for MacAddress {
+    type Error = ParserError;
+
+    /// Constructs a `MacAddress` from a slice, failing unless it is exactly 6 bytes long.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        <[u8; MAC_ADDRESS_BYTES]>::try_from(bytes)
+            .map(MacAddress)
+            .map_err(|_| ParserError::InvalidLength("MAC address".to_string()))
+    }
+}
+
+impl AsRef<[u8]> for MacAddress {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Deref for MacAddress {
+    type Target = [u8; MAC_ADDRESS_BYTES];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
 impl fmt::Display for MacAddress {
     /// Formats the MAC address for display purposes.
     /// This implementation will print the MAC address in the common hex notation, separated by colons.
@@ -62,16 +115,136 @@ impl fmt::Display for MacAddress {
     }
 }
 
+impl FromStr for MacAddress {
+    type Err = ParserError;
+
+    /// Parses a MAC address in the colon-separated hex notation produced by `Display`,
+    /// e.g. `0C:19:3C:FF:58:0C`. Hex digits may be upper or lower case, and an octet may
+    /// be written with a single digit.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::InvalidMacString` - Unless `s` holds exactly six groups of one or
+    ///   two hex digits.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParserError::InvalidMacString(s.to_string());
+
+        let mut bytes = [0u8; MAC_ADDRESS_BYTES];
+        let mut groups = s.split(':');
+        for byte in bytes.iter_mut() {
+            let group = groups.next().ok_or_else(invalid)?;
+            if group.is_empty() || group.len() > 2 || !group.chars().all(|c| c.is_ascii_hexdigit())
+            {
+                return Err(invalid());
+            }
+            *byte = u8::from_str_radix(group, 16).map_err(|_| invalid())?;
+        }
+        if groups.next().is_some() {
+            return Err(invalid());
+        }
+
+        Ok(MacAddress(bytes))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for MacAddress {
+    /// Serializes the MAC address in the colon-separated hex notation of `Display`.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 // Constants representing various parameters and offsets within an Ethernet frame.
 // These are used for parsing the frame correctly.
 const TPID_VLAN: u32 = 33024; // [0x81, 0x00];
+const TPID_SERVICE_VLAN: u32 = 0x88A8; // 802.1ad S-Tag, the outer tag of QinQ frames.
+const FCS_SIZE: usize = 4;
+const HEADER_SIZE: usize = 14; // Two MAC addresses and the type field, without tags.
+const MAX_8023_LENGTH: u16 = 1500; // Larger values of the type field are EtherTypes.
+const MIN_ETHER_TYPE: u16 = 0x0600; // Values from 1501 up to this are undefined.
+const VLAN_ID_MASK: u32 = 0x0FFF;
+const DEI_MASK: u16 = 0x1000;
+// IEEE 802 Local Experimental EtherType, used for payloads whose protocol is unknown.
+const LOCAL_EXPERIMENTAL_ETHER_TYPE: u16 = 0x88B5;
+const SNAP_SAP: u8 = 0xAA;
+const SNAP_HEADER_SIZE: usize = 5;
+const LLC_UNNUMBERED_FORMAT: u8 = 0x03; // U-format PDUs have a 1-byte control field.
+
+// The optional S-Tag and QTag of a frame, in the order they appear.
+type VlanTags = (Option<u32>, Option<u32>);
+
+/// The IEEE 802.2 Logical Link Control header of an 802.3 frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LlcHeader {
+    /// The Destination Service Access Point.
+    pub dsap: u8,
+
+    /// The Source Service Access Point.
+    pub ssap: u8,
+
+    /// The control field: 1 byte for unnumbered (U-format) PDUs, 2 bytes otherwise.
+    pub control: u16,
+}
+
+/// The Subnetwork Access Protocol extension of an LLC header, used when both SAPs are
+/// `0xAA`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SnapHeader {
+    /// The Organizationally Unique Identifier. Zero when `protocol_id` is an EtherType.
+    pub oui: [u8; 3],
+
+    /// The protocol identifier, interpreted according to `oui`.
+    pub protocol_id: u16,
+}
+
+/// The Tag Control Information of an 802.1Q tag, decoded from its 16 bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VlanTag {
+    /// The 3-bit Priority Code Point, the frame's IEEE 802.1p class of service.
+    pub pcp: u8,
 
-#[derive(Debug, PartialEq)]
+    /// The Drop Eligible Indicator, set if the frame may be dropped under congestion.
+    pub dei: bool,
+
+    /// The 12-bit VLAN Identifier of the VLAN the frame belongs to.
+    pub vid: u16,
+}
+
+impl From<u16> for VlanTag {
+    fn from(tci: u16) -> Self {
+        VlanTag {
+            pcp: (tci >> 13) as u8,
+            dei: tci & DEI_MASK != 0,
+            vid: tci & VLAN_ID_MASK as u16,
+        }
+    }
+}
+
+/// Distinguishes Ethernet II framing from IEEE 802.3 framing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum EthernetFrameKind {
+    /// An Ethernet II frame, whose type field is an EtherType.
+    EthernetII,
+
+    /// An IEEE 802.3 frame, whose type field is the `length` of the LLC header and payload.
+    Ieee8023 {
+        length: u16,
+        llc: LlcHeader,
+        snap: Option<SnapHeader>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// Represents the header of an Ethernet frame.
 ///
 /// Ethernet frames begin with a header that contains the essential fields
 /// for network communication. This struct captures the key components of
-/// that header, specifically catering to Ethernet II framing.
+/// that header, for both Ethernet II and IEEE 802.3 framing.
 pub struct EthernetFrameHeader {
     /// The MAC (Media Access Control) address of the intended recipient of the packet.
     pub mac_destination: MacAddress,
@@ -79,13 +252,47 @@ pub struct EthernetFrameHeader {
     /// The MAC address of the sender of the packet.
     pub mac_source: MacAddress,
 
-    /// An optional 802.1Q tag specifying VLAN membership and priority information.
-    /// It's present in VLAN-tagged frames, otherwise `None`.
+    /// An optional 802.1ad service tag (S-Tag), the outer tag of a double-tagged (QinQ)
+    /// frame: the TPID `0x88A8` in the upper 16 bits and the TCI in the lower 16, which
+    /// `service_vlan_tag` decodes. The customer tag it carries is held in `q_tag`.
+    pub s_tag: Option<u32>,
+
+    /// An optional 802.1Q tag specifying VLAN membership and priority information: the
+    /// TPID `0x8100` in the upper 16 bits and the TCI in the lower 16, which `vlan_tag`
+    /// decodes. It's present in VLAN-tagged frames, otherwise `None`.
     pub q_tag: Option<u32>,
 
     /// The EtherType field indicating the protocol encapsulated in the payload of the frame.
     /// Common values indicate IPv4, IPv6, ARP, etc.
+    ///
+    /// For 802.3 frames this is the EtherType carried by SNAP. Frames without SNAP, or
+    /// whose SNAP OUI is vendor specific, hold the raw type field as `EtherType::Other`.
     pub ether_type: EtherType,
+
+    /// Whether the frame uses Ethernet II or 802.3 framing, along with the LLC and SNAP
+    /// headers of the latter.
+    pub frame_kind: EthernetFrameKind,
+
+    /// The position of the header within the bytes the frame was parsed from.
+    pub span: Range<usize>,
+
+    /// The header as received, including any VLAN tags and, for 802.3 frames, the LLC
+    /// and SNAP headers, which `to_bytes` writes back from here.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub raw_header: Vec<u8>,
+}
+
+impl EthernetFrameHeader {
+    /// Returns the decoded Tag Control Information of the 802.1Q tag, if the frame has one.
+    pub fn vlan_tag(&self) -> Option<VlanTag> {
+        self.q_tag.map(|q_tag| VlanTag::from(q_tag as u16))
+    }
+
+    /// Returns the decoded Tag Control Information of the 802.1ad service tag, if the frame
+    /// is double-tagged.
+    pub fn service_vlan_tag(&self) -> Option<VlanTag> {
+        self.s_tag.map(|s_tag| VlanTag::from(s_tag as u16))
+    }
 }
 
 /// Represents a complete Ethernet frame.
@@ -94,8 +301,9 @@ pub struct EthernetFrameHeader {
 /// both the header and the payload of the frame. It is fundamental for
 /// handling network data at a low level, allowing for the parsing, creation,
 /// and manipulation of Ethernet frames for various networking operations.
-#[derive(Debug, PartialEq)]
-pub struct EthernetFrame {
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EthernetFrame<'a> {
     /// The header of the Ethernet frame, containing all the relevant
     /// information for routing and type of content.
     pub header: EthernetFrameHeader,
@@ -103,10 +311,226 @@ pub struct EthernetFrame {
     /// The actual payload of the Ethernet frame encapsulated as `LayeredData`.
     /// This can represent various forms of data as structured in different
     /// network layers, depending on the EtherType specified in the header.
-    pub data: Box<LayeredData>,
+    pub data: Box<LayeredData<'a>>,
+}
+
+/// An Ethernet frame borrowed from the bytes it was parsed by
+/// `EthernetFrame::from_bytes_ref`.
+///
+/// The addresses and tags are read from the bytes when asked for, so nothing is copied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EthernetFrameRef<'a> {
+    bytes: &'a [u8],
+    header_length: usize,
+    ether_type: u16,
+    frame_kind: EthernetFrameKind,
+}
+
+impl<'a> EthernetFrameRef<'a> {
+    /// Checks the header of `frame`, and locates the payload before any FCS, or padding
+    /// past an 802.3 length.
+    pub(crate) fn parse(frame: &'a [u8], fcs_enabled: bool) -> Result<Self, ParserError> {
+        let fcs_size = if fcs_enabled { FCS_SIZE } else { 0 };
+        // Captures often hold frames shorter than the 64 bytes of `constants::MIN_FRAME_SIZE`,
+        // e.g. a 42-byte ARP request with its padding and FCS stripped, so only the header
+        // is required here. Each payload is checked for length by its own parser.
+        if frame.len() < HEADER_SIZE + fcs_size {
+            return Err(ParserError::HeaderTooShort {
+                layer: "Ethernet Frame".to_string(),
+                got: frame.len(),
+                need: HEADER_SIZE + fcs_size,
+            });
+        }
+        let mut cursor: ByteReader = ByteReader::new(frame);
+
+        let (_, _, _, type_or_length) = EthernetFrame::extract_header(&mut cursor)?;
+
+        let (frame_kind, ether_type, data_size) = if type_or_length <= MAX_8023_LENGTH {
+            let length = type_or_length as usize;
+            let llc_offset = cursor.position();
+            let available = frame
+                .len()
+                .checked_sub(llc_offset + fcs_size)
+                .ok_or_else(|| ParserError::HeaderTooShort {
+                    layer: "Ethernet Frame".to_string(),
+                    got: frame.len(),
+                    need: llc_offset + fcs_size,
+                })?;
+            if available < length {
+                return Err(ParserError::InconsistentLength {
+                    layer: "Ethernet 802.3 frame".to_string(),
+                    declared: length,
+                    available,
+                });
+            }
+
+            let (llc, snap) = EthernetFrame::extract_llc_snap(&mut cursor)?;
+            let data_size = length
+                .checked_sub(cursor.position() - llc_offset)
+                .ok_or_else(|| ParserError::InvalidLength("LLC header".to_string()))?;
+
+            let ether_type = match snap {
+                Some(SnapHeader {
+                    oui: [0, 0, 0],
+                    protocol_id,
+                }) => protocol_id,
+                _ => type_or_length,
+            };
+            let frame_kind = EthernetFrameKind::Ieee8023 {
+                length: type_or_length,
+                llc,
+                snap,
+            };
+
+            (frame_kind, ether_type, data_size)
+        } else {
+            // Any EtherType is accepted, its payload left raw if it has no parser, but the
+            // values between the largest length and the smallest EtherType mean neither.
+            if type_or_length < MIN_ETHER_TYPE {
+                return Err(ParserError::InvalidEtherType);
+            }
+
+            let header_end = cursor.position() + fcs_size;
+            let data_size =
+                frame
+                    .len()
+                    .checked_sub(header_end)
+                    .ok_or_else(|| ParserError::HeaderTooShort {
+                        layer: "Ethernet Frame".to_string(),
+                        got: frame.len(),
+                        need: header_end,
+                    })?;
+            (EthernetFrameKind::EthernetII, type_or_length, data_size)
+        };
+        let header_length = cursor.position();
+
+        cursor.read_slice(data_size, "EtherFrame_Data")?;
+
+        Ok(EthernetFrameRef {
+            bytes: &frame[..cursor.position()],
+            header_length,
+            ether_type,
+            frame_kind,
+        })
+    }
+
+    /// Builds the `EthernetFrame`, taking its payload from `source`, the buffer `bytes`
+    /// starts.
+    fn to_owned_in<'s, S: PayloadSource<'s> + ?Sized>(self, source: &S) -> EthernetFrame<'s> {
+        EthernetFrame {
+            header: EthernetFrameHeader {
+                mac_destination: self.mac_destination(),
+                mac_source: self.mac_source(),
+                s_tag: self.s_tag(),
+                q_tag: self.q_tag(),
+                ether_type: self.ether_type(),
+                frame_kind: self.frame_kind,
+                span: 0..self.header_length,
+                raw_header: self.header().to_vec(),
+            },
+            data: Box::new(source.payload(self.header_length..self.bytes.len())),
+        }
+    }
+
+    /// Returns the MAC address of the intended recipient.
+    pub fn mac_destination(&self) -> MacAddress {
+        self.mac_address(0)
+    }
+
+    /// Returns the MAC address of the sender.
+    pub fn mac_source(&self) -> MacAddress {
+        self.mac_address(MAC_ADDRESS_BYTES)
+    }
+
+    fn mac_address(&self, offset: usize) -> MacAddress {
+        MacAddress(
+            self.bytes[offset..offset + MAC_ADDRESS_BYTES]
+                .try_into()
+                .unwrap(),
+        )
+    }
+
+    /// Returns the 802.1ad service tag, as held in `EthernetFrameHeader::s_tag`.
+    pub fn s_tag(&self) -> Option<u32> {
+        self.tag_at(2 * MAC_ADDRESS_BYTES, TPID_SERVICE_VLAN)
+    }
+
+    /// Returns the 802.1Q tag, as held in `EthernetFrameHeader::q_tag`.
+    pub fn q_tag(&self) -> Option<u32> {
+        let offset = 2 * MAC_ADDRESS_BYTES + self.s_tag().map_or(0, |_| 4);
+        self.tag_at(offset, TPID_VLAN)
+    }
+
+    /// Returns the tag at `offset` if it starts with `tpid`.
+    fn tag_at(&self, offset: usize, tpid: u32) -> Option<u32> {
+        (be_u16(self.bytes, offset) as u32 == tpid).then(|| be_u32(self.bytes, offset))
+    }
+
+    /// Returns the decoded Tag Control Information of the 802.1Q tag, if the frame has one.
+    pub fn vlan_tag(&self) -> Option<VlanTag> {
+        self.q_tag().map(|q_tag| VlanTag::from(q_tag as u16))
+    }
+
+    /// Returns the EtherType of the payload, or for 802.3 frames the one carried by SNAP.
+    /// See `EthernetFrameHeader::ether_type`.
+    pub fn ether_type(&self) -> EtherType {
+        EtherType::from(self.ether_type)
+    }
+
+    /// Returns whether the frame uses Ethernet II or 802.3 framing.
+    pub fn frame_kind(&self) -> EthernetFrameKind {
+        self.frame_kind
+    }
+
+    /// Returns the header, including any VLAN tags and, for 802.3 frames, the LLC and SNAP
+    /// headers.
+    pub fn header(&self) -> &'a [u8] {
+        &self.bytes[..self.header_length]
+    }
+
+    /// Returns the payload, which excludes any FCS, and padding past an 802.3 length.
+    pub fn data(&self) -> &'a [u8] {
+        &self.bytes[self.header_length..]
+    }
+
+    /// Parses the payload as an IPv4 or IPv6 packet according to the EtherType, borrowing
+    /// from the same bytes. Payloads of any other EtherType have no borrowed form, so are
+    /// returned as `LayeredDataRef::Payload`.
+    ///
+    /// # Errors
+    ///
+    /// * Any error from parsing the IPv4 or IPv6 packet.
+    pub fn next_layer(&self) -> Result<LayeredDataRef<'a>, ParserError> {
+        let layer = match self.ether_type() {
+            EtherType::IPv4 => LayeredDataRef::Ipv4Data(Ipv4PacketRef::parse(self.data(), false)?),
+            EtherType::IPv6 => LayeredDataRef::Ipv6Data(Ipv6PacketRef::parse(self.data(), false)?),
+            _ => LayeredDataRef::Payload(self.data()),
+        };
+
+        Ok(layer)
+    }
+}
+
+impl<'a> From<EthernetFrameRef<'a>> for EthernetFrame<'a> {
+    /// Converts the frame into an `EthernetFrame` borrowing the same bytes, with its
+    /// payload left raw.
+    fn from(frame: EthernetFrameRef<'a>) -> Self {
+        frame.to_owned_in(&frame.bytes)
+    }
+}
+
+impl<'a> DeepParser<'a> for EthernetFrameRef<'a> {
+    /// Converts the frame into an `EthernetFrame`, and parses its payload from there.
+    fn parse_next_layer_with(
+        self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        EthernetFrame::from(self).parse_next_layer_with(options, depth)
+    }
 }
 
-impl EthernetFrame {
+impl<'a> EthernetFrame<'a> {
     /// Constructs an `EthernetFrame` from a slice of bytes.
     ///
     /// This function parses the bytes to form an Ethernet frame, accounting for the presence
@@ -116,93 +540,161 @@ impl EthernetFrame {
     /// configuration. Some network setups or packet capture software may strip the FCS during
     /// capture, hence the need for a flag to accommodate both scenarios.
     ///
+    /// The payload borrows from `frame`, as do the raw payloads of the layers parsed from
+    /// it. Use `into_owned` to keep the frame once `frame` is gone.
+    ///
     /// # Arguments
     ///
     /// * `frame`: A byte slice representing the Ethernet frame.
     /// * `fcs_enabled`: A boolean indicating whether the FCS should be expected and parsed.
-    ///  This is necessary  because not all packet captures include the FCS.
-    ///  It is typically stripped by the NIC before packet data is passed to the capturing
-    /// software, and only  included if the capture setup explicitly preserves it.
+    ///   This is necessary  because not all packet captures include the FCS.
+    ///   It is typically stripped by the NIC before packet data is passed to the capturing
+    ///   software, and only  included if the capture setup explicitly preserves it.
     ///
     /// # Returns
     ///
     /// A `Result` which is `Ok` containing the `EthernetFrame` if the byte slice
     /// could be parsed successfully, or an `Err` with `ParserError` if parsing fails.
     ///
+    /// When the type field holds a length of at most 1500, the frame is parsed as IEEE 802.3:
+    /// the LLC header, and SNAP if present, become part of the header, and the payload is
+    /// bounded by the length, so any padding after it is discarded.
+    ///
     /// # Errors
     ///
-    /// * `ParserError::InvalidLength`: If the byte slice is shorter than the minimum frame size.
+    /// * `ParserError::HeaderTooShort`: If the byte slice is shorter than an untagged header,
+    ///   i.e. 14 bytes, or 18 with the FCS. Frames below the 64-byte minimum allowed on the
+    ///   wire are accepted, as captures commonly strip their padding and FCS.
+    /// * `ParserError::InvalidEtherType`: If the type field holds a value from 1501 to 1535,
+    ///   which is neither an 802.3 length nor an EtherType.
+    /// * `ParserError::InvalidLength`: If an 802.3 length is too short to hold the LLC header.
+    /// * `ParserError::InconsistentLength`: If an 802.3 length runs past the end of the frame.
     /// * Other `ParserError` variants as determined by `extract_header` and `read_arbitrary_length`.
     ///
-    pub fn from_bytes(frame: &[u8], fcs_enabled: bool) -> Result<Self, ParserError> {
-        if frame.len() < constants::MIN_FRAME_SIZE {
-            return Err(ParserError::InvalidLength("Ethernet Frame".to_string()));
-        }
-        let mut cursor: Cursor<&[u8]> = Cursor::new(frame);
+    pub fn from_bytes(frame: &'a [u8], fcs_enabled: bool) -> Result<Self, ParserError> {
+        Self::parse(&frame, fcs_enabled)
+    }
 
-        let (mac_destination, mac_source, q_tag, ether_type) = Self::extract_header(&mut cursor)?;
+    /// Constructs an `EthernetFrame` whose payload shares `buf` rather than copying it.
+    ///
+    /// Behaves like `from_bytes`, except that the payload is left as a `LayeredData::Bytes`
+    /// slice of `buf`. Layers parsed from it by `parse_next_layer` share `buf` too, down to
+    /// the payload of a TCP segment or UDP datagram; application layers are copied.
+    #[cfg(feature = "bytes")]
+    pub fn from_bytes_buf(buf: &bytes::Bytes, fcs_enabled: bool) -> Result<Self, ParserError> {
+        Self::parse(buf, fcs_enabled)
+    }
 
-        let data = read_arbitrary_length(
-            &mut cursor,
-            Self::data_size(frame.len(), q_tag, fcs_enabled),
-            "EtherFrame_Data",
-        )?;
+    /// Constructs an `EthernetFrameRef` borrowing from `frame`, without allocating.
+    ///
+    /// Fails in the same cases as `from_bytes`. Layers parsed from it by
+    /// `EthernetFrameRef::next_layer` borrow from `frame` too.
+    pub fn from_bytes_ref(
+        frame: &[u8],
+        fcs_enabled: bool,
+    ) -> Result<EthernetFrameRef<'_>, ParserError> {
+        EthernetFrameRef::parse(frame, fcs_enabled)
+    }
 
-        Ok(EthernetFrame {
-            header: EthernetFrameHeader {
-                mac_destination,
-                mac_source,
-                q_tag,
-                ether_type,
-            },
-            data: Box::new(LayeredData::Payload(data)),
-        })
+    pub(crate) fn parse<S: PayloadSource<'a> + ?Sized>(
+        source: &S,
+        fcs_enabled: bool,
+    ) -> Result<Self, ParserError> {
+        Ok(EthernetFrameRef::parse(source.as_slice(), fcs_enabled)?.to_owned_in(source))
     }
 
     /// Extracts the Ethernet frame header from a byte stream.
     ///
-    /// This function parses the destination and source MAC addresses, optional VLAN tag (QTag),
-    /// and EtherType from the provided byte stream accessed via a cursor.
+    /// This function parses the destination and source MAC addresses, optional VLAN tags
+    /// (an 802.1ad S-Tag and an 802.1Q QTag), and the type field from the provided byte stream
+    /// accessed via a cursor.
     ///
     /// # Parameters
     /// * `cursor`: A mutable reference to a cursor over the byte slice containing the Ethernet frame.
     ///
     /// # Returns
-    /// * `Ok((MacAddress, MacAddress, Option<u32>, EtherType))`: A tuple containing the destination MAC
-    ///   address, the source MAC address, an optional VLAN tag (QTag), and the EtherType if successful.
+    /// * `Ok((MacAddress, MacAddress, VlanTags, u16))`: A tuple containing the destination MAC
+    ///   address, the source MAC address, the optional service tag (S-Tag) and VLAN tag (QTag),
+    ///   and the raw type field following the last tag, which is either an EtherType or an
+    ///   802.3 length.
     /// * `Err(ParserError)`: An error if the header could not be parsed, which could be due to
-    ///   insufficient data, unrecognized EtherType, or other parsing issues.
+    ///   insufficient data or other parsing issues.
     ///
     /// # Errors
     /// This function will return an error if the byte slice does not contain enough data for a
-    /// complete Ethernet header, if the EtherType is not one of the accepted types, or if any
-    /// other parsing issue occurs.
+    /// complete Ethernet header, or if any other parsing issue occurs.
     fn extract_header(
-        cursor: &mut Cursor<&[u8]>,
-    ) -> Result<(MacAddress, MacAddress, Option<u32>, EtherType), ParserError> {
-        let bytes = read_u128(cursor, "Ethernet_Header")?;
-        let mac_dest = Self::extract_mac_address(((bytes >> 80) & 0xFFFFFFFFFFFF) as u64);
-        let mac_src = Self::extract_mac_address(((bytes >> 32) & 0xFFFFFFFFFFFF) as u64);
-        let leftover_bytes = (bytes & 0xFFFFFFFF) as u32;
-
-        let (q_tag, ether_type) = match leftover_bytes >> 16 {
-            TPID_VLAN => {
-                let ether_type = read_u16(cursor, "Ether_Type")?;
-                (Some(leftover_bytes), ether_type)
-            }
-            _ => {
-                // QTag isn't present in the frame, hence we move the cursor
-                // back 2 positions.
-                cursor.set_position(cursor.position() - 2);
-                (None, (leftover_bytes >> 16) as u16)
-            }
+        cursor: &mut ByteReader,
+    ) -> Result<(MacAddress, MacAddress, VlanTags, u16), ParserError> {
+        // The two addresses span 96 bits, so are read in two parts that do not run past them.
+        let addresses = ((cursor.read_u64_be("Ethernet_Header")? as u128) << 32)
+            | cursor.read_u32_be("Ethernet_Header")? as u128;
+        let mac_dest = Self::extract_mac_address(((addresses >> 48) & 0xFFFFFFFFFFFF) as u64);
+        let mac_src = Self::extract_mac_address((addresses & 0xFFFFFFFFFFFF) as u64);
+
+        let mut type_or_tpid = cursor.read_u16_be("Ether_Type")? as u32;
+
+        let s_tag = if type_or_tpid == TPID_SERVICE_VLAN {
+            let tci = cursor.read_u16_be("S_Tag")? as u32;
+            trace::trace!(vlan_id = tci & VLAN_ID_MASK, "802.1ad service tag found");
+            type_or_tpid = cursor.read_u16_be("Ether_Type")? as u32;
+            Some((TPID_SERVICE_VLAN << 16) | tci)
+        } else {
+            None
         };
 
-        if !constants::ACCEPTED_ETHERTYPES.contains(&ether_type.to_be_bytes()) {
-            return Err(ParserError::InvalidEtherType);
-        }
+        let q_tag = if type_or_tpid == TPID_VLAN {
+            let tci = cursor.read_u16_be("Q_Tag")? as u32;
+            trace::trace!(vlan_id = tci & VLAN_ID_MASK, "802.1Q tag found");
+            type_or_tpid = cursor.read_u16_be("Ether_Type")? as u32;
+            Some((TPID_VLAN << 16) | tci)
+        } else {
+            None
+        };
 
-        Ok((mac_dest, mac_src, q_tag, EtherType::from(ether_type)))
+        Ok((mac_dest, mac_src, (s_tag, q_tag), type_or_tpid as u16))
+    }
+
+    /// Extracts the LLC header of an 802.3 frame, and its SNAP extension if both SAPs are
+    /// `0xAA`.
+    ///
+    /// # Parameters
+    /// * `cursor`: A cursor positioned just after the 802.3 length field.
+    ///
+    /// # Errors
+    /// Returns an error if the frame ends before the headers do.
+    fn extract_llc_snap(
+        cursor: &mut ByteReader,
+    ) -> Result<(LlcHeader, Option<SnapHeader>), ParserError> {
+        let dsap = cursor.read_u8("LLC_DSAP")?;
+        let ssap = cursor.read_u8("LLC_SSAP")?;
+        let control = cursor.read_u8("LLC_Control")?;
+
+        // Information and supervisory PDUs carry a second control byte.
+        let control = match control & LLC_UNNUMBERED_FORMAT {
+            LLC_UNNUMBERED_FORMAT => control as u16,
+            _ => u16::from_be_bytes([control, cursor.read_u8("LLC_Control")?]),
+        };
+
+        let snap = match (dsap, ssap) {
+            (SNAP_SAP, SNAP_SAP) => {
+                let oui = cursor.read_slice(SNAP_HEADER_SIZE - 2, "SNAP_OUI")?;
+                Some(SnapHeader {
+                    oui: [oui[0], oui[1], oui[2]],
+                    protocol_id: cursor.read_u16_be("SNAP_Protocol_Id")?,
+                })
+            }
+            _ => None,
+        };
+
+        Ok((
+            LlcHeader {
+                dsap,
+                ssap,
+                control,
+            },
+            snap,
+        ))
     }
 
     /// Extracts a MAC address from a 64-bit integer.
@@ -232,36 +724,265 @@ impl EthernetFrame {
         MacAddress::from_bytes(bytes)
     }
 
-    fn data_size(frame_size: usize, q_tag: Option<u32>, fcs_enabled: bool) -> usize {
-        let header_size_without_q_tag = 14; // Header size (excluding the VLAN field) is 14 bytes
-        let vlan_tag_size = q_tag.map_or(0, |_| 4); // VLAN tag is 4 bytes if present
-        let fcs_size = if fcs_enabled { 4 } else { 0 }; // Frame Check Sequence is 4 bytes
+    /// Serializes the frame back into its wire format, without an FCS.
+    ///
+    /// The type field of an 802.3 frame is recomputed from the payload, and its LLC and
+    /// SNAP headers are copied from `raw_header`. Short frames are not padded.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let payload = self.data.to_bytes();
 
-        // Calculate payload size by subtracting the header size and FCS from the frame size
-        frame_size - (header_size_without_q_tag + vlan_tag_size + fcs_size)
+        let mut frame = self.header_bytes(payload.len());
+        frame.extend_from_slice(&payload);
+        frame
     }
-}
 
-impl DeepParser for EthernetFrame {
-    fn parse_next_layer(mut self) -> Result<LayeredData, ParserError> {
-        let data = match &*self.data {
-            LayeredData::Payload(data) => data,
-            _ => return Err(ParserError::InvalidPayload),
+    /// Serializes the header for a payload of `payload_len` bytes, as done by `to_bytes`.
+    fn header_bytes(&self, payload_len: usize) -> Vec<u8> {
+        let header = &self.header;
+
+        let mut bytes = Vec::with_capacity(self.header.raw_header.len());
+        bytes.extend_from_slice(&header.mac_destination.0);
+        bytes.extend_from_slice(&header.mac_source.0);
+        for tag in [header.s_tag, header.q_tag].into_iter().flatten() {
+            bytes.extend_from_slice(&tag.to_be_bytes());
+        }
+
+        match header.frame_kind {
+            EthernetFrameKind::EthernetII => {
+                bytes.extend_from_slice(&u16::from(&header.ether_type).to_be_bytes());
+            }
+            EthernetFrameKind::Ieee8023 { .. } => {
+                let llc_offset = bytes.len() + 2;
+                let llc_snap = header.raw_header.get(llc_offset..).unwrap_or_default();
+                let length = (llc_snap.len() + payload_len).min(u16::MAX as usize) as u16;
+
+                bytes.extend_from_slice(&length.to_be_bytes());
+                bytes.extend_from_slice(llc_snap);
+            }
+        }
+
+        bytes
+    }
+
+    /// Returns a short description of the frame, e.g.
+    /// `6C:64:13:19:C8:C7 -> 0C:19:3C:FF:58:0C LLDP`.
+    pub fn describe(&self) -> String {
+        let ether_type = match self.header.ether_type {
+            EtherType::Other(ether_type) => format!("ethertype {ether_type:#06x}"),
+            ref ether_type => format!("{ether_type:?}"),
         };
 
-        let layered_data = match self.header.ether_type {
-            EtherType::IPv4 => {
-                let ipv4_packet = Ipv4Packet::from_bytes(data)?;
-                ipv4_packet.parse_next_layer()?
+        format!(
+            "{} -> {} {ether_type}",
+            self.header.mac_source, self.header.mac_destination
+        )
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Ethernet
+    }
+
+    /// Detaches the frame from the bytes it was parsed from, copying its payload if it is
+    /// borrowed.
+    pub fn into_owned(self) -> EthernetFrame<'static> {
+        EthernetFrame {
+            header: self.header,
+            data: Box::new(self.data.into_owned()),
+        }
+    }
+}
+
+/// Assembles an Ethernet II frame around an already parsed or constructed payload.
+///
+/// Unless set with `ether_type`, the EtherType is inferred from the payload: IPv4, IPv6,
+/// ARP, RARP, LLDP, MPLS and PPPoE layers map to their own EtherTypes. Other payloads,
+/// such as raw bytes, fall back to the IEEE 802 Local Experimental EtherType, `0x88B5`.
+///
+/// # Example
+///
+/// ```
+/// use net_sift::parsers::{
+///     definitions::{EtherType, LayeredData},
+///     ethernet_frame::{EthernetFrameBuilder, MacAddress},
+/// };
+///
+/// let frame = EthernetFrameBuilder::new()
+///     .dst_mac(MacAddress([0xFF; 6]))
+///     .src_mac(MacAddress([0x02, 0, 0, 0, 0, 1]))
+///     .vlan(100)
+///     .ether_type(EtherType::IPv4)
+///     .payload(LayeredData::Payload(vec![0x45].into()))
+///     .build();
+///
+/// assert_eq!(&frame.to_bytes()[12..18], &[0x81, 0x00, 0x00, 0x64, 0x08, 0x00]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EthernetFrameBuilder<'a> {
+    mac_destination: MacAddress,
+    mac_source: MacAddress,
+    s_tag: Option<u32>,
+    q_tag: Option<u32>,
+    ether_type: Option<EtherType>,
+    data: LayeredData<'a>,
+}
+
+impl Default for EthernetFrameBuilder<'_> {
+    fn default() -> Self {
+        EthernetFrameBuilder {
+            mac_destination: MacAddress([0; MAC_ADDRESS_BYTES]),
+            mac_source: MacAddress([0; MAC_ADDRESS_BYTES]),
+            s_tag: None,
+            q_tag: None,
+            ether_type: None,
+            data: LayeredData::Empty,
+        }
+    }
+}
+
+impl<'a> EthernetFrameBuilder<'a> {
+    /// Creates a builder for an untagged frame with zeroed addresses and no payload.
+    pub fn new() -> Self {
+        EthernetFrameBuilder::default()
+    }
+
+    pub fn dst_mac(mut self, mac_destination: MacAddress) -> Self {
+        self.mac_destination = mac_destination;
+        self
+    }
+
+    pub fn src_mac(mut self, mac_source: MacAddress) -> Self {
+        self.mac_source = mac_source;
+        self
+    }
+
+    /// Tags the frame with an 802.1Q header for `vlan_id`, keeping its lower 12 bits. The
+    /// priority and drop eligibility bits are left clear.
+    pub fn vlan(mut self, vlan_id: u16) -> Self {
+        self.q_tag = Some((TPID_VLAN << 16) | (vlan_id as u32 & VLAN_ID_MASK));
+        self
+    }
+
+    /// Tags the frame with an 802.1ad service tag for `vlan_id` ahead of any 802.1Q tag,
+    /// making it double-tagged (QinQ). Like `vlan`, only the VLAN ID bits are set.
+    pub fn service_vlan(mut self, vlan_id: u16) -> Self {
+        self.s_tag = Some((TPID_SERVICE_VLAN << 16) | (vlan_id as u32 & VLAN_ID_MASK));
+        self
+    }
+
+    /// Sets the EtherType explicitly, overriding the one inferred from the payload.
+    pub fn ether_type(mut self, ether_type: EtherType) -> Self {
+        self.ether_type = Some(ether_type);
+        self
+    }
+
+    pub fn payload(mut self, data: LayeredData<'a>) -> Self {
+        self.data = data;
+        self
+    }
+
+    /// Builds the frame, with `span` and `raw_header` describing the header `to_bytes`
+    /// will write.
+    pub fn build(self) -> EthernetFrame<'a> {
+        let ether_type = self.ether_type.unwrap_or_else(|| match &self.data {
+            LayeredData::Ipv4Data(_) => EtherType::IPv4,
+            LayeredData::Ipv6Data(_) => EtherType::IPv6,
+            LayeredData::ArpData(_) => EtherType::ARP,
+            LayeredData::RarpData(_) => EtherType::RARP,
+            LayeredData::LldpData(_) => EtherType::LLDP,
+            LayeredData::MplsData(_) => EtherType::MPLSUnicast,
+            LayeredData::PppoeData(packet) if packet.header.ppp_protocol.is_some() => {
+                EtherType::PPPoESession
             }
-            EtherType::IPv6 => {
-                let ipv6_packet = Ipv6Packet::from_bytes(data)?;
-                ipv6_packet.parse_next_layer()?
+            LayeredData::PppoeData(_) => EtherType::PPPoEDiscovery,
+            _ => EtherType::Other(LOCAL_EXPERIMENTAL_ETHER_TYPE),
+        });
+        let mut frame = EthernetFrame {
+            header: EthernetFrameHeader {
+                mac_destination: self.mac_destination,
+                mac_source: self.mac_source,
+                s_tag: self.s_tag,
+                q_tag: self.q_tag,
+                ether_type,
+                frame_kind: EthernetFrameKind::EthernetII,
+                span: 0..0,
+                raw_header: Vec::new(),
+            },
+            data: Box::new(self.data),
+        };
+        frame.header.raw_header = frame.header_bytes(0);
+        frame.header.span = 0..frame.header.raw_header.len();
+        frame
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for EthernetFrame<'a> {
+    type Error = ParserError;
+
+    /// Constructs an `EthernetFrame` from a slice holding a frame without its FCS. See
+    /// `from_bytes`.
+    fn try_from(frame: &'a [u8]) -> Result<Self, Self::Error> {
+        EthernetFrame::from_bytes(frame, false)
+    }
+}
+
+impl<'a> DeepParser<'a> for EthernetFrame<'a> {
+    /// Parses the payload according to the EtherType.
+    ///
+    /// The whole payload is handed to the next layer, which bounds itself by its own
+    /// length fields: an IPv4 or IPv6 packet shorter than the payload leaves the rest,
+    /// e.g. padding, out of its data, and one longer than the payload fails with
+    /// `ParserError::InconsistentLength` unless `options.allow_truncated` is set.
+    fn parse_next_layer_with(
+        mut self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Ethernet, depth);
+
+        let data = self.data.payload().ok_or(ParserError::InvalidPayload)?;
+
+        let result = match self.header.ether_type {
+            EtherType::IPv4 => options
+                .timed(LayerKind::Ipv4, || {
+                    Ipv4Packet::from_bytes_with(&*self.data, options)
+                })
+                .and_then(|ipv4_packet| ipv4_packet.parse_next_layer_with(options, depth)),
+            EtherType::IPv6 => options
+                .timed(LayerKind::Ipv6, || {
+                    Ipv6Packet::from_bytes_with(&*self.data, options)
+                })
+                .and_then(|ipv6_packet| ipv6_packet.parse_next_layer_with(options, depth)),
+            EtherType::MPLSUnicast | EtherType::MPLSMulticast => options
+                .timed(LayerKind::Mpls, || MplsPacket::parse(&*self.data))
+                .and_then(|mpls_packet| mpls_packet.parse_next_layer_with(options, depth)),
+            EtherType::LLDP => options
+                .timed(LayerKind::Lldp, || LldpPacket::from_bytes(data))
+                .and_then(|lldp_packet| lldp_packet.parse_next_layer_with(options, depth)),
+            EtherType::PPPoEDiscovery | EtherType::PPPoESession => options
+                .timed(LayerKind::Pppoe, || PppoePacket::parse(&*self.data))
+                .and_then(|pppoe_packet| pppoe_packet.parse_next_layer_with(options, depth)),
+            EtherType::ARP => options
+                .descend(depth)
+                .and_then(|_| options.timed(LayerKind::Arp, || ArpPacket::from_bytes(data)))
+                .map(LayeredData::ArpData),
+            EtherType::RARP => options
+                .descend(depth)
+                .and_then(|_| options.timed(LayerKind::Rarp, || ArpPacket::from_bytes(data)))
+                .map(LayeredData::RarpData),
+            // The EtherType is valid but there is no parser for it yet, so the raw
+            // payload is passed through untouched.
+            _ => {
+                trace::debug!(
+                    ether_type = ?self.header.ether_type,
+                    "no parser for EtherType, payload left raw"
+                );
+                return Ok(LayeredData::EthernetFrameData(self));
             }
-            _ => return Err(ParserError::UnSupportedEtherType),
         };
 
-        *self.data = layered_data;
+        options.recover(result, &mut self.data)?;
         Ok(LayeredData::EthernetFrameData(self))
     }
 }
@@ -0,0 +1,229 @@
+//! Conversions between net-sift's headers and their `etherparse` equivalents.
+//!
+//! Headers converted from `etherparse` are given the `raw_header` that `etherparse`
+//! serializes them to, and a `span` starting at zero. Fields `etherparse` does not model
+//! are dropped on the way there: the 802.1Q tag and LLC/SNAP headers of an Ethernet frame,
+//! the reserved flag of an IPv4 packet and the reserved bits of a TCP segment, other than
+//! the one `etherparse` calls `ns`.
+
+use super::{
+    definitions::{EtherType, IPType},
+    ethernet_frame::{EthernetFrameHeader, EthernetFrameKind, MacAddress},
+    ipv4::{Ipv4Flags, Ipv4PacketHeader},
+    ipv6::Ipv6PacketHeader,
+    tcp::{decode_options, encode_options, Flags, TcpSegmentHeader},
+    udp::UdpDatagramHeader,
+};
+
+use alloc::vec::Vec;
+use etherparse::{
+    Ethernet2Header, IpDscp, IpEcn, IpFragOffset, IpNumber, Ipv4Header, Ipv4Options, Ipv6FlowLabel,
+    Ipv6Header, TcpHeader, TcpOptions, UdpHeader,
+};
+
+const MAX_OPTIONS_SIZE: usize = 40;
+
+impl From<EthernetFrameHeader> for Ethernet2Header {
+    fn from(header: EthernetFrameHeader) -> Self {
+        Ethernet2Header {
+            source: header.mac_source.0,
+            destination: header.mac_destination.0,
+            ether_type: etherparse::EtherType(u16::from(&header.ether_type)),
+        }
+    }
+}
+
+impl From<Ethernet2Header> for EthernetFrameHeader {
+    fn from(header: Ethernet2Header) -> Self {
+        let raw_header = header.to_bytes().to_vec();
+
+        EthernetFrameHeader {
+            mac_destination: MacAddress(header.destination),
+            mac_source: MacAddress(header.source),
+            s_tag: None,
+            q_tag: None,
+            ether_type: EtherType::from(header.ether_type.0),
+            frame_kind: EthernetFrameKind::EthernetII,
+            span: 0..raw_header.len(),
+            raw_header,
+        }
+    }
+}
+
+impl From<Ipv4PacketHeader> for Ipv4Header {
+    /// Converts the header, zero-padding the options to a 32-bit boundary and capping them
+    /// at 40 bytes, as `Ipv4Packet::to_bytes` does.
+    fn from(header: Ipv4PacketHeader) -> Self {
+        let flags = header.flags_decoded();
+        let mut options = header.options.unwrap_or_default();
+        options.truncate(MAX_OPTIONS_SIZE);
+        options.resize(options.len().div_ceil(4) * 4, 0);
+
+        Ipv4Header {
+            dscp: IpDscp::try_new(header.type_of_service >> 2).unwrap_or_default(),
+            ecn: IpEcn::try_new(header.type_of_service & 0b11).unwrap_or(IpEcn::ZERO),
+            total_len: header.total_length,
+            identification: header.identification,
+            dont_fragment: flags.dont_fragment,
+            more_fragments: flags.more_fragments,
+            fragment_offset: IpFragOffset::try_new(header.fragment_offset & 0x1FFF)
+                .unwrap_or_default(),
+            time_to_live: header.time_to_live,
+            protocol: IpNumber(u8::from(&header.protocol)),
+            header_checksum: header.header_checksum,
+            source: header.source_address.octets(),
+            destination: header.destination_address.octets(),
+            options: Ipv4Options::try_from(&options[..]).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<Ipv4Header> for Ipv4PacketHeader {
+    fn from(header: Ipv4Header) -> Self {
+        let raw_header = header.to_bytes().to_vec();
+        let options = header.options.as_slice();
+        let flags = u8::from(Ipv4Flags {
+            reserved: false,
+            dont_fragment: header.dont_fragment,
+            more_fragments: header.more_fragments,
+        });
+
+        Ipv4PacketHeader {
+            version: 4,
+            type_of_service: (header.dscp.value() << 2) | header.ecn.value(),
+            internet_header_length: header.ihl(),
+            total_length: header.total_len,
+            identification: header.identification,
+            flags,
+            fragment_offset: header.fragment_offset.value(),
+            time_to_live: header.time_to_live,
+            protocol: IPType::from(header.protocol.0),
+            header_checksum: header.header_checksum,
+            source_address: header.source.into(),
+            destination_address: header.destination.into(),
+            options: (!options.is_empty()).then(|| options.to_vec()),
+            span: 0..raw_header.len(),
+            raw_header,
+        }
+    }
+}
+
+impl From<Ipv6PacketHeader> for Ipv6Header {
+    fn from(header: Ipv6PacketHeader) -> Self {
+        Ipv6Header {
+            traffic_class: header.traffic_class,
+            flow_label: Ipv6FlowLabel::try_new(header.flow_label & 0xFFFFF).unwrap_or_default(),
+            payload_length: header.payload_length,
+            next_header: IpNumber(u8::from(&header.next_header)),
+            hop_limit: header.hop_limit,
+            source: header.source_address.octets(),
+            destination: header.destination_address.octets(),
+        }
+    }
+}
+
+impl From<Ipv6Header> for Ipv6PacketHeader {
+    fn from(header: Ipv6Header) -> Self {
+        let raw_header = header.to_bytes().to_vec();
+
+        Ipv6PacketHeader {
+            version: 6,
+            traffic_class: header.traffic_class,
+            flow_label: header.flow_label.value(),
+            payload_length: header.payload_length,
+            next_header: IPType::from(header.next_header.0),
+            hop_limit: header.hop_limit,
+            source_address: header.source.into(),
+            destination_address: header.destination.into(),
+            extension_headers: Vec::new(),
+            span: 0..raw_header.len(),
+            raw_header,
+        }
+    }
+}
+
+impl From<TcpSegmentHeader> for TcpHeader {
+    /// Converts the header, encoding its `options`.
+    fn from(header: TcpSegmentHeader) -> Self {
+        let options = encode_options(&header.options);
+        let flags = header.flags;
+
+        TcpHeader {
+            source_port: header.source_port,
+            destination_port: header.destination_port,
+            sequence_number: header.sequence_number,
+            acknowledgment_number: header.acknowledgment_value,
+            ns: header.reserved & 1 != 0,
+            fin: flags.fin,
+            syn: flags.syn,
+            rst: flags.rst,
+            psh: flags.psh,
+            ack: flags.ack,
+            urg: flags.urg,
+            ece: flags.ece,
+            cwr: flags.cwr,
+            window_size: header.window_size,
+            checksum: header.checksum,
+            urgent_pointer: header.urg_pointer,
+            options: TcpOptions::try_from_slice(&options).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<TcpHeader> for TcpSegmentHeader {
+    fn from(header: TcpHeader) -> Self {
+        let raw_header: Vec<u8> = header.to_bytes().to_vec();
+
+        TcpSegmentHeader {
+            source_port: header.source_port,
+            destination_port: header.destination_port,
+            sequence_number: header.sequence_number,
+            acknowledgment_value: header.acknowledgment_number,
+            data_offset: header.data_offset(),
+            reserved: header.ns as u8,
+            flags: Flags {
+                cwr: header.cwr,
+                ece: header.ece,
+                urg: header.urg,
+                ack: header.ack,
+                psh: header.psh,
+                rst: header.rst,
+                syn: header.syn,
+                fin: header.fin,
+            },
+            window_size: header.window_size,
+            checksum: header.checksum,
+            urg_pointer: header.urgent_pointer,
+            // Left empty if malformed; `options_iter` then reports the error.
+            options: decode_options(header.options.as_slice()).unwrap_or_default(),
+            span: 0..raw_header.len(),
+            raw_header,
+        }
+    }
+}
+
+impl From<UdpDatagramHeader> for UdpHeader {
+    fn from(header: UdpDatagramHeader) -> Self {
+        UdpHeader {
+            source_port: header.source_port,
+            destination_port: header.destination_port,
+            length: header.length,
+            checksum: header.checksum,
+        }
+    }
+}
+
+impl From<UdpHeader> for UdpDatagramHeader {
+    fn from(header: UdpHeader) -> Self {
+        let raw_header = header.to_bytes().to_vec();
+
+        UdpDatagramHeader {
+            source_port: header.source_port,
+            destination_port: header.destination_port,
+            length: header.length,
+            checksum: header.checksum,
+            span: 0..raw_header.len(),
+            raw_header,
+        }
+    }
+}
@@ -0,0 +1,229 @@
+// A small subset of the pcap-filter(7) language, evaluated against parsed layers.
+//
+// Grammar, where `not` binds tightest and `and` and `or` share a precedence and associate
+// left to right, as in pcap:
+//
+//   expression := unary (("and" | "or") unary)*
+//   unary      := "not" unary | primary
+//   primary    := "(" expression ")" | protocol | ["src" | "dst"] ("port" N | "host" A)
+//   protocol   := "tcp" | "udp" | "icmp" | "icmp6" | "ip" | "ip6" | "arp"
+//
+// `&&`, `||` and `!` are accepted in place of `and`, `or` and `not`.
+
+use super::{
+    definitions::{LayerKind, LayeredData},
+    errors::ParserError,
+};
+
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::net::IpAddr;
+
+/// Which end of a packet a `port` or `host` primitive is matched against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    Source,      // `src`
+    Destination, // `dst`
+    Either,      // Neither qualifier given
+}
+
+/// A compiled filter expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Matches stacks containing a layer of the given kind.
+    Protocol(LayerKind),
+    /// Matches the ports of the outermost TCP or UDP layer.
+    Port(Endpoint, u16),
+    /// Matches the addresses of the outermost IPv4 or IPv6 packet.
+    Host(Endpoint, IpAddr),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    /// Compiles a filter expression, e.g. `tcp and port 80 and host 100.127.60.5`.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::InvalidFilter` - If the expression is empty, uses an unsupported
+    ///   keyword, or has a malformed port, address or parenthesis.
+    pub fn parse(expression: &str) -> Result<Self, ParserError> {
+        let tokens = tokenize(expression);
+        let mut parser = FilterParser {
+            tokens: &tokens,
+            position: 0,
+        };
+
+        let filter = parser.expression()?;
+        match parser.next() {
+            None => Ok(filter),
+            Some(token) => Err(invalid(format!("unexpected `{token}`"))),
+        }
+    }
+
+    /// Returns `true` if the parsed packet in `layered_data` satisfies the filter.
+    pub fn matches(&self, layered_data: &LayeredData) -> bool {
+        match self {
+            Filter::Protocol(kind) => layered_data.contains(*kind),
+            Filter::Port(endpoint, port) => endpoint.matches(
+                layered_data.source_port(),
+                layered_data.destination_port(),
+                port,
+            ),
+            Filter::Host(endpoint, address) => endpoint.matches(
+                layered_data.source_ip(),
+                layered_data.destination_ip(),
+                address,
+            ),
+            Filter::And(left, right) => left.matches(layered_data) && right.matches(layered_data),
+            Filter::Or(left, right) => left.matches(layered_data) || right.matches(layered_data),
+            Filter::Not(filter) => !filter.matches(layered_data),
+        }
+    }
+}
+
+impl Endpoint {
+    fn matches<T: PartialEq>(&self, source: Option<T>, destination: Option<T>, value: &T) -> bool {
+        let source = source.as_ref() == Some(value);
+        let destination = destination.as_ref() == Some(value);
+
+        match self {
+            Endpoint::Source => source,
+            Endpoint::Destination => destination,
+            Endpoint::Either => source || destination,
+        }
+    }
+}
+
+/// Splits an expression on whitespace, keeping parentheses and `!` as tokens of their own.
+fn tokenize(expression: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+
+    for (i, c) in expression.char_indices() {
+        let single = c == '(' || c == ')' || (c == '!' && start.is_none());
+        if c.is_whitespace() || single {
+            if let Some(start) = start.take() {
+                tokens.push(&expression[start..i]);
+            }
+            if single {
+                tokens.push(&expression[i..i + 1]);
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(start) = start {
+        tokens.push(&expression[start..]);
+    }
+
+    tokens
+}
+
+fn invalid(detail: String) -> ParserError {
+    ParserError::InvalidFilter(detail)
+}
+
+struct FilterParser<'a> {
+    tokens: &'a [&'a str],
+    position: usize,
+}
+
+impl<'a> FilterParser<'a> {
+    fn next(&mut self) -> Option<&'a str> {
+        let token = self.tokens.get(self.position).copied();
+        self.position += token.is_some() as usize;
+        token
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn expect_next(&mut self, expected: &str) -> Result<&'a str, ParserError> {
+        self.next()
+            .ok_or_else(|| invalid(format!("expected {expected} at end of expression")))
+    }
+
+    fn expression(&mut self) -> Result<Filter, ParserError> {
+        let mut filter = self.unary()?;
+
+        while let Some(token) = self.peek() {
+            let combine = match token {
+                "and" | "&&" => Filter::And,
+                "or" | "||" => Filter::Or,
+                _ => break,
+            };
+            self.next();
+            filter = combine(Box::new(filter), Box::new(self.unary()?));
+        }
+
+        Ok(filter)
+    }
+
+    fn unary(&mut self) -> Result<Filter, ParserError> {
+        match self.peek() {
+            Some("not" | "!") => {
+                self.next();
+                Ok(Filter::Not(Box::new(self.unary()?)))
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Filter, ParserError> {
+        let token = self.expect_next("a primitive")?;
+
+        let protocol = match token {
+            "(" => {
+                let filter = self.expression()?;
+                return match self.next() {
+                    Some(")") => Ok(filter),
+                    _ => Err(invalid("unbalanced parenthesis".to_string())),
+                };
+            }
+            "tcp" => LayerKind::Tcp,
+            "udp" => LayerKind::Udp,
+            "icmp" => LayerKind::Icmp,
+            "icmp6" => LayerKind::Icmpv6,
+            "ip" => LayerKind::Ipv4,
+            "ip6" => LayerKind::Ipv6,
+            "arp" => LayerKind::Arp,
+            "src" => return self.qualified(Endpoint::Source),
+            "dst" => return self.qualified(Endpoint::Destination),
+            "port" | "host" => {
+                self.position -= 1;
+                return self.qualified(Endpoint::Either);
+            }
+            other => return Err(invalid(format!("unsupported primitive `{other}`"))),
+        };
+
+        Ok(Filter::Protocol(protocol))
+    }
+
+    /// Parses a `port N` or `host A` primitive, after any `src` or `dst` qualifier.
+    fn qualified(&mut self, endpoint: Endpoint) -> Result<Filter, ParserError> {
+        match self.expect_next("`port` or `host`")? {
+            "port" => {
+                let value = self.expect_next("a port number")?;
+                let port = value
+                    .parse()
+                    .map_err(|_| invalid(format!("invalid port `{value}`")))?;
+                Ok(Filter::Port(endpoint, port))
+            }
+            "host" => {
+                let value = self.expect_next("an address")?;
+                let address = value
+                    .parse()
+                    .map_err(|_| invalid(format!("invalid address `{value}`")))?;
+                Ok(Filter::Host(endpoint, address))
+            }
+            other => Err(invalid(format!("expected `port` or `host`, got `{other}`"))),
+        }
+    }
+}
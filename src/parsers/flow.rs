@@ -0,0 +1,242 @@
+use super::definitions::{IPType, LayeredData};
+#[cfg(feature = "std")]
+use super::{definitions::DeepParser, errors::ParserError, frame_reader::TimestampedFrame};
+
+#[cfg(feature = "std")]
+use alloc::collections::BTreeMap;
+use core::net::IpAddr;
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime};
+
+/// The 5-tuple identifying the flow a packet belongs to.
+///
+/// Keys order by source address, destination address, source port, destination port and
+/// then protocol, so a collection of flows can be sorted deterministically. Protocols
+/// without ports, such as ICMP, use port 0.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FlowKey {
+    pub source: IpAddr,
+    pub destination: IpAddr,
+    pub source_port: u16,
+    pub destination_port: u16,
+    pub protocol: IPType,
+}
+
+impl FlowKey {
+    pub fn new(
+        source: IpAddr,
+        destination: IpAddr,
+        source_port: u16,
+        destination_port: u16,
+        protocol: IPType,
+    ) -> Self {
+        FlowKey {
+            source,
+            destination,
+            source_port,
+            destination_port,
+            protocol,
+        }
+    }
+
+    /// Extracts the key of the outermost IP packet within `layered_data`.
+    ///
+    /// The ports are taken from a TCP or UDP layer directly beneath the IP packet, and
+    /// are 0 for any other payload.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(FlowKey)` - The key of the outermost IPv4 or IPv6 packet.
+    /// * `None` - If `layered_data` contains no parsed IP packet.
+    pub fn from_layered_data(layered_data: &LayeredData) -> Option<Self> {
+        let (source, destination, protocol, payload) =
+            layered_data.layers().find_map(|layer| match layer {
+                LayeredData::Ipv4Data(packet) => Some((
+                    IpAddr::V4(packet.header.source_address),
+                    IpAddr::V4(packet.header.destination_address),
+                    packet.header.protocol.clone(),
+                    &*packet.data,
+                )),
+                LayeredData::Ipv6Data(packet) => Some((
+                    IpAddr::V6(packet.header.source_address),
+                    IpAddr::V6(packet.header.destination_address),
+                    packet.header.upper_layer_protocol(),
+                    &*packet.data,
+                )),
+                _ => None,
+            })?;
+
+        let (source_port, destination_port) = match payload {
+            LayeredData::TcpData(segment) => {
+                (segment.header.source_port, segment.header.destination_port)
+            }
+            LayeredData::UdpData(datagram) => (
+                datagram.header.source_port,
+                datagram.header.destination_port,
+            ),
+            _ => (0, 0),
+        };
+
+        Some(FlowKey::new(
+            source,
+            destination,
+            source_port,
+            destination_port,
+            protocol,
+        ))
+    }
+
+    /// Returns the key with its endpoints swapped, describing the opposite direction.
+    pub fn reversed(&self) -> Self {
+        FlowKey::new(
+            self.destination,
+            self.source,
+            self.destination_port,
+            self.source_port,
+            self.protocol.clone(),
+        )
+    }
+
+    /// Returns the key shared by both directions of the flow.
+    ///
+    /// The endpoints are ordered so that the lower `(address, port)` pair becomes the
+    /// source, meaning A→B and B→A canonicalize to the same key.
+    pub fn canonical(&self) -> Self {
+        if (self.source, self.source_port) <= (self.destination, self.destination_port) {
+            self.clone()
+        } else {
+            self.reversed()
+        }
+    }
+}
+
+/// The packet count and timing of a flow, as tracked by `FlowTable`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowStats {
+    /// The number of packets seen in either direction.
+    pub packets: u64,
+    /// The timestamp of the earliest packet.
+    pub first_seen: SystemTime,
+    /// The timestamp of the latest packet.
+    pub last_seen: SystemTime,
+    /// The shortest gap between a packet and the latest packet before it, or `None` until a
+    /// second packet is seen.
+    pub min_inter_arrival: Option<Duration>,
+    /// The longest gap between a packet and the latest packet before it, or `None` until a
+    /// second packet is seen.
+    pub max_inter_arrival: Option<Duration>,
+}
+
+#[cfg(feature = "std")]
+impl FlowStats {
+    fn new(timestamp: SystemTime) -> Self {
+        FlowStats {
+            packets: 1,
+            first_seen: timestamp,
+            last_seen: timestamp,
+            min_inter_arrival: None,
+            max_inter_arrival: None,
+        }
+    }
+
+    /// Accounts for a packet seen at `timestamp`. Packets older than `last_seen`, e.g.
+    /// reordered by the capture, arrive with a zero gap.
+    fn record(&mut self, timestamp: SystemTime) {
+        let gap = timestamp
+            .duration_since(self.last_seen)
+            .unwrap_or(Duration::ZERO);
+
+        self.packets += 1;
+        self.first_seen = self.first_seen.min(timestamp);
+        self.last_seen = self.last_seen.max(timestamp);
+        self.min_inter_arrival = Some(self.min_inter_arrival.map_or(gap, |min| min.min(gap)));
+        self.max_inter_arrival = Some(self.max_inter_arrival.map_or(gap, |max| max.max(gap)));
+    }
+
+    /// Returns the time between the first and the last packet.
+    pub fn duration(&self) -> Duration {
+        self.last_seen
+            .duration_since(self.first_seen)
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// Returns the mean gap between consecutive packets, or `None` for a single packet.
+    pub fn mean_inter_arrival(&self) -> Option<Duration> {
+        let gaps = u32::try_from(self.packets - 1).unwrap_or(u32::MAX);
+        (gaps > 0).then(|| self.duration() / gaps)
+    }
+}
+
+/// Aggregates packets into bidirectional flows, tracking when each flow was first and last
+/// seen and the gaps between its packets.
+///
+/// Flows are keyed by `FlowKey::canonical`, so both directions of a conversation count
+/// towards the same flow. Time is taken from the capture timestamps supplied by the caller.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct FlowTable {
+    flows: BTreeMap<FlowKey, FlowStats>,
+}
+
+#[cfg(feature = "std")]
+impl FlowTable {
+    pub fn new() -> Self {
+        FlowTable::default()
+    }
+
+    /// Accounts for a packet captured at `timestamp`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&FlowStats)` - The updated statistics of the packet's flow.
+    /// * `None` - If `layered_data` contains no parsed IP packet, so belongs to no flow.
+    pub fn record(
+        &mut self,
+        timestamp: SystemTime,
+        layered_data: &LayeredData,
+    ) -> Option<&FlowStats> {
+        let key = FlowKey::from_layered_data(layered_data)?.canonical();
+
+        let stats = self
+            .flows
+            .entry(key)
+            .and_modify(|stats| stats.record(timestamp))
+            .or_insert_with(|| FlowStats::new(timestamp));
+        Some(stats)
+    }
+
+    /// Deep-parses a frame read with `FrameReader::timestamped` and accounts for it at its
+    /// capture timestamp. See `record`.
+    ///
+    /// # Errors
+    ///
+    /// * Any error returned while parsing the layers the frame carries.
+    pub fn record_frame(
+        &mut self,
+        frame: TimestampedFrame,
+    ) -> Result<Option<&FlowStats>, ParserError> {
+        let layered_data = frame.frame.parse_next_layer()?;
+        Ok(self.record(frame.timestamp, &layered_data))
+    }
+
+    /// Returns the statistics of the flow `key` belongs to, in either direction.
+    pub fn get(&self, key: &FlowKey) -> Option<&FlowStats> {
+        self.flows.get(&key.canonical())
+    }
+
+    /// Returns an iterator over the flows, ordered by their canonical keys.
+    pub fn iter(&self) -> impl Iterator<Item = (&FlowKey, &FlowStats)> {
+        self.flows.iter()
+    }
+
+    /// Returns the number of flows seen.
+    pub fn len(&self) -> usize {
+        self.flows.len()
+    }
+
+    /// Returns `true` if no flow has been seen.
+    pub fn is_empty(&self) -> bool {
+        self.flows.is_empty()
+    }
+}
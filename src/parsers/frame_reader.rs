@@ -0,0 +1,498 @@
+// Pcap Record Structure:
+// +---------------------------------+
+// | Timestamp seconds (4 bytes)     |
+// +---------------------------------+
+// | Timestamp micro/nanos (4 bytes) |
+// +---------------------------------+
+// | Captured length (4 bytes)       |
+// +---------------------------------+
+// | Original length (4 bytes)       |
+// +---------------------------------+
+// | Frame data (captured length)    |
+// +---------------------------------+
+
+use super::{
+    errors::{ErrorSource, ParserError},
+    ethernet_frame::EthernetFrame,
+};
+
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    time::{Duration, SystemTime},
+};
+
+const PCAP_GLOBAL_HEADER_SIZE: usize = 24;
+const PCAP_RECORD_HEADER_SIZE: usize = 16;
+
+// Upper bound on a single record's captured length. Anything larger is treated as
+// a corrupt record header rather than an allocation request.
+const MAX_PCAP_RECORD_SIZE: usize = 262_144;
+
+const PCAP_MAGIC_MICROS: u32 = 0xA1B2C3D4;
+const PCAP_MAGIC_NANOS: u32 = 0xA1B23C4D;
+
+const PCAP_VERSION_MAJOR: u16 = 2;
+const PCAP_VERSION_MINOR: u16 = 4;
+const LINKTYPE_ETHERNET: u32 = 1;
+
+/// Describes how consecutive frames are delimited within the underlying byte stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Framing {
+    /// A classic libpcap capture of Ethernet frames: a 24-byte global header followed by
+    /// records, each prefixed with a 16-byte record header carrying the captured length.
+    Pcap,
+
+    /// Frames are stored back-to-back, each exactly `n` bytes long (e.g. a fixed snaplen).
+    FixedLength(usize),
+}
+
+/// An Ethernet frame together with the time it was captured.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimestampedFrame {
+    pub timestamp: SystemTime,
+    pub frame: EthernetFrame<'static>,
+}
+
+/// Reads Ethernet frames one at a time from any `Read` source.
+///
+/// Only a single frame is held in memory at any time, which makes the reader suitable
+/// for large captures. A frame that fails to parse is reported as an `Err` item and the
+/// reader moves on to the next frame. Errors from the underlying reader, or a stream that
+/// ends midway through a frame, are reported once and end the iteration.
+pub struct FrameReader<R: Read> {
+    reader: R,
+    framing: Framing,
+    fcs_enabled: bool,
+    big_endian: bool,
+    nanosecond_timestamps: bool,
+    header_consumed: bool,
+    finished: bool,
+}
+
+impl<R: Read> FrameReader<R> {
+    /// Creates a new `FrameReader`.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The byte source the frames are read from.
+    /// * `framing` - The strategy used to find the boundaries between frames.
+    /// * `fcs_enabled` - Whether each frame is expected to carry a Frame Check Sequence.
+    ///   This is forwarded to `EthernetFrame::from_bytes`.
+    ///
+    /// # Panics
+    ///
+    /// If `framing` is `Framing::FixedLength(0)`, as a stream cannot be split into empty
+    /// frames.
+    pub fn new(reader: R, framing: Framing, fcs_enabled: bool) -> Self {
+        assert!(
+            framing != Framing::FixedLength(0),
+            "fixed-length frames must be at least one byte long"
+        );
+
+        FrameReader {
+            reader,
+            framing,
+            fcs_enabled,
+            big_endian: false,
+            nanosecond_timestamps: false,
+            header_consumed: false,
+            finished: false,
+        }
+    }
+
+    /// Consumes the reader, returning the underlying byte source.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Turns the reader into an iterator over frames paired with their capture timestamps.
+    ///
+    /// Pcap records carry the time each frame was captured. Frames read with
+    /// `Framing::FixedLength` have no timestamp and are stamped with `UNIX_EPOCH`.
+    pub fn timestamped(self) -> TimestampedFrames<R> {
+        TimestampedFrames(self)
+    }
+
+    /// Reads the next raw frame from the stream without parsing it.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(Vec<u8>))` - The bytes of the next frame.
+    /// * `Ok(None)` - The stream ended cleanly on a frame boundary.
+    /// * `Err(ParserError)` - The stream could not be read or was truncated.
+    pub fn next_raw_frame(&mut self) -> Result<Option<Vec<u8>>, ParserError> {
+        Ok(self.next_record()?.map(|(_, frame)| frame))
+    }
+
+    /// Reads the next raw frame along with its capture timestamp, which is `UNIX_EPOCH`
+    /// unless the framing records one.
+    fn next_record(&mut self) -> Result<Option<(SystemTime, Vec<u8>)>, ParserError> {
+        match self.framing {
+            Framing::FixedLength(length) => {
+                let mut frame = vec![0; length];
+                match fill(&mut self.reader, &mut frame, "Frame")? {
+                    true => Ok(Some((SystemTime::UNIX_EPOCH, frame))),
+                    false => Ok(None),
+                }
+            }
+            Framing::Pcap => {
+                if !self.header_consumed {
+                    self.read_global_header()?;
+                }
+
+                let mut record_header = [0u8; PCAP_RECORD_HEADER_SIZE];
+                if !fill(&mut self.reader, &mut record_header, "Pcap_Record_Header")? {
+                    return Ok(None);
+                }
+
+                let seconds = self.u32_field(&record_header[0..4]) as u64;
+                let fraction = self.u32_field(&record_header[4..8]);
+                let since_epoch = match self.nanosecond_timestamps {
+                    true => Duration::new(seconds, 0) + Duration::from_nanos(fraction as u64),
+                    false => Duration::new(seconds, 0) + Duration::from_micros(fraction as u64),
+                };
+
+                let captured_length = self.u32_field(&record_header[8..12]) as usize;
+                if captured_length > MAX_PCAP_RECORD_SIZE {
+                    return Err(ParserError::InvalidLength("Pcap record".to_string()));
+                }
+
+                let mut frame = vec![0; captured_length];
+                if !fill(&mut self.reader, &mut frame, "Pcap_Record_Data")? {
+                    return Err(truncated("Pcap_Record_Data"));
+                }
+
+                Ok(Some((SystemTime::UNIX_EPOCH + since_epoch, frame)))
+            }
+        }
+    }
+
+    /// Reads and validates the pcap global header, recording the byte order and timestamp
+    /// resolution of the capture. Only captures of Ethernet frames are accepted, as every
+    /// record is parsed as one.
+    fn read_global_header(&mut self) -> Result<(), ParserError> {
+        let mut header = [0u8; PCAP_GLOBAL_HEADER_SIZE];
+        if !fill(&mut self.reader, &mut header, "Pcap_Global_Header")? {
+            return Err(truncated("Pcap_Global_Header"));
+        }
+
+        let magic = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        self.big_endian = match magic {
+            PCAP_MAGIC_MICROS | PCAP_MAGIC_NANOS => false,
+            _ if magic.swap_bytes() == PCAP_MAGIC_MICROS
+                || magic.swap_bytes() == PCAP_MAGIC_NANOS =>
+            {
+                true
+            }
+            _ => return Err(ParserError::InvalidPcapMagic(magic)),
+        };
+        self.nanosecond_timestamps =
+            PCAP_MAGIC_NANOS == magic || PCAP_MAGIC_NANOS == magic.swap_bytes();
+
+        let linktype = self.u32_field(&header[20..24]);
+        if linktype != LINKTYPE_ETHERNET {
+            return Err(ParserError::UnsupportedLinkType(linktype));
+        }
+
+        self.header_consumed = true;
+        Ok(())
+    }
+
+    fn u32_field(&self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        match self.big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        }
+    }
+}
+
+impl<R: Read> Iterator for FrameReader<R> {
+    type Item = Result<EthernetFrame<'static>, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        match self.next_raw_frame() {
+            Ok(Some(frame)) => Some(
+                EthernetFrame::from_bytes(&frame, self.fcs_enabled).map(EthernetFrame::into_owned),
+            ),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                // The stream itself is broken, so frame boundaries can no longer be trusted.
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Reads frames from a `FrameReader` along with their capture timestamps, returned by
+/// `FrameReader::timestamped`. Errors are reported as they are by `FrameReader`.
+pub struct TimestampedFrames<R: Read>(FrameReader<R>);
+
+impl<R: Read> TimestampedFrames<R> {
+    /// Consumes the iterator, returning the underlying byte source.
+    pub fn into_inner(self) -> R {
+        self.0.into_inner()
+    }
+}
+
+impl<R: Read> Iterator for TimestampedFrames<R> {
+    type Item = Result<TimestampedFrame, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let reader = &mut self.0;
+        if reader.finished {
+            return None;
+        }
+
+        match reader.next_record() {
+            Ok(Some((timestamp, frame))) => Some(
+                EthernetFrame::from_bytes(&frame, reader.fcs_enabled).map(|frame| {
+                    TimestampedFrame {
+                        timestamp,
+                        frame: frame.into_owned(),
+                    }
+                }),
+            ),
+            Ok(None) => {
+                reader.finished = true;
+                None
+            }
+            Err(e) => {
+                reader.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Reads a frame of exactly `len` bytes from `reader` and parses it.
+///
+/// This is the building block for capture formats other than pcap: callers that know
+/// where each frame starts and how long it is can parse it straight from the stream.
+///
+/// # Errors
+///
+/// * `ParserError::ExtractionError` - If the reader fails, or ends before `len` bytes.
+/// * Any error returned by `EthernetFrame::from_bytes`.
+pub fn read_frame<R: Read>(
+    reader: &mut R,
+    len: usize,
+    fcs_enabled: bool,
+) -> Result<EthernetFrame<'static>, ParserError> {
+    let mut frame = vec![0; len];
+    if !fill(reader, &mut frame, "Frame")? {
+        return Err(truncated("Frame"));
+    }
+
+    EthernetFrame::from_bytes(&frame, fcs_enabled).map(EthernetFrame::into_owned)
+}
+
+/// Reads Ethernet frames whose boundaries are found by a caller-supplied closure.
+///
+/// Before each frame, `next_len` is handed the underlying reader and returns the length
+/// of the frame that follows, or `None` once the stream is exhausted. The closure may
+/// consume any record header of its own format in the process. Errors follow the same
+/// rules as `FrameReader`: parse failures are reported per frame, while errors from the
+/// reader or the closure end the iteration.
+///
+/// # Example
+///
+/// ```
+/// use net_sift::parsers::frame_reader::LengthFramedReader;
+/// use std::io::{Cursor, Read};
+///
+/// // Each frame is preceded by its length as a big-endian u16.
+/// let frames = LengthFramedReader::new(Cursor::new(Vec::new()), false, |reader| {
+///     let mut length = [0u8; 2];
+///     match reader.read_exact(&mut length) {
+///         Ok(()) => Ok(Some(u16::from_be_bytes(length) as usize)),
+///         Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+///         Err(e) => Err(e),
+///     }
+/// });
+///
+/// assert_eq!(frames.count(), 0);
+/// ```
+pub struct LengthFramedReader<R, F> {
+    reader: R,
+    next_len: F,
+    fcs_enabled: bool,
+    finished: bool,
+}
+
+impl<R, F> LengthFramedReader<R, F>
+where
+    R: Read,
+    F: FnMut(&mut R) -> std::io::Result<Option<usize>>,
+{
+    /// Creates a new `LengthFramedReader`.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The byte source the frames are read from.
+    /// * `fcs_enabled` - Whether each frame is expected to carry a Frame Check Sequence.
+    /// * `next_len` - Yields the length of the next frame, or `None` at the end of the stream.
+    pub fn new(reader: R, fcs_enabled: bool, next_len: F) -> Self {
+        LengthFramedReader {
+            reader,
+            next_len,
+            fcs_enabled,
+            finished: false,
+        }
+    }
+
+    /// Consumes the reader, returning the underlying byte source.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads the next raw frame from the stream without parsing it.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(Vec<u8>))` - The bytes of the next frame.
+    /// * `Ok(None)` - `next_len` reported the end of the stream.
+    /// * `Err(ParserError)` - The stream could not be read or was truncated.
+    pub fn next_raw_frame(&mut self) -> Result<Option<Vec<u8>>, ParserError> {
+        let length = match (self.next_len)(&mut self.reader) {
+            Ok(Some(length)) => length,
+            Ok(None) => return Ok(None),
+            Err(e) => {
+                return Err(ParserError::ExtractionError {
+                    string: "Frame_Length".to_string(),
+                    source: ErrorSource::Io(e),
+                })
+            }
+        };
+
+        let mut frame = vec![0; length];
+        if !fill(&mut self.reader, &mut frame, "Frame")? {
+            return Err(truncated("Frame"));
+        }
+
+        Ok(Some(frame))
+    }
+}
+
+impl<R, F> Iterator for LengthFramedReader<R, F>
+where
+    R: Read,
+    F: FnMut(&mut R) -> std::io::Result<Option<usize>>,
+{
+    type Item = Result<EthernetFrame<'static>, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        match self.next_raw_frame() {
+            Ok(Some(frame)) => Some(
+                EthernetFrame::from_bytes(&frame, self.fcs_enabled).map(EthernetFrame::into_owned),
+            ),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Writes the global header of a little-endian, microsecond-resolution pcap capture of
+/// Ethernet frames, which must precede any records written with `write_pcap_record`.
+///
+/// # Errors
+///
+/// Returns any error from the underlying writer.
+pub fn write_pcap_header<W: Write>(w: &mut W) -> io::Result<()> {
+    let mut header = Vec::with_capacity(PCAP_GLOBAL_HEADER_SIZE);
+    header.extend_from_slice(&PCAP_MAGIC_MICROS.to_le_bytes());
+    header.extend_from_slice(&PCAP_VERSION_MAJOR.to_le_bytes());
+    header.extend_from_slice(&PCAP_VERSION_MINOR.to_le_bytes());
+    header.extend_from_slice(&0i32.to_le_bytes()); // Offset from UTC
+    header.extend_from_slice(&0u32.to_le_bytes()); // Timestamp accuracy
+    header.extend_from_slice(&(MAX_PCAP_RECORD_SIZE as u32).to_le_bytes()); // Snaplen
+    header.extend_from_slice(&LINKTYPE_ETHERNET.to_le_bytes());
+
+    w.write_all(&header)
+}
+
+/// Writes `frame` as a pcap record captured at `ts`, in the format started by
+/// `write_pcap_header`.
+///
+/// The frame is serialized with `EthernetFrame::to_bytes`, so no FCS is written.
+/// Timestamps are truncated to microseconds. Frames longer than the snaplen of
+/// 262,144 bytes are cut short, with the original length kept in the record header.
+///
+/// # Errors
+///
+/// * `ErrorKind::InvalidInput` - If `ts` is before the Unix epoch, or too late for the
+///   32-bit seconds field of a pcap record.
+/// * Any error from the underlying writer.
+pub fn write_pcap_record<W: Write>(
+    w: &mut W,
+    frame: &EthernetFrame,
+    ts: SystemTime,
+) -> io::Result<()> {
+    let since_epoch = ts
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "timestamp before Unix epoch"))?;
+    let seconds = u32::try_from(since_epoch.as_secs())
+        .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "timestamp past the pcap range"))?;
+
+    let bytes = frame.to_bytes();
+    let captured = &bytes[..bytes.len().min(MAX_PCAP_RECORD_SIZE)];
+
+    let mut record = Vec::with_capacity(PCAP_RECORD_HEADER_SIZE + captured.len());
+    record.extend_from_slice(&seconds.to_le_bytes());
+    record.extend_from_slice(&since_epoch.subsec_micros().to_le_bytes());
+    record.extend_from_slice(&(captured.len() as u32).to_le_bytes());
+    record.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    record.extend_from_slice(captured);
+
+    w.write_all(&record)
+}
+
+/// Fills `buffer` completely from `reader`.
+///
+/// Returns `Ok(false)` if the stream was already exhausted before the first byte,
+/// and an error if it ended part way through the buffer.
+fn fill<R: Read>(reader: &mut R, buffer: &mut [u8], field: &str) -> Result<bool, ParserError> {
+    let mut filled = 0;
+
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(truncated(field)),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => {
+                return Err(ParserError::ExtractionError {
+                    string: field.to_string(),
+                    source: ErrorSource::Io(e),
+                })
+            }
+        }
+    }
+
+    Ok(true)
+}
+
+fn truncated(field: &str) -> ParserError {
+    ParserError::ExtractionError {
+        string: field.to_string(),
+        source: ErrorSource::Io(ErrorKind::UnexpectedEof.into()),
+    }
+}
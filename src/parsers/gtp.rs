@@ -0,0 +1,321 @@
+// GTP-U Header (3GPP TS 29.281, section 5.1):
+//  0                   1                   2                   3
+//  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |Ver  |P|R|E|S|N| Message Type  |            Length             |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |               Tunnel Endpoint Identifier (TEID)               |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |        Sequence Number        | N-PDU Number  | Next Ext Type |  (if E, S or N)
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |                 Extension headers ...                          |  (if E)
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |                 T-PDU (inner IP packet) ...
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//
+// The Length field counts every byte after the TEID, optional fields included.
+
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
+use super::{
+    definitions::{DeepParser, LayerKind, LayeredData, ParseOptions},
+    errors::ParserError,
+    ipv4::Ipv4Packet,
+    ipv6::Ipv6Packet,
+    trace,
+    utils::{ByteReader, PayloadSource},
+};
+
+use alloc::{boxed::Box, string::ToString, vec::Vec};
+use core::ops::Range;
+
+const MANDATORY_HEADER_SIZE: usize = 8;
+const GTP_VERSION: u8 = 1;
+
+/// The E flag, set when the header is followed by extension headers.
+pub const EXTENSION_HEADER_FLAG: u8 = 0x04;
+
+/// The S flag, set when the sequence number field is meaningful.
+pub const SEQUENCE_NUMBER_FLAG: u8 = 0x02;
+
+/// The PN flag, set when the N-PDU number field is meaningful.
+pub const N_PDU_NUMBER_FLAG: u8 = 0x01;
+
+/// The message type of a G-PDU, which carries a user's IP packet.
+pub const G_PDU: u8 = 255;
+
+/// An extension header following the GTP-U header.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GtpExtensionHeader {
+    /// The type of this extension header, as given by the field preceding it.
+    pub extension_type: u8,
+
+    /// The contents of the extension header, without its length and next type fields.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub content: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GtpHeader {
+    /// The 3-bit version, always 1 for GTP-U.
+    pub version: u8,
+
+    /// The protocol type bit: 1 for GTP, 0 for GTP'.
+    pub protocol_type: u8,
+
+    /// The E, S and PN flags, in the three least significant bits.
+    pub flags: u8,
+
+    pub message_type: u8,
+
+    /// The number of bytes following the TEID, including the optional fields.
+    pub length: u16,
+
+    /// The Tunnel Endpoint Identifier of the tunnel the packet belongs to.
+    pub teid: u32,
+
+    /// The sequence number, if the S flag is set.
+    pub sequence_number: Option<u16>,
+
+    /// The N-PDU number, if the PN flag is set.
+    pub n_pdu_number: Option<u8>,
+
+    /// The extension headers in the order they appear. Empty unless the E flag is set.
+    pub extension_headers: Vec<GtpExtensionHeader>,
+
+    /// The position of the header, including its optional fields and extension headers,
+    /// within the bytes the packet was parsed from.
+    pub span: Range<usize>,
+
+    /// The header as received, optional fields and extension headers included, e.g. to
+    /// tunnel a rewritten payload under the same TEID and sequence number.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub raw_header: Vec<u8>,
+}
+
+impl GtpHeader {
+    /// Returns `true` if the packet is a G-PDU carrying a user's IP packet.
+    pub fn is_g_pdu(&self) -> bool {
+        self.message_type == G_PDU
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GtpPacket<'a> {
+    pub header: GtpHeader,
+    pub data: Box<LayeredData<'a>>,
+}
+
+impl<'a> GtpPacket<'a> {
+    /// Constructs a `GtpPacket` from a slice of bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `packets` - A byte slice beginning with the GTP-U header, typically the payload of
+    ///   a UDP datagram sent to port 2152.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, ParserError>` - A `GtpPacket` instance or a `ParserError`.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::HeaderTooShort` - If the slice is shorter than the mandatory header.
+    /// * `ParserError::MalformedMessage` - If the version is not 1, or an extension header
+    ///   has a length of zero.
+    /// * `ParserError::InconsistentLength` - If the length field runs past the slice.
+    /// * `ParserError::ExtractionError` - If the optional fields or extension headers run
+    ///   past the length field.
+    pub fn from_bytes(packets: &'a [u8]) -> Result<Self, ParserError> {
+        Self::parse(&packets)
+    }
+
+    /// Constructs a `GtpPacket` whose payload is taken from `source`.
+    pub(crate) fn parse<S: PayloadSource<'a> + ?Sized>(source: &S) -> Result<Self, ParserError> {
+        let packets = source.as_slice();
+        if packets.len() < MANDATORY_HEADER_SIZE {
+            return Err(ParserError::HeaderTooShort {
+                layer: "GTP-U packet".to_string(),
+                got: packets.len(),
+                need: MANDATORY_HEADER_SIZE,
+            });
+        }
+
+        let mut cursor = ByteReader::new(packets);
+
+        let first_byte = cursor.read_u8("GTP_Flags")?;
+        let version = first_byte >> 5;
+        if version != GTP_VERSION {
+            return Err(malformed("version is not 1"));
+        }
+        let protocol_type = (first_byte >> 4) & 0x01;
+        let flags = first_byte & 0x07;
+        let message_type = cursor.read_u8("GTP_Message_Type")?;
+        let length = cursor.read_u16_be("GTP_Length")?;
+        let teid = cursor.read_u32_be("GTP_TEID")?;
+
+        let declared = length as usize;
+        let available = cursor.remaining();
+        if declared > available {
+            return Err(ParserError::InconsistentLength {
+                layer: "GTP-U packet".to_string(),
+                declared,
+                available,
+            });
+        }
+        // Bytes past the length field, such as Ethernet padding, are not part of the packet.
+        let mut cursor = ByteReader::new(&packets[..MANDATORY_HEADER_SIZE + declared]);
+        cursor.read_slice(MANDATORY_HEADER_SIZE, "GTP_Header")?;
+
+        let mut sequence_number = None;
+        let mut n_pdu_number = None;
+        let mut extension_headers = Vec::new();
+
+        // The optional fields are present together if any of the flags is set, but each
+        // is only meaningful if its own flag is.
+        if flags != 0 {
+            let sequence = cursor.read_u16_be("GTP_Sequence_Number")?;
+            let n_pdu = cursor.read_u8("GTP_N_PDU_Number")?;
+            let mut next_type = cursor.read_u8("GTP_Next_Extension_Header_Type")?;
+
+            sequence_number = (flags & SEQUENCE_NUMBER_FLAG != 0).then_some(sequence);
+            n_pdu_number = (flags & N_PDU_NUMBER_FLAG != 0).then_some(n_pdu);
+
+            if flags & EXTENSION_HEADER_FLAG == 0 {
+                next_type = 0;
+            }
+            while next_type != 0 {
+                // The length counts 4-byte units, including itself and the next type field.
+                let units = cursor.read_u8("GTP_Extension_Header_Length")? as usize;
+                if units == 0 {
+                    return Err(malformed("extension header has a length of zero"));
+                }
+                let content = cursor.read_slice(units * 4 - 2, "GTP_Extension_Header")?;
+
+                extension_headers.push(GtpExtensionHeader {
+                    extension_type: next_type,
+                    content: content.to_vec(),
+                });
+                next_type = cursor.read_u8("GTP_Next_Extension_Header_Type")?;
+            }
+        }
+
+        let header_end = cursor.position();
+
+        Ok(GtpPacket {
+            header: GtpHeader {
+                version,
+                protocol_type,
+                flags,
+                message_type,
+                length,
+                teid,
+                sequence_number,
+                n_pdu_number,
+                extension_headers,
+                span: 0..header_end,
+                raw_header: packets[..header_end].to_vec(),
+            },
+            data: Box::new(source.payload(header_end..MANDATORY_HEADER_SIZE + declared)),
+        })
+    }
+
+    /// Serializes the header, its optional fields and extension headers, and the payload
+    /// back into their wire format, recomputing the length field.
+    ///
+    /// A sequence or N-PDU number whose flag is clear is written as zero.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = &self.header;
+
+        let mut optional = Vec::new();
+        if header.flags != 0 {
+            optional.extend_from_slice(&header.sequence_number.unwrap_or(0).to_be_bytes());
+            optional.push(header.n_pdu_number.unwrap_or(0));
+
+            let extension_headers = match header.flags & EXTENSION_HEADER_FLAG {
+                0 => &[][..],
+                _ => &header.extension_headers[..],
+            };
+            for extension_header in extension_headers {
+                optional.push(extension_header.extension_type);
+                // The length counts 4-byte units, including itself and the next type field.
+                optional.push(((extension_header.content.len() + 2) / 4) as u8);
+                optional.extend_from_slice(&extension_header.content);
+            }
+            optional.push(0);
+        }
+
+        let payload = self.data.to_bytes();
+        let length = (optional.len() + payload.len()).min(u16::MAX as usize) as u16;
+
+        let mut bytes = Vec::with_capacity(MANDATORY_HEADER_SIZE + length as usize);
+        bytes.push((header.version << 5) | ((header.protocol_type & 0x01) << 4) | header.flags);
+        bytes.push(header.message_type);
+        bytes.extend_from_slice(&length.to_be_bytes());
+        bytes.extend_from_slice(&header.teid.to_be_bytes());
+        bytes.extend_from_slice(&optional);
+        bytes.extend_from_slice(&payload);
+        bytes
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Gtp
+    }
+
+    /// Detaches the packet from the bytes it was parsed from, copying its payload if it is
+    /// borrowed.
+    pub fn into_owned(self) -> GtpPacket<'static> {
+        GtpPacket {
+            header: self.header,
+            data: Box::new(self.data.into_owned()),
+        }
+    }
+}
+
+impl<'a> DeepParser<'a> for GtpPacket<'a> {
+    /// Parses the IPv4 or IPv6 packet carried by a G-PDU, and everything nested within it,
+    /// telling the two apart by the version nibble. Other messages, such as echo requests,
+    /// and G-PDUs carrying anything else are left as raw data.
+    fn parse_next_layer_with(
+        mut self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Gtp, depth);
+
+        if !self.header.is_g_pdu() {
+            return Ok(LayeredData::GtpData(self));
+        }
+
+        let data = self.data.payload().ok_or(ParserError::InvalidPayload)?;
+
+        let result = match data.first().map(|byte| byte >> 4) {
+            Some(4) => options
+                .timed(LayerKind::Ipv4, || {
+                    Ipv4Packet::from_bytes_with(&*self.data, options)
+                })
+                .and_then(|packet| packet.parse_next_layer_with(options, depth)),
+            Some(6) => options
+                .timed(LayerKind::Ipv6, || {
+                    Ipv6Packet::from_bytes_with(&*self.data, options)
+                })
+                .and_then(|packet| packet.parse_next_layer_with(options, depth)),
+            _ => return Ok(LayeredData::GtpData(self)),
+        };
+
+        options.recover(result, &mut self.data)?;
+        Ok(LayeredData::GtpData(self))
+    }
+}
+
+fn malformed(detail: &str) -> ParserError {
+    ParserError::MalformedMessage {
+        layer: "GTP-U packet".to_string(),
+        detail: detail.to_string(),
+    }
+}
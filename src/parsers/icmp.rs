@@ -11,31 +11,123 @@
  *  +---------------------------------------------------------------+
  */
 
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
 use super::{
-    definitions::{DeepParser, LayeredData},
+    definitions::{DeepParser, LayerKind, LayeredData, ParseOptions},
     errors::ParserError,
-    utils::{read_arbitrary_length, read_u64},
+    ipv4::Ipv4Packet,
+    ipv6::Ipv6Packet,
+    trace,
+    utils::{internet_checksum, transport_checksum, ByteReader, PayloadSource},
 };
 
-use std::io::Cursor;
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{net::Ipv6Addr, ops::Range};
 
 const DATA_OFFSET_OR_MIN_SIZE: usize = 8;
+const CHECKSUM_OFFSET: usize = 2;
+const ICMPV6_NEXT_HEADER: u8 = 58;
+
+// Error messages that quote the IP header and leading payload of the offending packet.
+const DESTINATION_UNREACHABLE: u8 = 3;
+const SOURCE_QUENCH: u8 = 4;
+const REDIRECT: u8 = 5;
+const TIME_EXCEEDED: u8 = 11;
+const PARAMETER_PROBLEM: u8 = 12;
+
+const ECHO_REPLY: u8 = 0;
+const ECHO_REQUEST: u8 = 8;
+const TIMESTAMP: u8 = 13;
+const TIMESTAMP_REPLY: u8 = 14;
+
+/// The reason given by a Destination Unreachable message (RFC 792 and RFC 1122).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnreachableCode {
+    NetUnreachable,
+    HostUnreachable,
+    ProtocolUnreachable,
+    PortUnreachable,
+    FragmentationNeeded, // The packet was too big and its Don't Fragment flag set.
+    SourceRouteFailed,
+    AdministrativelyProhibited,
+    Other(u8), // Codes without a variant are preserved
+}
+
+impl From<u8> for UnreachableCode {
+    fn from(code: u8) -> Self {
+        match code {
+            0 => Self::NetUnreachable,
+            1 => Self::HostUnreachable,
+            2 => Self::ProtocolUnreachable,
+            3 => Self::PortUnreachable,
+            4 => Self::FragmentationNeeded,
+            5 => Self::SourceRouteFailed,
+            13 => Self::AdministrativelyProhibited,
+            other => Self::Other(other),
+        }
+    }
+}
 
-#[derive(Debug, PartialEq)]
+/// The type of an ICMP message, with its code and the fields of the rest of the header
+/// decoded where they have a meaning of their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IcmpType {
+    EchoReply {
+        identifier: u16,
+        sequence_number: u16,
+    },
+    DestinationUnreachable(UnreachableCode),
+    SourceQuench,
+    Redirect {
+        code: u8,
+    },
+    EchoRequest {
+        identifier: u16,
+        sequence_number: u16,
+    },
+    /// The time to live reached zero in transit, or otherwise fragment reassembly timed out.
+    TimeExceeded {
+        in_transit: bool,
+    },
+    ParameterProblem {
+        code: u8,
+    },
+    Timestamp,
+    TimestampReply,
+    Other(u8), // Types without a variant are preserved
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct IcmpPacketHeader {
     pub icmp_type: u8,       // Type of ICMP message.
     pub icmp_code: u8,       // Subtype to further specify the message.
     pub checksum: u16,       // Error-checking data calculated from the ICMP message.
     pub rest_of_header: u32, // Remaining data in the header (depends on type and code).
+    pub span: Range<usize>,  // Position of the header within the parsed bytes.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub raw_header: Vec<u8>, // The first 8 bytes as received, covered by the checksum.
 }
 
-#[derive(Debug, PartialEq)]
-pub struct IcmpPacket {
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct IcmpPacket<'a> {
     pub header: IcmpPacketHeader,
-    pub data: Box<LayeredData>,
+    pub data: Box<LayeredData<'a>>,
+    /// The bytes the packet was parsed from, if it was constructed with
+    /// `from_bytes_keep_raw`. Left unset otherwise, as keeping them doubles the memory
+    /// held by each packet.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub raw: Option<Vec<u8>>,
 }
 
-impl IcmpPacket {
+impl<'a> IcmpPacket<'a> {
     /// Constructs a new IcmpPacket from a slice of bytes.
     ///
     /// The function expects a byte slice representing a full ICMP packet and returns an
@@ -48,33 +140,64 @@ impl IcmpPacket {
     /// # Returns:
     ///
     /// * `Result<Self, ParserError>` - An IcmpPacket instance or a ParserError.
-    pub fn from_bytes(packets: &[u8]) -> Result<Self, ParserError> {
+    pub fn from_bytes(packets: &'a [u8]) -> Result<Self, ParserError> {
+        Self::parse(&packets)
+    }
+
+    /// Constructs an `IcmpPacket` whose payload is taken from `source`.
+    pub(crate) fn parse<S: PayloadSource<'a> + ?Sized>(source: &S) -> Result<Self, ParserError> {
+        let packets = source.as_slice();
         if packets.len() < DATA_OFFSET_OR_MIN_SIZE {
-            return Err(ParserError::InvalidLength("ICMP packet".to_string()));
+            return Err(ParserError::HeaderTooShort {
+                layer: "ICMP packet".to_string(),
+                got: packets.len(),
+                need: DATA_OFFSET_OR_MIN_SIZE,
+            });
         }
 
-        let mut cursor = Cursor::new(packets);
+        let mut cursor = ByteReader::new(packets);
 
         let (icmp_type, icmp_code, checksum, rest_of_header) =
             Self::extract_icmp_header_fields(&mut cursor)?;
 
-        let data = read_arbitrary_length(
-            &mut cursor,
-            packets.len() - DATA_OFFSET_OR_MIN_SIZE,
-            "ICMP_Data",
-        )?;
-
         Ok(IcmpPacket {
             header: IcmpPacketHeader {
                 icmp_type,
                 icmp_code,
                 checksum,
                 rest_of_header,
+                span: 0..DATA_OFFSET_OR_MIN_SIZE,
+                raw_header: packets[..DATA_OFFSET_OR_MIN_SIZE].to_vec(),
             },
-            data: Box::new(LayeredData::Payload(data)),
+            data: Box::new(source.payload(DATA_OFFSET_OR_MIN_SIZE..packets.len())),
+            raw: None,
         })
     }
 
+    /// Constructs an `IcmpPacket` like `from_bytes`, also keeping a copy of `packets` in
+    /// `raw` for `verify_stored_checksum`.
+    ///
+    /// The copy doubles the memory held by the packet, so use `from_bytes` unless the
+    /// original bytes are needed after parsing.
+    pub fn from_bytes_keep_raw(packets: &'a [u8]) -> Result<Self, ParserError> {
+        let mut packet = Self::from_bytes(packets)?;
+        packet.raw = Some(packets.to_vec());
+        Ok(packet)
+    }
+
+    /// Serializes the message back into its wire format, with the checksum as parsed.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = &self.header;
+
+        let mut bytes = Vec::with_capacity(DATA_OFFSET_OR_MIN_SIZE);
+        bytes.push(header.icmp_type);
+        bytes.push(header.icmp_code);
+        bytes.extend_from_slice(&header.checksum.to_be_bytes());
+        bytes.extend_from_slice(&header.rest_of_header.to_be_bytes());
+        bytes.extend_from_slice(&self.data.to_bytes());
+        bytes
+    }
+
     /// Extracts fields from the ICMP header.
     ///
     /// This function reads the first 8 bytes of an ICMP message, parses the bytes, and extracts the
@@ -92,9 +215,9 @@ impl IcmpPacket {
     /// * `Err(ParserError)`: An error occurred during reading from the byte stream, possibly
     ///   because the stream ended prematurely.
     fn extract_icmp_header_fields(
-        cursor: &mut Cursor<&[u8]>,
+        cursor: &mut ByteReader,
     ) -> Result<(u8, u8, u16, u32), ParserError> {
-        let bytes = read_u64(cursor, "Type_Code_Checksum_Header")?;
+        let bytes = cursor.read_u64_be("Type_Code_Checksum_Header")?;
 
         let icmp_type = (bytes >> 56) as u8;
         let icmp_code = ((bytes >> 48) & 0xFF) as u8;
@@ -103,10 +226,197 @@ impl IcmpPacket {
 
         Ok((icmp_type, icmp_code, checksum, rest_of_header))
     }
+
+    /// Verifies the checksum of an ICMP (IPv4) message.
+    ///
+    /// The checksum is recomputed over the whole of `raw`, with the checksum field treated
+    /// as zero, and compared against the parsed `checksum`.
+    ///
+    /// # Arguments
+    /// - `raw`: The raw ICMP message, i.e. the header followed by the payload.
+    ///
+    /// # Returns
+    /// - `true` if the computed checksum matches `checksum`, `false` otherwise or if `raw`
+    ///   is too short to hold an ICMP header.
+    pub fn verify_checksum(&self, raw: &[u8]) -> bool {
+        if raw.len() < DATA_OFFSET_OR_MIN_SIZE {
+            return false;
+        }
+
+        let mut message = raw.to_vec();
+        message[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 2].copy_from_slice(&[0, 0]);
+
+        internet_checksum(&message) == self.header.checksum
+    }
+
+    /// Verifies the checksum of an ICMP message against the bytes kept in `raw`, like
+    /// `verify_checksum`.
+    ///
+    /// # Returns
+    /// - `None` if the packet was not constructed with `from_bytes_keep_raw`.
+    pub fn verify_stored_checksum(&self) -> Option<bool> {
+        let raw = self.raw.as_deref()?;
+        Some(self.verify_checksum(raw))
+    }
+
+    /// Verifies the checksum of an ICMPv6 message.
+    ///
+    /// Unlike ICMP over IPv4, the ICMPv6 checksum also covers a pseudo-header built from
+    /// the enclosing IPv6 packet (RFC 8200, section 8.1): the source and destination
+    /// addresses, the message length and the ICMPv6 next header value (58).
+    ///
+    /// # Arguments
+    /// - `raw`: The raw ICMPv6 message, i.e. the header followed by the payload.
+    /// - `source`: The source address of the enclosing IPv6 packet.
+    /// - `destination`: The final destination address of the enclosing IPv6 packet.
+    ///
+    /// # Returns
+    /// - `true` if the computed checksum matches `checksum`, `false` otherwise or if `raw`
+    ///   is too short to hold an ICMP header.
+    pub fn verify_checksum_v6(&self, raw: &[u8], source: Ipv6Addr, destination: Ipv6Addr) -> bool {
+        if raw.len() < DATA_OFFSET_OR_MIN_SIZE {
+            return false;
+        }
+
+        transport_checksum(
+            raw,
+            CHECKSUM_OFFSET,
+            source.into(),
+            destination.into(),
+            ICMPV6_NEXT_HEADER,
+        ) == Some(self.header.checksum)
+    }
+
+    /// Returns `true` if the message reports an error with another packet, and so quotes
+    /// that packet's IP header followed by the first 8 bytes of its payload.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self.header.icmp_type,
+            DESTINATION_UNREACHABLE | SOURCE_QUENCH | REDIRECT | TIME_EXCEEDED | PARAMETER_PROBLEM
+        )
+    }
+
+    /// Parses the packet quoted by an error message, e.g. Destination Unreachable or Time
+    /// Exceeded, revealing the flow that triggered the error.
+    ///
+    /// The quoted packet is normally cut short of the length its header declares, so its
+    /// payload holds just the bytes that were quoted, left unparsed.
+    ///
+    /// # Returns
+    ///
+    /// * `LayeredData::Ipv4Data` or `LayeredData::Ipv6Data` - The quoted packet, chosen by
+    ///   its version.
+    /// * A clone of `data` - If the message is not an error, or the quoted IP header is
+    ///   truncated or malformed.
+    pub fn embedded_packet(&self) -> LayeredData<'a> {
+        let data = match self.data.payload() {
+            Some(data) if self.is_error() => data,
+            _ => return (*self.data).clone(),
+        };
+
+        let embedded = match data.first().map(|byte| byte >> 4) {
+            Some(4) => Ipv4Packet::from_truncated_bytes(&*self.data).map(LayeredData::Ipv4Data),
+            Some(6) => Ipv6Packet::from_truncated_bytes(&*self.data).map(LayeredData::Ipv6Data),
+            _ => Err(ParserError::InvalidPayload),
+        };
+
+        embedded.unwrap_or_else(|_| (*self.data).clone())
+    }
+
+    /// Decodes the type of the message, along with its code and, for echo messages, the
+    /// identifier and sequence number held in `rest_of_header`.
+    pub fn decoded_type(&self) -> IcmpType {
+        let header = &self.header;
+        let identifier = (header.rest_of_header >> 16) as u16;
+        let sequence_number = header.rest_of_header as u16;
+
+        match header.icmp_type {
+            ECHO_REPLY => IcmpType::EchoReply {
+                identifier,
+                sequence_number,
+            },
+            DESTINATION_UNREACHABLE => {
+                IcmpType::DestinationUnreachable(UnreachableCode::from(header.icmp_code))
+            }
+            SOURCE_QUENCH => IcmpType::SourceQuench,
+            REDIRECT => IcmpType::Redirect {
+                code: header.icmp_code,
+            },
+            ECHO_REQUEST => IcmpType::EchoRequest {
+                identifier,
+                sequence_number,
+            },
+            TIME_EXCEEDED => IcmpType::TimeExceeded {
+                in_transit: header.icmp_code == 0,
+            },
+            PARAMETER_PROBLEM => IcmpType::ParameterProblem {
+                code: header.icmp_code,
+            },
+            TIMESTAMP => IcmpType::Timestamp,
+            TIMESTAMP_REPLY => IcmpType::TimestampReply,
+            other => IcmpType::Other(other),
+        }
+    }
+
+    /// Returns a short description of the message, e.g. `echo request id 1 seq 44`, or its
+    /// type and code for messages without a name here.
+    pub fn describe(&self) -> String {
+        let code = self.header.icmp_code;
+
+        match self.decoded_type() {
+            IcmpType::EchoReply {
+                identifier,
+                sequence_number,
+            } => format!("echo reply id {identifier} seq {sequence_number}"),
+            IcmpType::EchoRequest {
+                identifier,
+                sequence_number,
+            } => format!("echo request id {identifier} seq {sequence_number}"),
+            IcmpType::DestinationUnreachable(_) => format!("destination unreachable code {code}"),
+            IcmpType::Redirect { code } => format!("redirect code {code}"),
+            IcmpType::TimeExceeded { in_transit: true } => "time exceeded in transit".to_string(),
+            IcmpType::TimeExceeded { in_transit: false } => {
+                "time exceeded in reassembly".to_string()
+            }
+            IcmpType::ParameterProblem { code } => format!("parameter problem code {code}"),
+            _ => format!("type {} code {code}", self.header.icmp_type),
+        }
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Icmp
+    }
+
+    /// Detaches the packet from the bytes it was parsed from, copying its payload if it is
+    /// borrowed.
+    pub fn into_owned(self) -> IcmpPacket<'static> {
+        IcmpPacket {
+            header: self.header,
+            raw: self.raw,
+            data: Box::new(self.data.into_owned()),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for IcmpPacket<'a> {
+    type Error = ParserError;
+
+    /// Constructs an `IcmpPacket` from a slice. See `from_bytes`.
+    fn try_from(packets: &'a [u8]) -> Result<Self, Self::Error> {
+        IcmpPacket::from_bytes(packets)
+    }
 }
 
-impl DeepParser for IcmpPacket {
-    fn parse_next_layer(self) -> Result<LayeredData, ParserError> {
+impl<'a> DeepParser<'a> for IcmpPacket<'a> {
+    fn parse_next_layer_with(
+        self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Icmp, depth);
+
         Ok(LayeredData::IcmpData(self))
     }
 }
@@ -0,0 +1,307 @@
+/*
+ *  ICMPv6 Message Structure (RFC 4443, section 2.1):
+ *
+ *  0               8               16                             31
+ *  +---------------+---------------+------------------------------+
+ *  |   Type (8)    |   Code (8)    |        Checksum (16)         |
+ *  +---------------+---------------+------------------------------+
+ *  |                                                               |
+ *  |                 Message Body (variable length)                |
+ *  |                                                               |
+ *  +---------------------------------------------------------------+
+ *
+ *  Types 0-127 are error messages and 128-255 informational messages. Neighbor
+ *  Discovery (RFC 4861) uses types 133-137.
+ */
+
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
+use super::{
+    definitions::{DeepParser, LayerKind, LayeredData, ParseOptions},
+    errors::ParserError,
+    trace,
+    utils::{transport_checksum, ByteReader, PayloadSource},
+};
+
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{net::Ipv6Addr, ops::Range};
+
+const DATA_OFFSET_OR_MIN_SIZE: usize = 8;
+const CHECKSUM_OFFSET: usize = 2;
+const ICMPV6_NEXT_HEADER: u8 = 58;
+
+const DESTINATION_UNREACHABLE: u8 = 1;
+const PACKET_TOO_BIG: u8 = 2;
+const TIME_EXCEEDED: u8 = 3;
+const PARAMETER_PROBLEM: u8 = 4;
+
+const ECHO_REQUEST: u8 = 128;
+const ECHO_REPLY: u8 = 129;
+
+// Neighbor Discovery messages (RFC 4861, section 4).
+const ROUTER_SOLICITATION: u8 = 133;
+const ROUTER_ADVERTISEMENT: u8 = 134;
+const NEIGHBOR_SOLICITATION: u8 = 135;
+const NEIGHBOR_ADVERTISEMENT: u8 = 136;
+const REDIRECT: u8 = 137;
+
+/// The type of an ICMPv6 message, with its code and the fields of the rest of the header
+/// decoded where they have a meaning of their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Icmpv6Type {
+    DestinationUnreachable {
+        code: u8,
+    },
+    /// The packet was larger than the MTU of the next link, given here.
+    PacketTooBig {
+        mtu: u32,
+    },
+    /// The hop limit reached zero in transit, or otherwise fragment reassembly timed out.
+    TimeExceeded {
+        in_transit: bool,
+    },
+    /// The offset of the erroneous field within the packet quoted by the message.
+    ParameterProblem {
+        code: u8,
+        pointer: u32,
+    },
+    EchoRequest {
+        identifier: u16,
+        sequence_number: u16,
+    },
+    EchoReply {
+        identifier: u16,
+        sequence_number: u16,
+    },
+    RouterSolicitation,
+    RouterAdvertisement,
+    NeighborSolicitation,
+    NeighborAdvertisement,
+    Redirect,
+    Other(u8), // Types without a variant are preserved
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Icmpv6PacketHeader {
+    pub icmp_type: u8,       // Type of ICMPv6 message.
+    pub icmp_code: u8,       // Subtype to further specify the message.
+    pub checksum: u16,       // Checksum over the message and the IPv6 pseudo-header.
+    pub rest_of_header: u32, // Remaining data in the header (depends on type and code).
+    pub span: Range<usize>,  // Position of the header within the parsed bytes.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub raw_header: Vec<u8>, // The first 8 bytes as received, covered by the checksum.
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Icmpv6Packet<'a> {
+    pub header: Icmpv6PacketHeader,
+
+    /// The message body following the first 8 bytes, e.g. the echo data, or the target
+    /// address and options of a Neighbor Discovery message.
+    pub data: Box<LayeredData<'a>>,
+}
+
+impl<'a> Icmpv6Packet<'a> {
+    /// Constructs a new `Icmpv6Packet` from a slice of bytes.
+    ///
+    /// # Arguments:
+    ///
+    /// * `packets` - A byte slice containing the ICMPv6 message, typically the payload of an
+    ///   IPv6 packet whose next header is 58.
+    ///
+    /// # Returns:
+    ///
+    /// * `Result<Self, ParserError>` - An `Icmpv6Packet` instance or a `ParserError`.
+    pub fn from_bytes(packets: &'a [u8]) -> Result<Self, ParserError> {
+        Self::parse(&packets)
+    }
+
+    /// Constructs an `Icmpv6Packet` whose body is taken from `source`.
+    pub(crate) fn parse<S: PayloadSource<'a> + ?Sized>(source: &S) -> Result<Self, ParserError> {
+        let packets = source.as_slice();
+        if packets.len() < DATA_OFFSET_OR_MIN_SIZE {
+            return Err(ParserError::HeaderTooShort {
+                layer: "ICMPv6 packet".to_string(),
+                got: packets.len(),
+                need: DATA_OFFSET_OR_MIN_SIZE,
+            });
+        }
+
+        let mut cursor = ByteReader::new(packets);
+
+        let icmp_type = cursor.read_u8("ICMPv6_Type")?;
+        let icmp_code = cursor.read_u8("ICMPv6_Code")?;
+        let checksum = cursor.read_u16_be("ICMPv6_Checksum")?;
+        let rest_of_header = cursor.read_u32_be("ICMPv6_Rest_Of_Header")?;
+
+        Ok(Icmpv6Packet {
+            header: Icmpv6PacketHeader {
+                icmp_type,
+                icmp_code,
+                checksum,
+                rest_of_header,
+                span: 0..DATA_OFFSET_OR_MIN_SIZE,
+                raw_header: packets[..DATA_OFFSET_OR_MIN_SIZE].to_vec(),
+            },
+            data: Box::new(source.payload(DATA_OFFSET_OR_MIN_SIZE..packets.len())),
+        })
+    }
+
+    /// Serializes the message back into its wire format, with the checksum as parsed.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = &self.header;
+
+        let mut bytes = Vec::with_capacity(DATA_OFFSET_OR_MIN_SIZE);
+        bytes.push(header.icmp_type);
+        bytes.push(header.icmp_code);
+        bytes.extend_from_slice(&header.checksum.to_be_bytes());
+        bytes.extend_from_slice(&header.rest_of_header.to_be_bytes());
+        bytes.extend_from_slice(&self.data.to_bytes());
+        bytes
+    }
+
+    /// Verifies the checksum of the message, which also covers a pseudo-header built from
+    /// the enclosing IPv6 packet (RFC 8200, section 8.1).
+    ///
+    /// # Arguments
+    /// - `raw`: The raw ICMPv6 message, i.e. the header followed by the payload.
+    /// - `source`: The source address of the enclosing IPv6 packet.
+    /// - `destination`: The final destination address of the enclosing IPv6 packet.
+    ///
+    /// # Returns
+    /// - `true` if the computed checksum matches `checksum`, `false` otherwise or if `raw`
+    ///   is too short to hold an ICMPv6 header.
+    pub fn verify_checksum(&self, raw: &[u8], source: Ipv6Addr, destination: Ipv6Addr) -> bool {
+        if raw.len() < DATA_OFFSET_OR_MIN_SIZE {
+            return false;
+        }
+
+        transport_checksum(
+            raw,
+            CHECKSUM_OFFSET,
+            source.into(),
+            destination.into(),
+            ICMPV6_NEXT_HEADER,
+        ) == Some(self.header.checksum)
+    }
+
+    /// Returns `true` for error messages, whose types are below 128 (RFC 4443, section 2.1).
+    pub fn is_error(&self) -> bool {
+        self.header.icmp_type < ECHO_REQUEST
+    }
+
+    /// Decodes the type of the message, along with the fields of `rest_of_header` that
+    /// have a meaning of their own, e.g. the identifier and sequence number of an echo.
+    pub fn decoded_type(&self) -> Icmpv6Type {
+        let header = &self.header;
+        let identifier = (header.rest_of_header >> 16) as u16;
+        let sequence_number = header.rest_of_header as u16;
+
+        match header.icmp_type {
+            DESTINATION_UNREACHABLE => Icmpv6Type::DestinationUnreachable {
+                code: header.icmp_code,
+            },
+            PACKET_TOO_BIG => Icmpv6Type::PacketTooBig {
+                mtu: header.rest_of_header,
+            },
+            TIME_EXCEEDED => Icmpv6Type::TimeExceeded {
+                in_transit: header.icmp_code == 0,
+            },
+            PARAMETER_PROBLEM => Icmpv6Type::ParameterProblem {
+                code: header.icmp_code,
+                pointer: header.rest_of_header,
+            },
+            ECHO_REQUEST => Icmpv6Type::EchoRequest {
+                identifier,
+                sequence_number,
+            },
+            ECHO_REPLY => Icmpv6Type::EchoReply {
+                identifier,
+                sequence_number,
+            },
+            ROUTER_SOLICITATION => Icmpv6Type::RouterSolicitation,
+            ROUTER_ADVERTISEMENT => Icmpv6Type::RouterAdvertisement,
+            NEIGHBOR_SOLICITATION => Icmpv6Type::NeighborSolicitation,
+            NEIGHBOR_ADVERTISEMENT => Icmpv6Type::NeighborAdvertisement,
+            REDIRECT => Icmpv6Type::Redirect,
+            other => Icmpv6Type::Other(other),
+        }
+    }
+
+    /// Returns a short description of the message, e.g. `echo request id 1 seq 1`, or its
+    /// type and code for messages without a name here.
+    pub fn describe(&self) -> String {
+        let code = self.header.icmp_code;
+
+        match self.decoded_type() {
+            Icmpv6Type::DestinationUnreachable { code } => {
+                format!("destination unreachable code {code}")
+            }
+            Icmpv6Type::PacketTooBig { mtu } => format!("packet too big mtu {mtu}"),
+            Icmpv6Type::TimeExceeded { in_transit: true } => "time exceeded in transit".to_string(),
+            Icmpv6Type::TimeExceeded { in_transit: false } => {
+                "time exceeded in reassembly".to_string()
+            }
+            Icmpv6Type::ParameterProblem { code, pointer } => {
+                format!("parameter problem code {code} pointer {pointer}")
+            }
+            Icmpv6Type::EchoRequest {
+                identifier,
+                sequence_number,
+            } => format!("echo request id {identifier} seq {sequence_number}"),
+            Icmpv6Type::EchoReply {
+                identifier,
+                sequence_number,
+            } => format!("echo reply id {identifier} seq {sequence_number}"),
+            Icmpv6Type::RouterSolicitation => "router solicitation".to_string(),
+            Icmpv6Type::RouterAdvertisement => "router advertisement".to_string(),
+            Icmpv6Type::NeighborSolicitation => "neighbor solicitation".to_string(),
+            Icmpv6Type::NeighborAdvertisement => "neighbor advertisement".to_string(),
+            Icmpv6Type::Redirect => "redirect".to_string(),
+            Icmpv6Type::Other(icmp_type) => format!("type {icmp_type} code {code}"),
+        }
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Icmpv6
+    }
+
+    /// Detaches the message from the bytes it was parsed from, copying its payload if it is
+    /// borrowed.
+    pub fn into_owned(self) -> Icmpv6Packet<'static> {
+        Icmpv6Packet {
+            header: self.header,
+            data: Box::new(self.data.into_owned()),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Icmpv6Packet<'a> {
+    type Error = ParserError;
+
+    /// Constructs an `Icmpv6Packet` from a slice. See `from_bytes`.
+    fn try_from(packets: &'a [u8]) -> Result<Self, Self::Error> {
+        Icmpv6Packet::from_bytes(packets)
+    }
+}
+
+impl<'a> DeepParser<'a> for Icmpv6Packet<'a> {
+    fn parse_next_layer_with(
+        self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Icmpv6, depth);
+
+        Ok(LayeredData::Icmpv6Data(self))
+    }
+}
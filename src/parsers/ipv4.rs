@@ -15,24 +15,166 @@
 // |                    Options                    |    Padding    |
 // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
 
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
 use super::{
-    definitions::{DeepParser, IPType, LayeredData},
+    definitions::{DeepParser, Ecn, IPType, LayerKind, LayeredData, LayeredDataRef, ParseOptions},
     errors::ParserError,
-    utils::{parse_ip_next_protocol_layer, read_arbitrary_length, read_u16, read_u32, read_u8},
+    registry::ParseContext,
+    trace,
+    utils::{
+        be_u16, be_u32, check_checksum, internet_checksum, ip_next_layer_ref,
+        parse_ip_next_protocol_layer, OptionReader, PayloadSource,
+    },
 };
 
-use std::io::{Cursor, Seek, SeekFrom};
-use std::net::Ipv4Addr;
+use alloc::{boxed::Box, format, string::ToString, vec::Vec};
+use core::{net::Ipv4Addr, ops::Range};
 
 const MIN_IHL_VALUE: u8 = 5;
 const MAX_IHL_VALUE: u8 = 15;
-
-const DEST_ADDRESS_OFFSET: usize = 16;
-const DEST_ADDRESS_LENGTH: usize = 4;
+const DEFAULT_TTL: u8 = 64;
 
 const MIN_PACKET_SIZE: usize = 20;
+const MAX_OPTIONS_SIZE: usize = 40;
+const CHECKSUM_OFFSET: usize = 10;
+
+// The most significant of the three flag bits, which must be zero.
+const RESERVED_FLAG: u8 = 0b100;
+const DONT_FRAGMENT_FLAG: u8 = 0b010;
+const MORE_FRAGMENTS_FLAG: u8 = 0b001;
+
+/// The three fragmentation flags of an IPv4 header, decoded from `Ipv4PacketHeader::flags`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ipv4Flags {
+    /// The reserved bit, which must be zero.
+    pub reserved: bool,
+
+    /// The Don't Fragment (DF) bit: routers drop the packet rather than fragment it.
+    pub dont_fragment: bool,
+
+    /// The More Fragments (MF) bit, set on every fragment of a datagram but the last.
+    pub more_fragments: bool,
+}
+
+impl Ipv4Flags {
+    /// Decodes the flags from the three least significant bits of `bits`, laid out as on
+    /// the wire: reserved, DF, then MF.
+    pub fn from_bits(bits: u8) -> Self {
+        Ipv4Flags {
+            reserved: bits & RESERVED_FLAG != 0,
+            dont_fragment: bits & DONT_FRAGMENT_FLAG != 0,
+            more_fragments: bits & MORE_FRAGMENTS_FLAG != 0,
+        }
+    }
+}
+
+impl From<Ipv4Flags> for u8 {
+    fn from(flags: Ipv4Flags) -> Self {
+        (flags.reserved as u8 * RESERVED_FLAG)
+            | (flags.dont_fragment as u8 * DONT_FRAGMENT_FLAG)
+            | (flags.more_fragments as u8 * MORE_FRAGMENTS_FLAG)
+    }
+}
+
+/// An IPv4 option, decoded from the options field of the header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ipv4Option {
+    EndOfOptionList,
+    NoOperation,
+    /// The route recorded so far. `pointer` is the 1-based offset, from the start of the
+    /// option, of the next slot to fill.
+    RecordRoute {
+        pointer: u8,
+        addresses: Vec<Ipv4Addr>,
+    },
+    /// A route the packet should follow, possibly via other routers in between.
+    LooseSourceRoute {
+        pointer: u8,
+        addresses: Vec<Ipv4Addr>,
+    },
+    /// A route the packet must follow exactly.
+    StrictSourceRoute {
+        pointer: u8,
+        addresses: Vec<Ipv4Addr>,
+    },
+    /// Asks every router on the path to examine the packet. Zero for all RSVP-like uses.
+    RouterAlert(u16),
+    /// An option of a type not decoded by net-sift, with its raw data.
+    Other {
+        option_type: u8,
+        data: Vec<u8>,
+    },
+}
+
+impl Ipv4Option {
+    /// Decodes an option of the given type from its data, i.e. the bytes after its type
+    /// and length.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::InvalidLength` - If the data is the wrong size for the type.
+    fn decode(option_type: u8, data: &[u8]) -> Result<Self, ParserError> {
+        let route = || match data.split_first() {
+            Some((&pointer, addresses)) if addresses.len() % 4 == 0 => Ok((
+                pointer,
+                addresses
+                    .chunks_exact(4)
+                    .map(|address| Ipv4Addr::new(address[0], address[1], address[2], address[3]))
+                    .collect(),
+            )),
+            _ => Err(ParserError::InvalidLength("Ipv4 option".to_string())),
+        };
+
+        let option = match option_type {
+            0 => Ipv4Option::EndOfOptionList,
+            1 => Ipv4Option::NoOperation,
+            7 => {
+                let (pointer, addresses) = route()?;
+                Ipv4Option::RecordRoute { pointer, addresses }
+            }
+            131 => {
+                let (pointer, addresses) = route()?;
+                Ipv4Option::LooseSourceRoute { pointer, addresses }
+            }
+            137 => {
+                let (pointer, addresses) = route()?;
+                Ipv4Option::StrictSourceRoute { pointer, addresses }
+            }
+            148 => match data {
+                [high, low] => Ipv4Option::RouterAlert(u16::from_be_bytes([*high, *low])),
+                _ => return Err(ParserError::InvalidLength("Ipv4 option".to_string())),
+            },
+            option_type => Ipv4Option::Other {
+                option_type,
+                data: data.to_vec(),
+            },
+        };
+
+        Ok(option)
+    }
+}
+
+/// An iterator over the options of an IPv4 packet, returned by `Ipv4Packet::options_iter`.
+///
+/// Options are decoded as the iterator advances, and malformed ones are yielded as errors.
+/// An option whose length is invalid ends the iteration, as the options after it cannot
+/// be located.
+#[derive(Debug, Clone)]
+pub struct Ipv4OptionsIter<'a>(OptionReader<'a>);
 
-#[derive(Debug, PartialEq)]
+impl Iterator for Ipv4OptionsIter<'_> {
+    type Item = Result<Ipv4Option, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|option| {
+            option.and_then(|(option_type, data)| Ipv4Option::decode(option_type, data))
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Ipv4PacketHeader {
     /// A single-byte field indicating the version of the IP protocol.
     /// For Ipv4, this is typically set to 4.
@@ -53,7 +195,7 @@ pub struct Ipv4PacketHeader {
     pub identification: u16,
 
     /// A single-byte field containing flags related to IP fragmentation,
-    /// such as "Don't Fragment" and "More Fragments".
+    /// such as "Don't Fragment" and "More Fragments". See `flags_decoded`.
     pub flags: u8,
 
     /// A two-byte field indicating where in the original IP datagram
@@ -81,43 +223,98 @@ pub struct Ipv4PacketHeader {
     /// represented as a vector of bytes. This field is variable in length
     /// and may be absent.
     pub options: Option<Vec<u8>>,
+
+    /// The position of the header, including options, within the bytes
+    /// the packet was parsed from.
+    pub span: Range<usize>,
+
+    /// The exact header bytes consumed by the parser, `IHL * 4` bytes including any
+    /// options. Useful for verifying the header checksum.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub raw_header: Vec<u8>,
+}
+
+/// A version 4 header without options, carrying TCP from and to the unspecified address
+/// with a TTL of 64. `total_length` covers the header alone.
+///
+/// The header was not parsed from bytes, so `raw_header` and `span` are empty.
+impl Default for Ipv4PacketHeader {
+    fn default() -> Self {
+        Ipv4PacketHeader {
+            version: 4,
+            type_of_service: 0,
+            internet_header_length: MIN_IHL_VALUE,
+            total_length: MIN_PACKET_SIZE as u16,
+            identification: 0,
+            flags: 0,
+            fragment_offset: 0,
+            time_to_live: DEFAULT_TTL,
+            protocol: IPType::TCP,
+            header_checksum: 0,
+            source_address: Ipv4Addr::UNSPECIFIED,
+            destination_address: Ipv4Addr::UNSPECIFIED,
+            options: None,
+            span: 0..0,
+            raw_header: Vec::new(),
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
-pub struct Ipv4Packet {
+impl Ipv4PacketHeader {
+    /// Returns the 6-bit Differentiated Services Code Point, the upper bits of
+    /// `type_of_service`.
+    pub fn dscp(&self) -> u8 {
+        self.type_of_service >> 2
+    }
+
+    /// Returns the Explicit Congestion Notification codepoint, the lower 2 bits of
+    /// `type_of_service`.
+    pub fn ecn(&self) -> Ecn {
+        Ecn::from(self.type_of_service)
+    }
+
+    /// Returns the fragmentation flags, decoded from `flags`.
+    pub fn flags_decoded(&self) -> Ipv4Flags {
+        Ipv4Flags::from_bits(self.flags)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Ipv4Packet<'a> {
     pub header: Ipv4PacketHeader,
-    /// A vector containing the payload or data portion of the IP packet.
-    pub data: Box<LayeredData>,
+    /// The payload or data portion of the IP packet.
+    pub data: Box<LayeredData<'a>>,
+    /// `true` if the packet is shorter than its `total_length`, so `data` holds only the
+    /// start of the payload. Only set for packets parsed with `allow_truncated`, or quoted
+    /// in an ICMP error message.
+    pub truncated: bool,
 }
 
-impl Ipv4Packet {
-    /// Constructs a new instance of `IPV4` by parsing raw packet data.
-    ///
-    /// This function expects `packets` to contain the raw bytes of an IPv4 packet and
-    /// tries to extract various fields from these bytes.
-    ///
-    /// # Arguments
-    /// - `packets`: A byte slice representing the raw data of an IPv4 packet.
-    ///
-    /// # Returns
-    /// - `Result<IPV4, ParserError>`: An `IPV4` instance if the parsing was successful,
-    /// or an error indicating the reason for failure.
-    pub fn from_bytes(packets: &[u8]) -> Result<Self, ParserError> {
+/// An IPv4 packet borrowed from the bytes it was parsed by `Ipv4Packet::from_bytes_ref`.
+///
+/// The header fields are read from the bytes when asked for, so nothing is copied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ipv4PacketRef<'a> {
+    bytes: &'a [u8],
+    truncated: bool,
+}
+
+impl<'a> Ipv4PacketRef<'a> {
+    /// Checks the header of `packets`, and bounds the packet by its `total_length`.
+    pub(crate) fn parse(packets: &'a [u8], allow_truncated: bool) -> Result<Self, ParserError> {
         // Ensure packet is of minimum expected length.
         if packets.len() < MIN_PACKET_SIZE {
-            return Err(ParserError::InvalidLength("Ipv4 packet".to_string()));
+            return Err(ParserError::HeaderTooShort {
+                layer: "Ipv4 packet".to_string(),
+                got: packets.len(),
+                need: MIN_PACKET_SIZE,
+            });
         }
-        let mut cursor = Cursor::new(packets);
-
-        let version_ihl = read_u8(&mut cursor, "Version & IHL")?;
-
-        // Right shift the byte `version_ihl` 4 times to get the version
-        // which is in the MSB.
-        let version = version_ihl >> 4;
-        let internet_header_length = version_ihl & 15;
 
         // Ensure the IHL is between 5 and 15.
-        if !(5..=15).contains(&internet_header_length) {
+        let internet_header_length = packets[0] & 15;
+        if !(MIN_IHL_VALUE..=MAX_IHL_VALUE).contains(&internet_header_length) {
             return Err(ParserError::InvalidIHLValue(
                 internet_header_length as u32,
                 MIN_IHL_VALUE,
@@ -125,140 +322,424 @@ impl Ipv4Packet {
             ));
         }
 
-        let type_of_service = read_u8(&mut cursor, "ToS")?;
-        let total_length = read_u16(&mut cursor, "Total Length")?;
-        let identification = read_u16(&mut cursor, "Identification")?;
-        let flags_fragment = read_u16(&mut cursor, "Flags & Fragment")?;
-
-        // Right shift the byte `flags_fragment` 13 times to get the flags
-        // which is in the MSB.
-        let flags = (flags_fragment >> 13) as u8;
-        let fragment_offset = flags_fragment & 8191;
-
-        let time_to_live = read_u8(&mut cursor, "TTL")?;
-        let protocol = IPType::from(read_u8(&mut cursor, "Protocol")?);
-        let header_checksum = read_u16(&mut cursor, "Header Checksum")?;
+        // Anything beyond `total_length` (e.g. Ethernet padding) is ignored, but the packet
+        // itself must be complete.
+        let total_length = be_u16(packets, 2) as usize;
+        if packets.len() < total_length && !allow_truncated {
+            return Err(ParserError::InconsistentLength {
+                layer: "Ipv4 packet".to_string(),
+                declared: total_length,
+                available: packets.len(),
+            });
+        }
 
-        let [a, b, c, d] = u32::to_be_bytes(read_u32(&mut cursor, "Source Address")?);
-        let source_address = Ipv4Addr::new(a, b, c, d);
+        let header_length = internet_header_length as usize * 4;
+        if packets.len() < header_length {
+            return Err(ParserError::OptionsOverrun {
+                layer: "Ipv4 options".to_string(),
+                declared: header_length - MIN_PACKET_SIZE,
+                available: packets.len() - MIN_PACKET_SIZE,
+            });
+        }
+        let end = packets.len().min(total_length);
+        if end < header_length {
+            return Err(ParserError::InvalidLength("Ipv4 total length".to_string()));
+        }
 
-        let [a, b, c, d] = u32::to_be_bytes(read_u32(&mut cursor, "Source Address")?);
-        let destination_address = Ipv4Addr::new(a, b, c, d);
+        Ok(Ipv4PacketRef {
+            bytes: &packets[..end],
+            truncated: packets.len() < total_length,
+        })
+    }
 
-        let (options, data) = Self::parse_options_and_payload(
-            &mut cursor,
-            internet_header_length as u16,
-            total_length,
-        )?;
+    /// Builds the `Ipv4Packet`, taking its payload from `source`, the buffer `bytes`
+    /// starts.
+    fn to_owned_in<'s, S: PayloadSource<'s> + ?Sized>(self, source: &S) -> Ipv4Packet<'s> {
+        let header_length = self.header().len();
 
-        Ok(Ipv4Packet {
+        Ipv4Packet {
             header: Ipv4PacketHeader {
-                version,
-                internet_header_length,
-                type_of_service,
-                total_length,
-                identification,
-                flags,
-                fragment_offset,
-                time_to_live,
-                protocol,
-                header_checksum,
-                source_address,
-                destination_address,
-                options,
+                version: self.version(),
+                internet_header_length: self.internet_header_length(),
+                type_of_service: self.type_of_service(),
+                total_length: self.total_length(),
+                identification: self.identification(),
+                flags: self.flags(),
+                fragment_offset: self.fragment_offset(),
+                time_to_live: self.time_to_live(),
+                protocol: self.protocol(),
+                header_checksum: self.header_checksum(),
+                source_address: self.source_address(),
+                destination_address: self.destination_address(),
+                options: self.options().map(<[u8]>::to_vec),
+                span: 0..header_length,
+                raw_header: self.header().to_vec(),
             },
-            data: Box::new(LayeredData::Payload(data)),
-        })
+            data: Box::new(source.payload(header_length..self.bytes.len())),
+            truncated: self.truncated,
+        }
+    }
+
+    pub fn version(&self) -> u8 {
+        self.bytes[0] >> 4
+    }
+
+    pub fn internet_header_length(&self) -> u8 {
+        self.bytes[0] & 15
+    }
+
+    pub fn type_of_service(&self) -> u8 {
+        self.bytes[1]
+    }
+
+    pub fn total_length(&self) -> u16 {
+        be_u16(self.bytes, 2)
+    }
+
+    pub fn identification(&self) -> u16 {
+        be_u16(self.bytes, 4)
+    }
+
+    /// Returns the fragmentation flags as their 3 raw bits. See `Ipv4Flags::from_bits`.
+    pub fn flags(&self) -> u8 {
+        self.bytes[6] >> 5
+    }
+
+    pub fn fragment_offset(&self) -> u16 {
+        be_u16(self.bytes, 6) & 8191
+    }
+
+    pub fn time_to_live(&self) -> u8 {
+        self.bytes[8]
+    }
+
+    pub fn protocol(&self) -> IPType {
+        IPType::from(self.bytes[9])
+    }
+
+    pub fn header_checksum(&self) -> u16 {
+        be_u16(self.bytes, CHECKSUM_OFFSET)
+    }
+
+    pub fn source_address(&self) -> Ipv4Addr {
+        Ipv4Addr::from(be_u32(self.bytes, 12))
     }
 
-    /// Parses the options and payload from a network packet.
+    pub fn destination_address(&self) -> Ipv4Addr {
+        Ipv4Addr::from(be_u32(self.bytes, 16))
+    }
+
+    /// Returns the options following the fixed header, or `None` if the IHL is 5.
+    pub fn options(&self) -> Option<&'a [u8]> {
+        let options = &self.header()[MIN_PACKET_SIZE..];
+        (!options.is_empty()).then_some(options)
+    }
+
+    /// Returns an iterator over the options in the header, decoding each on demand.
+    pub fn options_iter(&self) -> Ipv4OptionsIter<'a> {
+        Ipv4OptionsIter(OptionReader::new(
+            self.options().unwrap_or_default(),
+            "Ipv4 option",
+        ))
+    }
+
+    /// Returns the header, `IHL * 4` bytes including any options.
+    pub fn header(&self) -> &'a [u8] {
+        &self.bytes[..self.internet_header_length() as usize * 4]
+    }
+
+    /// Returns the payload, which excludes any bytes past `total_length`.
+    pub fn data(&self) -> &'a [u8] {
+        &self.bytes[self.header().len()..]
+    }
+
+    /// Returns `true` if the packet is shorter than its `total_length`.
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Parses the payload as the protocol given in the header, borrowing from the same
+    /// bytes. See `LayeredDataRef` for the layers that can be borrowed.
+    ///
+    /// # Errors
+    ///
+    /// * Any error from parsing the TCP, UDP, IPv4 or IPv6 layer in the payload.
+    pub fn next_layer(&self) -> Result<LayeredDataRef<'a>, ParserError> {
+        ip_next_layer_ref(self.data(), &self.protocol())
+    }
+}
+
+impl<'a> From<Ipv4PacketRef<'a>> for Ipv4Packet<'a> {
+    /// Converts the packet into an `Ipv4Packet` borrowing the same bytes, with its payload left
+    /// raw.
+    fn from(packet: Ipv4PacketRef<'a>) -> Self {
+        packet.to_owned_in(&packet.bytes)
+    }
+}
+
+impl<'a> DeepParser<'a> for Ipv4PacketRef<'a> {
+    /// Converts the packet into an `Ipv4Packet`, and parses its payload from there.
+    fn parse_next_layer_with(
+        self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        Ipv4Packet::from(self).parse_next_layer_with(options, depth)
+    }
+}
+
+impl<'a> Ipv4Packet<'a> {
+    /// Constructs a new instance of `IPV4` by parsing raw packet data.
     ///
-    /// Given the `internet_header_length` and `total_length` from the packet's header,
-    /// this function extracts optional information (if present) and the main payload.
+    /// This function expects `packets` to contain the raw bytes of an IPv4 packet and
+    /// tries to extract various fields from these bytes.
     ///
-    /// # Parameters
-    /// * `cursor`: A mutable reference to a `Cursor` positioned at the start of the packet data.
-    /// * `internet_header_length`: The header length value from the packet, indicating where option data (if any) starts.
-    /// * `total_length`: The total packet length value, used to calculate the payload's size.
+    /// # Arguments
+    /// - `packets`: A byte slice representing the raw data of an IPv4 packet.
     ///
     /// # Returns
-    /// * On success, returns a tuple containing an `Option<Vec<u8>>` for options (None if no options are present)
-    ///   and a `Vec<u8>` for the payload.
-    /// * On failure, returns a `ParserError` indicating the reason for the failure.
+    /// - `Result<IPV4, ParserError>`: An `IPV4` instance if the parsing was successful,
+    ///   or an error indicating the reason for failure.
     ///
     /// # Errors
-    /// * Returns `ParserError::CursorError` if the function fails to seek the cursor to the correct position.
-    /// * May also return other errors encapsulated by `ParserError` if reading from the cursor fails.
-    fn parse_options_and_payload(
-        cursor: &mut Cursor<&[u8]>,
-        internet_header_length: u16,
-        total_length: u16,
-    ) -> Result<(Option<Vec<u8>>, Vec<u8>), ParserError> {
-        // Calculate offsets and sizes for options and payload data.
-        let (options_offset, options_size, _payload_offset) =
-            Self::payload_and_options_offsets(internet_header_length as usize);
-
-        let mut options: Option<Vec<u8>> = Default::default();
-        let payload: Vec<u8>;
-
-        if options_offset != 0 {
-            cursor
-                .seek(SeekFrom::Start(options_offset as u64))
-                .map_err(|e| ParserError::CursorError {
-                    string: "Options".to_string(),
-                    source: e,
-                })?;
-
-            options = Some(read_arbitrary_length(cursor, options_size, "Options")?);
+    /// - `ParserError::HeaderTooShort` if `packets` is shorter than the fixed header.
+    /// - `ParserError::OptionsOverrun` if the IHL implies options past the end of `packets`.
+    /// - `ParserError::InconsistentLength` if `packets` is shorter than `total_length`.
+    ///   Bytes beyond `total_length`, such as Ethernet padding, are excluded from the payload.
+    pub fn from_bytes(packets: &'a [u8]) -> Result<Self, ParserError> {
+        Self::parse(&packets, false)
+    }
+
+    /// Constructs an `Ipv4Packet` from a packet that may be cut short of its declared length, such
+    /// as the copy quoted in an ICMP error message. The header must be complete, but the
+    /// payload holds only the bytes that are present.
+    pub(crate) fn from_truncated_bytes<S: PayloadSource<'a> + ?Sized>(
+        packets: &S,
+    ) -> Result<Self, ParserError> {
+        Self::parse(packets, true)
+    }
+
+    /// Constructs an `Ipv4Packet` nested within another layer, accepting a truncated packet
+    /// if `options.allow_truncated` is set.
+    pub(crate) fn from_bytes_with<S: PayloadSource<'a> + ?Sized>(
+        packets: &S,
+        options: &ParseOptions,
+    ) -> Result<Self, ParserError> {
+        Self::parse(packets, options.allow_truncated)
+    }
+
+    /// Constructs an `Ipv4Packet` whose payload shares `buf` rather than copying it.
+    ///
+    /// Behaves like `from_bytes`, except that the payload is left as a `LayeredData::Bytes`
+    /// slice of `buf`. Layers parsed from it by `parse_next_layer` share `buf` too, down to
+    /// the payload of a TCP segment or UDP datagram; application layers are copied.
+    #[cfg(feature = "bytes")]
+    pub fn from_bytes_buf(buf: &bytes::Bytes) -> Result<Self, ParserError> {
+        Self::parse(buf, false)
+    }
+
+    /// Constructs an `Ipv4PacketRef` borrowing from `packets`, without allocating.
+    ///
+    /// Fails in the same cases as `from_bytes`. Layers parsed from it by
+    /// `Ipv4PacketRef::next_layer` borrow from `packets` too.
+    pub fn from_bytes_ref(packets: &[u8]) -> Result<Ipv4PacketRef<'_>, ParserError> {
+        Ipv4PacketRef::parse(packets, false)
+    }
+
+    fn parse<S: PayloadSource<'a> + ?Sized>(
+        source: &S,
+        allow_truncated: bool,
+    ) -> Result<Self, ParserError> {
+        Ok(Ipv4PacketRef::parse(source.as_slice(), allow_truncated)?.to_owned_in(source))
+    }
+
+    /// Returns the length of the payload the packet declares, which exceeds the bytes held
+    /// in `data` if the packet is truncated.
+    fn payload_len(&self) -> usize {
+        let present = self.data.payload().map_or(0, <[u8]>::len);
+        if !self.truncated {
+            return present;
         }
+        let header_length = self.header.internet_header_length as usize * 4;
+        (self.header.total_length as usize)
+            .saturating_sub(header_length)
+            .max(present)
+    }
 
-        let payload_size = total_length - (internet_header_length as u16 * 4);
-        payload = read_arbitrary_length(cursor, payload_size as usize, "IPV4_Data")?;
+    /// Returns an iterator over the options in the header, decoding each on demand.
+    pub fn options_iter(&self) -> Ipv4OptionsIter<'_> {
+        let options = self.header.options.as_deref().unwrap_or_default();
+        Ipv4OptionsIter(OptionReader::new(options, "Ipv4 option"))
+    }
 
-        Ok((options, payload))
+    /// Returns the payload as a slice of `original`, the bytes the packet was parsed from,
+    /// without copying it.
+    ///
+    /// The payload follows the header `span` and ends at `total_length`, so trailing bytes
+    /// such as Ethernet padding are excluded. It is cut short at the end of `original` if
+    /// the packet is truncated, and is empty if `original` ends before the header does.
+    pub fn payload_slice<'b>(&self, original: &'b [u8]) -> &'b [u8] {
+        let span = &self.header.span;
+        let start = span.end.min(original.len());
+        let end = (span.start + self.header.total_length as usize).clamp(start, original.len());
+        &original[start..end]
     }
 
-    /// Calculate offsets and sizes for the optional "options" field and the "payload" data
-    /// based on the Internet Header Length (IHL) field in the IPv4 header.
+    /// Serializes the packet back into its wire format.
     ///
-    /// The IHL field indicates the length of the header in 32-bit words. To determine the
-    /// location and size of the "options" field and the "payload" data, we perform the
-    /// following calculations:
+    /// The header is rebuilt from the parsed fields, with the following recomputed so that
+    /// the output is always self-consistent:
+    /// * The options are zero-padded to a 32-bit boundary (and capped at 40 bytes), and the
+    ///   IHL is derived from the resulting header length.
+    /// * `total_length` is derived from the header length plus the serialized payload.
+    /// * The header checksum is computed over the new header with the checksum field zeroed.
     ///
-    /// - If the IHL is greater than the minimum expected value of 5 (indicating the presence
-    ///   of additional options), we calculate the size of the "options" field as follows:
-    ///   - Multiply the IHL value by 4 (each IHL unit represents 32 bits or 4 bytes).
-    ///   - Subtract the size of the base header (20 bytes) to get the options size.
-    ///   - Set the options offset to the end of the base header (destination address + 4 bytes).
-    ///   - Calculate the payload offset as the options offset plus the options size.
+    /// The stored `internet_header_length`, `total_length` and `header_checksum` values are
+    /// ignored.
+    ///
+    /// # Returns
+    /// - A `Vec<u8>` holding the serialized header followed by the payload.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let payload = self.data.to_bytes();
+
+        let mut packet = self.header_bytes(payload.len());
+        packet.extend_from_slice(&payload);
+        packet
+    }
+
+    /// Sets `header_checksum` to the checksum written by `to_bytes`.
+    pub(crate) fn finalize_checksum(&mut self) {
+        let header = self.header_bytes(self.data.to_bytes().len());
+        self.header.header_checksum =
+            u16::from_be_bytes([header[CHECKSUM_OFFSET], header[CHECKSUM_OFFSET + 1]]);
+    }
+
+    /// Serializes the header for a payload of `payload_len` bytes, as done by `to_bytes`.
+    fn header_bytes(&self, payload_len: usize) -> Vec<u8> {
+        let header = &self.header;
+
+        let mut options = header.options.clone().unwrap_or_default();
+        options.truncate(MAX_OPTIONS_SIZE);
+        options.resize(options.len().div_ceil(4) * 4, 0);
+
+        let header_length = MIN_PACKET_SIZE + options.len();
+        let total_length = (header_length + payload_len).min(u16::MAX as usize) as u16;
+
+        let flags_fragment = ((header.flags as u16) << 13) | (header.fragment_offset & 8191);
+
+        let mut packet = Vec::with_capacity(header_length + payload_len);
+        packet.push((header.version << 4) | (header_length / 4) as u8);
+        packet.push(header.type_of_service);
+        packet.extend_from_slice(&total_length.to_be_bytes());
+        packet.extend_from_slice(&header.identification.to_be_bytes());
+        packet.extend_from_slice(&flags_fragment.to_be_bytes());
+        packet.push(header.time_to_live);
+        packet.push(u8::from(&header.protocol));
+        packet.extend_from_slice(&[0, 0]); // Checksum is computed once the header is complete.
+        packet.extend_from_slice(&header.source_address.octets());
+        packet.extend_from_slice(&header.destination_address.octets());
+        packet.extend_from_slice(&options);
+
+        let checksum = internet_checksum(&packet[..header_length]);
+        packet[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 2].copy_from_slice(&checksum.to_be_bytes());
+
+        packet
+    }
+
+    /// Verifies the header checksum against the raw header bytes.
     ///
-    /// - If the IHL is 5 (indicating no additional options), there are no "options" in the header.
-    ///   - Set both the options offset and options size to 0.
-    ///   - Calculate the payload offset as the end of the base header (destination address + 4 bytes).
+    /// The checksum is recomputed over `raw_header` with the checksum field treated as
+    /// zero, and compared against the parsed `header_checksum`.
     ///
     /// # Arguments
-    /// - `ihl`: The Internet Header Length (IHL) field from the IPv4 header.
+    /// - `raw_header`: The raw header bytes, i.e. the first `IHL * 4` bytes of the packet,
+    ///   including any options.
     ///
     /// # Returns
-    /// - A tuple containing:
-    ///   1. Options offset: The offset where the "options" field starts in the header.
-    ///   2. Options size: The size (in bytes) of the "options" field.
-    ///   3. Payload offset: The offset where the "payload" data starts in the header.
-    fn payload_and_options_offsets(ihl: usize) -> (usize, usize, usize) {
-        if ihl > MIN_IHL_VALUE as usize {
-            let options_size = (ihl * 4) - MIN_PACKET_SIZE; // 4 bytes per IHL unit minus base header size
-            let options_offset = DEST_ADDRESS_OFFSET + DEST_ADDRESS_LENGTH;
-            let payload_offset = options_offset + options_size;
-            return (options_offset, options_size, payload_offset);
+    /// - `true` if the computed checksum matches `header_checksum`, `false` otherwise or if
+    ///   `raw_header` is too short to hold an IPv4 header.
+    pub fn verify_checksum(&self, raw_header: &[u8]) -> bool {
+        Self::compute_checksum(raw_header) == Some(self.header.header_checksum)
+    }
+
+    /// Computes the checksum of `raw_header` with the checksum field treated as zero, or
+    /// returns `None` if it is too short to hold an IPv4 header.
+    fn compute_checksum(raw_header: &[u8]) -> Option<u16> {
+        if raw_header.len() < MIN_PACKET_SIZE {
+            return None;
+        }
+
+        let mut header = raw_header.to_vec();
+        header[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 2].copy_from_slice(&[0, 0]);
+
+        Some(internet_checksum(&header))
+    }
+
+    /// Checks the header fields that lenient parsing ignores, as done in strict mode.
+    ///
+    /// # Errors
+    /// - `ParserError::ProtocolViolation` if the version is not 4, or the reserved flag
+    ///   bit is set.
+    pub fn check_conformance(&self) -> Result<(), ParserError> {
+        if self.header.version != 4 {
+            return Err(ParserError::ProtocolViolation {
+                field: "Ipv4 version".to_string(),
+                detail: format!("expected 4, got {}", self.header.version),
+            });
+        }
+        if self.header.flags_decoded().reserved {
+            return Err(ParserError::ProtocolViolation {
+                field: "Ipv4 flags".to_string(),
+                detail: "reserved bit is set".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if `addr` is either the source or the destination of the packet.
+    pub fn matches_host(&self, addr: Ipv4Addr) -> bool {
+        self.header.source_address == addr || self.header.destination_address == addr
+    }
+
+    /// Returns `true` if the source or the destination of the packet lies within the
+    /// subnet `net/prefix`. Prefixes longer than 32 bits are treated as `/32`.
+    pub fn matches_subnet(&self, net: Ipv4Addr, prefix: u8) -> bool {
+        let mask = u32::MAX
+            .checked_shl(32 - prefix.min(32) as u32)
+            .unwrap_or(0);
+        let network = u32::from(net) & mask;
+
+        u32::from(self.header.source_address) & mask == network
+            || u32::from(self.header.destination_address) & mask == network
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Ipv4
+    }
+
+    /// Detaches the packet from the bytes it was parsed from, copying its payload if it is
+    /// borrowed.
+    pub fn into_owned(self) -> Ipv4Packet<'static> {
+        Ipv4Packet {
+            header: self.header,
+            truncated: self.truncated,
+            data: Box::new(self.data.into_owned()),
         }
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Ipv4Packet<'a> {
+    type Error = ParserError;
 
-        // If IHL is 5 (no options), there are no "options" in the header
-        // Set both options offset and options size to 0
-        (0, 0, DEST_ADDRESS_OFFSET + DEST_ADDRESS_LENGTH)
+    /// Constructs an `Ipv4Packet` from a slice. See `from_bytes`.
+    fn try_from(packets: &'a [u8]) -> Result<Self, Self::Error> {
+        Ipv4Packet::from_bytes(packets)
     }
 }
 
-impl DeepParser for Ipv4Packet {
+impl<'a> DeepParser<'a> for Ipv4Packet<'a> {
     /// Parses the payload based on the protocol specified in the IPv4 packet header.
     ///
     /// This method inspects the current protocol layer, extracts its payload, and attempts
@@ -284,13 +765,41 @@ impl DeepParser for Ipv4Packet {
     /// # Errors
     /// The method may fail if:
     /// * The protocol specified in the packet's header is unsupported or unknown.
+    /// * `options.strict` is set and `check_conformance` fails.
+    /// * `options.verify_checksums` is set and the header checksum is wrong.
     /// * There are issues encountered during the parsing process, such as malformed data or
     ///   unexpected input.
-    fn parse_next_layer(mut self) -> Result<LayeredData, ParserError> {
-        let layered_data: LayeredData =
-            parse_ip_next_protocol_layer(&*self.data, &self.header.protocol)?;
+    fn parse_next_layer_with(
+        mut self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Ipv4, depth);
+        if options.strict {
+            self.check_conformance()?;
+        }
+        if options.verify_checksums {
+            check_checksum(
+                self.header.header_checksum,
+                Self::compute_checksum(&self.header.raw_header),
+            )?;
+        }
 
-        *self.data = layered_data;
+        let context = ParseContext::new(LayerKind::Ipv4, 0, 0).with_ip(
+            self.header.source_address.into(),
+            self.header.destination_address.into(),
+            self.payload_len(),
+        );
+        let result = parse_ip_next_protocol_layer(
+            &self.data,
+            &self.header.protocol,
+            &context,
+            options,
+            depth,
+        );
+
+        options.recover(result, &mut self.data)?;
         Ok(LayeredData::Ipv4Data(self))
     }
 }
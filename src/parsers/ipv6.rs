@@ -20,22 +20,136 @@
 // +                                                               +
 // |                                                               |
 // +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//
+// Extension headers may follow, each starting with the next header and its length in
+// 8-byte units, not counting the first 8 bytes:
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |  Next Header  |  Hdr Ext Len  |                               |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+                               +
+// |                 Options or routing data ...                   |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
 
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
 use super::{
-    definitions::{DeepParser, IPType, LayeredData},
-    errors::{ErrorSource, ParserError},
-    utils::{parse_ip_next_protocol_layer, read_u32},
+    definitions::{DeepParser, Ecn, IPType, LayerKind, LayeredData, LayeredDataRef, ParseOptions},
+    errors::ParserError,
+    registry::ParseContext,
+    trace,
+    utils::{
+        be_u16, be_u32, ip_next_layer_ref, parse_ip_next_protocol_layer, ByteReader, PayloadSource,
+    },
 };
 
-use std::io::Cursor;
-use std::net::Ipv6Addr;
+use alloc::{boxed::Box, format, string::ToString, vec::Vec};
+use core::{net::Ipv6Addr, ops::Range};
 
 const SRC_ADDRESS_OFFSET: usize = 8;
 const DEST_ADDRESS_OFFSET: usize = 24;
 const PAYLOAD_OFFSET: usize = 40;
 const MIN_PACKET_SIZE: usize = 40;
+const DEFAULT_HOP_LIMIT: u8 = 64;
+const HOP_BY_HOP_NEXT_HEADER: u8 = 0;
+const ROUTING_NEXT_HEADER: u8 = 43;
+const DESTINATION_OPTIONS_NEXT_HEADER: u8 = 60;
+const PAD1_OPTION: u8 = 0;
+const JUMBO_PAYLOAD_OPTION: u8 = 0xC2;
+
+/// The scope of an IPv6 multicast address, from its low four flag bits (RFC 4291,
+/// section 2.7 and RFC 7346).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ipv6MulticastScope {
+    InterfaceLocal,
+    LinkLocal,
+    RealmLocal,
+    AdminLocal,
+    SiteLocal,
+    OrganizationLocal,
+    Global,
+    Other(u8), // Reserved and unassigned scopes
+}
 
-#[derive(Debug, PartialEq)]
+impl From<u8> for Ipv6MulticastScope {
+    fn from(scope: u8) -> Self {
+        match scope {
+            0x1 => Self::InterfaceLocal,
+            0x2 => Self::LinkLocal,
+            0x3 => Self::RealmLocal,
+            0x4 => Self::AdminLocal,
+            0x5 => Self::SiteLocal,
+            0x8 => Self::OrganizationLocal,
+            0xE => Self::Global,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// Returns `true` for the unspecified address, `::`.
+pub fn is_unspecified(address: &Ipv6Addr) -> bool {
+    address.is_unspecified()
+}
+
+/// Returns `true` for the loopback address, `::1`.
+pub fn is_loopback(address: &Ipv6Addr) -> bool {
+    address.is_loopback()
+}
+
+/// Returns `true` for multicast addresses, `ff00::/8`.
+pub fn is_multicast(address: &Ipv6Addr) -> bool {
+    address.is_multicast()
+}
+
+/// Returns `true` for link-local unicast addresses, `fe80::/10`.
+pub fn is_link_local(address: &Ipv6Addr) -> bool {
+    address.segments()[0] & 0xFFC0 == 0xFE80
+}
+
+/// Returns `true` for unique local addresses, `fc00::/7` (RFC 4193).
+pub fn is_unique_local(address: &Ipv6Addr) -> bool {
+    address.segments()[0] & 0xFE00 == 0xFC00
+}
+
+/// Returns `true` for addresses reserved for documentation, `2001:db8::/32` (RFC 3849).
+pub fn is_documentation(address: &Ipv6Addr) -> bool {
+    address.segments()[..2] == [0x2001, 0x0DB8]
+}
+
+/// Returns `true` for solicited-node multicast addresses, `ff02::1:ff00:0/104`, to which
+/// Neighbor Solicitations are sent (RFC 4291, section 2.7.1).
+pub fn is_solicited_node_multicast(address: &Ipv6Addr) -> bool {
+    let octets = address.octets();
+    octets[..13] == [0xFF, 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01, 0xFF]
+}
+
+/// Returns the scope of a multicast address, or `None` if the address is not multicast.
+pub fn multicast_scope(address: &Ipv6Addr) -> Option<Ipv6MulticastScope> {
+    address
+        .is_multicast()
+        .then(|| Ipv6MulticastScope::from(address.octets()[1] & 0x0F))
+}
+
+/// An extension header between the base header and the upper-layer header.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Ipv6ExtensionHeader {
+    /// The type of the header, given by the next header field before it, e.g. 0 for
+    /// Hop-by-Hop Options.
+    pub header_type: u8,
+
+    /// The type of the header that follows.
+    pub next_header: u8,
+
+    /// The length of the header in bytes, including the next header and length fields.
+    pub length: usize,
+
+    /// The header as received, which `to_bytes` writes back unchanged, as the options it
+    /// carries are not decoded.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub raw_header: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Ipv6PacketHeader {
     pub version: u8,
     pub traffic_class: u8,
@@ -45,15 +159,339 @@ pub struct Ipv6PacketHeader {
     pub hop_limit: u8,
     pub source_address: Ipv6Addr,
     pub destination_address: Ipv6Addr,
+    /// The Hop-by-Hop Options, Routing and Destination Options headers following the base
+    /// header, in order. A Fragment header ends the chain and is left in the payload.
+    pub extension_headers: Vec<Ipv6ExtensionHeader>,
+    /// The position of the header, including extension headers, within the bytes the
+    /// packet was parsed from.
+    pub span: Range<usize>,
+    /// The header as received, including extension headers, e.g. to quote the packet in
+    /// an ICMPv6 error message.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub raw_header: Vec<u8>,
+}
+
+/// A version 6 header with an empty payload, carrying TCP from and to the unspecified
+/// address with a hop limit of 64.
+///
+/// The header was not parsed from bytes, so `raw_header` and `span` are empty.
+impl Default for Ipv6PacketHeader {
+    fn default() -> Self {
+        Ipv6PacketHeader {
+            version: 6,
+            traffic_class: 0,
+            flow_label: 0,
+            payload_length: 0,
+            next_header: IPType::TCP,
+            hop_limit: DEFAULT_HOP_LIMIT,
+            source_address: Ipv6Addr::UNSPECIFIED,
+            destination_address: Ipv6Addr::UNSPECIFIED,
+            extension_headers: Vec::new(),
+            span: 0..0,
+            raw_header: Vec::new(),
+        }
+    }
+}
+
+impl Ipv6PacketHeader {
+    /// Returns the protocol of the payload following the extension headers, e.g. TCP for
+    /// a Hop-by-Hop header followed by a TCP segment.
+    pub fn upper_layer_protocol(&self) -> IPType {
+        match self.extension_headers.last() {
+            Some(extension_header) => IPType::from(extension_header.next_header),
+            None => self.next_header.clone(),
+        }
+    }
+
+    /// Returns the combined length of the extension headers in bytes.
+    fn extension_headers_len(&self) -> usize {
+        self.extension_headers
+            .iter()
+            .map(|header| header.length)
+            .sum()
+    }
+
+    /// Returns the 6-bit Differentiated Services Code Point, the upper bits of
+    /// `traffic_class`.
+    pub fn dscp(&self) -> u8 {
+        self.traffic_class >> 2
+    }
+
+    /// Returns the Explicit Congestion Notification codepoint, the lower 2 bits of
+    /// `traffic_class`.
+    pub fn ecn(&self) -> Ecn {
+        Ecn::from(self.traffic_class)
+    }
 }
 
-#[derive(Debug, PartialEq)]
-pub struct Ipv6Packet {
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Ipv6Packet<'a> {
     pub header: Ipv6PacketHeader,
-    pub data: Box<LayeredData>,
+    pub data: Box<LayeredData<'a>>,
+    /// `true` if the packet is shorter than its declared payload length, so `data` holds
+    /// only the start of the payload. Only set for packets parsed with `allow_truncated`, or
+    /// quoted in an ICMP error message.
+    pub truncated: bool,
+}
+
+/// An iterator over the extension headers at the start of an IPv6 payload, yielding the
+/// type and bytes of each.
+///
+/// Hop-by-Hop Options, Routing and Destination Options headers are walked until a header
+/// of any other type is reached. A Fragment header also ends the walk, as the headers after
+/// it may be split across fragments; it is left in place to be handled by
+/// `Ipv6Reassembler`. A header running past the end of a complete payload is yielded as
+/// `ParserError::InconsistentLength`, while one cut off with a truncated payload ends the
+/// walk.
+#[derive(Debug, Clone)]
+struct ExtensionHeaderReader<'a> {
+    rest: &'a [u8],
+    next_header: Option<u8>,
+    truncated: bool,
 }
 
-impl Ipv6Packet {
+impl<'a> ExtensionHeaderReader<'a> {
+    fn new(payload: &'a [u8], next_header: u8, truncated: bool) -> Self {
+        ExtensionHeaderReader {
+            rest: payload,
+            next_header: Some(next_header),
+            truncated,
+        }
+    }
+}
+
+impl<'a> Iterator for ExtensionHeaderReader<'a> {
+    type Item = Result<(u8, &'a [u8]), ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let header_type = self.next_header.filter(|next_header| {
+            matches!(
+                *next_header,
+                HOP_BY_HOP_NEXT_HEADER | ROUTING_NEXT_HEADER | DESTINATION_OPTIONS_NEXT_HEADER
+            )
+        })?;
+
+        // The header length is given in 8-octet units, not including the first 8 octets.
+        let length = self
+            .rest
+            .get(1)
+            .map_or(2, |length| (*length as usize + 1) * 8);
+        let Some((raw_header, rest)) = self.rest.split_at_checked(length) else {
+            self.next_header = None;
+            return (!self.truncated).then(|| {
+                Err(ParserError::InconsistentLength {
+                    layer: "Ipv6 extension header".to_string(),
+                    declared: length,
+                    available: self.rest.len(),
+                })
+            });
+        };
+
+        self.rest = rest;
+        self.next_header = Some(raw_header[0]);
+        Some(Ok((header_type, raw_header)))
+    }
+}
+
+/// An IPv6 packet borrowed from the bytes it was parsed by `Ipv6Packet::from_bytes_ref`.
+///
+/// The header fields are read from the bytes when asked for, so nothing is copied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ipv6PacketRef<'a> {
+    bytes: &'a [u8],
+    header_length: usize,
+    upper_layer_protocol: u8,
+    truncated: bool,
+}
+
+impl<'a> Ipv6PacketRef<'a> {
+    /// Checks the header of `packets`, walks its extension headers and bounds the packet by
+    /// its declared payload length.
+    pub(crate) fn parse(packets: &'a [u8], allow_truncated: bool) -> Result<Self, ParserError> {
+        // Ensure packet is of minimum expected length.
+        if packets.len() < MIN_PACKET_SIZE {
+            return Err(ParserError::HeaderTooShort {
+                layer: "Ipv6 packet".to_string(),
+                got: packets.len(),
+                need: MIN_PACKET_SIZE,
+            });
+        }
+        let next_header = packets[6];
+
+        // A zero payload length either marks a jumbogram, whose real length is carried in
+        // a Hop-by-Hop option, or a packet with no payload at all.
+        let available = packets.len() - PAYLOAD_OFFSET;
+        let declared = match be_u16(packets, 4) {
+            0 => match Ipv6Packet::extract_jumbo_payload_length(packets, next_header)? {
+                Some(jumbo_length) => jumbo_length as usize,
+                None if available == 0 => 0,
+                None => {
+                    return Err(ParserError::InconsistentLength {
+                        layer: "Ipv6 payload".to_string(),
+                        declared: 0,
+                        available,
+                    })
+                }
+            },
+            length => length as usize,
+        };
+
+        // Anything beyond the declared length (e.g. Ethernet padding) is ignored, but the
+        // payload itself must be complete.
+        let truncated = available < declared;
+        if truncated && !allow_truncated {
+            return Err(ParserError::InconsistentLength {
+                layer: "Ipv6 payload".to_string(),
+                declared,
+                available,
+            });
+        }
+
+        let bytes = &packets[..PAYLOAD_OFFSET + declared.min(available)];
+        let mut header_length = PAYLOAD_OFFSET;
+        let mut upper_layer_protocol = next_header;
+        for extension_header in
+            ExtensionHeaderReader::new(&bytes[PAYLOAD_OFFSET..], next_header, truncated)
+        {
+            let (_, raw_header) = extension_header?;
+            header_length += raw_header.len();
+            upper_layer_protocol = raw_header[0];
+        }
+
+        Ok(Ipv6PacketRef {
+            bytes,
+            header_length,
+            upper_layer_protocol,
+            truncated,
+        })
+    }
+
+    /// Builds the `Ipv6Packet`, taking its payload from `source`, the buffer `bytes`
+    /// starts.
+    fn to_owned_in<'s, S: PayloadSource<'s> + ?Sized>(self, source: &S) -> Ipv6Packet<'s> {
+        let extension_headers = ExtensionHeaderReader::new(
+            &self.bytes[PAYLOAD_OFFSET..],
+            self.bytes[6],
+            self.truncated,
+        )
+        .map_while(Result::ok)
+        .map(|(header_type, raw_header)| Ipv6ExtensionHeader {
+            header_type,
+            next_header: raw_header[0],
+            length: raw_header.len(),
+            raw_header: raw_header.to_vec(),
+        })
+        .collect();
+
+        Ipv6Packet {
+            header: Ipv6PacketHeader {
+                version: self.version(),
+                traffic_class: self.traffic_class(),
+                flow_label: self.flow_label(),
+                payload_length: self.payload_length(),
+                next_header: self.next_header(),
+                hop_limit: self.hop_limit(),
+                source_address: self.source_address(),
+                destination_address: self.destination_address(),
+                extension_headers,
+                span: 0..self.header_length,
+                raw_header: self.header().to_vec(),
+            },
+            data: Box::new(source.payload(self.header_length..self.bytes.len())),
+            truncated: self.truncated,
+        }
+    }
+
+    pub fn version(&self) -> u8 {
+        self.bytes[0] >> 4
+    }
+
+    pub fn traffic_class(&self) -> u8 {
+        (be_u16(self.bytes, 0) >> 4) as u8
+    }
+
+    pub fn flow_label(&self) -> u32 {
+        be_u32(self.bytes, 0) & 0xFFFFF
+    }
+
+    pub fn payload_length(&self) -> u16 {
+        be_u16(self.bytes, 4)
+    }
+
+    pub fn next_header(&self) -> IPType {
+        IPType::from(self.bytes[6])
+    }
+
+    pub fn hop_limit(&self) -> u8 {
+        self.bytes[7]
+    }
+
+    pub fn source_address(&self) -> Ipv6Addr {
+        Ipv6Addr::from(self.address(SRC_ADDRESS_OFFSET))
+    }
+
+    pub fn destination_address(&self) -> Ipv6Addr {
+        Ipv6Addr::from(self.address(DEST_ADDRESS_OFFSET))
+    }
+
+    fn address(&self, offset: usize) -> [u8; 16] {
+        self.bytes[offset..offset + 16].try_into().unwrap()
+    }
+
+    /// Returns the protocol of the payload following the extension headers, e.g. TCP for
+    /// a Hop-by-Hop header followed by a TCP segment.
+    pub fn upper_layer_protocol(&self) -> IPType {
+        IPType::from(self.upper_layer_protocol)
+    }
+
+    /// Returns the header: the 40-byte base header followed by any extension headers.
+    pub fn header(&self) -> &'a [u8] {
+        &self.bytes[..self.header_length]
+    }
+
+    /// Returns the payload following the extension headers, which excludes any bytes past
+    /// the declared payload length.
+    pub fn data(&self) -> &'a [u8] {
+        &self.bytes[self.header_length..]
+    }
+
+    /// Returns `true` if the packet is shorter than its declared payload length.
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Parses the payload as the upper-layer protocol, borrowing from the same bytes. See
+    /// `LayeredDataRef` for the layers that can be borrowed.
+    ///
+    /// # Errors
+    ///
+    /// * Any error from parsing the TCP, UDP, IPv4 or IPv6 layer in the payload.
+    pub fn next_layer(&self) -> Result<LayeredDataRef<'a>, ParserError> {
+        ip_next_layer_ref(self.data(), &self.upper_layer_protocol())
+    }
+}
+
+impl<'a> From<Ipv6PacketRef<'a>> for Ipv6Packet<'a> {
+    /// Converts the packet into an `Ipv6Packet` borrowing the same bytes, with its payload left
+    /// raw.
+    fn from(packet: Ipv6PacketRef<'a>) -> Self {
+        packet.to_owned_in(&packet.bytes)
+    }
+}
+
+impl<'a> DeepParser<'a> for Ipv6PacketRef<'a> {
+    /// Converts the packet into an `Ipv6Packet`, and parses its payload from there.
+    fn parse_next_layer_with(
+        self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        Ipv6Packet::from(self).parse_next_layer_with(options, depth)
+    }
+}
+
+impl<'a> Ipv6Packet<'a> {
     /// Constructs a new `Ipv6` object from a slice of bytes representing
     /// an IPv6 packet.
     /// This function parses the byte slice, extracting essential
@@ -65,7 +503,7 @@ impl Ipv6Packet {
     ///
     /// # Parameters
     /// - `packets`: A byte slice representing a complete IPv6
-    /// packet, including both header and payload.
+    ///   packet, including both header and payload.
     ///
     /// # Returns
     /// If the operation is successful, the function returns an
@@ -81,177 +519,256 @@ impl Ipv6Packet {
     /// - The packet is too short to contain a valid IPv6 header.
     /// - There's an error extracting data for one of the packet's components.
     /// - There's an inconsistency between the stated payload length
-    ///  and the actual data available.
+    ///   and the actual data available (`ParserError::InconsistentLength`). A zero
+    ///   payload length is only accepted for jumbograms, whose length is taken from the
+    ///   Hop-by-Hop Jumbo Payload option, or for packets with nothing after the header.
     // TODO: Optimise this function. Use of cursor and slice isn't efficient
-    pub fn from_bytes(packets: &[u8]) -> Result<Self, ParserError> {
-        // Ensure packet is of minimum expected length.
-        if packets.len() < MIN_PACKET_SIZE {
-            return Err(ParserError::InvalidLength("Ipv6 packet".to_string()));
-        }
-        let mut cursor = Cursor::new(packets);
+    pub fn from_bytes(packets: &'a [u8]) -> Result<Self, ParserError> {
+        Self::parse(&packets, false)
+    }
 
-        // Parse the first segment of the packet: version, traffic class, and flow label.
-        // These are contained in the first 32 bits of the IPv6 header.
-        let (version, traffic_class, flow_label) =
-            Self::extract_ipv6_version_trafficclass_flowlabel(&mut cursor)?;
+    /// Constructs an `Ipv6Packet` from a packet that may be cut short of its declared length, such
+    /// as the copy quoted in an ICMP error message. The header must be complete, but the
+    /// payload holds only the bytes that are present.
+    pub(crate) fn from_truncated_bytes<S: PayloadSource<'a> + ?Sized>(
+        packets: &S,
+    ) -> Result<Self, ParserError> {
+        Self::parse(packets, true)
+    }
 
-        // Parse the next segment of the packet: payload length, next header, and hop limit.
-        // These are contained in the subsequent 32 bits of the IPv6 header.
-        let (payload_length, next_header, hop_limit) =
-            Self::extract_ipv6_length_header_hoplimit(&mut cursor)?;
+    /// Constructs an `Ipv6Packet` nested within another layer, accepting a truncated packet
+    /// if `options.allow_truncated` is set.
+    pub(crate) fn from_bytes_with<S: PayloadSource<'a> + ?Sized>(
+        packets: &S,
+        options: &ParseOptions,
+    ) -> Result<Self, ParserError> {
+        Self::parse(packets, options.allow_truncated)
+    }
 
-        // Extract the source and destination addresses.
-        // These are each 128 bits (or 16 bytes) and are located after the initial 64-bit header.
-        let src_address_bytes = Self::extract_ipv6_address(packets, SRC_ADDRESS_OFFSET)?;
-        let dest_address_bytes = Self::extract_ipv6_address(packets, DEST_ADDRESS_OFFSET)?;
+    /// Constructs an `Ipv6Packet` whose payload shares `buf` rather than copying it.
+    ///
+    /// Behaves like `from_bytes`, except that the payload is left as a `LayeredData::Bytes`
+    /// slice of `buf`. Layers parsed from it by `parse_next_layer` share `buf` too, down to
+    /// the payload of a TCP segment or UDP datagram; application layers are copied.
+    #[cfg(feature = "bytes")]
+    pub fn from_bytes_buf(buf: &bytes::Bytes) -> Result<Self, ParserError> {
+        Self::parse(buf, false)
+    }
 
-        // Extract the payload. It's the segment of the packet that follows the IPv6 header
-        // and addresses, which contains the actual transmitted data.
-        let data = (&packets[PAYLOAD_OFFSET..(packets.len())]).to_vec();
+    /// Constructs an `Ipv6PacketRef` borrowing from `packets`, without allocating.
+    ///
+    /// Fails in the same cases as `from_bytes`. Layers parsed from it by
+    /// `Ipv6PacketRef::next_layer` borrow from `packets` too.
+    pub fn from_bytes_ref(packets: &[u8]) -> Result<Ipv6PacketRef<'_>, ParserError> {
+        Ipv6PacketRef::parse(packets, false)
+    }
 
-        Ok(Ipv6Packet {
-            header: Ipv6PacketHeader {
-                version,
-                traffic_class,
-                flow_label,
-                payload_length,
-                next_header: IPType::from(next_header),
-                hop_limit,
-                source_address: Ipv6Addr::from(src_address_bytes),
-                destination_address: Ipv6Addr::from(dest_address_bytes),
-            },
-            data: Box::new(LayeredData::Payload(data)),
-        })
+    fn parse<S: PayloadSource<'a> + ?Sized>(
+        source: &S,
+        allow_truncated: bool,
+    ) -> Result<Self, ParserError> {
+        Ok(Ipv6PacketRef::parse(source.as_slice(), allow_truncated)?.to_owned_in(source))
     }
 
-    /// Parses the first 32 bits of an IPv6 header from the given cursor, extracting the version, traffic class, and flow label.
+    /// Extracts the length carried by a Jumbo Payload option (RFC 2675), if present.
     ///
-    /// The function reads a 32-bit segment from the cursor's current position and then extracts:
-    /// 1. Version (4 bits): Identifies the IP version, which is 6 for IPv6 packets.
-    /// 2. Traffic Class (8 bits): The traffic class field in the IPv6 header used for QoS management.
-    /// 3. Flow Label (20 bits): Used by a source to label sequences of packets for which it requests special handling by the IPv6 routers.
+    /// The option can only appear in a Hop-by-Hop Options header directly following the
+    /// base header. Its value counts every byte after the base header, including the
+    /// Hop-by-Hop header itself.
     ///
     /// # Parameters
-    /// - `cursor`: A mutable reference to a cursor which is expected to be at the position of the 32-bit segment
-    ///   containing the version, traffic class, and flow label in the IPv6 header.
+    /// - `packets`: The whole packet, starting at the base header.
+    /// - `next_header`: The next header value of the base header.
     ///
     /// # Returns
-    /// If successful, returns a tuple of `u8` and `u32` integers representing the version, traffic class, and flow label respectively.
-    /// If there is an error reading from the cursor, a `ParserError` will be returned.
+    /// `Ok(Some(length))` if a jumbo option is found, `Ok(None)` if the packet carries no
+    /// Hop-by-Hop header or the header has no jumbo option.
     ///
     /// # Errors
-    /// Returns `ParserError` if there is any issue in reading data from the cursor.
-    fn extract_ipv6_version_trafficclass_flowlabel(
-        cursor: &mut Cursor<&[u8]>,
-    ) -> Result<(u8, u8, u32), ParserError> {
-        // Read the first 32 bits, that contains the `version`, `traffic class` and `flow label`
-        let first_32_bits = read_u32(cursor, "Version_TrafficClass_FlowLabel")?;
+    /// Returns `ParserError::HeaderTooShort` if the Hop-by-Hop header runs past the end of
+    /// the packet, and `ParserError::OptionsOverrun` if one of its options runs past the
+    /// end of the header.
+    fn extract_jumbo_payload_length(
+        packets: &[u8],
+        next_header: u8,
+    ) -> Result<Option<u32>, ParserError> {
+        if next_header != HOP_BY_HOP_NEXT_HEADER {
+            return Ok(None);
+        }
+
+        let truncated = |need| ParserError::HeaderTooShort {
+            layer: "Ipv6 hop-by-hop header".to_string(),
+            got: packets.len().saturating_sub(PAYLOAD_OFFSET),
+            need,
+        };
 
-        // The version is contained in the highest 4 bits of the 32-bit word.
-        // Shift by 28 bits to the right to drop the lower 28 bits.
-        let version = (first_32_bits >> 28) as u8;
+        // The header length is given in 8-octet units, not including the first 8 octets.
+        let header_length = match packets.get(PAYLOAD_OFFSET + 1) {
+            Some(length) => (*length as usize + 1) * 8,
+            None => return Err(truncated(2)),
+        };
+        let options = packets
+            .get(PAYLOAD_OFFSET + 2..PAYLOAD_OFFSET + header_length)
+            .ok_or_else(|| truncated(header_length))?;
 
-        // The traffic class is in the next 8 bits. Shift 20 bits to the right to drop the lower
-        // 20 bits (flow label), and then mask to get only the lower 8 bits.
-        let traffic_class = ((first_32_bits >> 20) & 0xFF) as u8;
+        let mut cursor = ByteReader::new(options);
+        while cursor.remaining() > 0 {
+            let option_type = cursor.read_u8("HopByHop_OptionType")?;
+            if option_type == PAD1_OPTION {
+                continue;
+            }
 
-        // The flow label is in the lowest 20 bits of the 32-bit word.
-        let flow_label = first_32_bits & 0xFFFFF; // Masking the lowest 20 bits.
+            let option_length = cursor.read_u8("HopByHop_OptionLength")? as usize;
+            if cursor.remaining() < option_length {
+                return Err(ParserError::OptionsOverrun {
+                    layer: "Ipv6 hop-by-hop option".to_string(),
+                    declared: option_length,
+                    available: cursor.remaining(),
+                });
+            }
 
-        Ok((version, traffic_class, flow_label))
+            if option_type == JUMBO_PAYLOAD_OPTION && option_length == 4 {
+                return Ok(Some(cursor.read_u32_be("Jumbo_Payload_Length")?));
+            }
+            cursor.read_slice(option_length, "HopByHop_OptionData")?;
+        }
+
+        Ok(None)
     }
 
-    /// Extracts specific details from the second 32 bits of the IPv6 header.
-    ///
-    /// This function is responsible for parsing the next 32 bits after the initial segment of the IPv6 header.
-    /// It retrieves the payload length, the identifier of the next header, and the hop limit from the raw header data.
+    /// Returns the length of the upper-layer payload the packet declares, which exceeds the
+    /// bytes held in `data` if the packet is truncated. A truncated jumbogram reports the
+    /// bytes held.
+    fn payload_len(&self) -> usize {
+        let present = self.data.payload().map_or(0, <[u8]>::len);
+        if !self.truncated {
+            return present;
+        }
+        (self.header.payload_length as usize)
+            .saturating_sub(self.header.extension_headers_len())
+            .max(present)
+    }
+
+    /// Serializes the packet back into its wire format.
     ///
-    /// # Parameters
-    /// - `cursor`: A mutable reference to a cursor over the byte slice of the packet. It should be positioned at the start of the 32 bits containing the relevant data.
+    /// The 40-byte base header is rebuilt from the parsed fields, with the traffic class and
+    /// flow label packed back into the first 32-bit word alongside the version. The
+    /// extension headers are written from their raw bytes, and `payload_length` is
+    /// recomputed from them and the serialized payload; the stored value is ignored.
+    /// Payloads too large for the field are written with a zero length, as a jumbogram's
+    /// Hop-by-Hop header carries its real length.
     ///
     /// # Returns
-    /// If successful, returns a tuple of three elements:
-    /// - `payload_length`: The length of the IPv6 payload (data coming after the header).
-    /// - `next_header`: An identifier for the next header in the packet data. This informs how to interpret the subsequent payload or extension.
-    /// - `hop_limit`: The limit of how many hops (routers) this packet can pass through before being discarded.
-    ///
-    /// If there is an error during parsing, this function returns a `ParserError`.
-    ///
-    /// # Errors
-    /// This function will return an error if reading from the byte slice fails,
-    ///  for instance, if there are fewer bytes available than expected.
-    fn extract_ipv6_length_header_hoplimit(
-        cursor: &mut Cursor<&[u8]>,
-    ) -> Result<(u16, u8, u8), ParserError> {
-        // Read the next 32 bits, that contains the `version`, `traffic class` and `flow label`
-        let second_32_bits = read_u32(cursor, "PayloadLength_NextHeader_HopLimit")?;
-
-        // The payload length is contained in the highest 16 bits of the 32-bit word.
-        // Shift by 16 bits to the right to drop the lower 16 bits.
-        let payload_length = (second_32_bits >> 16) as u16;
+    /// - A `Vec<u8>` holding the serialized header followed by the payload.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = &self.header;
+        let mut payload = Vec::with_capacity(header.extension_headers_len());
+        for extension_header in &header.extension_headers {
+            payload.extend_from_slice(&extension_header.raw_header);
+        }
+        payload.extend_from_slice(&self.data.to_bytes());
+        // Jumbograms declare a zero length, their real length being carried in an option.
+        let payload_length = u16::try_from(payload.len()).unwrap_or(0);
 
-        // The next header is in the next 8 bits. Shift 8 bits to the right to drop the lower
-        // 8 bits (hop limit), and then mask to get only the lower 8 bits.
-        let next_header = ((second_32_bits >> 8) & 0xFF) as u8;
+        let first_word = ((header.version as u32 & 0xF) << 28)
+            | ((header.traffic_class as u32) << 20)
+            | (header.flow_label & 0xFFFFF);
 
-        // The flow label is in the lowest 208 bits of the 32-bit word.
-        let hop_limit = (second_32_bits & 0xFF) as u8; // Masking the lowest 8 bits.
+        let mut packet = Vec::with_capacity(PAYLOAD_OFFSET + payload.len());
+        packet.extend_from_slice(&first_word.to_be_bytes());
+        packet.extend_from_slice(&payload_length.to_be_bytes());
+        packet.push(u8::from(&header.next_header));
+        packet.push(header.hop_limit);
+        packet.extend_from_slice(&header.source_address.octets());
+        packet.extend_from_slice(&header.destination_address.octets());
 
-        Ok((payload_length, next_header, hop_limit))
+        packet.extend_from_slice(&payload);
+        packet
     }
 
-    /// Extracts an IPv6 address from a byte frame starting at a specified offset.
-    ///
-    /// Given a byte slice representing a frame and an offset within that frame,
-    /// this function attempts to extract 16 bytes from the offset, interprets them as
-    /// an IPv6 address, and returns the address as an array of eight `u16` segments.
-    ///
-    /// # Arguments
-    ///
-    /// * `frame`: A byte slice representing the frame from which to extract the IPv6 address.
-    /// * `offset`: The position within `frame` where the 16 bytes representing the IPv6 address begin.
+    /// Checks the header fields that lenient parsing ignores, as done in strict mode.
     ///
     /// # Errors
-    ///
-    /// Returns `ParserError::FrameTooShort` if the `frame` does not contain enough bytes
-    /// (i.e., `offset + 16` exceeds the frame's length).
-    ///
-    /// Returns `ParserError::ExtractionError` if the attempt to build a 16-byte array from
-    /// the frame slice fails (which can happen if the slice is not exactly 16 bytes).
-    ///
-    /// # Return Value
-    ///
-    /// Returns `Ok([u16; 8])` representing the IPv6 address if the extraction succeeds.
-    fn extract_ipv6_address(frame: &[u8], offset: usize) -> Result<[u16; 8], ParserError> {
-        if frame.len() < offset + 16 {
-            return Err(ParserError::InvalidLength("Ipv4 address".to_string()));
+    /// - `ParserError::ProtocolViolation` if the version is not 6.
+    pub fn check_conformance(&self) -> Result<(), ParserError> {
+        if self.header.version != 6 {
+            return Err(ParserError::ProtocolViolation {
+                field: "Ipv6 version".to_string(),
+                detail: format!("expected 6, got {}", self.header.version),
+            });
         }
 
-        // Extracting 16 bytes from the frame for the IPv6 address.
-        let bytes: [u8; 16] =
-            frame[offset..offset + 16]
-                .try_into()
-                .map_err(|e| ParserError::ExtractionError {
-                    source: ErrorSource::TryFromSlice(e),
-                    string: "IPv6 Address".to_string(),
-                })?;
-
-        // Converting each pair of bytes into a u16 to form the components of the IPv6 address.
-        let mut address = [0u16; 8];
-        for i in 0..8 {
-            address[i] = u16::from_be_bytes([bytes[i * 2], bytes[i * 2 + 1]]);
+        Ok(())
+    }
+
+    /// Returns `true` if `addr` is either the source or the destination of the packet.
+    pub fn matches_host(&self, addr: Ipv6Addr) -> bool {
+        self.header.source_address == addr || self.header.destination_address == addr
+    }
+
+    /// Returns `true` if the source or the destination of the packet lies within the
+    /// subnet `net/prefix`. Prefixes longer than 128 bits are treated as `/128`.
+    pub fn matches_subnet(&self, net: Ipv6Addr, prefix: u8) -> bool {
+        let mask = u128::MAX
+            .checked_shl(128 - prefix.min(128) as u32)
+            .unwrap_or(0);
+        let network = u128::from(net) & mask;
+
+        u128::from(self.header.source_address) & mask == network
+            || u128::from(self.header.destination_address) & mask == network
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Ipv6
+    }
+
+    /// Detaches the packet from the bytes it was parsed from, copying its payload if it is
+    /// borrowed.
+    pub fn into_owned(self) -> Ipv6Packet<'static> {
+        Ipv6Packet {
+            header: self.header,
+            truncated: self.truncated,
+            data: Box::new(self.data.into_owned()),
         }
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Ipv6Packet<'a> {
+    type Error = ParserError;
 
-        Ok(address)
+    /// Constructs an `Ipv6Packet` from a slice. See `from_bytes`.
+    fn try_from(packets: &'a [u8]) -> Result<Self, Self::Error> {
+        Ipv6Packet::from_bytes(packets)
     }
 }
 
-impl DeepParser for Ipv6Packet {
-    fn parse_next_layer(mut self) -> Result<LayeredData, ParserError> {
-        let layered_data: LayeredData =
-            parse_ip_next_protocol_layer(&*self.data, &self.header.next_header)?;
+impl<'a> DeepParser<'a> for Ipv6Packet<'a> {
+    fn parse_next_layer_with(
+        mut self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Ipv6, depth);
+        if options.strict {
+            self.check_conformance()?;
+        }
+
+        let context = ParseContext::new(LayerKind::Ipv6, 0, 0).with_ip(
+            self.header.source_address.into(),
+            self.header.destination_address.into(),
+            self.payload_len(),
+        );
+        let result = parse_ip_next_protocol_layer(
+            &self.data,
+            &self.header.upper_layer_protocol(),
+            &context,
+            options,
+            depth,
+        );
 
-        *self.data = layered_data;
+        options.recover(result, &mut self.data)?;
         Ok(LayeredData::Ipv6Data(self))
     }
 }
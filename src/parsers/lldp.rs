@@ -0,0 +1,229 @@
+// LLDP TLV Structure:
+//  0                   1
+//  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-
+// |    Type     |     Length      |  Value ...
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-
+//
+// An LLDPDU is a sequence of TLVs: the mandatory Chassis ID, Port ID and TTL
+// TLVs, in that order, followed by optional TLVs and terminated by an
+// End-of-LLDPDU TLV (type 0, length 0).
+
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
+use super::{
+    definitions::{DeepParser, LayerKind, LayeredData, ParseOptions},
+    errors::ParserError,
+    trace,
+    utils::ByteReader,
+};
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ops::Range;
+
+const TLV_HEADER_SIZE: usize = 2;
+
+const END_OF_LLDPDU: u8 = 0;
+const CHASSIS_ID: u8 = 1;
+const PORT_ID: u8 = 2;
+const TIME_TO_LIVE: u8 = 3;
+const SYSTEM_NAME: u8 = 5;
+const SYSTEM_DESCRIPTION: u8 = 6;
+
+/// A Chassis ID or Port ID, qualified by a subtype describing its format
+/// (e.g. 4 for a MAC address or 5 for an interface name).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LldpId {
+    pub subtype: u8,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub id: Vec<u8>,
+}
+
+/// A TLV that is not decoded into a dedicated field.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LldpTlv {
+    /// The 7-bit TLV type.
+    pub tlv_type: u8,
+
+    /// The raw TLV value.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub value: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LldpPacket {
+    /// Identifies the chassis of the sending device.
+    pub chassis_id: LldpId,
+
+    /// Identifies the port the LLDPDU was sent from.
+    pub port_id: LldpId,
+
+    /// The number of seconds the information remains valid. Zero asks the receiver
+    /// to discard it.
+    pub time_to_live: u16,
+
+    /// The administratively assigned name of the system, if advertised.
+    pub system_name: Option<String>,
+
+    /// A description of the system, if advertised.
+    pub system_description: Option<String>,
+
+    /// Every other optional TLV, in the order received.
+    pub other_tlvs: Vec<LldpTlv>,
+
+    /// The position of the LLDPDU, up to and including the End-of-LLDPDU TLV, within the
+    /// bytes the packet was parsed from.
+    pub span: Range<usize>,
+}
+
+impl LldpPacket {
+    /// Constructs an `LldpPacket` from a slice of bytes.
+    ///
+    /// TLVs are read until the End-of-LLDPDU TLV or the end of the slice, so any padding
+    /// after the End-of-LLDPDU TLV is ignored. Text TLVs that are not valid UTF-8 are
+    /// decoded lossily.
+    ///
+    /// # Arguments
+    ///
+    /// * `packets` - A byte slice beginning with the first TLV of an LLDPDU.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, ParserError>` - An `LldpPacket` instance or a `ParserError`.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::InconsistentLength` - If a TLV is longer than the bytes that remain.
+    /// * `ParserError::InvalidLength` - If a mandatory TLV is too short to hold its value.
+    /// * `ParserError::MissingLldpTlv` - If the Chassis ID, Port ID or TTL TLV is absent.
+    pub fn from_bytes(packets: &[u8]) -> Result<Self, ParserError> {
+        let mut cursor = ByteReader::new(packets);
+
+        let mut chassis_id = None;
+        let mut port_id = None;
+        let mut time_to_live = None;
+        let mut system_name = None;
+        let mut system_description = None;
+        let mut other_tlvs = Vec::new();
+
+        while cursor.remaining() >= TLV_HEADER_SIZE {
+            let type_length = cursor.read_u16_be("LLDP_TLV_Header")?;
+            let tlv_type = (type_length >> 9) as u8;
+            let length = (type_length & 0x1FF) as usize;
+
+            if cursor.remaining() < length {
+                return Err(ParserError::InconsistentLength {
+                    layer: "LLDP TLV".to_string(),
+                    declared: length,
+                    available: cursor.remaining(),
+                });
+            }
+            let value = cursor.read_slice(length, "LLDP_TLV_Value")?;
+
+            match tlv_type {
+                END_OF_LLDPDU => break,
+                CHASSIS_ID => chassis_id = Some(Self::parse_id(value)?),
+                PORT_ID => port_id = Some(Self::parse_id(value)?),
+                TIME_TO_LIVE => match value {
+                    [high, low] => time_to_live = Some(u16::from_be_bytes([*high, *low])),
+                    _ => return Err(ParserError::InvalidLength("LLDP TTL".to_string())),
+                },
+                SYSTEM_NAME => system_name = Some(String::from_utf8_lossy(value).into_owned()),
+                SYSTEM_DESCRIPTION => {
+                    system_description = Some(String::from_utf8_lossy(value).into_owned())
+                }
+                _ => other_tlvs.push(LldpTlv {
+                    tlv_type,
+                    value: value.to_vec(),
+                }),
+            }
+        }
+
+        Ok(LldpPacket {
+            chassis_id: chassis_id
+                .ok_or_else(|| ParserError::MissingLldpTlv("Chassis ID".to_string()))?,
+            port_id: port_id.ok_or_else(|| ParserError::MissingLldpTlv("Port ID".to_string()))?,
+            time_to_live: time_to_live
+                .ok_or_else(|| ParserError::MissingLldpTlv("TTL".to_string()))?,
+            system_name,
+            system_description,
+            other_tlvs,
+            span: 0..cursor.position(),
+        })
+    }
+
+    /// Splits a Chassis ID or Port ID value into its subtype and identifier.
+    fn parse_id(value: &[u8]) -> Result<LldpId, ParserError> {
+        match value {
+            [subtype, id @ ..] if !id.is_empty() => Ok(LldpId {
+                subtype: *subtype,
+                id: id.to_vec(),
+            }),
+            _ => Err(ParserError::InvalidLength("LLDP ID".to_string())),
+        }
+    }
+
+    /// Serializes the LLDPDU back into its wire format, ending it with an End-of-LLDPDU TLV.
+    ///
+    /// The mandatory TLVs come first. The System Name and System Description are placed
+    /// among `other_tlvs` by type, so LLDPDUs whose optional TLVs were sent in ascending
+    /// order, as is usual, are reproduced exactly.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let id_value = |id: &LldpId| [&[id.subtype][..], &id.id].concat();
+        Self::write_tlv(&mut bytes, CHASSIS_ID, &id_value(&self.chassis_id));
+        Self::write_tlv(&mut bytes, PORT_ID, &id_value(&self.port_id));
+        Self::write_tlv(&mut bytes, TIME_TO_LIVE, &self.time_to_live.to_be_bytes());
+
+        let mut text_tlvs = [
+            (SYSTEM_NAME, &self.system_name),
+            (SYSTEM_DESCRIPTION, &self.system_description),
+        ]
+        .into_iter()
+        .filter_map(|(tlv_type, text)| Some((tlv_type, text.as_ref()?.as_bytes())))
+        .peekable();
+        for tlv in &self.other_tlvs {
+            while let Some((tlv_type, text)) = text_tlvs.next_if(|(t, _)| *t < tlv.tlv_type) {
+                Self::write_tlv(&mut bytes, tlv_type, text);
+            }
+            Self::write_tlv(&mut bytes, tlv.tlv_type, &tlv.value);
+        }
+        for (tlv_type, text) in text_tlvs {
+            Self::write_tlv(&mut bytes, tlv_type, text);
+        }
+
+        Self::write_tlv(&mut bytes, END_OF_LLDPDU, &[]);
+        bytes
+    }
+
+    /// Appends a TLV, its 7-bit type and 9-bit length packed into the first two bytes.
+    fn write_tlv(bytes: &mut Vec<u8>, tlv_type: u8, value: &[u8]) {
+        let type_length = ((tlv_type as u16) << 9) | (value.len() as u16 & 0x1FF);
+        bytes.extend_from_slice(&type_length.to_be_bytes());
+        bytes.extend_from_slice(value);
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Lldp
+    }
+}
+
+impl<'a> DeepParser<'a> for LldpPacket {
+    fn parse_next_layer_with(
+        self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Lldp, depth);
+
+        Ok(LayeredData::LldpData(self))
+    }
+}
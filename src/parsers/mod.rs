@@ -1,10 +1,36 @@
+pub mod aggregator;
+pub mod arp;
+pub mod checksum;
 pub mod constants;
 pub mod definitions;
+pub mod dns;
 pub mod errors;
 pub mod ethernet_frame;
+#[cfg(feature = "etherparse")]
+pub mod etherparse;
+pub mod filter;
+pub mod flow;
+#[cfg(feature = "std")]
+pub mod frame_reader;
+pub mod gtp;
 pub mod icmp;
+pub mod icmpv6;
 pub mod ipv4;
 pub mod ipv6;
+pub mod lldp;
+pub mod mpls;
+pub mod pppoe;
+pub mod reassembly;
+pub mod registry;
+pub mod sip;
+pub mod sll;
+pub mod snmp;
 pub mod tcp;
+pub mod tcp_state;
+pub mod tcp_stream;
+mod trace;
 pub mod udp;
 pub mod utils;
+pub mod view;
+pub mod vxlan;
+pub mod websocket;
@@ -0,0 +1,209 @@
+// MPLS Label Stack Entry:
+//  0                   1                   2                   3
+//  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |                Label                  | TC  |S|      TTL      |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//
+// Entries repeat until one has the bottom-of-stack (S) bit set, after which the
+// encapsulated payload begins.
+
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
+use super::{
+    definitions::{DeepParser, LayerKind, LayeredData, ParseOptions},
+    errors::ParserError,
+    ipv4::Ipv4Packet,
+    ipv6::Ipv6Packet,
+    trace,
+    utils::{ByteReader, PayloadSource},
+};
+
+use alloc::{boxed::Box, string::ToString, vec::Vec};
+use core::ops::Range;
+
+const LABEL_ENTRY_SIZE: usize = 4;
+
+/// A single entry of an MPLS label stack.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MplsLabel {
+    /// The 20-bit label value.
+    pub label: u32,
+
+    /// The 3-bit traffic class (formerly EXP) field, used for QoS.
+    pub traffic_class: u8,
+
+    /// Set on the last entry of the label stack.
+    pub bottom_of_stack: bool,
+
+    /// The 8-bit time-to-live field.
+    pub time_to_live: u8,
+}
+
+impl MplsLabel {
+    /// Constructs an `MplsLabel` from a raw 32-bit label stack entry.
+    pub fn new(entry: u32) -> Self {
+        MplsLabel {
+            label: entry >> 12,
+            traffic_class: ((entry >> 9) & 0x7) as u8,
+            bottom_of_stack: (entry >> 8) & 1 != 0,
+            time_to_live: (entry & 0xFF) as u8,
+        }
+    }
+
+    /// Returns the raw 32-bit label stack entry, the inverse of `new`.
+    pub fn entry(&self) -> u32 {
+        ((self.label & 0xFFFFF) << 12)
+            | ((self.traffic_class as u32 & 0x7) << 9)
+            | ((self.bottom_of_stack as u32) << 8)
+            | self.time_to_live as u32
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MplsPacketHeader {
+    /// The label stack, ordered from the outermost entry to the bottom of the stack.
+    pub labels: Vec<MplsLabel>,
+
+    /// The position of the label stack within the bytes the packet was parsed from.
+    pub span: Range<usize>,
+
+    /// The label stack as received, four bytes per entry, e.g. to push the same stack
+    /// onto another packet without encoding each label again.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub raw_header: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MplsPacket<'a> {
+    pub header: MplsPacketHeader,
+    pub data: Box<LayeredData<'a>>,
+}
+
+impl<'a> MplsPacket<'a> {
+    /// Constructs an `MplsPacket` from a slice of bytes.
+    ///
+    /// Label stack entries are read until one with the bottom-of-stack bit set is found.
+    /// Everything after that entry is treated as the encapsulated payload.
+    ///
+    /// # Arguments
+    ///
+    /// * `packets` - A byte slice beginning with the MPLS label stack.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, ParserError>` - An `MplsPacket` instance or a `ParserError`.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::HeaderTooShort` - If the slice is too short to hold a single label.
+    /// * `ParserError::UnterminatedLabelStack` - If the bytes run out before a
+    ///   bottom-of-stack entry is found.
+    pub fn from_bytes(packets: &'a [u8]) -> Result<Self, ParserError> {
+        Self::parse(&packets)
+    }
+
+    /// Constructs an `MplsPacket` whose payload is taken from `source`.
+    pub(crate) fn parse<S: PayloadSource<'a> + ?Sized>(source: &S) -> Result<Self, ParserError> {
+        let packets = source.as_slice();
+        if packets.len() < LABEL_ENTRY_SIZE {
+            return Err(ParserError::HeaderTooShort {
+                layer: "MPLS packet".to_string(),
+                got: packets.len(),
+                need: LABEL_ENTRY_SIZE,
+            });
+        }
+
+        let mut cursor = ByteReader::new(packets);
+        let mut labels = Vec::new();
+
+        loop {
+            if cursor.remaining() < LABEL_ENTRY_SIZE {
+                return Err(ParserError::UnterminatedLabelStack);
+            }
+
+            let label = MplsLabel::new(cursor.read_u32_be("MPLS_Label")?);
+            let bottom_of_stack = label.bottom_of_stack;
+            labels.push(label);
+
+            if bottom_of_stack {
+                break;
+            }
+        }
+
+        let header_length = cursor.position();
+
+        Ok(MplsPacket {
+            header: MplsPacketHeader {
+                labels,
+                span: 0..header_length,
+                raw_header: packets[..header_length].to_vec(),
+            },
+            data: Box::new(source.payload(header_length..packets.len())),
+        })
+    }
+
+    /// Serializes the label stack and payload back into their wire format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self
+            .header
+            .labels
+            .iter()
+            .flat_map(|label| label.entry().to_be_bytes())
+            .collect();
+        bytes.extend_from_slice(&self.data.to_bytes());
+        bytes
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Mpls
+    }
+
+    /// Detaches the packet from the bytes it was parsed from, copying its payload if it is
+    /// borrowed.
+    pub fn into_owned(self) -> MplsPacket<'static> {
+        MplsPacket {
+            header: self.header,
+            data: Box::new(self.data.into_owned()),
+        }
+    }
+}
+
+impl<'a> DeepParser<'a> for MplsPacket<'a> {
+    /// Parses the payload carried beneath the label stack.
+    ///
+    /// MPLS does not identify the encapsulated protocol, so the first nibble of the payload
+    /// is used to guess it: 4 is parsed as IPv4 and 6 as IPv6. Any other payload (e.g. a
+    /// pseudowire) is left as raw data.
+    fn parse_next_layer_with(
+        mut self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Mpls, depth);
+
+        let data = self.data.payload().ok_or(ParserError::InvalidPayload)?;
+
+        let result = match data.first().map(|byte| byte >> 4) {
+            Some(4) => options
+                .timed(LayerKind::Ipv4, || {
+                    Ipv4Packet::from_bytes_with(&*self.data, options)
+                })
+                .and_then(|packet| packet.parse_next_layer_with(options, depth)),
+            Some(6) => options
+                .timed(LayerKind::Ipv6, || {
+                    Ipv6Packet::from_bytes_with(&*self.data, options)
+                })
+                .and_then(|packet| packet.parse_next_layer_with(options, depth)),
+            _ => return Ok(LayeredData::MplsData(self)),
+        };
+
+        options.recover(result, &mut self.data)?;
+        Ok(LayeredData::MplsData(self))
+    }
+}
@@ -0,0 +1,222 @@
+// PPPoE Header:
+//  0                   1                   2                   3
+//  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |  VER  | TYPE  |      CODE     |          SESSION_ID           |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |            LENGTH             |   PPP Protocol (session only) |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |                          payload ...
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//
+// LENGTH counts every byte after the 6-byte PPPoE header, including the PPP
+// protocol field of session packets.
+
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
+use super::{
+    definitions::{DeepParser, LayerKind, LayeredData, ParseOptions},
+    errors::ParserError,
+    ipv4::Ipv4Packet,
+    ipv6::Ipv6Packet,
+    trace,
+    utils::{ByteReader, PayloadSource},
+};
+
+use alloc::{boxed::Box, string::ToString, vec::Vec};
+use core::ops::Range;
+
+const HEADER_SIZE: usize = 6;
+const PPP_PROTOCOL_SIZE: usize = 2;
+
+// Session stage packets always carry code 0x00; the discovery stage never does.
+const SESSION_DATA_CODE: u8 = 0x00;
+
+const PPP_PROTOCOL_IPV4: u16 = 0x0021;
+const PPP_PROTOCOL_IPV6: u16 = 0x0057;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PppoeHeader {
+    /// The 4-bit PPPoE version, always 1.
+    pub version: u8,
+
+    /// The 4-bit PPPoE type, always 1.
+    pub packet_type: u8,
+
+    /// The packet code. `0x00` for session data, otherwise a discovery stage
+    /// code such as PADI (`0x09`) or PADT (`0xA7`).
+    pub code: u8,
+
+    /// The session identifier, fixed for the lifetime of the PPP session.
+    pub session_id: u16,
+
+    /// The length of the PPPoE payload, excluding the PPPoE header.
+    pub length: u16,
+
+    /// The PPP protocol number of a session packet's payload. Absent for discovery packets.
+    pub ppp_protocol: Option<u16>,
+
+    /// The position of the header, including the PPP protocol field, within the bytes
+    /// the packet was parsed from.
+    pub span: Range<usize>,
+
+    /// The header as received, including the PPP protocol field, e.g. to send a
+    /// rewritten payload over the same session.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub raw_header: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PppoePacket<'a> {
+    pub header: PppoeHeader,
+    pub data: Box<LayeredData<'a>>,
+}
+
+impl<'a> PppoePacket<'a> {
+    /// Constructs a `PppoePacket` from a slice of bytes.
+    ///
+    /// Session packets are recognised by their code and have the PPP protocol field that
+    /// follows the header extracted. The payload is bounded by the length declared in the
+    /// header, so any Ethernet padding after it is discarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `packets` - A byte slice beginning with the PPPoE header.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, ParserError>` - A `PppoePacket` instance or a `ParserError`.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::HeaderTooShort` - If the slice is shorter than the PPPoE header.
+    /// * `ParserError::InconsistentLength` - If the slice is shorter than the payload length
+    ///   the header declares.
+    /// * `ParserError::InvalidLength` - If a session packet is too short for its PPP
+    ///   protocol field.
+    pub fn from_bytes(packets: &'a [u8]) -> Result<Self, ParserError> {
+        Self::parse(&packets)
+    }
+
+    /// Constructs a `PppoePacket` whose payload is taken from `source`.
+    pub(crate) fn parse<S: PayloadSource<'a> + ?Sized>(source: &S) -> Result<Self, ParserError> {
+        let packets = source.as_slice();
+        if packets.len() < HEADER_SIZE {
+            return Err(ParserError::HeaderTooShort {
+                layer: "PPPoE packet".to_string(),
+                got: packets.len(),
+                need: HEADER_SIZE,
+            });
+        }
+
+        let mut cursor = ByteReader::new(packets);
+
+        let version_type = cursor.read_u8("PPPoE_Version_Type")?;
+        let code = cursor.read_u8("PPPoE_Code")?;
+        let session_id = cursor.read_u16_be("PPPoE_Session_Id")?;
+        let length = cursor.read_u16_be("PPPoE_Length")?;
+
+        if cursor.remaining() < length as usize {
+            return Err(ParserError::InconsistentLength {
+                layer: "PPPoE packet".to_string(),
+                declared: length as usize,
+                available: cursor.remaining(),
+            });
+        }
+
+        let ppp_protocol = match code {
+            SESSION_DATA_CODE if length as usize >= PPP_PROTOCOL_SIZE => {
+                Some(cursor.read_u16_be("PPP_Protocol")?)
+            }
+            SESSION_DATA_CODE => return Err(ParserError::InvalidLength("PPP frame".to_string())),
+            _ => None,
+        };
+
+        let header_length = cursor.position();
+        let payload_end = HEADER_SIZE + length as usize;
+
+        Ok(PppoePacket {
+            header: PppoeHeader {
+                version: version_type >> 4,
+                packet_type: version_type & 0x0F,
+                code,
+                session_id,
+                length,
+                ppp_protocol,
+                span: 0..header_length,
+                raw_header: packets[..header_length].to_vec(),
+            },
+            data: Box::new(source.payload(header_length..payload_end)),
+        })
+    }
+
+    /// Serializes the packet back into its wire format, recomputing the length field from
+    /// the payload.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = &self.header;
+        let payload = self.data.to_bytes();
+        let ppp_protocol_size = header.ppp_protocol.map_or(0, |_| PPP_PROTOCOL_SIZE);
+        let length = (ppp_protocol_size + payload.len()).min(u16::MAX as usize) as u16;
+
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + ppp_protocol_size + payload.len());
+        bytes.push((header.version << 4) | (header.packet_type & 0x0F));
+        bytes.push(header.code);
+        bytes.extend_from_slice(&header.session_id.to_be_bytes());
+        bytes.extend_from_slice(&length.to_be_bytes());
+        if let Some(ppp_protocol) = header.ppp_protocol {
+            bytes.extend_from_slice(&ppp_protocol.to_be_bytes());
+        }
+        bytes.extend_from_slice(&payload);
+        bytes
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Pppoe
+    }
+
+    /// Detaches the packet from the bytes it was parsed from, copying its payload if it is
+    /// borrowed.
+    pub fn into_owned(self) -> PppoePacket<'static> {
+        PppoePacket {
+            header: self.header,
+            data: Box::new(self.data.into_owned()),
+        }
+    }
+}
+
+impl<'a> DeepParser<'a> for PppoePacket<'a> {
+    /// Parses the payload of a session packet according to its PPP protocol number.
+    ///
+    /// IPv4 (`0x0021`) and IPv6 (`0x0057`) payloads are parsed further. Discovery packets
+    /// and other PPP protocols, such as LCP or IPCP, are left as raw data.
+    fn parse_next_layer_with(
+        mut self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Pppoe, depth);
+
+        self.data.payload().ok_or(ParserError::InvalidPayload)?;
+
+        let result = match self.header.ppp_protocol {
+            Some(PPP_PROTOCOL_IPV4) => options
+                .timed(LayerKind::Ipv4, || {
+                    Ipv4Packet::from_bytes_with(&*self.data, options)
+                })
+                .and_then(|packet| packet.parse_next_layer_with(options, depth)),
+            Some(PPP_PROTOCOL_IPV6) => options
+                .timed(LayerKind::Ipv6, || {
+                    Ipv6Packet::from_bytes_with(&*self.data, options)
+                })
+                .and_then(|packet| packet.parse_next_layer_with(options, depth)),
+            _ => return Ok(LayeredData::PppoeData(self)),
+        };
+
+        options.recover(result, &mut self.data)?;
+        Ok(LayeredData::PppoeData(self))
+    }
+}
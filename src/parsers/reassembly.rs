@@ -0,0 +1,295 @@
+// IPv6 Fragment Header (next header 44):
+//  0                   1                   2                   3
+//  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |  Next Header  |   Reserved    |      Fragment Offset    |Res|M|
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |                         Identification                        |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+
+use super::{definitions::IPType, errors::ParserError, ipv6::Ipv6Packet, utils::ByteReader};
+
+use alloc::{collections::BTreeMap, string::ToString, vec::Vec};
+use core::{net::Ipv6Addr, time::Duration};
+
+const FRAGMENT_HEADER_SIZE: usize = 8;
+const FRAGMENT_NEXT_HEADER: u8 = 44;
+
+/// The IPv6 Fragment extension header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ipv6FragmentHeader {
+    /// The protocol of the header that follows the fragmentable part.
+    pub next_header: u8,
+
+    /// The offset of the fragment's data, in 8-byte units, relative to the start of the
+    /// fragmentable part of the original packet.
+    pub fragment_offset: u16,
+
+    /// The M flag. Set on every fragment except the last.
+    pub more_fragments: bool,
+
+    /// Identifies the fragments that belong to the same original packet.
+    pub identification: u32,
+}
+
+impl Ipv6FragmentHeader {
+    /// Constructs an `Ipv6FragmentHeader` from the first 8 bytes of a slice.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::HeaderTooShort` - If the slice is shorter than a fragment header.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParserError> {
+        if bytes.len() < FRAGMENT_HEADER_SIZE {
+            return Err(ParserError::HeaderTooShort {
+                layer: "Ipv6 fragment header".to_string(),
+                got: bytes.len(),
+                need: FRAGMENT_HEADER_SIZE,
+            });
+        }
+
+        let mut cursor = ByteReader::new(bytes);
+
+        let next_header = cursor.read_u8("Fragment_NextHeader")?;
+        let _reserved = cursor.read_u8("Fragment_Reserved")?;
+        let offset_flags = cursor.read_u16_be("Fragment_Offset_Flags")?;
+        let identification = cursor.read_u32_be("Fragment_Identification")?;
+
+        Ok(Ipv6FragmentHeader {
+            next_header,
+            fragment_offset: offset_flags >> 3,
+            more_fragments: offset_flags & 1 != 0,
+            identification,
+        })
+    }
+
+    /// The byte offset of the fragment's data within the original payload.
+    pub fn byte_offset(&self) -> usize {
+        self.fragment_offset as usize * 8
+    }
+}
+
+/// The payload of an IPv6 packet once all of its fragments have been collected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReassembledPayload {
+    /// The protocol of the reassembled payload, taken from the first fragment.
+    pub next_header: IPType,
+
+    /// The reassembled fragmentable part of the original packet.
+    pub data: Vec<u8>,
+}
+
+type FragmentKey = (Ipv6Addr, Ipv6Addr, u32);
+
+struct PendingPacket {
+    /// Received fragments as `(byte offset, data)`, ordered by offset.
+    fragments: Vec<(usize, Vec<u8>)>,
+    /// The length of the original payload, known once the last fragment arrives.
+    total_length: Option<usize>,
+    /// The next header of the offset-zero fragment.
+    next_header: Option<u8>,
+    /// When the first fragment was received.
+    first_seen: Duration,
+}
+
+impl PendingPacket {
+    fn new(first_seen: Duration) -> Self {
+        PendingPacket {
+            fragments: Vec::new(),
+            total_length: None,
+            next_header: None,
+            first_seen,
+        }
+    }
+
+    /// Inserts a fragment, returning `false` if it overlaps data already received.
+    /// Exact duplicates are accepted and ignored.
+    fn insert(&mut self, offset: usize, data: Vec<u8>) -> bool {
+        let end = offset + data.len();
+        let position = self.fragments.partition_point(|(start, _)| *start < offset);
+
+        if let Some((start, existing)) = self.fragments.get(position) {
+            if *start == offset && *existing == data {
+                return true;
+            }
+            if *start < end {
+                return false;
+            }
+        }
+
+        if let Some((start, existing)) = position.checked_sub(1).map(|i| &self.fragments[i]) {
+            if start + existing.len() > offset {
+                return false;
+            }
+        }
+
+        self.fragments.insert(position, (offset, data));
+        true
+    }
+
+    /// Returns the reassembled payload if every byte up to the final fragment is present.
+    fn assemble(&mut self) -> Option<Vec<u8>> {
+        let total_length = self.total_length?;
+        let mut expected = 0;
+
+        for (start, data) in &self.fragments {
+            if *start != expected {
+                return None;
+            }
+            expected += data.len();
+        }
+
+        if expected != total_length {
+            return None;
+        }
+
+        let mut payload = Vec::with_capacity(total_length);
+        for (_, data) in self.fragments.drain(..) {
+            payload.extend_from_slice(&data);
+        }
+
+        Some(payload)
+    }
+}
+
+/// Reassembles fragmented IPv6 packets.
+///
+/// Fragments are grouped by `(source, destination, identification)` and ordered by their
+/// offset. Once the final fragment (M flag clear) has arrived and every byte before it has
+/// been received, the reassembled payload is returned along with the next header value of
+/// the first fragment.
+///
+/// Overlapping fragments cause the whole packet to be discarded, as required by RFC 5722.
+/// Packets that are not completed within the configured timeout are evicted.
+///
+/// Time is supplied by the caller (e.g. from capture timestamps), so the reassembler does
+/// not depend on a system clock.
+pub struct Ipv6Reassembler {
+    timeout: Duration,
+    pending: BTreeMap<FragmentKey, PendingPacket>,
+}
+
+impl Ipv6Reassembler {
+    /// Creates a reassembler that evicts incomplete packets after `timeout`.
+    ///
+    /// RFC 8200 recommends 60 seconds.
+    pub fn new(timeout: Duration) -> Self {
+        Ipv6Reassembler {
+            timeout,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Feeds a packet into the reassembler.
+    ///
+    /// The fragment header is expected to directly follow the base IPv6 header. Packets
+    /// without a fragment header are returned immediately, as are atomic fragments
+    /// (offset 0 with the M flag clear).
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - A packet parsed with `Ipv6Packet::from_bytes`.
+    /// * `now` - The time the packet was received.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(ReassembledPayload))` - The packet is complete.
+    /// * `Ok(None)` - More fragments are needed, or the packet was discarded because
+    ///   its fragments overlap.
+    /// * `Err(ParserError)` - The fragment is malformed.
+    pub fn process(
+        &mut self,
+        packet: &Ipv6Packet,
+        now: Duration,
+    ) -> Result<Option<ReassembledPayload>, ParserError> {
+        self.evict_expired(now);
+
+        let data = packet.data.payload().ok_or(ParserError::InvalidPayload)?;
+
+        let next_header = packet.header.upper_layer_protocol();
+        if next_header != IPType::from(FRAGMENT_NEXT_HEADER) {
+            return Ok(Some(ReassembledPayload {
+                next_header,
+                data: data.to_vec(),
+            }));
+        }
+
+        let fragment = Ipv6FragmentHeader::from_bytes(data)?;
+        let fragment_data = data[FRAGMENT_HEADER_SIZE..].to_vec();
+
+        // Every fragment but the last must carry a multiple of 8 bytes.
+        if fragment.more_fragments && fragment_data.len() % 8 != 0 {
+            return Err(ParserError::InvalidLength("Ipv6 fragment".to_string()));
+        }
+
+        if fragment.fragment_offset == 0 && !fragment.more_fragments {
+            return Ok(Some(ReassembledPayload {
+                next_header: IPType::from(fragment.next_header),
+                data: fragment_data,
+            }));
+        }
+
+        let key = (
+            packet.header.source_address,
+            packet.header.destination_address,
+            fragment.identification,
+        );
+        let pending = self
+            .pending
+            .entry(key)
+            .or_insert_with(|| PendingPacket::new(now));
+
+        let offset = fragment.byte_offset();
+        let end = offset + fragment_data.len();
+
+        if !fragment.more_fragments {
+            if pending.total_length.is_some_and(|length| length != end) {
+                self.pending.remove(&key);
+                return Ok(None);
+            }
+            pending.total_length = Some(end);
+        }
+
+        if offset == 0 {
+            pending.next_header = Some(fragment.next_header);
+        }
+
+        let beyond_last = pending.total_length.is_some_and(|length| end > length);
+        if beyond_last || !pending.insert(offset, fragment_data) {
+            self.pending.remove(&key);
+            return Ok(None);
+        }
+
+        let next_header = match pending.next_header {
+            Some(next_header) => next_header,
+            None => return Ok(None),
+        };
+
+        match pending.assemble() {
+            Some(data) => {
+                self.pending.remove(&key);
+                Ok(Some(ReassembledPayload {
+                    next_header: IPType::from(next_header),
+                    data,
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Discards every incomplete packet whose first fragment arrived more than the
+    /// timeout before `now`, returning the number of packets evicted.
+    pub fn evict_expired(&mut self, now: Duration) -> usize {
+        let before = self.pending.len();
+        let timeout = self.timeout;
+
+        self.pending
+            .retain(|_, pending| now.saturating_sub(pending.first_seen) <= timeout);
+
+        before - self.pending.len()
+    }
+
+    /// Returns the number of packets still waiting for fragments.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
@@ -0,0 +1,221 @@
+use super::definitions::{LayerKind, LayeredData};
+
+use alloc::{sync::Arc, vec::Vec};
+use core::{any::Any, fmt, net::IpAddr};
+
+/// Describes where a payload was found, including metadata from the layers enclosing it.
+///
+/// IP packets pass their addresses and payload length down to the transport layer, which
+/// uses them to verify its checksum. Custom `Parser`s receive the same context.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseContext {
+    /// The layer carrying the payload, e.g. `LayerKind::Tcp`.
+    pub layer: LayerKind,
+    pub source_port: u16,
+    pub destination_port: u16,
+
+    /// The addresses of the enclosing IP packet. `None` if the payload was parsed from a
+    /// bare transport-layer buffer.
+    pub src_ip: Option<IpAddr>,
+    pub dst_ip: Option<IpAddr>,
+
+    /// The length of the enclosing IP packet's payload, i.e. of the transport-layer
+    /// segment including its header.
+    pub l3_payload_len: Option<usize>,
+}
+
+impl ParseContext {
+    pub fn new(layer: LayerKind, source_port: u16, destination_port: u16) -> Self {
+        ParseContext {
+            layer,
+            source_port,
+            destination_port,
+            src_ip: None,
+            dst_ip: None,
+            l3_payload_len: None,
+        }
+    }
+
+    /// Returns the context with the addresses and payload length of an enclosing IP packet.
+    pub fn with_ip(mut self, src_ip: IpAddr, dst_ip: IpAddr, l3_payload_len: usize) -> Self {
+        self.src_ip = Some(src_ip);
+        self.dst_ip = Some(dst_ip);
+        self.l3_payload_len = Some(l3_payload_len);
+        self
+    }
+
+    /// Returns `true` if either port equals `port`.
+    pub fn has_port(&self, port: u16) -> bool {
+        self.source_port == port || self.destination_port == port
+    }
+}
+
+/// A dissector for a protocol net-sift does not parse itself.
+///
+/// Implemented for any `Fn(&ParseContext, &[u8]) -> Option<LayeredData<'static>>`, so closures can
+/// be registered directly.
+pub trait Parser: Send + Sync {
+    /// Attempts to parse `payload`, returning `None` if it is not in this parser's format.
+    ///
+    /// The result usually wraps the decoded message in a `LayeredData::Custom`.
+    fn try_parse(&self, ctx: &ParseContext, payload: &[u8]) -> Option<LayeredData<'static>>;
+}
+
+impl<F> Parser for F
+where
+    F: Fn(&ParseContext, &[u8]) -> Option<LayeredData<'static>> + Send + Sync,
+{
+    fn try_parse(&self, ctx: &ParseContext, payload: &[u8]) -> Option<LayeredData<'static>> {
+        self(ctx, payload)
+    }
+}
+
+type Predicate = dyn Fn(&ParseContext) -> bool + Send + Sync;
+
+#[derive(Clone)]
+struct RegisteredParser {
+    predicate: Arc<Predicate>,
+    parser: Arc<dyn Parser>,
+}
+
+/// A set of custom parsers consulted while deep-parsing TCP and UDP payloads.
+///
+/// Parsers are tried in registration order, and the first whose predicate accepts the
+/// `ParseContext` and which recognises the payload wins. Custom parsers take precedence
+/// over net-sift's own port-based dispatch. Cloning a registry is cheap, as parsers are
+/// shared rather than copied.
+///
+/// # Example
+///
+/// ```
+/// use net_sift::parsers::{
+///     definitions::{LayerKind, LayeredData, ParseOptions},
+///     registry::{CustomLayer, ParseContext},
+/// };
+///
+/// let mut options = ParseOptions::default();
+/// options.parsers.register(
+///     |ctx: &ParseContext| ctx.layer == LayerKind::Udp && ctx.has_port(5060),
+///     |_: &ParseContext, payload: &[u8]| {
+///         let text = core::str::from_utf8(payload).ok()?;
+///         Some(LayeredData::Custom(CustomLayer::new("sip", text.to_string())))
+///     },
+/// );
+/// ```
+#[derive(Clone, Default)]
+pub struct ParserRegistry {
+    parsers: Vec<RegisteredParser>,
+}
+
+impl ParserRegistry {
+    pub fn new() -> Self {
+        ParserRegistry::default()
+    }
+
+    /// Registers `parser` for payloads whose context satisfies `predicate`.
+    pub fn register<P, F>(&mut self, predicate: P, parser: F)
+    where
+        P: Fn(&ParseContext) -> bool + Send + Sync + 'static,
+        F: Parser + 'static,
+    {
+        self.parsers.push(RegisteredParser {
+            predicate: Arc::new(predicate),
+            parser: Arc::new(parser),
+        });
+    }
+
+    /// Offers `payload` to the registered parsers, returning the first successful result.
+    pub fn dissect(&self, ctx: &ParseContext, payload: &[u8]) -> Option<LayeredData<'static>> {
+        self.parsers
+            .iter()
+            .filter(|registered| (registered.predicate)(ctx))
+            .find_map(|registered| registered.parser.try_parse(ctx, payload))
+            .map(|layered_data| match layered_data {
+                // The decoded value cannot be serialized, so the payload is kept instead.
+                LayeredData::Custom(mut layer) => {
+                    layer.bytes = payload.to_vec();
+                    LayeredData::Custom(layer)
+                }
+                other => other,
+            })
+    }
+
+    /// Returns the number of registered parsers.
+    pub fn len(&self) -> usize {
+        self.parsers.len()
+    }
+
+    /// Returns `true` if no parsers are registered.
+    pub fn is_empty(&self) -> bool {
+        self.parsers.is_empty()
+    }
+}
+
+impl fmt::Debug for ParserRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ParserRegistry")
+            .field("parsers", &self.parsers.len())
+            .finish()
+    }
+}
+
+/// A layer produced by a custom `Parser`.
+///
+/// The decoded value is type-erased; retrieve it with `downcast_ref`. Clones share the
+/// same value, and two layers are equal only if they share it.
+#[derive(Clone)]
+pub struct CustomLayer {
+    /// A short name for the protocol, e.g. `"sip"`.
+    pub name: &'static str,
+    value: Arc<dyn Any + Send + Sync>,
+    bytes: Vec<u8>,
+}
+
+impl CustomLayer {
+    pub fn new<T: Any + Send + Sync>(name: &'static str, value: T) -> Self {
+        CustomLayer {
+            name,
+            value: Arc::new(value),
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Returns the payload the layer was decoded from, which `LayeredData::to_bytes` emits
+    /// in its place. Filled in by `ParserRegistry::dissect`, so empty for layers built
+    /// by hand.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the decoded value if it is of type `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.value.downcast_ref()
+    }
+}
+
+impl fmt::Debug for CustomLayer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CustomLayer")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CustomLayer {
+    /// Serializes the name alone, as the type-erased value cannot be serialized.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("CustomLayer", 1)?;
+        state.serialize_field("name", self.name)?;
+        state.end()
+    }
+}
+
+impl PartialEq for CustomLayer {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && Arc::as_ptr(&self.value) as *const () == Arc::as_ptr(&other.value) as *const ()
+    }
+}
@@ -0,0 +1,317 @@
+// SIP Message (RFC 3261, section 7):
+//
+//   start-line       request:  Method SP Request-URI SP SIP-Version CRLF
+//                    response: SIP-Version SP Status-Code SP Reason-Phrase CRLF
+//   *message-header  Name ":" value CRLF
+//   CRLF
+//   [ message-body ]
+//
+// Over UDP a datagram carries exactly one message. Over TCP the Content-Length header
+// marks where the body ends.
+
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
+use super::{
+    definitions::{DeepParser, LayerKind, LayeredData, ParseOptions},
+    errors::ParserError,
+    trace,
+    utils::parse_header_lines,
+};
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ops::Range;
+
+const SIP_VERSION_PREFIX: &str = "SIP/";
+const HEADER_TERMINATOR: &[u8] = b"\r\n\r\n";
+
+// Compact header names (RFC 3261, section 7.3.3) and the full names they stand for.
+const COMPACT_HEADERS: [(&str, &str); 10] = [
+    ("i", "Call-ID"),
+    ("m", "Contact"),
+    ("e", "Content-Encoding"),
+    ("l", "Content-Length"),
+    ("c", "Content-Type"),
+    ("f", "From"),
+    ("s", "Subject"),
+    ("k", "Supported"),
+    ("t", "To"),
+    ("v", "Via"),
+];
+
+/// The method of a SIP request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum SipMethod {
+    Invite,
+    Ack,
+    Bye,
+    Cancel,
+    Register,
+    Options,
+    Prack,
+    Subscribe,
+    Notify,
+    Publish,
+    Info,
+    Refer,
+    Message,
+    Update,
+    Other(String), // Extension methods are preserved
+}
+
+impl From<&str> for SipMethod {
+    fn from(method: &str) -> Self {
+        match method {
+            "INVITE" => Self::Invite,
+            "ACK" => Self::Ack,
+            "BYE" => Self::Bye,
+            "CANCEL" => Self::Cancel,
+            "REGISTER" => Self::Register,
+            "OPTIONS" => Self::Options,
+            "PRACK" => Self::Prack,
+            "SUBSCRIBE" => Self::Subscribe,
+            "NOTIFY" => Self::Notify,
+            "PUBLISH" => Self::Publish,
+            "INFO" => Self::Info,
+            "REFER" => Self::Refer,
+            "MESSAGE" => Self::Message,
+            "UPDATE" => Self::Update,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl SipMethod {
+    /// Returns the method as it appears on the wire.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Invite => "INVITE",
+            Self::Ack => "ACK",
+            Self::Bye => "BYE",
+            Self::Cancel => "CANCEL",
+            Self::Register => "REGISTER",
+            Self::Options => "OPTIONS",
+            Self::Prack => "PRACK",
+            Self::Subscribe => "SUBSCRIBE",
+            Self::Notify => "NOTIFY",
+            Self::Publish => "PUBLISH",
+            Self::Info => "INFO",
+            Self::Refer => "REFER",
+            Self::Message => "MESSAGE",
+            Self::Update => "UPDATE",
+            Self::Other(method) => method,
+        }
+    }
+}
+
+/// The first line of a SIP message, which tells requests and responses apart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum SipStartLine {
+    Request {
+        method: SipMethod,
+        uri: String,
+        version: String,
+    },
+    Response {
+        version: String,
+        status_code: u16,
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SipMessage {
+    pub start_line: SipStartLine,
+
+    /// The headers in the order they appear, with folded lines joined. Compact names,
+    /// such as `v` for Via, are kept as sent; `header` looks up either form.
+    pub headers: Vec<(String, String)>,
+
+    /// The message body, e.g. an SDP session description. Empty if there is none.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub body: Vec<u8>,
+
+    /// The position of the start line and headers, including the blank line ending them,
+    /// within the bytes the message was parsed from.
+    pub span: Range<usize>,
+}
+
+impl SipMessage {
+    /// Constructs a `SipMessage` from a slice of bytes holding one message.
+    ///
+    /// The body is bounded by the Content-Length header if there is one, and otherwise
+    /// runs to the end of the slice. A message without the blank line that ends the
+    /// headers is taken to have no body.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::MalformedMessage` - If the start line or headers are not valid
+    ///   UTF-8, the start line is neither a request nor a status line, a header line has
+    ///   no colon, or the Content-Length is not a number.
+    /// * `ParserError::InconsistentLength` - If the body is shorter than the Content-Length.
+    pub fn from_bytes(message: &[u8]) -> Result<Self, ParserError> {
+        let head_length = message
+            .windows(HEADER_TERMINATOR.len())
+            .position(|window| window == HEADER_TERMINATOR)
+            .map_or(message.len(), |position| position + HEADER_TERMINATOR.len());
+
+        let head = core::str::from_utf8(&message[..head_length])
+            .map_err(|_| malformed("start line and headers are not valid UTF-8"))?;
+        let (start_line, header_block) = head.split_once("\r\n").unwrap_or((head, ""));
+
+        let start_line = Self::parse_start_line(start_line)?;
+        let headers = parse_header_lines(header_block, "SIP message")?;
+
+        let available = &message[head_length..];
+        let body = match Self::find_header(&headers, "Content-Length") {
+            Some(length) => {
+                let declared = length
+                    .parse::<usize>()
+                    .map_err(|_| malformed("Content-Length is not a number"))?;
+                available
+                    .get(..declared)
+                    .ok_or_else(|| ParserError::InconsistentLength {
+                        layer: "SIP body".to_string(),
+                        declared,
+                        available: available.len(),
+                    })?
+            }
+            None => available,
+        };
+
+        Ok(SipMessage {
+            start_line,
+            headers,
+            body: body.to_vec(),
+            span: 0..head_length,
+        })
+    }
+
+    /// Parses a request line or a status line.
+    fn parse_start_line(line: &str) -> Result<SipStartLine, ParserError> {
+        if let Some(status) = line.strip_prefix(SIP_VERSION_PREFIX) {
+            let mut parts = status.splitn(3, ' ');
+            let version = parts.next().unwrap_or_default();
+            let status_code = parts
+                .next()
+                .and_then(|code| code.parse::<u16>().ok())
+                .ok_or_else(|| malformed("status line has no valid status code"))?;
+
+            return Ok(SipStartLine::Response {
+                version: format!("{SIP_VERSION_PREFIX}{version}"),
+                status_code,
+                reason: parts.next().unwrap_or_default().to_string(),
+            });
+        }
+
+        match line.split(' ').collect::<Vec<_>>()[..] {
+            [method, uri, version] if version.starts_with(SIP_VERSION_PREFIX) => {
+                Ok(SipStartLine::Request {
+                    method: SipMethod::from(method),
+                    uri: uri.to_string(),
+                    version: version.to_string(),
+                })
+            }
+            _ => Err(malformed(
+                "start line is neither a request nor a status line",
+            )),
+        }
+    }
+
+    /// Finds the first header named `name` or its compact form, ignoring case.
+    fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+        let compact = COMPACT_HEADERS
+            .iter()
+            .find(|(_, full)| full.eq_ignore_ascii_case(name))
+            .map(|(compact, _)| *compact);
+
+        headers
+            .iter()
+            .find(|(header, _)| {
+                header.eq_ignore_ascii_case(name)
+                    || compact.is_some_and(|compact| header.eq_ignore_ascii_case(compact))
+            })
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the value of the first header named `name`, ignoring case. Headers sent in
+    /// their compact form are found by their full name too, e.g. `v` for `Via`.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        Self::find_header(&self.headers, name)
+    }
+
+    /// Returns the Call-ID, which identifies the dialog the message belongs to.
+    pub fn call_id(&self) -> Option<&str> {
+        self.header("Call-ID")
+    }
+
+    /// Returns the sequence number and method of the CSeq header.
+    pub fn cseq(&self) -> Option<(u32, SipMethod)> {
+        let (number, method) = self.header("CSeq")?.split_once(' ')?;
+        Some((number.parse().ok()?, SipMethod::from(method.trim())))
+    }
+
+    /// Serializes the message back into its wire format.
+    ///
+    /// Headers are written one per line in their stored order, so folded headers are
+    /// emitted on a single line.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut message = match &self.start_line {
+            SipStartLine::Request {
+                method,
+                uri,
+                version,
+            } => format!("{} {uri} {version}\r\n", method.as_str()),
+            SipStartLine::Response {
+                version,
+                status_code,
+                reason,
+            } => format!("{version} {status_code} {reason}\r\n"),
+        };
+        for (name, value) in &self.headers {
+            message.push_str(&format!("{name}: {value}\r\n"));
+        }
+        message.push_str("\r\n");
+
+        let mut bytes = message.into_bytes();
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Sip
+    }
+}
+
+impl<'a> DeepParser<'a> for SipMessage {
+    fn parse_next_layer_with(
+        self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Sip, depth);
+
+        Ok(LayeredData::SipData(self))
+    }
+}
+
+/// Returns `true` for payloads made up only of CR and LF, which SIP endpoints send as
+/// keep-alives (RFC 5626, section 3.5.1) rather than as messages.
+pub(crate) fn is_keepalive(payload: &[u8]) -> bool {
+    payload.iter().all(|byte| matches!(byte, b'\r' | b'\n'))
+}
+
+fn malformed(detail: &str) -> ParserError {
+    ParserError::MalformedMessage {
+        layer: "SIP message".to_string(),
+        detail: detail.to_string(),
+    }
+}
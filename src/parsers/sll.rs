@@ -0,0 +1,237 @@
+// Linux cooked capture (SLL) header, used in place of a link-layer header for
+// captures on the `any` interface (DLT_LINUX_SLL):
+//  0                   1                   2                   3
+//  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |          PACKET TYPE          |        ARPHRD_ TYPE           |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |    LINK-LAYER ADDRESS LENGTH  |                               |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+                               +
+// |                LINK-LAYER ADDRESS (8 bytes)                   |
+// +                               +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |                               |         PROTOCOL TYPE         |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+//
+// The address field is always 8 bytes, of which only the first LENGTH are used.
+
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
+use super::{
+    arp::ArpPacket,
+    definitions::{DeepParser, EtherType, LayerKind, LayeredData, ParseOptions},
+    errors::ParserError,
+    ipv4::Ipv4Packet,
+    ipv6::Ipv6Packet,
+    trace,
+    utils::{ByteReader, PayloadSource},
+};
+
+use alloc::{boxed::Box, string::ToString, vec::Vec};
+use core::ops::Range;
+
+const HEADER_SIZE: usize = 16;
+const ADDRESS_FIELD_SIZE: usize = 8;
+
+/// The direction of a packet relative to the capturing host, as seen by the kernel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum SllPacketType {
+    Host,       // Sent to this host
+    Broadcast,  // Broadcast by another host
+    Multicast,  // Multicast by another host
+    OtherHost,  // Sent by another host to a third host
+    Outgoing,   // Sent by this host
+    Other(u16), // Any other type is preserved
+}
+
+// Enables conversion back into the raw packet type, used when serializing headers.
+impl From<SllPacketType> for u16 {
+    fn from(packet_type: SllPacketType) -> u16 {
+        match packet_type {
+            SllPacketType::Host => 0,
+            SllPacketType::Broadcast => 1,
+            SllPacketType::Multicast => 2,
+            SllPacketType::OtherHost => 3,
+            SllPacketType::Outgoing => 4,
+            SllPacketType::Other(raw) => raw,
+        }
+    }
+}
+
+impl From<u16> for SllPacketType {
+    fn from(raw: u16) -> Self {
+        match raw {
+            0 => Self::Host,
+            1 => Self::Broadcast,
+            2 => Self::Multicast,
+            3 => Self::OtherHost,
+            4 => Self::Outgoing,
+            other => Self::Other(other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SllHeader {
+    /// Whether the packet was sent or received by the capturing host, and how.
+    pub packet_type: SllPacketType,
+
+    /// The `ARPHRD_` type of the interface, e.g. 1 for Ethernet or 772 for loopback.
+    pub link_layer_type: u16,
+
+    /// The number of bytes of `address` in use, as declared in the header.
+    pub address_length: u16,
+
+    /// The link-layer source address, holding at most the 8 bytes the header has room for.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub address: Vec<u8>,
+
+    /// The protocol of the payload, an EtherType for most link-layer types.
+    pub protocol: EtherType,
+
+    /// The position of the header within the bytes the packet was parsed from.
+    pub span: Range<usize>,
+
+    /// The 16 header bytes as received, including the padding after short link-layer
+    /// addresses, e.g. to write the packet back to a capture of the same link type.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub raw_header: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SllPacket<'a> {
+    pub header: SllHeader,
+    pub data: Box<LayeredData<'a>>,
+}
+
+impl<'a> SllPacket<'a> {
+    /// Constructs an `SllPacket` from a slice of bytes.
+    ///
+    /// The header carries no length field, so the payload is everything after it.
+    ///
+    /// # Arguments
+    ///
+    /// * `packets` - A byte slice beginning with the SLL header.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, ParserError>` - An `SllPacket` instance or a `ParserError`.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::HeaderTooShort` - If the slice is shorter than the SLL header.
+    pub fn from_bytes(packets: &'a [u8]) -> Result<Self, ParserError> {
+        Self::parse(&packets)
+    }
+
+    /// Constructs an `SllPacket` whose payload is taken from `source`.
+    pub(crate) fn parse<S: PayloadSource<'a> + ?Sized>(source: &S) -> Result<Self, ParserError> {
+        let packets = source.as_slice();
+        if packets.len() < HEADER_SIZE {
+            return Err(ParserError::HeaderTooShort {
+                layer: "SLL packet".to_string(),
+                got: packets.len(),
+                need: HEADER_SIZE,
+            });
+        }
+
+        let mut cursor = ByteReader::new(packets);
+
+        let packet_type = cursor.read_u16_be("SLL_Packet_Type")?;
+        let link_layer_type = cursor.read_u16_be("SLL_ARPHRD_Type")?;
+        let address_length = cursor.read_u16_be("SLL_Address_Length")?;
+        let address = cursor.read_slice(ADDRESS_FIELD_SIZE, "SLL_Address")?;
+        let protocol = cursor.read_u16_be("SLL_Protocol")?;
+
+        Ok(SllPacket {
+            header: SllHeader {
+                packet_type: SllPacketType::from(packet_type),
+                link_layer_type,
+                address_length,
+                address: address[..(address_length as usize).min(ADDRESS_FIELD_SIZE)].to_vec(),
+                protocol: EtherType::from(protocol),
+                span: 0..HEADER_SIZE,
+                raw_header: packets[..HEADER_SIZE].to_vec(),
+            },
+            data: Box::new(source.payload(HEADER_SIZE..packets.len())),
+        })
+    }
+
+    /// Serializes the header and payload back into their wire format. The unused bytes of
+    /// the address field are written as zeros.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = &self.header;
+        let mut address = [0; ADDRESS_FIELD_SIZE];
+        let address_size = header.address.len().min(ADDRESS_FIELD_SIZE);
+        address[..address_size].copy_from_slice(&header.address[..address_size]);
+
+        let mut bytes = Vec::with_capacity(HEADER_SIZE);
+        bytes.extend_from_slice(&u16::from(header.packet_type).to_be_bytes());
+        bytes.extend_from_slice(&header.link_layer_type.to_be_bytes());
+        bytes.extend_from_slice(&header.address_length.to_be_bytes());
+        bytes.extend_from_slice(&address);
+        bytes.extend_from_slice(&u16::from(&header.protocol).to_be_bytes());
+        bytes.extend_from_slice(&self.data.to_bytes());
+        bytes
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Sll
+    }
+
+    /// Detaches the packet from the bytes it was parsed from, copying its payload if it is
+    /// borrowed.
+    pub fn into_owned(self) -> SllPacket<'static> {
+        SllPacket {
+            header: self.header,
+            data: Box::new(self.data.into_owned()),
+        }
+    }
+}
+
+impl<'a> DeepParser<'a> for SllPacket<'a> {
+    /// Parses the payload according to the protocol field, as an Ethernet frame would.
+    ///
+    /// IPv4, IPv6 and ARP payloads are parsed further. Other protocols, including the
+    /// non-EtherType values used for 802.2 LLC and CAN frames, are left as raw data.
+    fn parse_next_layer_with(
+        mut self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Sll, depth);
+
+        let data = self.data.payload().ok_or(ParserError::InvalidPayload)?;
+
+        let result = match self.header.protocol {
+            EtherType::IPv4 => options
+                .timed(LayerKind::Ipv4, || {
+                    Ipv4Packet::from_bytes_with(&*self.data, options)
+                })
+                .and_then(|packet| packet.parse_next_layer_with(options, depth)),
+            EtherType::IPv6 => options
+                .timed(LayerKind::Ipv6, || {
+                    Ipv6Packet::from_bytes_with(&*self.data, options)
+                })
+                .and_then(|packet| packet.parse_next_layer_with(options, depth)),
+            EtherType::ARP => options
+                .descend(depth)
+                .and_then(|_| options.timed(LayerKind::Arp, || ArpPacket::from_bytes(data)))
+                .map(LayeredData::ArpData),
+            _ => {
+                trace::debug!(
+                    protocol = ?self.header.protocol,
+                    "no parser for SLL protocol, payload left raw"
+                );
+                return Ok(LayeredData::SllData(self));
+            }
+        };
+
+        options.recover(result, &mut self.data)?;
+        Ok(LayeredData::SllData(self))
+    }
+}
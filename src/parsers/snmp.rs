@@ -0,0 +1,266 @@
+// SNMPv1/v2c Message (ASN.1 BER):
+//
+// Message ::= SEQUENCE {
+//     version    INTEGER,      -- 0 for SNMPv1, 1 for SNMPv2c
+//     community  OCTET STRING,
+//     data       PDU           -- context-specific tag, e.g. [0] GetRequest
+// }
+//
+// Every element is encoded as a tag byte, a length and the value. Lengths below
+// 128 use a single byte (short form); larger ones use a byte 0x80 | n followed by
+// n big-endian length bytes (long form).
+
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
+use super::{
+    definitions::{DeepParser, LayerKind, LayeredData, ParseOptions},
+    errors::ParserError,
+    trace,
+    utils::ByteReader,
+};
+
+use alloc::{string::ToString, vec::Vec};
+use core::ops::Range;
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_SEQUENCE: u8 = 0x30;
+
+// Long-form lengths of more than four bytes cannot describe a UDP payload.
+const MAX_LENGTH_BYTES: usize = 4;
+
+/// The version of the SNMP message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum SnmpVersion {
+    V1,
+    V2c,
+}
+
+/// The type of PDU carried in the message, identified by its context-specific tag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum SnmpPduType {
+    GetRequest,
+    GetNextRequest,
+    GetResponse,
+    SetRequest,
+    Trap,
+    GetBulkRequest,
+    InformRequest,
+    TrapV2,
+    Report,
+    Other(u8),
+}
+
+impl From<u8> for SnmpPduType {
+    fn from(tag: u8) -> Self {
+        match tag {
+            0xA0 => Self::GetRequest,
+            0xA1 => Self::GetNextRequest,
+            0xA2 => Self::GetResponse,
+            0xA3 => Self::SetRequest,
+            0xA4 => Self::Trap,
+            0xA5 => Self::GetBulkRequest,
+            0xA6 => Self::InformRequest,
+            0xA7 => Self::TrapV2,
+            0xA8 => Self::Report,
+            other => Self::Other(other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SnmpMessage {
+    pub version: SnmpVersion,
+
+    /// The community string, acting as a shared password. Usually ASCII.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub community: Vec<u8>,
+
+    pub pdu_type: SnmpPduType,
+
+    /// The undecoded contents of the PDU (request id, error fields and variable bindings).
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub pdu: Vec<u8>,
+
+    /// The position of the message within the bytes it was parsed from.
+    pub span: Range<usize>,
+}
+
+impl SnmpMessage {
+    /// Constructs an `SnmpMessage` from a slice of bytes.
+    ///
+    /// Only the message envelope is decoded: the version, the community string and the
+    /// PDU type. The PDU contents are kept as raw bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `packets` - A byte slice holding a BER encoded SNMP message, typically the payload
+    ///   of a UDP datagram on port 161 or 162.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, ParserError>` - An `SnmpMessage` instance or a `ParserError`.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::InvalidLength` - If an element's length is malformed.
+    /// * `ParserError::InconsistentLength` - If an element runs past the end of the
+    ///   enclosing element.
+    /// * `ParserError::UnexpectedBerTag` - If an element does not have the expected type.
+    /// * `ParserError::UnsupportedSnmpVersion` - If the message is not SNMPv1 or SNMPv2c.
+    pub fn from_bytes(packets: &[u8]) -> Result<Self, ParserError> {
+        let mut cursor = ByteReader::new(packets);
+
+        let message = Self::read_element(&mut cursor, TAG_SEQUENCE)?;
+        let span = 0..cursor.position();
+        let mut cursor = ByteReader::new(message);
+
+        let version = match Self::read_integer(&mut cursor)? {
+            0 => SnmpVersion::V1,
+            1 => SnmpVersion::V2c,
+            other => return Err(ParserError::UnsupportedSnmpVersion(other)),
+        };
+
+        let community = Self::read_element(&mut cursor, TAG_OCTET_STRING)?.to_vec();
+
+        let (tag, pdu) = Self::read_tlv(&mut cursor)?;
+
+        Ok(SnmpMessage {
+            version,
+            community,
+            pdu_type: SnmpPduType::from(tag),
+            pdu: pdu.to_vec(),
+            span,
+        })
+    }
+
+    /// Reads an element and returns its tag and value.
+    fn read_tlv<'a>(cursor: &mut ByteReader<'a>) -> Result<(u8, &'a [u8]), ParserError> {
+        let tag = cursor.read_u8("BER_Tag")?;
+        let length = Self::read_length(cursor)?;
+
+        if cursor.remaining() < length {
+            return Err(ParserError::InconsistentLength {
+                layer: "BER element".to_string(),
+                declared: length,
+                available: cursor.remaining(),
+            });
+        }
+
+        Ok((tag, cursor.read_slice(length, "BER_Value")?))
+    }
+
+    /// Reads an element of the expected type and returns its value.
+    fn read_element<'a>(
+        cursor: &mut ByteReader<'a>,
+        expected_tag: u8,
+    ) -> Result<&'a [u8], ParserError> {
+        match Self::read_tlv(cursor)? {
+            (tag, value) if tag == expected_tag => Ok(value),
+            (tag, _) => Err(ParserError::UnexpectedBerTag(tag)),
+        }
+    }
+
+    /// Decodes a short- or long-form BER length.
+    ///
+    /// The indefinite form (`0x80`) is not allowed in SNMP and is rejected.
+    fn read_length(cursor: &mut ByteReader) -> Result<usize, ParserError> {
+        let first = cursor.read_u8("BER_Length")?;
+
+        if first & 0x80 == 0 {
+            return Ok(first as usize);
+        }
+
+        let length_bytes = (first & 0x7F) as usize;
+        if length_bytes == 0 || length_bytes > MAX_LENGTH_BYTES {
+            return Err(ParserError::InvalidLength("BER length".to_string()));
+        }
+
+        let length = cursor
+            .read_slice(length_bytes, "BER_Long_Length")?
+            .iter()
+            .fold(0usize, |length, byte| (length << 8) | *byte as usize);
+
+        Ok(length)
+    }
+
+    /// Decodes a two's complement INTEGER element of up to 8 bytes.
+    fn read_integer(cursor: &mut ByteReader) -> Result<i64, ParserError> {
+        let value = Self::read_element(cursor, TAG_INTEGER)?;
+
+        if value.is_empty() || value.len() > 8 {
+            return Err(ParserError::InvalidLength("BER integer".to_string()));
+        }
+
+        // Sign-extend from the most significant byte.
+        let initial = if value[0] & 0x80 != 0 { -1i64 } else { 0 };
+        Ok(value
+            .iter()
+            .fold(initial, |integer, byte| (integer << 8) | *byte as i64))
+    }
+
+    /// Serializes the message back into BER, using the shortest form of each length.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let version: u8 = match self.version {
+            SnmpVersion::V1 => 0,
+            SnmpVersion::V2c => 1,
+        };
+        let pdu_tag = match self.pdu_type {
+            SnmpPduType::GetRequest => 0xA0,
+            SnmpPduType::GetNextRequest => 0xA1,
+            SnmpPduType::GetResponse => 0xA2,
+            SnmpPduType::SetRequest => 0xA3,
+            SnmpPduType::Trap => 0xA4,
+            SnmpPduType::GetBulkRequest => 0xA5,
+            SnmpPduType::InformRequest => 0xA6,
+            SnmpPduType::TrapV2 => 0xA7,
+            SnmpPduType::Report => 0xA8,
+            SnmpPduType::Other(tag) => tag,
+        };
+
+        let mut message = Vec::new();
+        Self::write_tlv(&mut message, TAG_INTEGER, &[version]);
+        Self::write_tlv(&mut message, TAG_OCTET_STRING, &self.community);
+        Self::write_tlv(&mut message, pdu_tag, &self.pdu);
+
+        let mut bytes = Vec::with_capacity(message.len() + 1 + MAX_LENGTH_BYTES);
+        Self::write_tlv(&mut bytes, TAG_SEQUENCE, &message);
+        bytes
+    }
+
+    /// Appends an element with the given tag and value, encoding its length in the short
+    /// form if it is below 128 and in the long form otherwise.
+    fn write_tlv(bytes: &mut Vec<u8>, tag: u8, value: &[u8]) {
+        bytes.push(tag);
+        if value.len() < 0x80 {
+            bytes.push(value.len() as u8);
+        } else {
+            let length = (value.len() as u32).to_be_bytes();
+            let length = &length[length.iter().take_while(|byte| **byte == 0).count()..];
+            bytes.push(0x80 | length.len() as u8);
+            bytes.extend_from_slice(length);
+        }
+        bytes.extend_from_slice(value);
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Snmp
+    }
+}
+
+impl<'a> DeepParser<'a> for SnmpMessage {
+    fn parse_next_layer_with(
+        self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Snmp, depth);
+
+        Ok(LayeredData::SnmpData(self))
+    }
+}
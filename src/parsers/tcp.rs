@@ -21,20 +21,30 @@
  *
  */
 
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
 use super::{
-    definitions::{DeepParser, LayeredData},
+    constants::{DNS_PORT, SIP_PORT, WEBSOCKET_PORTS},
+    definitions::{DeepParser, LayerKind, LayeredData, ParseOptions},
+    dns::DnsTcpMessage,
     errors::ParserError,
-    utils::{read_arbitrary_length, read_u32},
+    registry::ParseContext,
+    sip::{self, SipMessage},
+    trace,
+    utils::{be_u16, be_u32, transport_checksum, OptionReader, PayloadSource},
+    websocket::{self, WebSocketFrame},
 };
 
-use std::io::{Cursor, Seek, SeekFrom};
+use alloc::{boxed::Box, format, string::ToString, vec::Vec};
+use core::{fmt, net::IpAddr, ops::Range};
 
 /// Represents the flags in the control field of a TCP segment.
 ///
 /// Each flag is a boolean value corresponding to a 1-bit field
 /// in the control section, indicating the presence (true) or absence (false)
 /// of certain optional control information.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Flags {
     pub cwr: bool, // Congestion Window Reduced flag.
     pub ece: bool, // ECN-Echo flag.
@@ -69,10 +79,193 @@ impl Flags {
     }
 }
 
+// Enables conversion back into the raw flag bits, used when serializing headers.
+impl From<&Flags> for u8 {
+    fn from(flags: &Flags) -> u8 {
+        [
+            flags.cwr, flags.ece, flags.urg, flags.ack, flags.psh, flags.rst, flags.syn, flags.fin,
+        ]
+        .iter()
+        .fold(0, |bits, &flag| (bits << 1) | flag as u8)
+    }
+}
+
+impl fmt::Display for Flags {
+    /// Formats the set flags as their names separated by spaces, e.g. `SYN ACK`, or
+    /// `none` if no flag is set.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = [
+            (self.syn, "SYN"),
+            (self.fin, "FIN"),
+            (self.rst, "RST"),
+            (self.psh, "PSH"),
+            (self.ack, "ACK"),
+            (self.urg, "URG"),
+            (self.ece, "ECE"),
+            (self.cwr, "CWR"),
+        ];
+        let mut set = names.iter().filter(|(set, _)| *set).map(|(_, name)| name);
+
+        match set.next() {
+            Some(first) => {
+                f.write_str(first)?;
+                set.try_for_each(|name| write!(f, " {name}"))
+            }
+            None => f.write_str("none"),
+        }
+    }
+}
+
+/// A TCP option, decoded from the options field of the header.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TcpOption {
+    EndOfOptions,
+    NoOp,
+    /// The maximum segment size the sender can receive.
+    Mss(u16),
+    /// The shift count applied to the advertised window.
+    WindowScale(u8),
+    SackPermitted,
+    /// The left and right edges of each block of data received out of order.
+    Sack(Vec<(u32, u32)>),
+    /// The sender's timestamp, and the most recent timestamp received from the peer.
+    Timestamp {
+        tsval: u32,
+        tsecr: u32,
+    },
+    /// An option of a kind not decoded by net-sift, with its raw data.
+    Unknown {
+        kind: u8,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+        data: Vec<u8>,
+    },
+}
+
+impl TcpOption {
+    /// Decodes an option of the given kind from its data, i.e. the bytes after its kind
+    /// and length.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::InvalidLength` - If the data is the wrong size for the kind.
+    fn decode(kind: u8, data: &[u8]) -> Result<Self, ParserError> {
+        Self::check_length(kind, data.len())?;
+        let word = |offset: usize| u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap());
+
+        let option = match kind {
+            0 => TcpOption::EndOfOptions,
+            1 => TcpOption::NoOp,
+            2 => TcpOption::Mss(u16::from_be_bytes([data[0], data[1]])),
+            3 => TcpOption::WindowScale(data[0]),
+            4 => TcpOption::SackPermitted,
+            5 => TcpOption::Sack(
+                (0..data.len())
+                    .step_by(8)
+                    .map(|offset| (word(offset), word(offset + 4)))
+                    .collect(),
+            ),
+            8 => TcpOption::Timestamp {
+                tsval: word(0),
+                tsecr: word(4),
+            },
+            kind => TcpOption::Unknown {
+                kind,
+                data: data.to_vec(),
+            },
+        };
+
+        Ok(option)
+    }
+
+    /// Checks that `len` bytes of data are the right size for an option of the given kind.
+    /// Options of kinds not decoded by net-sift may be of any size.
+    fn check_length(kind: u8, len: usize) -> Result<(), ParserError> {
+        match (kind, len) {
+            (2, 2) | (3, 1) | (4, 0) | (8, 8) => Ok(()),
+            (5, len) if len > 0 && len % 8 == 0 => Ok(()),
+            (2..=5 | 8, _) => Err(ParserError::InvalidLength("TCP option".to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Appends the option to `bytes` in its wire format.
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        let mut write = |kind: u8, data: &[u8]| {
+            bytes.push(kind);
+            bytes.push((data.len() + 2) as u8);
+            bytes.extend_from_slice(data);
+        };
+
+        match self {
+            TcpOption::EndOfOptions => bytes.push(0),
+            TcpOption::NoOp => bytes.push(1),
+            TcpOption::Mss(mss) => write(2, &mss.to_be_bytes()),
+            TcpOption::WindowScale(shift) => write(3, &[*shift]),
+            TcpOption::SackPermitted => write(4, &[]),
+            TcpOption::Sack(blocks) => {
+                let data: Vec<u8> = blocks
+                    .iter()
+                    .flat_map(|(left, right)| [left.to_be_bytes(), right.to_be_bytes()])
+                    .flatten()
+                    .collect();
+                write(5, &data)
+            }
+            TcpOption::Timestamp { tsval, tsecr } => {
+                write(8, &[tsval.to_be_bytes(), tsecr.to_be_bytes()].concat())
+            }
+            TcpOption::Unknown { kind, data } => write(*kind, data),
+        }
+    }
+}
+
+/// Decodes the options region of a header, up to and including the End of Option List.
+///
+/// # Errors
+///
+/// * `ParserError::InvalidLength` - If an option's length is below 2, or wrong for its kind.
+/// * `ParserError::OptionsOverrun` - If an option's length runs past the end of `bytes`.
+#[cfg(feature = "etherparse")]
+pub(crate) fn decode_options(bytes: &[u8]) -> Result<Vec<TcpOption>, ParserError> {
+    TcpOptionsIter(OptionReader::new(bytes, "TCP option")).collect()
+}
+
+/// Encodes `options` in their wire format, padded with zeros to a whole number of 32-bit
+/// words and cut off at the 40 bytes a header can hold.
+pub(crate) fn encode_options(options: &[TcpOption]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    options.iter().for_each(|option| option.encode(&mut bytes));
+    bytes.truncate(MAX_OPTIONS_SIZE);
+    bytes.resize(bytes.len().div_ceil(4) * 4, 0);
+    bytes
+}
+
+/// An iterator over the options of a TCP segment, returned by `TcpSegment::options_iter`.
+///
+/// Options are decoded as the iterator advances, and malformed ones are yielded as errors.
+/// An option whose length is invalid ends the iteration, as the options after it cannot
+/// be located.
+#[derive(Debug, Clone)]
+pub struct TcpOptionsIter<'a>(OptionReader<'a>);
+
+impl Iterator for TcpOptionsIter<'_> {
+    type Item = Result<TcpOption, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .next()
+            .map(|option| option.and_then(|(kind, data)| TcpOption::decode(kind, data)))
+    }
+}
+
 const MIN_SEGMENT_SIZE: usize = 20;
 const OPTIONS_OFFSET: usize = 20;
+const MAX_OPTIONS_SIZE: usize = 40;
+const CHECKSUM_OFFSET: usize = 16;
+const TCP_PROTOCOL: u8 = 6;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TcpSegmentHeader {
     pub source_port: u16,
     pub destination_port: u16,
@@ -84,155 +277,504 @@ pub struct TcpSegmentHeader {
     pub window_size: u16,
     pub checksum: u16,
     pub urg_pointer: u16,
+    /// The options in the header, in the order received, up to the End of Option List or
+    /// the first malformed option, which `TcpSegment::options_iter` reports as an error.
+    pub options: Vec<TcpOption>,
+    /// The position of the header, including options, within the bytes the segment was parsed from.
+    pub span: Range<usize>,
+    /// The header as received, `data_offset * 4` bytes including options. `options_iter`
+    /// decodes the options from here.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub raw_header: Vec<u8>,
 }
 
-#[derive(Debug, PartialEq)]
-pub struct TcpSegment {
+/// A header without options or flags, between port 0 on both ends, advertising a window of
+/// 65535 bytes.
+///
+/// The header was not parsed from bytes, so `raw_header` and `span` are empty.
+impl Default for TcpSegmentHeader {
+    fn default() -> Self {
+        TcpSegmentHeader {
+            source_port: 0,
+            destination_port: 0,
+            sequence_number: 0,
+            acknowledgment_value: 0,
+            data_offset: (MIN_SEGMENT_SIZE / 4) as u8,
+            reserved: 0,
+            flags: Flags::default(),
+            window_size: u16::MAX,
+            checksum: 0,
+            urg_pointer: 0,
+            options: Vec::new(),
+            span: 0..0,
+            raw_header: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TcpSegment<'a> {
     pub header: TcpSegmentHeader,
-    pub data: Box<LayeredData>,
+    pub data: Box<LayeredData<'a>>,
+    /// `true` if the segment was cut short, either within its header or within the payload
+    /// of the enclosing IP packet. Only set for segments parsed with `allow_truncated`.
+    pub truncated: bool,
+    /// The bytes the segment was parsed from, if it was constructed with
+    /// `from_bytes_keep_raw`. Left unset otherwise, as keeping them doubles the memory
+    /// held by each segment.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub raw: Option<Vec<u8>>,
 }
 
-impl TcpSegment {
-    pub fn from_bytes(segments: &[u8]) -> Result<Self, ParserError> {
+/// A TCP segment borrowed from the bytes it was parsed by `TcpSegment::from_bytes_ref`.
+///
+/// The header fields are read from the bytes when asked for, so nothing is copied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TcpSegmentRef<'a> {
+    bytes: &'a [u8],
+    header_length: usize,
+    truncated: bool,
+}
+
+impl<'a> TcpSegmentRef<'a> {
+    /// Checks the header of `segments`, options included, and locates the payload.
+    pub(crate) fn parse(segments: &'a [u8], allow_truncated: bool) -> Result<Self, ParserError> {
         if segments.len() < MIN_SEGMENT_SIZE {
-            return Err(ParserError::InvalidLength("TCP segment".to_string()));
+            return Err(ParserError::HeaderTooShort {
+                layer: "TCP segment".to_string(),
+                got: segments.len(),
+                need: MIN_SEGMENT_SIZE,
+            });
         }
-        let mut cursor = Cursor::new(segments);
 
-        let (source_port, destination_port) = Self::extract_tcp_ports(&mut cursor)?;
-        let sequence_number = read_u32(&mut cursor, "Sequence_Number")?;
-        let acknowledgment_value = read_u32(&mut cursor, "Ack_Number")?;
+        // The header, options included, is `data_offset` 32-bit words long.
+        let header_length = (segments[12] >> 4) as usize * 4;
+        if header_length < MIN_SEGMENT_SIZE {
+            return Err(ParserError::InvalidLength("TCP data offset".to_string()));
+        }
+        let truncated = header_length > segments.len();
+        if truncated && !allow_truncated {
+            return Err(ParserError::OptionsOverrun {
+                layer: "TCP options".to_string(),
+                declared: header_length - MIN_SEGMENT_SIZE,
+                available: segments.len() - MIN_SEGMENT_SIZE,
+            });
+        }
+
+        // Malformed options are left for `options_iter` to report, so they don't cost the
+        // rest of the segment.
+        Ok(TcpSegmentRef {
+            bytes: segments,
+            header_length: header_length.min(segments.len()),
+            truncated,
+        })
+    }
+
+    /// Builds the `TcpSegment`, taking its payload from `source`, the buffer `bytes`
+    /// starts.
+    fn to_owned_in<'s, S: PayloadSource<'s> + ?Sized>(self, source: &S) -> TcpSegment<'s> {
+        TcpSegment {
+            header: TcpSegmentHeader {
+                source_port: self.source_port(),
+                destination_port: self.destination_port(),
+                sequence_number: self.sequence_number(),
+                acknowledgment_value: self.acknowledgment_value(),
+                data_offset: self.data_offset(),
+                reserved: self.reserved(),
+                flags: self.flags(),
+                window_size: self.window_size(),
+                checksum: self.checksum(),
+                urg_pointer: self.urg_pointer(),
+                options: self.options_iter().map_while(Result::ok).collect(),
+                span: 0..self.header_length,
+                raw_header: self.header().to_vec(),
+            },
+            data: Box::new(source.payload(self.header_length..self.bytes.len())),
+            truncated: self.truncated,
+            raw: None,
+        }
+    }
 
-        let (data_offset, reserved, flags, window_size) =
-            Self::extract_tcp_offset_flags_window(&mut cursor)?;
+    pub fn source_port(&self) -> u16 {
+        be_u16(self.bytes, 0)
+    }
 
-        let (checksum, urg_pointer) = Self::extract_tcp_checksum_urg_pointer(&mut cursor)?;
+    pub fn destination_port(&self) -> u16 {
+        be_u16(self.bytes, 2)
+    }
 
-        // Get the size of the options field
-        let options_size = (data_offset * 4) - MIN_SEGMENT_SIZE as u8; // data_offset is in 32-bit words
+    pub fn sequence_number(&self) -> u32 {
+        be_u32(self.bytes, 4)
+    }
 
-        let payload_offset = match options_size {
-            0 => OPTIONS_OFFSET,
-            _ => {
-                let offset = OPTIONS_OFFSET + options_size as usize;
-                cursor.seek(SeekFrom::Start(offset as u64)).map_err(|e| {
-                    ParserError::CursorError {
-                        string: "Options".to_string(),
-                        source: e,
-                    }
-                })?;
+    pub fn acknowledgment_value(&self) -> u32 {
+        be_u32(self.bytes, 8)
+    }
 
-                offset
-            }
-        };
+    pub fn data_offset(&self) -> u8 {
+        self.bytes[12] >> 4
+    }
 
-        let data = read_arbitrary_length(&mut cursor, segments.len() - payload_offset, "TCP_Data")?;
+    pub fn reserved(&self) -> u8 {
+        self.bytes[12] & 0xF
+    }
 
-        Ok(TcpSegment {
-            header: TcpSegmentHeader {
-                source_port,
-                destination_port,
-                sequence_number,
-                acknowledgment_value,
-                data_offset,
-                reserved,
-                flags,
-                window_size,
-                checksum,
-                urg_pointer,
-            },
+    pub fn flags(&self) -> Flags {
+        Flags::new(self.bytes[13])
+    }
 
-            data: Box::new(LayeredData::Payload(data)),
-        })
+    pub fn window_size(&self) -> u16 {
+        be_u16(self.bytes, 14)
     }
 
-    /// Extracts the source and destination ports from a TCP segment.
-    ///
-    /// The function reads the first 4 bytes at the cursor's current position,
-    /// interpreting the first 2 bytes as the source port and the last 2 bytes as the destination port.
+    pub fn checksum(&self) -> u16 {
+        be_u16(self.bytes, CHECKSUM_OFFSET)
+    }
+
+    pub fn urg_pointer(&self) -> u16 {
+        be_u16(self.bytes, 18)
+    }
+
+    /// Returns an iterator over the options in the header, decoding each on demand.
+    pub fn options_iter(&self) -> TcpOptionsIter<'a> {
+        TcpOptionsIter(OptionReader::new(
+            &self.bytes[OPTIONS_OFFSET..self.header_length],
+            "TCP option",
+        ))
+    }
+
+    /// Returns the header, `data_offset * 4` bytes including options, or fewer if it was
+    /// cut short.
+    pub fn header(&self) -> &'a [u8] {
+        &self.bytes[..self.header_length]
+    }
+
+    /// Returns the payload following the header.
+    pub fn data(&self) -> &'a [u8] {
+        &self.bytes[self.header_length..]
+    }
+
+    /// Returns `true` if the header was cut short, leaving the segment without a payload.
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl<'a> From<TcpSegmentRef<'a>> for TcpSegment<'a> {
+    /// Converts the segment into a `TcpSegment` borrowing the same bytes, with its payload left
+    /// raw.
+    fn from(segment: TcpSegmentRef<'a>) -> Self {
+        segment.to_owned_in(&segment.bytes)
+    }
+}
+
+impl<'a> DeepParser<'a> for TcpSegmentRef<'a> {
+    /// Converts the segment into a `TcpSegment`, and parses its payload from there.
+    fn parse_next_layer_with(
+        self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        TcpSegment::from(self).parse_next_layer_with(options, depth)
+    }
+}
+
+impl<'a> TcpSegment<'a> {
+    /// Constructs a `TcpSegment` from a slice of bytes holding a complete segment.
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `cursor` - A cursor over the byte slice of the TCP segment.
+    /// * `ParserError::HeaderTooShort` - If the slice is shorter than the fixed header.
+    /// * `ParserError::InvalidLength` - If `data_offset` is less than 5.
+    /// * `ParserError::OptionsOverrun` - If the options `data_offset` implies run past the
+    ///   end of the slice.
     ///
-    /// # Returns
+    /// A malformed option does not fail the parse: `header.options` ends before it, and
+    /// `options_iter` yields it as an error.
+    pub fn from_bytes(segments: &'a [u8]) -> Result<Self, ParserError> {
+        Self::parse(&segments, false)
+    }
+
+    /// Constructs a `TcpSegment` like `from_bytes`, also keeping a copy of `segments` in
+    /// `raw` for `verify_stored_checksum`.
     ///
-    /// * On success, returns `Ok` with a tuple of two `u16` values: the source port and the destination port.
-    /// * On failure, returns an `Err` with a `ParserError` variant.
+    /// The copy doubles the memory held by the segment, so use `from_bytes` unless the
+    /// original bytes are needed after parsing.
+    pub fn from_bytes_keep_raw(segments: &'a [u8]) -> Result<Self, ParserError> {
+        let mut segment = Self::parse(&segments, false)?;
+        segment.raw = Some(segments.to_vec());
+        Ok(segment)
+    }
+
+    /// Constructs a `TcpSegment` nested within another layer, accepting a segment whose
+    /// header is cut short if `options.allow_truncated` is set. The header then holds only
+    /// the options present, and the payload is empty.
+    pub(crate) fn from_bytes_with<S: PayloadSource<'a> + ?Sized>(
+        segments: &S,
+        options: &ParseOptions,
+    ) -> Result<Self, ParserError> {
+        Self::parse(segments, options.allow_truncated)
+    }
+
+    /// Constructs a `TcpSegment` whose payload shares `buf` rather than copying it.
     ///
-    /// # Errors
+    /// Behaves like `from_bytes`, except that the payload is left as a `LayeredData::Bytes`
+    /// slice of `buf`. Layers parsed from it by `parse_next_layer` are still copied.
+    #[cfg(feature = "bytes")]
+    pub fn from_bytes_buf(buf: &bytes::Bytes) -> Result<Self, ParserError> {
+        Self::parse(buf, false)
+    }
+
+    /// Constructs a `TcpSegmentRef` borrowing from `segments`, without allocating.
     ///
-    /// This function will return an error if it encounters any issue while reading from the byte cursor.
-    fn extract_tcp_ports(cursor: &mut Cursor<&[u8]>) -> Result<(u16, u16), ParserError> {
-        let bytes = read_u32(cursor, "SrcPort_DestPort")?;
+    /// Fails in the same cases as `from_bytes`. The options are only decoded when iterated
+    /// with `TcpSegmentRef::options_iter`.
+    pub fn from_bytes_ref(segments: &[u8]) -> Result<TcpSegmentRef<'_>, ParserError> {
+        TcpSegmentRef::parse(segments, false)
+    }
 
-        // The `Source Port` is contained in the highest 16 bits of the 32-bit word
-        let src_port = (bytes >> 16) as u16;
-        let dest_port = (bytes & 0xFFFF) as u16;
+    fn parse<S: PayloadSource<'a> + ?Sized>(
+        source: &S,
+        allow_truncated: bool,
+    ) -> Result<Self, ParserError> {
+        Ok(TcpSegmentRef::parse(source.as_slice(), allow_truncated)?.to_owned_in(source))
+    }
 
-        Ok((src_port, dest_port))
+    /// Returns the number of payload bytes carried by the segment.
+    ///
+    /// If the payload was handed to a custom parser, the length of its serialized form is
+    /// returned instead.
+    pub fn payload_len(&self) -> usize {
+        match self.data.payload() {
+            Some(data) => data.len(),
+            None => self.data.to_bytes().len(),
+        }
     }
 
-    /// Extracts the Data Offset, Reserved, Flags, and Window Size fields from a segment of the TCP header.
+    /// Returns the length of the segment in sequence space: the payload length, plus one
+    /// each for the SYN and FIN flags, which consume a sequence number of their own.
+    pub fn seq_len(&self) -> u32 {
+        self.payload_len() as u32 + self.header.flags.syn as u32 + self.header.flags.fin as u32
+    }
+
+    /// Returns the sequence numbers occupied by the segment, `[seq, seq + seq_len)`.
     ///
-    /// This function reads a 4-byte sequence from the provided cursor, then decodes and
-    /// separates it into the respective fields: Data Offset, Reserved, Flags, and Window Size.
-    /// These represent crucial parameters for the TCP header's structure and subsequent data processing.
+    /// Sequence numbers wrap around, so near `u32::MAX` the end of the range can be smaller
+    /// than its start.
+    pub fn seq_range(&self) -> Range<u32> {
+        let start = self.header.sequence_number;
+        start..start.wrapping_add(self.seq_len())
+    }
+
+    /// Returns the urgent pointer if the URG flag is set, and `None` otherwise, as the field
+    /// is meaningless without it. The raw value remains available as `header.urg_pointer`.
+    pub fn urgent_pointer(&self) -> Option<u16> {
+        self.header.flags.urg.then_some(self.header.urg_pointer)
+    }
+
+    /// Returns an iterator over the options in `raw_header`, decoding each on demand.
     ///
-    /// # Parameters:
-    /// * `cursor`: A cursor over the slice of the TCP segment data, positioned at the
-    ///  start of the 4-byte sequence.
+    /// Parsing already decodes them into `header.options`, up to the first malformed one.
+    /// This re-reads the bytes, yielding that option as an error, and also covers headers
+    /// that were not parsed by `from_bytes`, e.g. those converted from etherparse.
+    pub fn options_iter(&self) -> TcpOptionsIter<'_> {
+        let options = self
+            .header
+            .raw_header
+            .get(OPTIONS_OFFSET..)
+            .unwrap_or_default();
+        TcpOptionsIter(OptionReader::new(options, "TCP option"))
+    }
+
+    /// Serializes the segment back into its wire format.
     ///
-    /// # Returns:
-    /// A `Result` which is:
-    /// * `Ok` - Tuple of the extracted fields: `(u8, u8, Flags, u16)` representing Data Offset,
-    ///  Reserved, Flags, and Window Size respectively.
-    /// * `Err` - An error of type `ParserError` that occurred during the reading from the
-    /// cursor or the decoding process.
+    /// The header is rebuilt from the parsed fields, including `options`, which are padded
+    /// with zeros to a whole number of 32-bit words. The padding parses back as an End of
+    /// Option List. The `data_offset` is derived from the resulting header length; the
+    /// stored value is ignored. The stored `checksum` is written unchanged, so use
+    /// `LayeredData::finalize_checksums` first if the segment was modified.
     ///
-    /// # Errors:
-    /// This function will return an error if there is an issue reading from the provided cursor,
-    /// such as if there is insufficient data to comprise a complete 4-byte sequence.
-    fn extract_tcp_offset_flags_window(
-        cursor: &mut Cursor<&[u8]>,
-    ) -> Result<(u8, u8, Flags, u16), ParserError> {
-        let bytes = read_u32(cursor, "DataOffset_Flags_Window")?;
+    /// # Returns
+    /// - A `Vec<u8>` holding the serialized header followed by the payload.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = &self.header;
+        let options = encode_options(&header.options);
+        let payload = self.data.to_bytes();
 
-        let data_offset = (bytes >> 28) as u8;
-        let reserved = ((bytes >> 24) & 0xF) as u8;
-        let flags = Flags::new(((bytes >> 16) & 0xFF) as u8);
-        let window = (bytes & 0xFFFF) as u16;
+        let data_offset = ((MIN_SEGMENT_SIZE + options.len()) / 4) as u32;
+        let offset_flags_window = (data_offset << 28)
+            | ((header.reserved as u32 & 0xF) << 24)
+            | ((u8::from(&header.flags) as u32) << 16)
+            | header.window_size as u32;
 
-        Ok((data_offset, reserved, flags, window))
+        let mut segment = Vec::with_capacity(MIN_SEGMENT_SIZE + options.len() + payload.len());
+        segment.extend_from_slice(&header.source_port.to_be_bytes());
+        segment.extend_from_slice(&header.destination_port.to_be_bytes());
+        segment.extend_from_slice(&header.sequence_number.to_be_bytes());
+        segment.extend_from_slice(&header.acknowledgment_value.to_be_bytes());
+        segment.extend_from_slice(&offset_flags_window.to_be_bytes());
+        segment.extend_from_slice(&header.checksum.to_be_bytes());
+        segment.extend_from_slice(&header.urg_pointer.to_be_bytes());
+        segment.extend_from_slice(&options);
+
+        segment.extend_from_slice(&payload);
+        segment
     }
 
-    /// Extracts the TCP segment's checksum and, if applicable, the urgent pointer.
+    /// Checks the header fields that lenient parsing ignores, as done in strict mode.
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `cursor` - A cursor over the TCP segment data.
-    /// * `urg` - A flag indicating whether the urgent pointer should be extracted.
+    /// * `ParserError::ProtocolViolation` - If the reserved bits are not zero.
+    pub fn check_conformance(&self) -> Result<(), ParserError> {
+        if self.header.reserved != 0 {
+            return Err(ParserError::ProtocolViolation {
+                field: "TCP reserved".to_string(),
+                detail: format!("expected 0, got {:#x}", self.header.reserved),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the checksum of the segment, which also covers a pseudo-header built from
+    /// the enclosing IP packet.
+    ///
+    /// # Arguments
+    /// - `raw`: The raw segment, i.e. the header followed by the payload.
+    /// - `source`: The source address of the enclosing IP packet.
+    /// - `destination`: The destination address of the enclosing IP packet.
     ///
     /// # Returns
+    /// - `true` if the computed checksum matches `checksum`, `false` otherwise, if `raw` is
+    ///   too short to hold a TCP header, or if the addresses are of different families.
+    pub fn verify_checksum(&self, raw: &[u8], source: IpAddr, destination: IpAddr) -> bool {
+        Self::compute_checksum(raw, source, destination) == Some(self.header.checksum)
+    }
+
+    /// Verifies the checksum against the bytes kept in `raw`, like `verify_checksum`.
     ///
-    /// A result containing a tuple of the checksum and the urgent pointer,
-    /// the latter being present only if `urg` is true
-    fn extract_tcp_checksum_urg_pointer(
-        cursor: &mut Cursor<&[u8]>,
-    ) -> Result<(u16, u16), ParserError> {
-        let bytes = read_u32(cursor, "Checksum_UrgPointer")?;
+    /// # Returns
+    /// - `None` if the segment was not constructed with `from_bytes_keep_raw`.
+    pub fn verify_stored_checksum(&self, source: IpAddr, destination: IpAddr) -> Option<bool> {
+        let raw = self.raw.as_deref()?;
+        Some(self.verify_checksum(raw, source, destination))
+    }
 
-        // Extracting the checksum from the upper 16 bits.
-        let checksum = (bytes >> 16) as u16;
+    /// Computes the checksum of a raw segment sent from `source` to `destination`, or
+    /// `None` if `raw` is too short or the addresses are of different families.
+    pub(crate) fn compute_checksum(raw: &[u8], source: IpAddr, destination: IpAddr) -> Option<u16> {
+        if raw.len() < MIN_SEGMENT_SIZE {
+            return None;
+        }
+        transport_checksum(raw, CHECKSUM_OFFSET, source, destination, TCP_PROTOCOL)
+    }
 
-        let urg_pointer = (bytes & 0xFFFF) as u16;
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Tcp
+    }
 
-        Ok((checksum, urg_pointer))
+    /// Detaches the segment from the bytes it was parsed from, copying its payload if it is
+    /// borrowed.
+    pub fn into_owned(self) -> TcpSegment<'static> {
+        TcpSegment {
+            header: self.header,
+            truncated: self.truncated,
+            raw: self.raw,
+            data: Box::new(self.data.into_owned()),
+        }
     }
-}
 
-impl DeepParser for TcpSegment {
-    fn parse_next_layer(self) -> Result<LayeredData, ParserError> {
+    /// Parses the payload like `parse_next_layer_with`, offering custom parsers the
+    /// addresses of the enclosing IP packet found in `context`.
+    pub(crate) fn parse_next_layer_in(
+        mut self,
+        context: &ParseContext,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Tcp, depth);
+        if options.strict {
+            self.check_conformance()?;
+        }
+
+        let data = self.data.payload().ok_or(ParserError::InvalidPayload)?;
+
+        let context = ParseContext {
+            layer: LayerKind::Tcp,
+            source_port: self.header.source_port,
+            destination_port: self.header.destination_port,
+            ..context.clone()
+        };
+        let result = if let Some(layered_data) = options.parsers.dissect(&context, data) {
+            options.descend(depth).map(|_| layered_data)
+        } else if context.has_port(DNS_PORT) && !data.is_empty() {
+            options
+                .descend(depth)
+                .map(|_| options.timed(LayerKind::Dns, || DnsTcpMessage::parse_all(data)))
+                .map(LayeredData::DnsTcpData)
+        } else if let Some(message) = (context.has_port(SIP_PORT) && !sip::is_keepalive(data))
+            .then(|| {
+                options
+                    .timed(LayerKind::Sip, || SipMessage::from_bytes(data))
+                    .ok()
+            })
+            .flatten()
+        {
+            // A segment that does not start a SIP message, e.g. one carrying the rest of a
+            // body, is left raw like a datagram that is not SIP.
+            message.parse_next_layer_with(options, depth)
+        } else if WEBSOCKET_PORTS.iter().any(|&port| context.has_port(port))
+            && websocket::is_websocket(data)
+        {
+            options
+                .descend(depth)
+                .and_then(|_| {
+                    options.timed(LayerKind::WebSocket, || WebSocketFrame::parse_all(data))
+                })
+                .map(LayeredData::WebSocketData)
+        } else {
+            return Ok(LayeredData::TcpData(self));
+        };
+
+        options.recover(result, &mut self.data)?;
         Ok(LayeredData::TcpData(self))
     }
 }
+
+impl<'a> TryFrom<&'a [u8]> for TcpSegment<'a> {
+    type Error = ParserError;
+
+    /// Constructs a `TcpSegment` from a slice. See `from_bytes`.
+    fn try_from(segments: &'a [u8]) -> Result<Self, Self::Error> {
+        TcpSegment::from_bytes(segments)
+    }
+}
+
+impl<'a> DeepParser<'a> for TcpSegment<'a> {
+    /// Offers the payload to the parsers registered in `options.parsers`. Without a
+    /// matching parser, payloads to or from port 53 are split into the length-prefixed
+    /// DNS messages they carry, payloads to or from port 5060 that start a SIP message are
+    /// parsed as one,
+    /// payloads to or from an HTTP port in `WEBSOCKET_PORTS` made up entirely of
+    /// well-formed WebSocket frames are split into those frames, and any other payload is
+    /// left as raw data.
+    ///
+    /// In strict mode the header is first checked with `check_conformance`. Parsed on its
+    /// own, the segment has no enclosing IP packet, so its checksum is never verified.
+    fn parse_next_layer_with(
+        self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let context = ParseContext::new(
+            LayerKind::Tcp,
+            self.header.source_port,
+            self.header.destination_port,
+        );
+        self.parse_next_layer_in(&context, options, depth)
+    }
+}
@@ -0,0 +1,176 @@
+use super::tcp::TcpSegment;
+
+/// The endpoint that sent a segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Initiator, // The endpoint that sent the first SYN
+    Responder, // The endpoint that answered it
+}
+
+impl Direction {
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            Direction::Initiator => Direction::Responder,
+            Direction::Responder => Direction::Initiator,
+        }
+    }
+}
+
+/// The state of a TCP connection as seen by an observer of both directions.
+///
+/// Each state is named after the RFC 793 state of the endpoint driving that step: the
+/// initiator during the handshake, and the endpoint that sent the first FIN during the
+/// teardown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpState {
+    Closed,      // No connection, or reset
+    SynSent,     // The initiator's SYN has been seen
+    SynReceived, // The responder has answered with SYN-ACK
+    Established, // The handshake has completed
+    FinWait1,    // One endpoint has sent a FIN that is not yet acknowledged
+    FinWait2,    // The first FIN is acknowledged, the other endpoint has not sent its own
+    Closing,     // Both endpoints sent a FIN before either was acknowledged
+    LastAck,     // Both endpoints have sent a FIN and one has been acknowledged
+    TimeWait,    // Both FINs are acknowledged and the connection closed gracefully
+}
+
+/// A change of state caused by a segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TcpTransition {
+    pub from: TcpState,
+    pub to: TcpState,
+}
+
+/// Tracks the state of one TCP connection from the flags of its segments.
+///
+/// Segments must be pushed in the order they were captured, along with the endpoint that
+/// sent them. Handshake and FIN acknowledgments are matched against the sequence numbers
+/// they acknowledge, and segments that do not fit the current state, such as a connection
+/// picked up mid-stream or a simultaneous open, are ignored. A RST closes the connection
+/// from any state.
+#[derive(Debug, Clone)]
+pub struct TcpStateMachine {
+    state: TcpState,
+    /// The sequence number each endpoint's SYN acknowledgment must carry, indexed by
+    /// `Direction`.
+    syn_ack: [Option<u32>; 2],
+    /// The acknowledgment number that covers each endpoint's FIN, once it has been sent.
+    fin_ack: [Option<u32>; 2],
+    fin_acked: [bool; 2],
+}
+
+impl Default for TcpStateMachine {
+    fn default() -> Self {
+        TcpStateMachine {
+            state: TcpState::Closed,
+            syn_ack: [None; 2],
+            fin_ack: [None; 2],
+            fin_acked: [false; 2],
+        }
+    }
+}
+
+impl TcpStateMachine {
+    pub fn new() -> Self {
+        TcpStateMachine::default()
+    }
+
+    /// Returns the current state of the connection.
+    pub fn state(&self) -> TcpState {
+        self.state
+    }
+
+    /// Returns `true` while the handshake has started but not completed.
+    pub fn is_half_open(&self) -> bool {
+        matches!(self.state, TcpState::SynSent | TcpState::SynReceived)
+    }
+
+    /// Feeds a segment sent by `direction` into the tracker, returning the transition it
+    /// caused, if any.
+    pub fn push(&mut self, segment: &TcpSegment, direction: Direction) -> Option<TcpTransition> {
+        let from = self.state;
+        let to = self.next_state(segment, direction);
+        self.state = to;
+
+        (from != to).then_some(TcpTransition { from, to })
+    }
+
+    fn next_state(&mut self, segment: &TcpSegment, direction: Direction) -> TcpState {
+        let header = &segment.header;
+        let flags = &header.flags;
+        let acknowledges =
+            |expected: Option<u32>| flags.ack && expected == Some(header.acknowledgment_value);
+
+        if flags.rst {
+            return TcpState::Closed;
+        }
+
+        match (self.state, direction) {
+            (TcpState::Closed, Direction::Initiator) if flags.syn && !flags.ack => {
+                *self = TcpStateMachine::new();
+                self.syn_ack[direction.index()] = Some(header.sequence_number.wrapping_add(1));
+                TcpState::SynSent
+            }
+            (TcpState::SynSent, Direction::Responder)
+                if flags.syn && acknowledges(self.syn_ack[Direction::Initiator.index()]) =>
+            {
+                self.syn_ack[direction.index()] = Some(header.sequence_number.wrapping_add(1));
+                TcpState::SynReceived
+            }
+            (TcpState::SynReceived, Direction::Initiator)
+                if !flags.syn && acknowledges(self.syn_ack[Direction::Responder.index()]) =>
+            {
+                self.close(segment, direction)
+            }
+            (
+                TcpState::Established
+                | TcpState::FinWait1
+                | TcpState::FinWait2
+                | TcpState::Closing
+                | TcpState::LastAck,
+                _,
+            ) => {
+                if acknowledges(self.fin_ack[direction.opposite().index()]) {
+                    self.fin_acked[direction.opposite().index()] = true;
+                }
+                self.close(segment, direction)
+            }
+            (state, _) => state,
+        }
+    }
+
+    /// Records a FIN sent by `direction`, then derives the state from the FINs sent and
+    /// acknowledged so far.
+    fn close(&mut self, segment: &TcpSegment, direction: Direction) -> TcpState {
+        let header = &segment.header;
+        if header.flags.fin && self.fin_ack[direction.index()].is_none() {
+            let length = match segment.data.payload() {
+                Some(data) => data.len(),
+                None => segment.data.to_bytes().len(),
+            };
+            // The FIN occupies the sequence number after the last data byte.
+            self.fin_ack[direction.index()] = Some(
+                header
+                    .sequence_number
+                    .wrapping_add(length as u32)
+                    .wrapping_add(1),
+            );
+        }
+
+        let sent = self.fin_ack.iter().filter(|fin| fin.is_some()).count();
+        let acked = self.fin_acked.iter().filter(|&&acked| acked).count();
+
+        match (sent, acked) {
+            (0, _) => TcpState::Established,
+            (1, 0) => TcpState::FinWait1,
+            (1, _) => TcpState::FinWait2,
+            (_, 0) => TcpState::Closing,
+            (_, 1) => TcpState::LastAck,
+            _ => TcpState::TimeWait,
+        }
+    }
+}
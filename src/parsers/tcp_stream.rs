@@ -0,0 +1,130 @@
+use super::tcp::TcpSegment;
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+/// Reassembles the byte stream carried by one direction of a TCP connection.
+///
+/// Segments may be pushed in any order. Bytes are released as soon as they are contiguous
+/// with everything released before them, and out-of-order segments are buffered until the
+/// gap before them is filled.
+///
+/// The initial sequence number is taken from the SYN. If the first segment pushed is not a
+/// SYN, the stream is picked up mid-connection and starts at that segment. Retransmitted
+/// bytes are never released twice: where segments overlap, the bytes seen first are kept.
+/// Once every byte up to the FIN has been released the stream is closed and further
+/// segments are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct TcpStreamReassembler {
+    /// The sequence number of the next byte to release, once the stream is established.
+    next_seq: Option<u32>,
+    /// The stream offset of the next byte to release, i.e. the number of bytes released.
+    next_offset: u64,
+    /// Buffered bytes keyed by stream offset. The entries never overlap.
+    pending: BTreeMap<u64, Vec<u8>>,
+    /// The stream offset of the FIN, once seen.
+    fin_offset: Option<u64>,
+    closed: bool,
+}
+
+impl TcpStreamReassembler {
+    pub fn new() -> Self {
+        TcpStreamReassembler::default()
+    }
+
+    /// Feeds a segment into the reassembler, returning the payload bytes that have become
+    /// contiguous as a result. The result is empty if the segment only filled the buffer,
+    /// was a pure retransmission, or arrived after the stream was closed.
+    pub fn push(&mut self, segment: &TcpSegment) -> Vec<u8> {
+        if self.closed {
+            return Vec::new();
+        }
+
+        let flags = &segment.header.flags;
+        // The SYN occupies the first sequence number, so data starts just after it.
+        let data_seq = segment
+            .header
+            .sequence_number
+            .wrapping_add(flags.syn as u32);
+        let next_seq = *self.next_seq.get_or_insert(data_seq);
+
+        let payload = match segment.data.payload() {
+            Some(data) => data.to_vec(),
+            None => segment.data.to_bytes(),
+        };
+
+        // Sequence numbers wrap, so the distance from the next expected byte is signed.
+        let start = self.next_offset as i64 + data_seq.wrapping_sub(next_seq) as i32 as i64;
+        let end = start + payload.len() as i64;
+
+        if flags.fin && self.fin_offset.is_none() && end >= self.next_offset as i64 {
+            self.fin_offset = Some(end as u64);
+        }
+
+        // Drop whatever has already been released.
+        let skip = (self.next_offset as i64 - start).clamp(0, payload.len() as i64) as usize;
+        if skip < payload.len() {
+            self.insert((start + skip as i64) as u64, &payload[skip..]);
+        }
+
+        self.release()
+    }
+
+    /// Returns `true` once every byte up to the FIN has been released.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Returns the number of bytes buffered while waiting for earlier data.
+    pub fn pending_bytes(&self) -> usize {
+        self.pending.values().map(Vec::len).sum()
+    }
+
+    /// Buffers `data`, found at stream offset `start`, filling only the gaps between the
+    /// bytes already buffered.
+    fn insert(&mut self, start: u64, data: &[u8]) {
+        let end = start + data.len() as u64;
+        let mut gaps = Vec::new();
+        let mut cursor = start;
+
+        for (&existing_start, existing) in self.pending.range(..end) {
+            let existing_end = existing_start + existing.len() as u64;
+            if existing_end <= cursor {
+                continue;
+            }
+            if existing_start > cursor {
+                gaps.push(cursor..existing_start);
+            }
+            cursor = existing_end;
+        }
+        if cursor < end {
+            gaps.push(cursor..end);
+        }
+
+        for gap in gaps {
+            let bytes = &data[(gap.start - start) as usize..(gap.end - start) as usize];
+            self.pending.insert(gap.start, bytes.to_vec());
+        }
+    }
+
+    /// Releases the buffered bytes that are contiguous with the stream, closing it if the
+    /// FIN has been reached.
+    fn release(&mut self) -> Vec<u8> {
+        let mut released = Vec::new();
+
+        while let Some(data) = self.pending.remove(&self.next_offset) {
+            self.next_offset += data.len() as u64;
+            released.extend_from_slice(&data);
+        }
+
+        if let Some(next_seq) = self.next_seq.as_mut() {
+            *next_seq = next_seq.wrapping_add(released.len() as u32);
+        }
+
+        if self.fin_offset == Some(self.next_offset) {
+            self.closed = true;
+            self.pending.clear();
+        }
+
+        released
+    }
+}
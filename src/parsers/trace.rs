@@ -0,0 +1,48 @@
+// Optional `tracing` instrumentation of parse decisions.
+//
+// With the `tracing` feature enabled, each layer's deep parse runs inside a `parse_layer`
+// span and the decisions made along the way are emitted as events. Without it, the
+// macros expand to nothing and `layer_span` returns a zero-sized guard, so the
+// instrumentation costs nothing.
+
+use super::definitions::LayerKind;
+
+/// Emits a `tracing` event at TRACE level when the `tracing` feature is enabled.
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!($($arg)*);
+    };
+}
+
+/// Emits a `tracing` event at DEBUG level when the `tracing` feature is enabled.
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+pub(crate) use {debug, trace};
+
+/// Keeps the span of the layer being parsed entered until dropped.
+#[cfg(feature = "tracing")]
+pub(crate) type LayerSpan = tracing::span::EnteredSpan;
+
+/// Keeps the span of the layer being parsed entered until dropped.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct LayerSpan;
+
+/// Enters a DEBUG span for the deep parse of a layer of the given kind, `depth` layers
+/// from the outermost.
+#[inline(always)]
+pub(crate) fn layer_span(kind: LayerKind, depth: usize) -> LayerSpan {
+    #[cfg(feature = "tracing")]
+    return tracing::debug_span!("parse_layer", layer = ?kind, depth).entered();
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = (kind, depth);
+        LayerSpan
+    }
+}
@@ -12,31 +12,183 @@
  *  +---------------- ...
  */
 
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
 use super::{
-    definitions::{DeepParser, LayeredData},
+    constants::{DNS_PORT, GTP_U_PORT, SIP_PORT, SNMP_PORT, SNMP_TRAP_PORT, VXLAN_PORT},
+    definitions::{DeepParser, LayerKind, LayeredData, ParseOptions},
+    dns::DnsMessage,
     errors::ParserError,
-    utils::{read_arbitrary_length, read_u64},
+    gtp::GtpPacket,
+    registry::ParseContext,
+    sip::{self, SipMessage},
+    snmp::SnmpMessage,
+    trace,
+    utils::{be_u16, transport_checksum, PayloadSource},
+    vxlan::VxlanPacket,
 };
 
-use std::io::Cursor;
+use alloc::{boxed::Box, string::ToString, vec::Vec};
+use core::{net::IpAddr, ops::Range};
 
 const DATA_OFFSET_OR_MIN_SIZE: usize = 8;
+const CHECKSUM_OFFSET: usize = 6;
+const UDP_PROTOCOL: u8 = 17;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct UdpDatagramHeader {
     pub source_port: u16,
     pub destination_port: u16,
     pub length: u16,
     pub checksum: u16,
+    /// The position of the header within the bytes the datagram was parsed from.
+    pub span: Range<usize>,
+    /// The 8 header bytes as received. The checksum covers them, along with the payload
+    /// and a pseudo-header built from the enclosing IP packet.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub raw_header: Vec<u8>,
 }
 
-#[derive(Debug, PartialEq)]
-pub struct UdpDatagram {
+/// A header for an empty datagram between port 0 on both ends, with no checksum.
+///
+/// The header was not parsed from bytes, so `raw_header` and `span` are empty.
+impl Default for UdpDatagramHeader {
+    fn default() -> Self {
+        UdpDatagramHeader {
+            source_port: 0,
+            destination_port: 0,
+            length: DATA_OFFSET_OR_MIN_SIZE as u16,
+            checksum: 0,
+            span: 0..0,
+            raw_header: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UdpDatagram<'a> {
     pub header: UdpDatagramHeader,
-    pub data: Box<LayeredData>,
+    pub data: Box<LayeredData<'a>>,
+    /// `true` if the datagram is shorter than its `length`, so `data` holds only the start
+    /// of the payload. Only set for datagrams parsed with `allow_truncated`.
+    pub truncated: bool,
+    /// The bytes the datagram was parsed from, up to its `length`, if it was constructed
+    /// with `from_bytes_keep_raw`. Left unset otherwise, as keeping them doubles the
+    /// memory held by each datagram.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub raw: Option<Vec<u8>>,
+}
+
+/// A UDP datagram borrowed from the bytes it was parsed by `UdpDatagram::from_bytes_ref`.
+///
+/// The header fields are read from the bytes when asked for, so nothing is copied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UdpDatagramRef<'a> {
+    bytes: &'a [u8],
+    truncated: bool,
 }
 
-impl UdpDatagram {
+impl<'a> UdpDatagramRef<'a> {
+    /// Checks the header of `packets`, and bounds the datagram by its `length`.
+    pub(crate) fn parse(packets: &'a [u8], allow_truncated: bool) -> Result<Self, ParserError> {
+        if packets.len() < DATA_OFFSET_OR_MIN_SIZE {
+            return Err(ParserError::HeaderTooShort {
+                layer: "UDP datagram".to_string(),
+                got: packets.len(),
+                need: DATA_OFFSET_OR_MIN_SIZE,
+            });
+        }
+
+        // The length covers the header and payload. Any bytes beyond it (e.g. Ethernet
+        // padding) are not part of the datagram.
+        let length = be_u16(packets, 4) as usize;
+        let truncated = length > packets.len();
+        if length < DATA_OFFSET_OR_MIN_SIZE || (truncated && !allow_truncated) {
+            return Err(ParserError::InconsistentLength {
+                layer: "UDP datagram".to_string(),
+                declared: length,
+                available: packets.len(),
+            });
+        }
+
+        Ok(UdpDatagramRef {
+            bytes: &packets[..length.min(packets.len())],
+            truncated,
+        })
+    }
+
+    /// Builds the `UdpDatagram`, taking its payload from `source`, the buffer `bytes`
+    /// starts.
+    fn to_owned_in<'s, S: PayloadSource<'s> + ?Sized>(self, source: &S) -> UdpDatagram<'s> {
+        UdpDatagram {
+            header: UdpDatagramHeader {
+                source_port: self.source_port(),
+                destination_port: self.destination_port(),
+                length: self.length(),
+                checksum: self.checksum(),
+                span: 0..DATA_OFFSET_OR_MIN_SIZE,
+                raw_header: self.header().to_vec(),
+            },
+            data: Box::new(source.payload(DATA_OFFSET_OR_MIN_SIZE..self.bytes.len())),
+            truncated: self.truncated,
+            raw: None,
+        }
+    }
+
+    pub fn source_port(&self) -> u16 {
+        be_u16(self.bytes, 0)
+    }
+
+    pub fn destination_port(&self) -> u16 {
+        be_u16(self.bytes, 2)
+    }
+
+    pub fn length(&self) -> u16 {
+        be_u16(self.bytes, 4)
+    }
+
+    pub fn checksum(&self) -> u16 {
+        be_u16(self.bytes, CHECKSUM_OFFSET)
+    }
+
+    /// Returns the 8 header bytes.
+    pub fn header(&self) -> &'a [u8] {
+        &self.bytes[..DATA_OFFSET_OR_MIN_SIZE]
+    }
+
+    /// Returns the payload, which excludes any bytes past `length`.
+    pub fn data(&self) -> &'a [u8] {
+        &self.bytes[DATA_OFFSET_OR_MIN_SIZE..]
+    }
+
+    /// Returns `true` if the datagram is shorter than its `length`.
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl<'a> From<UdpDatagramRef<'a>> for UdpDatagram<'a> {
+    /// Converts the datagram into a `UdpDatagram` borrowing the same bytes, with its payload left
+    /// raw.
+    fn from(datagram: UdpDatagramRef<'a>) -> Self {
+        datagram.to_owned_in(&datagram.bytes)
+    }
+}
+
+impl<'a> DeepParser<'a> for UdpDatagramRef<'a> {
+    /// Converts the datagram into a `UdpDatagram`, and parses its payload from there.
+    fn parse_next_layer_with(
+        self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        UdpDatagram::from(self).parse_next_layer_with(options, depth)
+    }
+}
+
+impl<'a> UdpDatagram<'a> {
     /// Parses the given UDP packet byte slice and constructs a `UDP` structure.
     ///
     /// This function will read the header fields such as source and destination ports,
@@ -53,71 +205,250 @@ impl UdpDatagram {
     ///
     /// # Errors:
     /// The function will return an error in cases such as:
-    /// * The packet data is shorter than the UDP header size.
-    /// * The indicated packet length is inconsistent with the actual data length.
-    pub fn from_bytes(packets: &[u8]) -> Result<Self, ParserError> {
-        if packets.len() < DATA_OFFSET_OR_MIN_SIZE {
-            return Err(ParserError::InvalidLength("UDP datagram".to_string()));
-        }
+    /// * The packet data is shorter than the UDP header size (`HeaderTooShort`).
+    /// * The indicated length is smaller than the header, or larger than the packet data
+    ///   (`InconsistentLength`). A length of exactly 8 yields an empty payload.
+    pub fn from_bytes(packets: &'a [u8]) -> Result<Self, ParserError> {
+        Self::parse(&packets, false)
+    }
 
-        let mut cursor = Cursor::new(packets);
+    /// Constructs a `UdpDatagram` like `from_bytes`, also keeping a copy of the datagram in
+    /// `raw` for `verify_stored_checksum`. Bytes past the `length` field are not kept.
+    ///
+    /// The copy doubles the memory held by the datagram, so use `from_bytes` unless the
+    /// original bytes are needed after parsing.
+    pub fn from_bytes_keep_raw(packets: &'a [u8]) -> Result<Self, ParserError> {
+        let mut datagram = Self::parse(&packets, false)?;
+        datagram.raw = Some(packets[..datagram.header.length as usize].to_vec());
+        Ok(datagram)
+    }
 
-        let (source_port, destination_port, length, checksum) =
-            Self::extract_udp_header_fields(&mut cursor)?;
+    /// Constructs a `UdpDatagram` nested within another layer, accepting a datagram cut
+    /// short of its `length` if `options.allow_truncated` is set.
+    pub(crate) fn from_bytes_with<S: PayloadSource<'a> + ?Sized>(
+        packets: &S,
+        options: &ParseOptions,
+    ) -> Result<Self, ParserError> {
+        Self::parse(packets, options.allow_truncated)
+    }
 
-        let data = read_arbitrary_length(
-            &mut cursor,
-            packets.len() - DATA_OFFSET_OR_MIN_SIZE,
-            "UDP_Data",
-        )?;
+    /// Constructs a `UdpDatagram` whose payload shares `buf` rather than copying it.
+    ///
+    /// Behaves like `from_bytes`, except that the payload is left as a `LayeredData::Bytes`
+    /// slice of `buf`. Layers parsed from it by `parse_next_layer` share `buf` too, down to
+    /// the frame inside a VXLAN tunnel or the packet inside GTP-U; application layers are
+    /// copied.
+    #[cfg(feature = "bytes")]
+    pub fn from_bytes_buf(buf: &bytes::Bytes) -> Result<Self, ParserError> {
+        Self::parse(buf, false)
+    }
 
-        Ok(UdpDatagram {
-            header: UdpDatagramHeader {
-                source_port,
-                destination_port,
-                length,
-                checksum,
-            },
-            data: Box::new(LayeredData::Payload(data)),
-        })
+    /// Constructs a `UdpDatagramRef` borrowing from `packets`, without allocating.
+    ///
+    /// Fails in the same cases as `from_bytes`.
+    pub fn from_bytes_ref(packets: &[u8]) -> Result<UdpDatagramRef<'_>, ParserError> {
+        UdpDatagramRef::parse(packets, false)
+    }
+
+    fn parse<S: PayloadSource<'a> + ?Sized>(
+        source: &S,
+        allow_truncated: bool,
+    ) -> Result<Self, ParserError> {
+        Ok(UdpDatagramRef::parse(source.as_slice(), allow_truncated)?.to_owned_in(source))
     }
 
-    /// Parses the UDP header from a segment and extracts the essential fields.
+    /// Serializes the datagram back into its wire format.
     ///
-    /// This function reads the first eight bytes from the provided segment—represented by a cursor
-    /// in the byte stream—and extracts the source port, destination port, length, and checksum.
+    /// The `length` is recomputed from the serialized payload; the stored value is ignored.
+    /// The stored `checksum` is written unchanged, so use `LayeredData::finalize_checksums`
+    /// first if the datagram was modified.
     ///
-    /// The cursor should be at the position where the UDP header starts, and the segment must contain
-    /// at least eight bytes from that position. The function does not handle the data payload of the
-    /// UDP segment; it only deals with the header information.
+    /// # Returns
+    /// - A `Vec<u8>` holding the serialized header followed by the payload.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = &self.header;
+        let payload = self.data.to_bytes();
+        let length = (DATA_OFFSET_OR_MIN_SIZE + payload.len()).min(u16::MAX as usize) as u16;
+
+        let mut datagram = Vec::with_capacity(DATA_OFFSET_OR_MIN_SIZE + payload.len());
+        datagram.extend_from_slice(&header.source_port.to_be_bytes());
+        datagram.extend_from_slice(&header.destination_port.to_be_bytes());
+        datagram.extend_from_slice(&length.to_be_bytes());
+        datagram.extend_from_slice(&header.checksum.to_be_bytes());
+
+        datagram.extend_from_slice(&payload);
+        datagram
+    }
+
+    /// Verifies the checksum of the datagram, which also covers a pseudo-header built from
+    /// the enclosing IP packet.
     ///
-    /// # Parameters
-    /// * `cursor`: A mutable reference to a cursor, which points to the start of a byte slice that
-    ///   contains the UDP header. The cursor's position updates to after the header once the function
-    ///   executes.
+    /// Over IPv4 a zero checksum means the sender did not compute one, and always verifies.
+    ///
+    /// # Arguments
+    /// - `raw`: The raw datagram, i.e. the header followed by the payload.
+    /// - `source`: The source address of the enclosing IP packet.
+    /// - `destination`: The destination address of the enclosing IP packet.
     ///
     /// # Returns
-    /// * `Ok((u16, u16, u16, u16))`: If the function succeeds, it returns a tuple containing the
-    ///   source port, destination port, length, and checksum, all as unsigned 16-bit integers.
-    /// * `Err(ParserError)`: If the function encounters an issue while reading the byte stream, it
-    ///   returns an error. Potential errors can arise from the cursor having insufficient data (less
-    ///   than eight bytes) or issues with the byte stream itself.
-    fn extract_udp_header_fields(
-        cursor: &mut Cursor<&[u8]>,
-    ) -> Result<(u16, u16, u16, u16), ParserError> {
-        let bytes = read_u64(cursor, "SrcPort_DestPort_Length_Checksum")?;
-
-        let src_port = (bytes >> 48) as u16;
-        let dest_port = ((bytes >> 32) & 0xFFFF) as u16;
-        let length = ((bytes >> 16) & 0xFFFF) as u16;
-        let checksum = (bytes & 0xFFFF) as u16;
-
-        Ok((src_port, dest_port, length, checksum))
+    /// - `true` if the computed checksum matches `checksum`, `false` otherwise, if `raw` is
+    ///   too short to hold a UDP header, or if the addresses are of different families.
+    pub fn verify_checksum(&self, raw: &[u8], source: IpAddr, destination: IpAddr) -> bool {
+        if self.header.checksum == 0 && source.is_ipv4() {
+            return raw.len() >= DATA_OFFSET_OR_MIN_SIZE;
+        }
+        Self::compute_checksum(raw, source, destination) == Some(self.header.checksum)
     }
-}
 
-impl DeepParser for UdpDatagram {
-    fn parse_next_layer(self) -> Result<LayeredData, ParserError> {
+    /// Verifies the checksum against the bytes kept in `raw`, like `verify_checksum`.
+    ///
+    /// # Returns
+    /// - `None` if the datagram was not constructed with `from_bytes_keep_raw`.
+    pub fn verify_stored_checksum(&self, source: IpAddr, destination: IpAddr) -> Option<bool> {
+        let raw = self.raw.as_deref()?;
+        Some(self.verify_checksum(raw, source, destination))
+    }
+
+    /// Computes the checksum of a raw datagram sent from `source` to `destination`, or
+    /// `None` if `raw` is too short or the addresses are of different families.
+    pub(crate) fn compute_checksum(raw: &[u8], source: IpAddr, destination: IpAddr) -> Option<u16> {
+        if raw.len() < DATA_OFFSET_OR_MIN_SIZE {
+            return None;
+        }
+        // A computed checksum of zero is sent as all ones, as zero means "no checksum".
+        transport_checksum(raw, CHECKSUM_OFFSET, source, destination, UDP_PROTOCOL)
+            .map(|checksum| if checksum == 0 { 0xFFFF } else { checksum })
+    }
+
+    /// Returns the number of payload bytes carried by the datagram.
+    ///
+    /// If the payload has been parsed further, the length of its serialized form is
+    /// returned instead.
+    pub fn payload_len(&self) -> usize {
+        match self.data.payload() {
+            Some(data) => data.len(),
+            None => self.data.to_bytes().len(),
+        }
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Udp
+    }
+
+    /// Detaches the datagram from the bytes it was parsed from, copying its payload if it is
+    /// borrowed.
+    pub fn into_owned(self) -> UdpDatagram<'static> {
+        UdpDatagram {
+            header: self.header,
+            truncated: self.truncated,
+            raw: self.raw,
+            data: Box::new(self.data.into_owned()),
+        }
+    }
+
+    /// Parses `data` with the built-in dissector for the datagram's ports, or returns `None`
+    /// if there is none.
+    ///
+    /// Tunnels are recognized by their destination port, and application protocols by
+    /// either port. As a port is only a hint for an application protocol, a payload that
+    /// does not decode as one is left raw rather than failing the datagram. To add a
+    /// protocol, add an arm for its ports.
+    fn dissect_by_port(
+        &self,
+        data: &[u8],
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Option<Result<LayeredData<'a>, ParserError>> {
+        let result = match (self.header.source_port, self.header.destination_port) {
+            (_, VXLAN_PORT) => options
+                .timed(LayerKind::Vxlan, || VxlanPacket::parse(&*self.data))
+                .and_then(|packet| packet.parse_next_layer_with(options, depth)),
+            (_, GTP_U_PORT) => options
+                .timed(LayerKind::Gtp, || GtpPacket::parse(&*self.data))
+                .and_then(|packet| packet.parse_next_layer_with(options, depth)),
+            (SNMP_PORT | SNMP_TRAP_PORT, _) | (_, SNMP_PORT | SNMP_TRAP_PORT) => options
+                .timed(LayerKind::Snmp, || SnmpMessage::from_bytes(data))
+                .ok()?
+                .parse_next_layer_with(options, depth),
+            (SIP_PORT, _) | (_, SIP_PORT) if !sip::is_keepalive(data) => options
+                .timed(LayerKind::Sip, || SipMessage::from_bytes(data))
+                .ok()?
+                .parse_next_layer_with(options, depth),
+            (DNS_PORT, _) | (_, DNS_PORT) => options
+                .timed(LayerKind::Dns, || DnsMessage::from_bytes(data))
+                .ok()?
+                .parse_next_layer_with(options, depth),
+            _ => return None,
+        };
+        Some(result)
+    }
+
+    /// Parses the payload like `parse_next_layer_with`, offering custom parsers the
+    /// addresses of the enclosing IP packet found in `context`.
+    pub(crate) fn parse_next_layer_in(
+        mut self,
+        context: &ParseContext,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Udp, depth);
+
+        let data = self.data.payload().ok_or(ParserError::InvalidPayload)?;
+
+        let context = ParseContext {
+            layer: LayerKind::Udp,
+            source_port: self.header.source_port,
+            destination_port: self.header.destination_port,
+            ..context.clone()
+        };
+        let result = if let Some(layered_data) = options.parsers.dissect(&context, data) {
+            options.descend(depth).map(|_| layered_data)
+        } else {
+            match self.dissect_by_port(data, options, depth) {
+                Some(result) => result,
+                None => return Ok(LayeredData::UdpData(self)),
+            }
+        };
+
+        options.recover(result, &mut self.data)?;
         Ok(LayeredData::UdpData(self))
     }
 }
+
+impl<'a> TryFrom<&'a [u8]> for UdpDatagram<'a> {
+    type Error = ParserError;
+
+    /// Constructs a `UdpDatagram` from a slice. See `from_bytes`.
+    fn try_from(packets: &'a [u8]) -> Result<Self, Self::Error> {
+        UdpDatagram::from_bytes(packets)
+    }
+}
+
+impl<'a> DeepParser<'a> for UdpDatagram<'a> {
+    /// Parses the payload of datagrams sent to a known port.
+    ///
+    /// Datagrams sent to the VXLAN port (4789) are parsed as VXLAN, datagrams sent to the
+    /// GTP-U port (2152) as GTP-U, datagrams to or from the SNMP ports (161 and 162) as
+    /// SNMP, datagrams to or from the SIP port (5060) as SIP, unless they are
+    /// keep-alives, and datagrams to or from the DNS port (53) as DNS. Any other payload,
+    /// or one that fails to decode as the application protocol its port suggests, is left
+    /// as raw data.
+    /// Parsers registered in `options.parsers` are tried first.
+    ///
+    /// Parsed on its own, the datagram has no enclosing IP packet, so its checksum is never
+    /// verified.
+    fn parse_next_layer_with(
+        self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let context = ParseContext::new(
+            LayerKind::Udp,
+            self.header.source_port,
+            self.header.destination_port,
+        );
+        self.parse_next_layer_in(&context, options, depth)
+    }
+}
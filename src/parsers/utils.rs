@@ -1,12 +1,178 @@
-use std::io::{Cursor, Read};
-
 use super::{
-    definitions::{DeepParser, IPType, LayeredData},
+    checksum::{fold_sum, ones_complement_sum, pseudo_header_sum_v4, pseudo_header_sum_v6},
+    definitions::{DeepParser, IPType, LayerKind, LayeredData, LayeredDataRef, ParseOptions},
     errors::{ErrorSource, ParserError},
     icmp::IcmpPacket,
-    tcp::TcpSegment,
-    udp::UdpDatagram,
+    icmpv6::Icmpv6Packet,
+    ipv4::{Ipv4Packet, Ipv4PacketRef},
+    ipv6::{Ipv6Packet, Ipv6PacketRef},
+    registry::ParseContext,
+    tcp::{TcpSegment, TcpSegmentRef},
+    trace,
+    udp::{UdpDatagram, UdpDatagramRef},
+};
+
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
 };
+use core::{fmt::Write, net::IpAddr, ops::Range};
+
+const HEXDUMP_LINE_WIDTH: usize = 16;
+
+/// The buffer a parser reads from, which also decides how the payload is stored.
+///
+/// Plain slices lend the payload to a borrowed `LayeredData::Payload`. With the `bytes`
+/// feature, a `Bytes` buffer hands out a `LayeredData::Bytes` sharing its allocation
+/// instead.
+pub(crate) trait PayloadSource<'a> {
+    /// Returns the whole buffer.
+    fn as_slice(&self) -> &[u8];
+
+    /// Returns `range` of the buffer as a raw payload.
+    fn payload(&self, range: Range<usize>) -> LayeredData<'a>;
+}
+
+impl<'a> PayloadSource<'a> for &'a [u8] {
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    fn payload(&self, range: Range<usize>) -> LayeredData<'a> {
+        LayeredData::Payload(Cow::Borrowed(&self[range]))
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<'a> PayloadSource<'a> for bytes::Bytes {
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    fn payload(&self, range: Range<usize>) -> LayeredData<'a> {
+        LayeredData::Bytes(self.slice(range))
+    }
+}
+
+/// A raw payload passes its storage on to the layer parsed from it, so a borrowed payload
+/// stays borrowed and a shared one shared. Only an owned payload is copied. Parsed layers
+/// have no raw bytes and read as empty.
+impl<'a> PayloadSource<'a> for LayeredData<'a> {
+    fn as_slice(&self) -> &[u8] {
+        self.payload().unwrap_or_default()
+    }
+
+    fn payload(&self, range: Range<usize>) -> LayeredData<'a> {
+        match self {
+            LayeredData::Payload(Cow::Borrowed(data)) => {
+                LayeredData::Payload(Cow::Borrowed(&data[range]))
+            }
+            #[cfg(feature = "bytes")]
+            LayeredData::Bytes(data) => LayeredData::Bytes(data.slice(range)),
+            _ => LayeredData::Payload(Cow::Owned(self.as_slice()[range].to_vec())),
+        }
+    }
+}
+
+/// A lightweight reader over a byte slice that tracks the current read offset.
+///
+/// Fields are decoded directly from sub-slices of the input with `from_be_bytes`,
+/// avoiding the intermediate buffer copies of `std::io::Cursor::read_exact`. This also
+/// keeps the parsers free of `std`. Reads never go past the end of the slice; attempting
+/// to do so leaves the position unchanged and returns a `ParserError::ExtractionError`.
+#[derive(Debug)]
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    /// Creates a new `ByteReader` positioned at the start of `bytes`.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        ByteReader { bytes, position: 0 }
+    }
+
+    /// Returns the current offset into the underlying slice.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Moves the read offset to `position`.
+    ///
+    /// The position may be set past the end of the slice, in which case any
+    /// subsequent read fails.
+    pub fn set_position(&mut self, position: usize) {
+        self.position = position;
+    }
+
+    /// Returns the number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len().saturating_sub(self.position)
+    }
+
+    /// Borrows the next `length` bytes and advances the offset past them.
+    ///
+    /// # Parameters
+    /// - `length`: The number of bytes to read.
+    /// - `field`: A description of the field being read, used for error reporting.
+    ///
+    /// # Returns
+    /// - `Ok(&[u8])`: A sub-slice of the input, borrowed for the input's lifetime.
+    /// - `Err(ParserError::ExtractionError)`: Fewer than `length` bytes remain.
+    pub fn read_slice(&mut self, length: usize, field: &str) -> Result<&'a [u8], ParserError> {
+        if self.remaining() < length {
+            return Err(ParserError::ExtractionError {
+                string: field.to_string(),
+                source: ErrorSource::unexpected_eof(),
+            });
+        }
+
+        let start = self.position;
+        self.position += length;
+
+        Ok(&self.bytes[start..self.position])
+    }
+
+    /// Reads a fixed number of bytes into an array.
+    fn read_array<const N: usize>(&mut self, field: &str) -> Result<[u8; N], ParserError> {
+        let mut buffer = [0u8; N];
+        buffer.copy_from_slice(self.read_slice(N, field)?);
+        Ok(buffer)
+    }
+
+    /// Reads a single byte.
+    pub fn read_u8(&mut self, field: &str) -> Result<u8, ParserError> {
+        Ok(self.read_array::<1>(field)?[0])
+    }
+
+    /// Reads a big-endian `u16`.
+    pub fn read_u16_be(&mut self, field: &str) -> Result<u16, ParserError> {
+        self.read_array(field).map(u16::from_be_bytes)
+    }
+
+    /// Reads a big-endian 24-bit value, such as a VXLAN VNI, into the low bits of a `u32`.
+    pub fn read_u24_be(&mut self, field: &str) -> Result<u32, ParserError> {
+        let [high, middle, low] = self.read_array(field)?;
+        Ok(u32::from_be_bytes([0, high, middle, low]))
+    }
+
+    /// Reads a big-endian `u32`.
+    pub fn read_u32_be(&mut self, field: &str) -> Result<u32, ParserError> {
+        self.read_array(field).map(u32::from_be_bytes)
+    }
+
+    /// Reads a big-endian `u64`.
+    pub fn read_u64_be(&mut self, field: &str) -> Result<u64, ParserError> {
+        self.read_array(field).map(u64::from_be_bytes)
+    }
+
+    /// Reads a big-endian `u128`.
+    pub fn read_u128_be(&mut self, field: &str) -> Result<u128, ParserError> {
+        self.read_array(field).map(u128::from_be_bytes)
+    }
+}
 
 /// Reads an arbitrary number of bytes from a cursor within a byte slice.
 ///This function attempts to read 'length' number of bytes from
@@ -18,140 +184,471 @@ use super::{
 ///
 /// # Parameters
 /// - `cursor`: A mutable reference to a cursor over the byte slice
-///  from which the data is read.
-///  The cursor is advanced by 'length' bytes if the operation is successful.
+///   from which the data is read.
+///   The cursor is advanced by 'length' bytes if the operation is successful.
 /// - `length`: The number of bytes to read from the current
-///  cursor position. The function allocates a buffer of this
-///  size to store the read bytes.
+///   cursor position. The function allocates a buffer of this
+///   size to store the read bytes.
 /// - `field`: A reference to a string that describes the field
-///  being read. This is used for error reporting purposes to specify
-///  which field encountered a read error.
+///   being read. This is used for error reporting purposes to specify
+///   which field encountered a read error.
 ///
 /// # Returns
 /// - `Ok`: If the read operation is successful, it returns the
-/// bytes read as a `Vec<u8>`.
+///   bytes read as a `Vec<u8>`.
 /// - `Err`: If the read operation fails (for example, trying
-///  to read beyond the end of the byte slice), it returns a
-///  `ParserError` with relevant error information.
+///   to read beyond the end of the byte slice), it returns a
+///   `ParserError` with relevant error information.
 pub fn read_arbitrary_length(
-    cursor: &mut Cursor<&[u8]>,
+    cursor: &mut ByteReader,
     length: usize,
     field: &str,
 ) -> Result<Vec<u8>, ParserError> {
-    let mut buffer = vec![0; length];
-
-    cursor
-        .read_exact(&mut buffer)
-        .map_err(|e| ParserError::ExtractionError {
-            source: ErrorSource::Io(e),
-            string: field.to_string(),
-        })?;
-
-    Ok(buffer)
+    Ok(cursor.read_slice(length, field)?.to_vec())
 }
 
-pub fn read_u32(cursor: &mut Cursor<&[u8]>, field: &str) -> Result<u32, ParserError> {
-    let mut buffer: [u8; 4] = Default::default();
-
-    cursor
-        .read_exact(&mut buffer)
-        .map_err(|e| ParserError::ExtractionError {
-            string: field.to_string(),
-            source: ErrorSource::Io(e),
-        })?;
-
-    Ok(u32::from_be_bytes(buffer))
+pub fn read_u24(cursor: &mut ByteReader, field: &str) -> Result<u32, ParserError> {
+    cursor.read_u24_be(field)
 }
 
-pub fn read_u64(cursor: &mut Cursor<&[u8]>, field: &str) -> Result<u64, ParserError> {
-    let mut buffer: [u8; 8] = Default::default();
-
-    cursor
-        .read_exact(&mut buffer)
-        .map_err(|e| ParserError::ExtractionError {
-            string: field.to_string(),
-            source: ErrorSource::Io(e),
-        })?;
-
-    Ok(u64::from_be_bytes(buffer))
+pub fn read_u32(cursor: &mut ByteReader, field: &str) -> Result<u32, ParserError> {
+    cursor.read_u32_be(field)
 }
 
-pub fn read_u16(cursor: &mut Cursor<&[u8]>, field: &str) -> Result<u16, ParserError> {
-    let mut buffer: [u8; 2] = Default::default();
-
-    cursor
-        .read_exact(&mut buffer)
-        .map_err(|e| ParserError::ExtractionError {
-            string: field.to_string(),
-            source: ErrorSource::Io(e),
-        })?;
-
-    Ok(u16::from_be_bytes(buffer))
+pub fn read_u64(cursor: &mut ByteReader, field: &str) -> Result<u64, ParserError> {
+    cursor.read_u64_be(field)
 }
 
-pub fn read_u128(cursor: &mut Cursor<&[u8]>, field: &str) -> Result<u128, ParserError> {
-    let mut buffer: [u8; 16] = Default::default();
-
-    cursor
-        .read_exact(&mut buffer)
-        .map_err(|e| ParserError::ExtractionError {
-            string: field.to_string(),
-            source: ErrorSource::Io(e),
-        })?;
+pub fn read_u16(cursor: &mut ByteReader, field: &str) -> Result<u16, ParserError> {
+    cursor.read_u16_be(field)
+}
 
-    Ok(u128::from_be_bytes(buffer))
+pub fn read_u128(cursor: &mut ByteReader, field: &str) -> Result<u128, ParserError> {
+    cursor.read_u128_be(field)
 }
 
-pub fn read_u8(cursor: &mut Cursor<&[u8]>, field: &str) -> Result<u8, ParserError> {
-    let mut buffer: [u8; 1] = Default::default();
+pub fn read_u8(cursor: &mut ByteReader, field: &str) -> Result<u8, ParserError> {
+    cursor.read_u8(field)
+}
 
-    cursor
-        .read_exact(&mut buffer)
-        .map_err(|e| ParserError::ExtractionError {
-            source: ErrorSource::Io(e),
-            string: field.to_string(),
-        })?;
+/// Reads the big-endian `u16` at `offset` of a slice whose length has been checked.
+pub(crate) fn be_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([bytes[offset], bytes[offset + 1]])
+}
 
-    Ok(u8::from_be_bytes(buffer))
+/// Reads the big-endian `u32` at `offset` of a slice whose length has been checked.
+pub(crate) fn be_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
 }
 
 /// Parses the encapsulated protocol layer within the payload of an IPv4 or IPv6 packet.
 ///
 /// The function decodes the payload based on the specified `IPType` (e.g., TCP, UDP, ICMP) and
-/// returns the parsed data as `LayeredData` or an error if the parsing fails.
+/// returns the parsed data as `LayeredData` or an error if the parsing fails. An IPv4 or IPv6
+/// packet tunnelled inside the payload (protocol 4 or 41) is parsed recursively.
 ///
 /// # Arguments
 ///
 /// * `payload` - A `LayeredData` instance containing the payload data to parse.
 /// * `ip_type` - An `IPType` enum indicating the protocol type contained in the payload.
+/// * `context` - The addresses and payload length of the enclosing packet.
+/// * `options` - The options the enclosing packet is being parsed with.
+/// * `depth` - The number of layers already parsed above the payload.
 ///
 /// # Returns
 ///
 /// * `Ok(LayeredData)` containing the parsed protocol data if parsing is successful.
 /// * `Err(ParserError)` if there is an error during parsing.
-pub fn parse_ip_next_protocol_layer(
-    payload: &LayeredData,
+///
+/// # Errors
+///
+/// * `ParserError::ChecksumMismatch` - If `options.verify_checksums` is set and a TCP or
+///   UDP checksum does not match the one computed from `context`. Truncated segments are
+///   not verified, as the checksum covers bytes that are missing.
+/// * `ParserError::MaxDepthExceeded` - If tunnels are nested deeper than `options.max_depth`.
+pub fn parse_ip_next_protocol_layer<'a>(
+    payload: &LayeredData<'a>,
     ip_type: &IPType,
-) -> Result<LayeredData, ParserError> {
-    if let LayeredData::Payload(data) = payload {
+    context: &ParseContext,
+    options: &ParseOptions,
+    depth: usize,
+) -> Result<LayeredData<'a>, ParserError> {
+    if let Some(data) = payload.payload() {
+        let addresses = context.src_ip.zip(context.dst_ip);
+        // The enclosing packet declares more payload than it holds if it was truncated.
+        let truncated = context.l3_payload_len.is_some_and(|len| data.len() < len);
+
         let layered_data = match ip_type {
             IPType::TCP => {
-                let tcp_packet = TcpSegment::from_bytes(data)?;
-                tcp_packet.parse_next_layer()
+                let mut tcp_packet = options.timed(LayerKind::Tcp, || {
+                    TcpSegment::from_bytes_with(payload, options)
+                })?;
+                tcp_packet.truncated |= truncated;
+                if let (true, false, Some((source, destination))) =
+                    (options.verify_checksums, tcp_packet.truncated, addresses)
+                {
+                    check_checksum(
+                        tcp_packet.header.checksum,
+                        TcpSegment::compute_checksum(data, source, destination),
+                    )?;
+                }
+                tcp_packet.parse_next_layer_in(context, options, depth)
             }
             IPType::UDP => {
-                let udp_datagram = UdpDatagram::from_bytes(data)?;
-                udp_datagram.parse_next_layer()
+                let mut udp_datagram = options.timed(LayerKind::Udp, || {
+                    UdpDatagram::from_bytes_with(payload, options)
+                })?;
+                udp_datagram.truncated |= truncated;
+                if let (true, false, Some((source, destination))) =
+                    (options.verify_checksums, udp_datagram.truncated, addresses)
+                {
+                    // Over IPv4, a zero checksum means the sender did not compute one.
+                    if udp_datagram.header.checksum != 0 || source.is_ipv6() {
+                        check_checksum(
+                            udp_datagram.header.checksum,
+                            UdpDatagram::compute_checksum(data, source, destination),
+                        )?;
+                    }
+                }
+                udp_datagram.parse_next_layer_in(context, options, depth)
             }
             IPType::ICMP => {
-                let icmp_packet = IcmpPacket::from_bytes(data)?;
-                icmp_packet.parse_next_layer()
+                let icmp_packet = options.timed(LayerKind::Icmp, || IcmpPacket::parse(payload))?;
+                icmp_packet.parse_next_layer_with(options, depth)
+            }
+            IPType::ICMPv6 => {
+                let icmpv6_packet =
+                    options.timed(LayerKind::Icmpv6, || Icmpv6Packet::parse(payload))?;
+                icmpv6_packet.parse_next_layer_with(options, depth)
+            }
+            // Tunnelled packets are parsed like any other, counting towards the depth limit.
+            IPType::IPv4 => {
+                let mut ipv4_packet = options.timed(LayerKind::Ipv4, || {
+                    Ipv4Packet::from_bytes_with(payload, options)
+                })?;
+                ipv4_packet.truncated |= truncated;
+                ipv4_packet.parse_next_layer_with(options, depth)
+            }
+            IPType::IPv6 => {
+                let mut ipv6_packet = options.timed(LayerKind::Ipv6, || {
+                    Ipv6Packet::from_bytes_with(payload, options)
+                })?;
+                ipv6_packet.truncated |= truncated;
+                ipv6_packet.parse_next_layer_with(options, depth)
             }
             IPType::Other(v) => Err(ParserError::UnknownIPType(*v)),
         }?;
 
         Ok(layered_data)
     } else {
-        return Err(ParserError::InvalidPayload);
+        Err(ParserError::InvalidPayload)
+    }
+}
+
+/// Parses the payload of an IPv4 or IPv6 packet as `ip_type`, borrowing from `payload`.
+///
+/// TCP, UDP and tunnelled IP packets are parsed into their borrowed forms. ICMP and ICMPv6
+/// messages have none, so are returned as `LayeredDataRef::Payload`.
+///
+/// # Errors
+///
+/// * `ParserError::UnknownIPType` - If `ip_type` is not a protocol net-sift parses.
+/// * Any error returned while parsing the TCP, UDP or IP layer.
+pub(crate) fn ip_next_layer_ref<'a>(
+    payload: &'a [u8],
+    ip_type: &IPType,
+) -> Result<LayeredDataRef<'a>, ParserError> {
+    let layer = match ip_type {
+        IPType::TCP => LayeredDataRef::TcpData(TcpSegmentRef::parse(payload, false)?),
+        IPType::UDP => LayeredDataRef::UdpData(UdpDatagramRef::parse(payload, false)?),
+        IPType::IPv4 => LayeredDataRef::Ipv4Data(Ipv4PacketRef::parse(payload, false)?),
+        IPType::IPv6 => LayeredDataRef::Ipv6Data(Ipv6PacketRef::parse(payload, false)?),
+        IPType::ICMP | IPType::ICMPv6 => LayeredDataRef::Payload(payload),
+        IPType::Other(v) => return Err(ParserError::UnknownIPType(*v)),
+    };
+
+    Ok(layer)
+}
+
+/// Parses an IP packet that is not preceded by a link-layer header, such as one read from
+/// a tun interface or a `DLT_RAW` capture, along with the layers it carries.
+///
+/// The IP version is taken from the first nibble, so IPv4 and IPv6 packets can be mixed.
+/// Parsing uses the default `ParseOptions`.
+///
+/// # Returns
+///
+/// * `LayeredData::Ipv4Data` or `LayeredData::Ipv6Data` - The packet, with its payload
+///   parsed as deeply as possible.
+///
+/// # Errors
+///
+/// * `ParserError::HeaderTooShort` - If `bytes` is empty.
+/// * `ParserError::UnknownIpVersion` - If the version is neither 4 nor 6.
+/// * Any error returned while parsing the packet or the layers it carries.
+pub fn parse_ip(bytes: &[u8]) -> Result<LayeredData<'_>, ParserError> {
+    parse_ip_with(bytes, &ParseOptions::default())
+}
+
+/// Parses an IP packet that is not preceded by a link-layer header according to
+/// `options`. See `parse_ip`.
+pub fn parse_ip_with<'a>(
+    bytes: &'a [u8],
+    options: &ParseOptions,
+) -> Result<LayeredData<'a>, ParserError> {
+    match bytes.first().map(|byte| byte >> 4) {
+        Some(4) => options
+            .timed(LayerKind::Ipv4, || {
+                Ipv4Packet::from_bytes_with(&bytes, options)
+            })
+            .and_then(|packet| packet.parse_next_layer_with(options, 0)),
+        Some(6) => options
+            .timed(LayerKind::Ipv6, || {
+                Ipv6Packet::from_bytes_with(&bytes, options)
+            })
+            .and_then(|packet| packet.parse_next_layer_with(options, 0)),
+        Some(version) => Err(ParserError::UnknownIpVersion(version)),
+        None => Err(ParserError::HeaderTooShort {
+            layer: "IP packet".to_string(),
+            got: 0,
+            need: 1,
+        }),
+    }
+}
+
+/// Fails with `ParserError::ChecksumMismatch` unless `computed` equals `expected`.
+///
+/// A `None` computed checksum, meaning the segment was too short to hold one, always fails.
+pub(crate) fn check_checksum(expected: u16, computed: Option<u16>) -> Result<(), ParserError> {
+    match computed {
+        Some(computed) if computed == expected => Ok(()),
+        computed => {
+            trace::trace!(expected, ?computed, "checksum mismatch");
+            Err(ParserError::ChecksumMismatch {
+                expected,
+                computed: computed.unwrap_or_default(),
+            })
+        }
+    }
+}
+
+/// Computes a TCP or UDP checksum, which covers a pseudo-header built from the enclosing
+/// IP packet followed by the whole segment (RFC 9293, section 3.1 and RFC 8200, section 8.1).
+///
+/// # Arguments
+///
+/// * `segment` - The raw segment, header included.
+/// * `checksum_offset` - The offset of the checksum field within `segment`, which is
+///   treated as zero. It must be even, as it is in every protocol using this checksum.
+/// * `source` - The source address of the enclosing packet.
+/// * `destination` - The destination address of the enclosing packet.
+/// * `protocol` - The protocol number of the segment, e.g. 6 for TCP.
+///
+/// # Returns
+///
+/// The checksum, or `None` if `segment` is too short to hold the checksum field or the
+/// addresses are of different families.
+pub(crate) fn transport_checksum(
+    segment: &[u8],
+    checksum_offset: usize,
+    source: IpAddr,
+    destination: IpAddr,
+    protocol: u8,
+) -> Option<u16> {
+    if segment.len() < checksum_offset + 2 {
+        return None;
+    }
+
+    let pseudo_header = match (source, destination) {
+        (IpAddr::V4(source), IpAddr::V4(destination)) => {
+            pseudo_header_sum_v4(source, destination, protocol, segment.len() as u16)
+        }
+        (IpAddr::V6(source), IpAddr::V6(destination)) => {
+            pseudo_header_sum_v6(source, destination, protocol, segment.len() as u32)
+        }
+        _ => return None,
+    };
+
+    // The checksum field is word-aligned, so skipping it sums the segment as if it were zero.
+    let before = ones_complement_sum(&segment[..checksum_offset]);
+    let after = ones_complement_sum(&segment[checksum_offset + 2..]);
+
+    Some(!fold_sum(pseudo_header + before as u32 + after as u32))
+}
+
+/// Splits the options of a TCP or IPv4 header into the type and data of each.
+///
+/// Both headers encode options the same way: a type byte, then for every type but End of
+/// Option List (0) and No-Operation (1), a length byte counting the whole option, then
+/// the data. Nothing is read after the End of Option List, as the rest is padding, nor
+/// after a malformed option, as the options that follow cannot be located.
+#[derive(Debug, Clone)]
+pub(crate) struct OptionReader<'a> {
+    bytes: &'a [u8],
+    layer: &'static str,
+}
+
+impl<'a> OptionReader<'a> {
+    /// Creates an `OptionReader` over `bytes`, naming `layer` in the errors it reports.
+    pub(crate) fn new(bytes: &'a [u8], layer: &'static str) -> Self {
+        OptionReader { bytes, layer }
+    }
+}
+
+impl<'a> Iterator for OptionReader<'a> {
+    type Item = Result<(u8, &'a [u8]), ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&option_type, rest) = self.bytes.split_first()?;
+
+        if option_type <= 1 {
+            self.bytes = if option_type == 0 { &[] } else { rest };
+            return Some(Ok((option_type, &[])));
+        }
+
+        let bytes = core::mem::take(&mut self.bytes);
+        let length = match rest.first() {
+            Some(&length) if length >= 2 => length as usize,
+            _ => return Some(Err(ParserError::InvalidLength(self.layer.to_string()))),
+        };
+        if length > bytes.len() {
+            return Some(Err(ParserError::OptionsOverrun {
+                layer: self.layer.to_string(),
+                declared: length,
+                available: bytes.len(),
+            }));
+        }
+
+        self.bytes = &bytes[length..];
+        Some(Ok((option_type, &bytes[2..length])))
+    }
+}
+
+/// Splits a block of `Name: value` header lines, as used by text protocols such as SIP
+/// and HTTP, into name and value pairs in the order they appear.
+///
+/// Lines end with CRLF, though a bare LF is accepted too. Whitespace around names and
+/// values is trimmed. A line starting with a space or tab continues the value of the
+/// header before it (obsolete line folding, RFC 3261 section 7.3.1), and is joined to it
+/// with a single space.
+///
+/// # Errors
+///
+/// * `ParserError::MalformedMessage` - If a line has no colon, or the block starts with a
+///   continuation line. The error names `layer`.
+pub(crate) fn parse_header_lines(
+    block: &str,
+    layer: &str,
+) -> Result<Vec<(String, String)>, ParserError> {
+    let malformed = |detail: String| ParserError::MalformedMessage {
+        layer: layer.to_string(),
+        detail,
+    };
+    let mut headers: Vec<(String, String)> = Vec::new();
+
+    for line in block.split('\n').map(|line| line.trim_end_matches('\r')) {
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with([' ', '\t']) {
+            let (_, value) = headers
+                .last_mut()
+                .ok_or_else(|| malformed("continuation line before any header".to_string()))?;
+            value.push(' ');
+            value.push_str(line.trim());
+            continue;
+        }
+
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| malformed(format!("header line `{line}` has no colon")))?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    Ok(headers)
+}
+
+/// Computes the Internet checksum (RFC 1071) over a byte slice.
+///
+/// The data is summed as a sequence of big-endian 16-bit words using one's-complement
+/// arithmetic, with a trailing odd byte padded with zero. The carries are folded back
+/// into the lower 16 bits and the one's complement of the result is returned.
+///
+/// # Arguments
+///
+/// * `data` - The bytes to checksum. Any checksum field within must already be zeroed.
+///
+/// # Returns
+///
+/// The 16-bit checksum value, ready to be written into a header.
+pub fn internet_checksum(data: &[u8]) -> u16 {
+    !ones_complement_sum(data)
+}
+
+/// Formats bytes in the classic `hexdump -C` layout, for eyeballing unparsed payloads.
+///
+/// Each line holds up to 16 bytes: the offset as eight hex digits, the bytes in hex split
+/// into two groups of eight, and their printable ASCII representation between `|`
+/// characters, with non-printable bytes shown as `.`. Every line, including the last, ends
+/// with a newline. An empty slice produces an empty string.
+///
+/// # Example
+///
+/// ```
+/// use net_sift::parsers::utils::hexdump;
+///
+/// assert_eq!(
+///     hexdump(b"Hi!\n"),
+///     "00000000  48 69 21 0a                                       |Hi!.|\n"
+/// );
+/// ```
+pub fn hexdump(bytes: &[u8]) -> String {
+    let mut output = String::new();
+
+    for (line, chunk) in bytes.chunks(HEXDUMP_LINE_WIDTH).enumerate() {
+        let _ = write!(output, "{:08x} ", line * HEXDUMP_LINE_WIDTH);
+
+        for i in 0..HEXDUMP_LINE_WIDTH {
+            if i % 8 == 0 {
+                output.push(' ');
+            }
+            match chunk.get(i) {
+                Some(byte) => {
+                    let _ = write!(output, "{:02x} ", byte);
+                }
+                None => output.push_str("   "),
+            }
+        }
+
+        output.push_str(" |");
+        for byte in chunk {
+            output.push(match byte {
+                0x20..=0x7E => *byte as char,
+                _ => '.',
+            });
+        }
+        output.push_str("|\n");
+    }
+
+    output
+}
+
+/// Serializes bytes as a lowercase hex string, so payloads appear in JSON as one compact
+/// string rather than an array of integers.
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_hex<S: serde::Serializer>(
+    bytes: &[u8],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(hex, "{:02x}", byte);
     }
+    serializer.serialize_str(&hex)
 }
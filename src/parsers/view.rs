@@ -0,0 +1,244 @@
+// A read-only view of an Ethernet frame, for scanning captures faster than a full parse.
+//
+// `scan_ethernet` only locates the network and transport headers. Every field is read
+// from the original slice when asked for, so no `Vec` is allocated and nothing is copied.
+
+use super::{errors::ParserError, tcp::Flags, utils::be_u16};
+
+use alloc::string::ToString;
+
+const ETHERNET_HEADER_SIZE: usize = 14;
+const Q_TAG_SIZE: usize = 4;
+const TPID_VLAN: u16 = 0x8100;
+const MAX_8023_LENGTH: u16 = 1500;
+
+const ETHER_TYPE_IPV4: u16 = 0x0800;
+const ETHER_TYPE_IPV6: u16 = 0x86DD;
+
+const IPV4_MIN_HEADER_SIZE: usize = 20;
+const MIN_IHL_VALUE: u8 = 5;
+const MAX_IHL_VALUE: u8 = 15;
+const IPV6_HEADER_SIZE: usize = 40;
+const TCP_MIN_HEADER_SIZE: usize = 20;
+const UDP_HEADER_SIZE: usize = 8;
+
+const PROTOCOL_TCP: u8 = 6;
+const PROTOCOL_UDP: u8 = 17;
+
+/// The offsets of the headers within an Ethernet frame, with accessors for the fields
+/// most often needed when scanning a capture.
+///
+/// Only Ethernet II frames, with at most one 802.1Q tag, carrying IPv4 or IPv6 are
+/// followed to the transport layer. The transport header is located when the IP packet
+/// carries TCP or UDP directly: IPv6 extension headers and non-initial IPv4 fragments
+/// leave it unset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PacketView<'a> {
+    bytes: &'a [u8],
+    network_offset: usize,
+    transport_offset: Option<usize>,
+    payload_offset: Option<usize>,
+}
+
+/// Locates the headers of the Ethernet frame in `bytes` without allocating.
+///
+/// Unlike `EthernetFrame::from_bytes`, the frame is not validated beyond what is needed to
+/// find the headers, and any FCS is treated as part of the payload.
+///
+/// # Errors
+///
+/// * `ParserError::HeaderTooShort` - If `bytes` is too short for the Ethernet header, or for
+///   an IP or transport header it announces.
+/// * `ParserError::InvalidIHLValue` and `ParserError::InvalidLength` - If an IPv4 or TCP
+///   header declares a length shorter than its fixed part.
+pub fn scan_ethernet(bytes: &[u8]) -> Result<PacketView<'_>, ParserError> {
+    if bytes.len() < ETHERNET_HEADER_SIZE {
+        return Err(ParserError::HeaderTooShort {
+            layer: "Ethernet Frame".to_string(),
+            got: bytes.len(),
+            need: ETHERNET_HEADER_SIZE,
+        });
+    }
+
+    let mut network_offset = ETHERNET_HEADER_SIZE;
+    if be_u16(bytes, 12) == TPID_VLAN {
+        network_offset += Q_TAG_SIZE;
+        if bytes.len() < network_offset {
+            return Err(ParserError::HeaderTooShort {
+                layer: "Ethernet Frame".to_string(),
+                got: bytes.len(),
+                need: network_offset,
+            });
+        }
+    }
+
+    let mut view = PacketView {
+        bytes,
+        network_offset,
+        transport_offset: None,
+        payload_offset: None,
+    };
+
+    let (protocol, transport_offset) = match view.ether_type() {
+        ETHER_TYPE_IPV4 => {
+            let packet = &bytes[network_offset..];
+            if packet.len() < IPV4_MIN_HEADER_SIZE {
+                return Err(ParserError::HeaderTooShort {
+                    layer: "Ipv4 packet".to_string(),
+                    got: packet.len(),
+                    need: IPV4_MIN_HEADER_SIZE,
+                });
+            }
+            let header_length = (packet[0] & 0x0F) as usize * 4;
+            if header_length < IPV4_MIN_HEADER_SIZE {
+                return Err(ParserError::InvalidIHLValue(
+                    (header_length / 4) as u32,
+                    MIN_IHL_VALUE,
+                    MAX_IHL_VALUE,
+                ));
+            }
+            if packet.len() < header_length {
+                return Err(ParserError::HeaderTooShort {
+                    layer: "Ipv4 packet".to_string(),
+                    got: packet.len(),
+                    need: header_length,
+                });
+            }
+
+            // Only the first fragment starts with the transport header.
+            if be_u16(packet, 6) & 0x1FFF != 0 {
+                return Ok(view);
+            }
+            (packet[9], network_offset + header_length)
+        }
+        ETHER_TYPE_IPV6 => {
+            if bytes.len() < network_offset + IPV6_HEADER_SIZE {
+                return Err(ParserError::HeaderTooShort {
+                    layer: "Ipv6 packet".to_string(),
+                    got: bytes.len() - network_offset,
+                    need: IPV6_HEADER_SIZE,
+                });
+            }
+            (bytes[network_offset + 6], network_offset + IPV6_HEADER_SIZE)
+        }
+        _ => return Ok(view),
+    };
+
+    let transport = &bytes[transport_offset..];
+    let header_length = match protocol {
+        PROTOCOL_TCP => {
+            if transport.len() < TCP_MIN_HEADER_SIZE {
+                return Err(ParserError::HeaderTooShort {
+                    layer: "TCP segment".to_string(),
+                    got: transport.len(),
+                    need: TCP_MIN_HEADER_SIZE,
+                });
+            }
+            let header_length = (transport[12] >> 4) as usize * 4;
+            if header_length < TCP_MIN_HEADER_SIZE {
+                return Err(ParserError::InvalidLength("TCP data offset".to_string()));
+            }
+            if transport.len() < header_length {
+                return Err(ParserError::HeaderTooShort {
+                    layer: "TCP segment".to_string(),
+                    got: transport.len(),
+                    need: header_length,
+                });
+            }
+            header_length
+        }
+        PROTOCOL_UDP => {
+            if transport.len() < UDP_HEADER_SIZE {
+                return Err(ParserError::HeaderTooShort {
+                    layer: "UDP datagram".to_string(),
+                    got: transport.len(),
+                    need: UDP_HEADER_SIZE,
+                });
+            }
+            UDP_HEADER_SIZE
+        }
+        _ => return Ok(view),
+    };
+
+    view.transport_offset = Some(transport_offset);
+    view.payload_offset = Some(transport_offset + header_length);
+    Ok(view)
+}
+
+impl<'a> PacketView<'a> {
+    /// Returns the EtherType, or the 802.3 length, following any 802.1Q tag.
+    pub fn ether_type(&self) -> u16 {
+        be_u16(self.bytes, self.network_offset - 2)
+    }
+
+    /// Returns the 802.1Q VLAN identifier, if the frame is tagged.
+    pub fn vlan_id(&self) -> Option<u16> {
+        (self.network_offset > ETHERNET_HEADER_SIZE).then(|| be_u16(self.bytes, 14) & 0x0FFF)
+    }
+
+    /// Returns `true` if the frame uses 802.3 framing, where the type field is a length.
+    pub fn is_8023(&self) -> bool {
+        self.ether_type() <= MAX_8023_LENGTH
+    }
+
+    /// Returns the offset of the network-layer header, right after the Ethernet header.
+    pub fn network_offset(&self) -> usize {
+        self.network_offset
+    }
+
+    /// Returns the offset of the TCP or UDP header, if one was located.
+    pub fn transport_offset(&self) -> Option<usize> {
+        self.transport_offset
+    }
+
+    /// Returns the offset of the TCP or UDP payload, if a transport header was located.
+    pub fn payload_offset(&self) -> Option<usize> {
+        self.payload_offset
+    }
+
+    /// Returns the IPv4 protocol or IPv6 next header number, for IP packets.
+    pub fn ip_protocol(&self) -> Option<u8> {
+        match self.ether_type() {
+            ETHER_TYPE_IPV4 => Some(self.bytes[self.network_offset + 9]),
+            ETHER_TYPE_IPV6 => Some(self.bytes[self.network_offset + 6]),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if a TCP header was located.
+    pub fn is_tcp(&self) -> bool {
+        self.transport_offset.is_some() && self.ip_protocol() == Some(PROTOCOL_TCP)
+    }
+
+    /// Returns `true` if a UDP header was located.
+    pub fn is_udp(&self) -> bool {
+        self.transport_offset.is_some() && self.ip_protocol() == Some(PROTOCOL_UDP)
+    }
+
+    /// Returns the TCP or UDP source port.
+    pub fn source_port(&self) -> Option<u16> {
+        self.transport_offset
+            .map(|offset| be_u16(self.bytes, offset))
+    }
+
+    /// Returns the TCP or UDP destination port.
+    pub fn destination_port(&self) -> Option<u16> {
+        self.transport_offset
+            .map(|offset| be_u16(self.bytes, offset + 2))
+    }
+
+    /// Returns the TCP flags, for TCP segments.
+    pub fn tcp_flags(&self) -> Option<Flags> {
+        match self.is_tcp() {
+            true => self
+                .transport_offset
+                .map(|offset| Flags::new(self.bytes[offset + 13])),
+            false => None,
+        }
+    }
+
+    /// Returns the TCP or UDP payload, which includes any FCS and Ethernet padding.
+    pub fn payload(&self) -> Option<&'a [u8]> {
+        self.payload_offset.map(|offset| &self.bytes[offset..])
+    }
+}
@@ -0,0 +1,158 @@
+// VXLAN Header:
+//  0                   1                   2                   3
+//  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |R|R|R|R|I|R|R|R|                 Reserved                      |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |            VXLAN Network Identifier (VNI)     |   Reserved    |
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+// |                   Inner Ethernet frame ...
+// +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
+use super::{
+    definitions::{DeepParser, LayerKind, LayeredData, ParseOptions},
+    errors::ParserError,
+    ethernet_frame::EthernetFrame,
+    trace,
+    utils::{ByteReader, PayloadSource},
+};
+
+use alloc::{boxed::Box, string::ToString, vec::Vec};
+use core::ops::Range;
+
+const HEADER_SIZE: usize = 8;
+
+/// The I flag, set when the VNI field carries a valid network identifier.
+pub const VNI_VALID_FLAG: u8 = 0x08;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VxlanHeader {
+    /// The 8-bit flags field. Only the I flag (`VNI_VALID_FLAG`) is defined.
+    pub flags: u8,
+
+    /// The 24-bit VXLAN Network Identifier of the overlay segment.
+    pub vni: u32,
+
+    /// The position of the header within the bytes the packet was parsed from.
+    pub span: Range<usize>,
+
+    /// The 8 header bytes as received, including the reserved fields not decoded above,
+    /// which some deployments use for extensions such as VXLAN-GPE.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub raw_header: Vec<u8>,
+}
+
+impl VxlanHeader {
+    /// Returns `true` if the I flag is set, meaning `vni` is valid.
+    pub fn has_valid_vni(&self) -> bool {
+        self.flags & VNI_VALID_FLAG != 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VxlanPacket<'a> {
+    pub header: VxlanHeader,
+    pub data: Box<LayeredData<'a>>,
+}
+
+impl<'a> VxlanPacket<'a> {
+    /// Constructs a `VxlanPacket` from a slice of bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `packets` - A byte slice beginning with the VXLAN header, typically the payload of
+    ///   a UDP datagram sent to port 4789.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, ParserError>` - A `VxlanPacket` instance or a `ParserError`.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::HeaderTooShort` - If the slice is shorter than the VXLAN header.
+    pub fn from_bytes(packets: &'a [u8]) -> Result<Self, ParserError> {
+        Self::parse(&packets)
+    }
+
+    /// Constructs a `VxlanPacket` whose inner frame is taken from `source`.
+    pub(crate) fn parse<S: PayloadSource<'a> + ?Sized>(source: &S) -> Result<Self, ParserError> {
+        let packets = source.as_slice();
+        if packets.len() < HEADER_SIZE {
+            return Err(ParserError::HeaderTooShort {
+                layer: "VXLAN packet".to_string(),
+                got: packets.len(),
+                need: HEADER_SIZE,
+            });
+        }
+
+        let mut cursor = ByteReader::new(packets);
+
+        let flags = cursor.read_u8("VXLAN_Flags")?;
+        cursor.read_slice(3, "VXLAN_Reserved")?;
+        let vni = cursor.read_u24_be("VXLAN_VNI")?;
+        cursor.read_u8("VXLAN_Reserved")?;
+
+        Ok(VxlanPacket {
+            header: VxlanHeader {
+                flags,
+                vni,
+                span: 0..HEADER_SIZE,
+                raw_header: packets[..HEADER_SIZE].to_vec(),
+            },
+            data: Box::new(source.payload(HEADER_SIZE..packets.len())),
+        })
+    }
+
+    /// Serializes the header and inner frame back into their wire format, with the
+    /// reserved fields zeroed.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE);
+        bytes.extend_from_slice(&[self.header.flags, 0, 0, 0]);
+        bytes.extend_from_slice(&(self.header.vni << 8).to_be_bytes());
+        bytes.extend_from_slice(&self.data.to_bytes());
+        bytes
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::Vxlan
+    }
+
+    /// Detaches the packet from the bytes it was parsed from, copying its payload if it is
+    /// borrowed.
+    pub fn into_owned(self) -> VxlanPacket<'static> {
+        VxlanPacket {
+            header: self.header,
+            data: Box::new(self.data.into_owned()),
+        }
+    }
+}
+
+impl<'a> DeepParser<'a> for VxlanPacket<'a> {
+    /// Parses the encapsulated Ethernet frame, and everything nested within it.
+    ///
+    /// The inner frame never carries a Frame Check Sequence.
+    fn parse_next_layer_with(
+        mut self,
+        options: &ParseOptions,
+        depth: usize,
+    ) -> Result<LayeredData<'a>, ParserError> {
+        let depth = options.descend(depth)?;
+        let _span = trace::layer_span(LayerKind::Vxlan, depth);
+
+        self.data.payload().ok_or(ParserError::InvalidPayload)?;
+
+        let result = options
+            .timed(LayerKind::Ethernet, || {
+                EthernetFrame::parse(&*self.data, false)
+            })
+            .and_then(|frame| frame.parse_next_layer_with(options, depth));
+
+        options.recover(result, &mut self.data)?;
+        Ok(LayeredData::VxlanData(self))
+    }
+}
@@ -0,0 +1,290 @@
+// WebSocket Frame (RFC 6455, section 5.2):
+//  0                   1                   2                   3
+//  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+// +-+-+-+-+-------+-+-------------+-------------------------------+
+// |F|R|R|R| opcode|M| Payload len |    Extended payload length    |
+// |I|S|S|S|  (4)  |A|     (7)     |             (16/64)           |
+// |N|V|V|V|       |S|             |   (if payload len==126/127)   |
+// | |1|2|3|       |K|             |                               |
+// +-+-+-+-+-------+-+-------------+ - - - - - - - - - - - - - - - +
+// |     Extended payload length continued, if payload len == 127  |
+// + - - - - - - - - - - - - - - - +-------------------------------+
+// |                               |Masking-key, if MASK set to 1  |
+// +-------------------------------+-------------------------------+
+// | Masking-key (continued)       |          Payload Data         |
+// +-------------------------------- - - - - - - - - - - - - - - - +
+//
+// Frames are only sent after an HTTP Upgrade handshake, which a single segment carries no
+// trace of. TCP payloads to or from the HTTP ports in `WEBSOCKET_PORTS` are therefore
+// recognised by their shape alone; see `is_websocket`.
+
+#[cfg(feature = "serde")]
+use super::utils::serialize_hex;
+use super::{definitions::LayerKind, errors::ParserError, utils::ByteReader};
+
+use alloc::{string::ToString, vec::Vec};
+use core::ops::Range;
+
+const MIN_HEADER_SIZE: usize = 2;
+const MASKING_KEY_SIZE: usize = 4;
+const LENGTH_16_BIT: u8 = 126;
+const LENGTH_64_BIT: u8 = 127;
+const MAX_CONTROL_PAYLOAD: u64 = 125;
+
+/// The type of a WebSocket frame, given by its opcode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum WebSocketOpcode {
+    Continuation, // A fragment continuing the previous data frame
+    Text,         // UTF-8 text data
+    Binary,       // Binary data
+    Close,        // Starts the closing handshake
+    Ping,         // Keepalive request
+    Pong,         // Keepalive response
+    Other(u8),    // Reserved opcodes
+}
+
+impl From<u8> for WebSocketOpcode {
+    fn from(raw: u8) -> Self {
+        match raw {
+            0x0 => Self::Continuation,
+            0x1 => Self::Text,
+            0x2 => Self::Binary,
+            0x8 => Self::Close,
+            0x9 => Self::Ping,
+            0xA => Self::Pong,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl WebSocketOpcode {
+    /// Returns `true` for Close, Ping and Pong, which may not be fragmented.
+    pub fn is_control(&self) -> bool {
+        matches!(self, Self::Close | Self::Ping | Self::Pong)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct WebSocketFrame {
+    /// Set on the final fragment of a message.
+    pub fin: bool,
+
+    /// The three RSV bits, as the low bits of the value. Zero unless an extension was
+    /// negotiated.
+    pub rsv: u8,
+
+    /// The raw 4-bit opcode. See `kind` for its meaning.
+    pub opcode: u8,
+
+    /// The key the payload was masked with. Present on every frame sent by a client.
+    pub masking_key: Option<[u8; 4]>,
+
+    /// The payload, already unmasked.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub payload: Vec<u8>,
+
+    /// The position of the header, including any extended length and masking key, within
+    /// the bytes the frame was parsed from.
+    pub span: Range<usize>,
+
+    /// The header as received, e.g. to tell whether the sender used the shortest length
+    /// encoding, which `to_bytes` always does.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
+    pub raw_header: Vec<u8>,
+}
+
+impl WebSocketFrame {
+    /// Constructs a `WebSocketFrame` from a slice of bytes beginning with a frame. Bytes
+    /// after the frame's payload are ignored.
+    ///
+    /// # Errors
+    ///
+    /// * `ParserError::HeaderTooShort` - If the slice ends within the header.
+    /// * `ParserError::InconsistentLength` - If the declared payload length runs past the
+    ///   end of the slice.
+    pub fn from_bytes(frame: &[u8]) -> Result<Self, ParserError> {
+        let header = FrameHeader::parse(frame)?;
+        let available = frame.len() - header.length;
+        if header.payload_len > available as u64 {
+            return Err(ParserError::InconsistentLength {
+                layer: "WebSocket frame".to_string(),
+                declared: header.payload_len.min(usize::MAX as u64) as usize,
+                available,
+            });
+        }
+
+        let end = header.length + header.payload_len as usize;
+        let mut payload = frame[header.length..end].to_vec();
+        if let Some(masking_key) = header.masking_key {
+            apply_mask(&mut payload, masking_key);
+        }
+
+        Ok(WebSocketFrame {
+            fin: header.byte0 & 0x80 != 0,
+            rsv: (header.byte0 >> 4) & 0x07,
+            opcode: header.byte0 & 0x0F,
+            masking_key: header.masking_key,
+            payload,
+            span: 0..header.length,
+            raw_header: frame[..header.length].to_vec(),
+        })
+    }
+
+    /// Splits a TCP payload into the WebSocket frames it carries, which must all be
+    /// complete.
+    ///
+    /// # Errors
+    ///
+    /// * Any error returned by `from_bytes` for one of the frames.
+    pub fn parse_all(payload: &[u8]) -> Result<Vec<Self>, ParserError> {
+        let mut frames = Vec::new();
+        let mut offset = 0;
+
+        while offset < payload.len() {
+            let frame = Self::from_bytes(&payload[offset..])?;
+            offset += frame.span.end + frame.payload.len();
+            frames.push(frame);
+        }
+
+        Ok(frames)
+    }
+
+    /// Returns the type of the frame.
+    pub fn kind(&self) -> WebSocketOpcode {
+        WebSocketOpcode::from(self.opcode)
+    }
+
+    /// Returns `true` if the payload was masked, i.e. the frame was sent by a client.
+    pub fn is_masked(&self) -> bool {
+        self.masking_key.is_some()
+    }
+
+    /// Returns the payload of a Text frame as a string, or `None` for other frames and
+    /// for text that is not valid UTF-8.
+    pub fn text(&self) -> Option<&str> {
+        match self.kind() {
+            WebSocketOpcode::Text => core::str::from_utf8(&self.payload).ok(),
+            _ => None,
+        }
+    }
+
+    /// Serializes the frame back into its wire format, masking the payload again with
+    /// `masking_key` and using the shortest encoding of its length.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(self.raw_header.len() + self.payload.len());
+        frame.push((self.fin as u8) << 7 | (self.rsv & 0x07) << 4 | (self.opcode & 0x0F));
+
+        let mask_bit = (self.masking_key.is_some() as u8) << 7;
+        match self.payload.len() {
+            length if length < LENGTH_16_BIT as usize => frame.push(mask_bit | length as u8),
+            length if length <= u16::MAX as usize => {
+                frame.push(mask_bit | LENGTH_16_BIT);
+                frame.extend_from_slice(&(length as u16).to_be_bytes());
+            }
+            length => {
+                frame.push(mask_bit | LENGTH_64_BIT);
+                frame.extend_from_slice(&(length as u64).to_be_bytes());
+            }
+        }
+
+        let mut payload = self.payload.clone();
+        if let Some(masking_key) = self.masking_key {
+            frame.extend_from_slice(&masking_key);
+            apply_mask(&mut payload, masking_key);
+        }
+        frame.extend_from_slice(&payload);
+        frame
+    }
+
+    /// Returns the kind of this layer.
+    pub fn layer_kind(&self) -> LayerKind {
+        LayerKind::WebSocket
+    }
+}
+
+/// Returns `true` if `payload` consists entirely of complete WebSocket frames that use no
+/// extensions.
+///
+/// Every frame must have its RSV bits clear and a defined opcode, and control frames must
+/// be final and carry at most 125 bytes. HTTP and other text protocols fail these checks
+/// on their first byte, which keeps a handshake from being mistaken for a frame.
+pub fn is_websocket(payload: &[u8]) -> bool {
+    let mut offset = 0;
+
+    while offset < payload.len() {
+        let Ok(header) = FrameHeader::parse(&payload[offset..]) else {
+            return false;
+        };
+        let opcode = WebSocketOpcode::from(header.byte0 & 0x0F);
+        let fin = header.byte0 & 0x80 != 0;
+
+        if header.byte0 & 0x70 != 0
+            || matches!(opcode, WebSocketOpcode::Other(_))
+            || (opcode.is_control() && (!fin || header.payload_len > MAX_CONTROL_PAYLOAD))
+        {
+            return false;
+        }
+
+        let remaining = (payload.len() - offset - header.length) as u64;
+        if header.payload_len > remaining {
+            return false;
+        }
+        offset += header.length + header.payload_len as usize;
+    }
+
+    !payload.is_empty()
+}
+
+/// The fields of a frame header needed to find the payload.
+struct FrameHeader {
+    byte0: u8,
+    payload_len: u64,
+    masking_key: Option<[u8; 4]>,
+    length: usize,
+}
+
+impl FrameHeader {
+    fn parse(frame: &[u8]) -> Result<Self, ParserError> {
+        if frame.len() < MIN_HEADER_SIZE {
+            return Err(ParserError::HeaderTooShort {
+                layer: "WebSocket frame".to_string(),
+                got: frame.len(),
+                need: MIN_HEADER_SIZE,
+            });
+        }
+
+        let mut cursor = ByteReader::new(frame);
+
+        let byte0 = cursor.read_u8("WebSocket_Flags_Opcode")?;
+        let byte1 = cursor.read_u8("WebSocket_Mask_Length")?;
+        let payload_len = match byte1 & 0x7F {
+            LENGTH_16_BIT => cursor.read_u16_be("WebSocket_Extended_Length")? as u64,
+            LENGTH_64_BIT => cursor.read_u64_be("WebSocket_Extended_Length")?,
+            length => length as u64,
+        };
+        let masking_key = match byte1 & 0x80 {
+            0 => None,
+            _ => {
+                let key = cursor.read_slice(MASKING_KEY_SIZE, "WebSocket_Masking_Key")?;
+                Some([key[0], key[1], key[2], key[3]])
+            }
+        };
+
+        Ok(FrameHeader {
+            byte0,
+            payload_len,
+            masking_key,
+            length: cursor.position(),
+        })
+    }
+}
+
+/// XORs `payload` with the repeating `masking_key`. Masking and unmasking are the same
+/// operation.
+fn apply_mask(payload: &mut [u8], masking_key: [u8; 4]) {
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= masking_key[i % MASKING_KEY_SIZE];
+    }
+}
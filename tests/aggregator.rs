@@ -0,0 +1,72 @@
+mod mock_data;
+
+use mock_data::{
+    generate_ethernet_mock_packets, generate_icmpv6_packet, generate_ipv4_packet,
+    generate_padded_ethernet_frame, generate_tcp_segment, DEFAULT_DEST_ADDR, DEFAULT_DEST_MAC,
+    DEFAULT_ETHER_TYPE, DEFAULT_SRC_ADDR, DEFAULT_SRC_MAC, ICMPV6_ECHO_REQUEST, IPV4_ETHER_TYPE,
+    LINK_LOCAL_DEST_ADDRESS, LINK_LOCAL_SRC_ADDRESS, TCP,
+};
+use net_sift::parsers::{
+    aggregator::PacketSummary,
+    definitions::{DeepParser, IPType, LayeredData},
+    ethernet_frame::{EthernetFrame, MacAddress},
+    ipv6::Ipv6Packet,
+};
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+#[test]
+fn summarizes_tcp_over_ipv4() {
+    let packet = generate_ipv4_packet(TCP, &generate_tcp_segment(443, b"hello"));
+    let frame = generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &packet);
+    let layered_data = EthernetFrame::from_bytes(&frame, false)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert_eq!(
+        PacketSummary::from_layered(&layered_data),
+        Some(PacketSummary {
+            source_mac: Some(MacAddress(DEFAULT_SRC_MAC)),
+            destination_mac: Some(MacAddress(DEFAULT_DEST_MAC)),
+            source_ip: Ipv4Addr::from(DEFAULT_SRC_ADDR).into(),
+            destination_ip: Ipv4Addr::from(DEFAULT_DEST_ADDR).into(),
+            protocol: IPType::TCP,
+            source_port: Some(53145),
+            destination_port: Some(443),
+            payload_len: 5,
+        })
+    );
+}
+
+#[test]
+fn summarizes_icmp_over_ipv6() {
+    let packet = generate_icmpv6_packet(&ICMPV6_ECHO_REQUEST);
+
+    // ICMPv6 has no ports, so the whole of the message counts as payload.
+    let layered_data = Ipv6Packet::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+    let summary = PacketSummary::from_layered(&layered_data).unwrap();
+
+    assert_eq!(summary.source_mac, None);
+    assert_eq!(summary.source_ip, Ipv6Addr::from(LINK_LOCAL_SRC_ADDRESS));
+    assert_eq!(
+        summary.destination_ip,
+        Ipv6Addr::from(LINK_LOCAL_DEST_ADDRESS)
+    );
+    assert_eq!(summary.protocol, IPType::ICMPv6);
+    assert_eq!(summary.source_port, None);
+    assert_eq!(summary.destination_port, None);
+    assert_eq!(summary.payload_len, ICMPV6_ECHO_REQUEST.len());
+}
+
+#[test]
+fn needs_an_ip_layer() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+    let layered_data =
+        LayeredData::EthernetFrameData(EthernetFrame::from_bytes(&frame, true).unwrap());
+
+    assert_eq!(PacketSummary::from_layered(&layered_data), None);
+}
@@ -0,0 +1,166 @@
+mod mock_data;
+
+use mock_data::{
+    generate_arp_mock_packet, generate_padded_ethernet_frame, ARP_ETHER_TYPE,
+    DEFAULT_ARP_SENDER_IP, DEFAULT_ARP_TARGET_IP, DEFAULT_DEST_MAC, DEFAULT_SRC_MAC,
+    RARP_ETHER_TYPE,
+};
+use net_sift::parsers::{
+    arp::{ArpOperation, ArpPacket},
+    definitions::{DeepParser, EtherType, LayerKind, LayeredData},
+    errors::ParserError,
+    ethernet_frame::EthernetFrame,
+};
+
+use std::net::Ipv4Addr;
+
+#[test]
+fn can_parse_rarp_request() {
+    let packet = generate_arp_mock_packet(3);
+
+    let rarp = ArpPacket::from_bytes(&packet).unwrap();
+
+    assert_eq!(rarp.hardware_type, 1);
+    assert_eq!(rarp.protocol_type, 0x0800);
+    assert_eq!(rarp.opcode(), ArpOperation::RequestReverse);
+    assert!(rarp.is_reverse());
+    assert_eq!(rarp.sender_hardware_address, DEFAULT_SRC_MAC);
+    assert_eq!(rarp.target_hardware_address, DEFAULT_DEST_MAC);
+    assert_eq!(
+        rarp.sender_ipv4(),
+        Some(Ipv4Addr::from(DEFAULT_ARP_SENDER_IP))
+    );
+    assert_eq!(
+        rarp.target_ipv4(),
+        Some(Ipv4Addr::from(DEFAULT_ARP_TARGET_IP))
+    );
+    assert_eq!(rarp.span, 0..28);
+}
+
+#[test]
+fn distinguishes_reverse_opcodes() {
+    let opcodes = [1, 2, 3, 4, 9].map(|operation| {
+        let packet = ArpPacket::from_bytes(&generate_arp_mock_packet(operation)).unwrap();
+        (packet.opcode(), packet.is_reverse())
+    });
+
+    assert_eq!(
+        opcodes,
+        [
+            (ArpOperation::Request, false),
+            (ArpOperation::Reply, false),
+            (ArpOperation::RequestReverse, true),
+            (ArpOperation::ReplyReverse, true),
+            (ArpOperation::Other(9), false),
+        ]
+    );
+}
+
+#[test]
+fn fails_if_addresses_are_cut_off() {
+    let packet = generate_arp_mock_packet(3);
+
+    for len in [4, 27] {
+        assert!(matches!(
+            ArpPacket::from_bytes(&packet[..len]),
+            Err(ParserError::InvalidLength(_))
+        ));
+    }
+}
+
+#[test]
+fn deep_parses_rarp_frame() {
+    let frame = generate_padded_ethernet_frame(RARP_ETHER_TYPE, &generate_arp_mock_packet(4));
+
+    let ethernet_frame = EthernetFrame::from_bytes(&frame, false).unwrap();
+    assert_eq!(ethernet_frame.header.ether_type, EtherType::RARP);
+
+    let layered_data = ethernet_frame.parse_next_layer().unwrap();
+    let rarp = match layered_data.inner() {
+        Some(LayeredData::RarpData(packet)) => packet,
+        other => panic!("expected RARP data, got {:?}", other),
+    };
+
+    assert_eq!(rarp.opcode(), ArpOperation::ReplyReverse);
+    assert!(layered_data.contains(LayerKind::Rarp));
+    assert_eq!(layered_data.spans(), vec![0..14, 14..42]);
+}
+
+#[test]
+fn deep_parses_arp_frame() {
+    let frame = generate_padded_ethernet_frame(ARP_ETHER_TYPE, &generate_arp_mock_packet(1));
+
+    let ethernet_frame = EthernetFrame::from_bytes(&frame, false).unwrap();
+    assert_eq!(ethernet_frame.header.ether_type, EtherType::ARP);
+
+    // ARP used to fall through to the raw payload, rather than reaching the ARP parser.
+    let layered_data = ethernet_frame.parse_next_layer().unwrap();
+    assert!(matches!(layered_data, LayeredData::EthernetFrameData(_)));
+    let arp = match layered_data.inner() {
+        Some(LayeredData::ArpData(packet)) => packet,
+        other => panic!("expected ARP data, got {:?}", other),
+    };
+
+    assert_eq!(arp.opcode(), ArpOperation::Request);
+    assert!(layered_data.contains(LayerKind::Arp));
+    assert!(!layered_data.contains(LayerKind::Rarp));
+    assert_eq!(
+        layered_data.summary(),
+        "ARP who-has 192.168.1.20 tell 192.168.1.1"
+    );
+}
+
+#[test]
+fn parses_arp_frames_shorter_than_the_minimum_frame_size() {
+    // A request padded to 60 bytes as captured once the NIC strips the FCS, and the same
+    // request captured on the sending host before it is padded at all.
+    let frame = generate_padded_ethernet_frame(ARP_ETHER_TYPE, &generate_arp_mock_packet(1));
+
+    for length in [60, 42] {
+        let ethernet_frame = EthernetFrame::from_bytes(&frame[..length], false).unwrap();
+        assert_eq!(
+            ethernet_frame.data.payload().map(<[u8]>::len),
+            Some(length - 14)
+        );
+
+        let layered_data = ethernet_frame.parse_next_layer().unwrap();
+        let arp = match layered_data.inner() {
+            Some(LayeredData::ArpData(packet)) => packet,
+            other => panic!("expected ARP data, got {:?}", other),
+        };
+        assert_eq!(arp.opcode(), ArpOperation::Request);
+        assert_eq!(
+            arp.target_ipv4(),
+            Some(Ipv4Addr::from(DEFAULT_ARP_TARGET_IP))
+        );
+    }
+
+    // The header alone is too short to carry an ARP packet.
+    let ethernet_frame = EthernetFrame::from_bytes(&frame[..14], false).unwrap();
+    assert!(matches!(
+        ethernet_frame.parse_next_layer(),
+        Err(ParserError::InvalidLength(_))
+    ));
+}
+
+#[test]
+fn serializes_arp_and_rarp_packets() {
+    for (ether_type, operation) in [(ARP_ETHER_TYPE, 1), (RARP_ETHER_TYPE, 3)] {
+        let packet = generate_arp_mock_packet(operation);
+        let frame = [
+            &DEFAULT_DEST_MAC[..],
+            &DEFAULT_SRC_MAC,
+            &ether_type,
+            &packet,
+        ]
+        .concat();
+
+        let layered_data = EthernetFrame::from_bytes(&frame, false)
+            .unwrap()
+            .parse_next_layer()
+            .unwrap();
+
+        assert_eq!(layered_data.inner().unwrap().to_bytes(), packet);
+        assert_eq!(layered_data.to_bytes(), frame);
+    }
+}
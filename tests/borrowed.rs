@@ -0,0 +1,283 @@
+mod mock_data;
+
+use mock_data::*;
+use net_sift::parsers::{
+    definitions::{DeepParser, IPType, LayerKind, LayeredData, LayeredDataRef},
+    errors::ParserError,
+    ethernet_frame::EthernetFrame,
+    ipv4::Ipv4Packet,
+    ipv6::Ipv6Packet,
+    tcp::TcpSegment,
+    udp::UdpDatagram,
+};
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    borrow::Cow,
+    cell::Cell,
+};
+
+// Counts the allocations made on each thread, so tests running in parallel don't see
+// each other's.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations_during<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+// Compares the outcome of the owned and borrowed parsers, down to the error message.
+fn assert_same_outcome<T, U>(owned: Result<T, ParserError>, borrowed: Result<U, ParserError>) {
+    match (owned, borrowed) {
+        (Ok(_), Ok(_)) => {}
+        (Err(owned), Err(borrowed)) => assert_eq!(owned.to_string(), borrowed.to_string()),
+        (owned, borrowed) => panic!(
+            "owned is_ok: {}, borrowed is_ok: {}",
+            owned.is_ok(),
+            borrowed.is_ok()
+        ),
+    }
+}
+
+fn ipv4_tcp_frame() -> Vec<u8> {
+    let segment = generate_tcp_packets_with_options();
+    generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &generate_ipv4_packet(TCP, &segment))
+}
+
+#[test]
+fn walks_the_layers_of_a_frame_without_allocating() {
+    let frame = ipv4_tcp_frame();
+
+    let (kinds, allocations) = allocations_during(|| {
+        let mut kinds = [None; 4];
+        let layers = EthernetFrame::from_bytes_ref(&frame, false).unwrap();
+        for (kind, layer) in kinds
+            .iter_mut()
+            .zip(LayeredDataRef::EthernetFrameData(layers).layers())
+        {
+            *kind = Some(layer.unwrap().layer_kind());
+        }
+        kinds
+    });
+
+    assert_eq!(allocations, 0);
+    assert_eq!(
+        kinds,
+        [
+            Some(Some(LayerKind::Ethernet)),
+            Some(Some(LayerKind::Ipv4)),
+            Some(Some(LayerKind::Tcp)),
+            Some(None),
+        ]
+    );
+}
+
+#[test]
+fn borrowed_layers_point_into_the_input() {
+    let frame = ipv4_tcp_frame();
+    let ipv4_offset = 14;
+    let tcp_offset = ipv4_offset + MIN_IPV4_LENGTH;
+    let payload_offset = tcp_offset + 24;
+
+    let ethernet = EthernetFrame::from_bytes_ref(&frame, false).unwrap();
+    let layers: Vec<_> = LayeredDataRef::EthernetFrameData(ethernet)
+        .layers()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    let (packet, segment, payload) = match &layers[1..] {
+        [LayeredDataRef::Ipv4Data(packet), LayeredDataRef::TcpData(segment), LayeredDataRef::Payload(payload)] => {
+            (packet, segment, payload)
+        }
+        other => panic!("unexpected layers {:?}", other),
+    };
+
+    assert_eq!(ethernet.data().as_ptr(), frame[ipv4_offset..].as_ptr());
+    assert_eq!(packet.header().as_ptr(), frame[ipv4_offset..].as_ptr());
+    assert_eq!(segment.header().as_ptr(), frame[tcp_offset..].as_ptr());
+    assert_eq!(payload.as_ptr(), frame[payload_offset..].as_ptr());
+    assert_eq!(*payload, &DEFAULT_DATA[..]);
+}
+
+#[test]
+fn borrowed_layers_match_the_owned_ones() {
+    let frame = generate_ethernet_mock_packets(Some(DEFAULT_Q_TAG), DEFAULT_ETHER_TYPE);
+    let ethernet = EthernetFrame::from_bytes_ref(&frame, true).unwrap();
+    assert_eq!(
+        EthernetFrame::from(ethernet),
+        EthernetFrame::from_bytes(&frame, true).unwrap()
+    );
+    assert_eq!(ethernet.vlan_tag().unwrap().vid, 534);
+
+    let packet = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, Some(&DEFAULT_IPV4_OPTIONS));
+    assert_eq!(
+        Ipv4Packet::from(Ipv4Packet::from_bytes_ref(&packet).unwrap()),
+        Ipv4Packet::from_bytes(&packet).unwrap()
+    );
+
+    let packet = generate_ipv6_jumbogram(&DEFAULT_PAYLOAD);
+    let borrowed = Ipv6Packet::from_bytes_ref(&packet).unwrap();
+    assert_eq!(borrowed.header().len(), MIN_IPV6_LENGTH + 8);
+    assert_eq!(
+        borrowed.upper_layer_protocol(),
+        IPType::from(NO_NEXT_HEADER)
+    );
+    assert_eq!(
+        Ipv6Packet::from(borrowed),
+        Ipv6Packet::from_bytes(&packet).unwrap()
+    );
+
+    let segment = generate_tcp_segment_with_options(&TCP_SYN_OPTIONS);
+    assert_eq!(
+        TcpSegment::from(TcpSegment::from_bytes_ref(&segment).unwrap()),
+        TcpSegment::from_bytes(&segment).unwrap()
+    );
+
+    assert_eq!(
+        UdpDatagram::from(UdpDatagram::from_bytes_ref(&UDP_PACKETS).unwrap()),
+        UdpDatagram::from_bytes(&UDP_PACKETS).unwrap()
+    );
+}
+
+#[test]
+fn borrowed_layers_deep_parse_like_the_owned_ones() {
+    for frame in [
+        ipv4_tcp_frame(),
+        generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE),
+    ] {
+        let borrowed = EthernetFrame::from_bytes_ref(&frame, false).unwrap();
+        let owned = EthernetFrame::from_bytes(&frame, false).unwrap();
+
+        assert_eq!(
+            borrowed.parse_next_layer().unwrap(),
+            owned.parse_next_layer().unwrap()
+        );
+    }
+}
+
+#[test]
+fn borrowed_and_owned_parsers_fail_alike() {
+    let frame = ipv4_tcp_frame();
+    let ipv4 = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, Some(&DEFAULT_IPV4_OPTIONS));
+    let ipv6 = generate_ipv6_fragment(0, false, 1, &generate_tcp_packets_with_options());
+    let tcp = generate_tcp_segment_with_options(&TCP_SYN_OPTIONS);
+
+    for len in 0..frame.len() {
+        assert_same_outcome(
+            EthernetFrame::from_bytes(&frame[..len], false),
+            EthernetFrame::from_bytes_ref(&frame[..len], false),
+        );
+    }
+    for len in 0..ipv4.len() {
+        assert_same_outcome(
+            Ipv4Packet::from_bytes(&ipv4[..len]),
+            Ipv4Packet::from_bytes_ref(&ipv4[..len]),
+        );
+    }
+    for len in 0..ipv6.len() {
+        assert_same_outcome(
+            Ipv6Packet::from_bytes(&ipv6[..len]),
+            Ipv6Packet::from_bytes_ref(&ipv6[..len]),
+        );
+    }
+    for len in 0..tcp.len() {
+        assert_same_outcome(
+            TcpSegment::from_bytes(&tcp[..len]),
+            TcpSegment::from_bytes_ref(&tcp[..len]),
+        );
+    }
+    for len in 0..UDP_PACKETS.len() {
+        assert_same_outcome(
+            UdpDatagram::from_bytes(&UDP_PACKETS[..len]),
+            UdpDatagram::from_bytes_ref(&UDP_PACKETS[..len]),
+        );
+    }
+}
+
+#[test]
+fn unknown_ip_protocol_ends_the_layers_with_an_error() {
+    let packet = generate_ipv4_packet(253, &DEFAULT_PAYLOAD);
+
+    let layers: Vec<_> = LayeredDataRef::Ipv4Data(Ipv4Packet::from_bytes_ref(&packet).unwrap())
+        .layers()
+        .collect();
+
+    assert_eq!(layers.len(), 2);
+    assert!(matches!(layers[1], Err(ParserError::UnknownIPType(253))));
+    assert!(matches!(
+        Ipv4Packet::from_bytes(&packet).unwrap().parse_next_layer(),
+        Err(ParserError::UnknownIPType(253))
+    ));
+}
+
+#[test]
+fn converts_a_borrowed_payload_to_an_owned_one() {
+    let layer = LayeredDataRef::Payload(&DEFAULT_PAYLOAD);
+
+    assert!(layer.next_layer().is_none());
+    assert_eq!(
+        LayeredData::from(layer),
+        LayeredData::Payload(DEFAULT_PAYLOAD.to_vec().into())
+    );
+}
+
+#[test]
+fn deep_parsed_payloads_point_into_the_input() {
+    let frame = ipv4_tcp_frame();
+    let payload_offset = 14 + MIN_IPV4_LENGTH + 24;
+
+    let ethernet = EthernetFrame::from_bytes(&frame, false).unwrap();
+    assert!(matches!(
+        *ethernet.data,
+        LayeredData::Payload(Cow::Borrowed(_))
+    ));
+
+    let layered_data = ethernet.parse_next_layer().unwrap();
+    let payload = layered_data.layers().last().unwrap();
+    assert!(matches!(payload, LayeredData::Payload(Cow::Borrowed(_))));
+    assert_eq!(
+        payload.payload().unwrap().as_ptr(),
+        frame[payload_offset..].as_ptr()
+    );
+
+    let owned = layered_data.clone().into_owned();
+    let payload = owned.layers().last().unwrap();
+    assert!(matches!(payload, LayeredData::Payload(Cow::Owned(_))));
+    assert_eq!(owned, layered_data);
+}
+
+#[test]
+fn deep_parse_allocates_less_than_copying_the_payloads() {
+    let frame = ipv4_tcp_frame();
+    let parse = || {
+        EthernetFrame::from_bytes(&frame, false)
+            .unwrap()
+            .parse_next_layer()
+            .unwrap()
+    };
+
+    let (borrowed, borrowed_allocations) = allocations_during(parse);
+    let (owned, owned_allocations) = allocations_during(|| parse().into_owned());
+
+    assert_eq!(borrowed, owned);
+    assert!(borrowed_allocations < owned_allocations);
+}
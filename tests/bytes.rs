@@ -0,0 +1,112 @@
+#![cfg(feature = "bytes")]
+
+mod mock_data;
+
+use bytes::Bytes;
+use mock_data::*;
+use net_sift::parsers::{
+    definitions::{DeepParser, LayeredData},
+    ethernet_frame::EthernetFrame,
+    ipv4::Ipv4Packet,
+    ipv6::Ipv6Packet,
+    tcp::TcpSegment,
+    udp::UdpDatagram,
+};
+
+// Asserts that `data` is a shared slice starting `offset` bytes into `buf`.
+fn assert_points_into(buf: &Bytes, data: &LayeredData, offset: usize) {
+    let payload = match data {
+        LayeredData::Bytes(payload) => payload,
+        other => panic!("expected a shared payload, got {:?}", other),
+    };
+    assert_eq!(payload.as_ptr(), buf[offset..].as_ptr());
+}
+
+#[test]
+fn ethernet_payload_shares_the_original_buffer() {
+    let buf = Bytes::from(generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE));
+    let frame = EthernetFrame::from_bytes_buf(&buf, true).unwrap();
+
+    assert_points_into(&buf, &frame.data, 14);
+    assert_eq!(frame.data.payload().unwrap(), &buf[14..buf.len() - 4]);
+}
+
+#[test]
+fn ipv4_payload_shares_the_original_buffer() {
+    let buf = Bytes::from(generate_ipv4_mock_packets(
+        DEFAULT_TCP_PROTOCOL,
+        Some(&DEFAULT_IPV4_OPTIONS),
+    ));
+    let packet = Ipv4Packet::from_bytes_buf(&buf).unwrap();
+
+    assert_points_into(&buf, &packet.data, 24);
+    assert_eq!(
+        packet.data.payload(),
+        Ipv4Packet::from_bytes(&buf).unwrap().data.payload()
+    );
+}
+
+#[test]
+fn ipv6_payload_shares_the_original_buffer() {
+    let buf = Bytes::from(generate_ipv6_mock_packet());
+    let packet = Ipv6Packet::from_bytes_buf(&buf).unwrap();
+
+    assert_points_into(&buf, &packet.data, MIN_IPV6_LENGTH);
+}
+
+#[test]
+fn transport_payloads_share_the_original_buffer() {
+    let buf = Bytes::from(generate_tcp_packets_with_options());
+    let segment = TcpSegment::from_bytes_buf(&buf).unwrap();
+    assert_points_into(&buf, &segment.data, 24);
+    assert_eq!(segment.payload_len(), DEFAULT_DATA.len());
+
+    let buf = Bytes::from_static(&UDP_PACKETS);
+    let datagram = UdpDatagram::from_bytes_buf(&buf).unwrap();
+    assert_points_into(&buf, &datagram.data, 8);
+}
+
+#[test]
+fn shared_payload_parses_like_an_owned_one() {
+    let raw = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+    let buf = Bytes::from(raw.clone());
+
+    let shared = EthernetFrame::from_bytes_buf(&buf, true).unwrap();
+    let owned = EthernetFrame::from_bytes(&raw, true).unwrap();
+    assert_eq!(shared.header, owned.header);
+    assert_eq!(shared.data.to_bytes(), owned.data.to_bytes());
+
+    assert_eq!(
+        shared.parse_next_layer().unwrap(),
+        owned.parse_next_layer().unwrap()
+    );
+}
+
+#[test]
+fn deep_parse_shares_the_buffer_down_to_the_transport_payload() {
+    let segment = generate_tcp_segment(9, b"discard me");
+    let packet = generate_ipv4_packet(DEFAULT_TCP_PROTOCOL[0], &segment);
+    let buf = Bytes::from(generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &packet));
+
+    let layered_data = EthernetFrame::from_bytes_buf(&buf, false)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    let Some(LayeredData::TcpData(segment)) = layered_data.layers().nth(2) else {
+        panic!("expected a TCP segment");
+    };
+    assert_points_into(&buf, &segment.data, 14 + MIN_IPV4_LENGTH + 20);
+    assert_eq!(segment.data.payload().unwrap(), b"discard me");
+
+    let buf = Bytes::from(generate_ipv4_packet(UDP, &UDP_PACKETS));
+    let layered_data = Ipv4Packet::from_bytes_buf(&buf)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    let Some(LayeredData::UdpData(datagram)) = layered_data.inner() else {
+        panic!("expected a UDP datagram");
+    };
+    assert_points_into(&buf, &datagram.data, MIN_IPV4_LENGTH + 8);
+}
@@ -0,0 +1,104 @@
+mod mock_data;
+
+use mock_data::{generate_ipv4_tcp_with_valid_checksum, DEFAULT_DEST_ADDR, DEFAULT_SRC_ADDR, TCP};
+use net_sift::parsers::checksum::{
+    fold_sum, ones_complement_sum, pseudo_header_sum_v4, pseudo_header_sum_v6,
+};
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+// The sample data of RFC 1071, section 3.
+const RFC_1071_DATA: [u8; 8] = [0x00, 0x01, 0xF2, 0x03, 0xF4, 0xF5, 0xF6, 0xF7];
+
+#[test]
+fn sums_the_rfc_1071_example() {
+    assert_eq!(ones_complement_sum(&RFC_1071_DATA), 0xDDF2);
+    assert_eq!(!ones_complement_sum(&RFC_1071_DATA), 0x220D);
+}
+
+#[test]
+fn sum_is_independent_of_byte_order() {
+    // Swapping the bytes of every word swaps the bytes of the sum (RFC 1071, section 2).
+    let swapped: Vec<u8> = RFC_1071_DATA
+        .chunks(2)
+        .flat_map(|word| [word[1], word[0]])
+        .collect();
+
+    assert_eq!(ones_complement_sum(&swapped), 0xF2DD);
+}
+
+#[test]
+fn pads_an_odd_tail_with_zero() {
+    assert_eq!(ones_complement_sum(&RFC_1071_DATA[..3]), 0xF201);
+    assert_eq!(ones_complement_sum(&[0xAB]), 0xAB00);
+    assert_eq!(ones_complement_sum(&[]), 0);
+}
+
+#[test]
+fn folds_carries_back_into_the_sum() {
+    assert_eq!(ones_complement_sum(&[0xFF, 0xFF, 0x00, 0x01]), 0x0001);
+    assert_eq!(ones_complement_sum(&[0xFF; 65536]), 0xFFFF);
+    assert_eq!(fold_sum(0x0002_FFFF), 0x0002);
+}
+
+#[test]
+fn partial_sums_of_even_length_slices_combine() {
+    let (head, tail) = RFC_1071_DATA.split_at(4);
+    let combined = ones_complement_sum(head) as u32 + ones_complement_sum(tail) as u32;
+
+    assert_eq!(fold_sum(combined), ones_complement_sum(&RFC_1071_DATA));
+}
+
+#[test]
+fn pseudo_header_sum_v4_matches_the_summed_header() {
+    let header = [
+        &DEFAULT_SRC_ADDR[..],
+        &DEFAULT_DEST_ADDR,
+        &[0, TCP],
+        &[0x01, 0x2C],
+    ]
+    .concat();
+    let sum = pseudo_header_sum_v4(
+        Ipv4Addr::from(DEFAULT_SRC_ADDR),
+        Ipv4Addr::from(DEFAULT_DEST_ADDR),
+        TCP,
+        300,
+    );
+
+    assert_eq!(fold_sum(sum), ones_complement_sum(&header));
+}
+
+#[test]
+fn pseudo_header_sum_v6_matches_the_summed_header() {
+    let source = Ipv6Addr::new(0xFE80, 0, 0, 0, 0x0202, 0xB3FF, 0xFE1E, 0x8329);
+    let destination = Ipv6Addr::new(0x2001, 0x0DB8, 0, 0, 0, 0, 0, 0xFFFF);
+    let length = 0x0001_0010;
+
+    let header = [
+        &source.octets()[..],
+        &destination.octets(),
+        &u32::to_be_bytes(length),
+        &[0, 0, 0, TCP],
+    ]
+    .concat();
+    let sum = pseudo_header_sum_v6(source, destination, TCP, length);
+
+    assert_eq!(fold_sum(sum), ones_complement_sum(&header));
+}
+
+#[test]
+fn a_valid_segment_sums_to_all_ones_with_its_pseudo_header() {
+    let packet = generate_ipv4_tcp_with_valid_checksum();
+    let segment = &packet[20..];
+    let pseudo_header = pseudo_header_sum_v4(
+        Ipv4Addr::from(DEFAULT_SRC_ADDR),
+        Ipv4Addr::from(DEFAULT_DEST_ADDR),
+        TCP,
+        segment.len() as u16,
+    );
+
+    assert_eq!(
+        fold_sum(pseudo_header + ones_complement_sum(segment) as u32),
+        0xFFFF
+    );
+}
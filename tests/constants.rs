@@ -0,0 +1,26 @@
+use net_sift::parsers::constants::{service_name, WELL_KNOWN_PORTS};
+
+#[test]
+fn looks_up_well_known_services() {
+    assert_eq!(service_name(53), Some("DNS"));
+    assert_eq!(service_name(67), Some("DHCP"));
+    assert_eq!(service_name(68), Some("DHCP"));
+    assert_eq!(service_name(443), Some("HTTPS"));
+    assert_eq!(service_name(4789), Some("VXLAN"));
+    assert_eq!(service_name(5060), Some("SIP"));
+}
+
+#[test]
+fn unknown_ports_have_no_service() {
+    assert_eq!(service_name(0), None);
+    assert_eq!(service_name(8080), None);
+}
+
+#[test]
+fn well_known_ports_are_unique() {
+    let mut ports: Vec<u16> = WELL_KNOWN_PORTS.iter().map(|(port, _)| *port).collect();
+    ports.sort_unstable();
+    ports.dedup();
+
+    assert_eq!(ports.len(), WELL_KNOWN_PORTS.len());
+}
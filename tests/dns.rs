@@ -0,0 +1,203 @@
+mod mock_data;
+
+use mock_data::{generate_dns_over_tcp, generate_tcp_segment, DNS_QUERY_EXAMPLE_COM};
+use net_sift::parsers::{
+    definitions::{DeepParser, LayerKind, LayeredData},
+    dns::{DnsMessage, DnsQuestion, DnsTcpMessage},
+    errors::ParserError,
+    tcp::TcpSegment,
+    udp::UdpDatagram,
+};
+
+fn dns_datagram(source_port: u16, message: &[u8]) -> Vec<u8> {
+    [
+        &source_port.to_be_bytes()[..],
+        &[0, 53],
+        &((8 + message.len()) as u16).to_be_bytes(),
+        &[0, 0],
+        message,
+    ]
+    .concat()
+}
+
+fn dns_messages(layered_data: LayeredData) -> Vec<DnsTcpMessage> {
+    match layered_data {
+        LayeredData::TcpData(segment) => match *segment.data {
+            LayeredData::DnsTcpData(messages) => messages,
+            other => panic!("Expected DNS messages, got {:?}", other),
+        },
+        other => panic!("Expected a TCP segment, got {:?}", other),
+    }
+}
+
+#[test]
+fn can_parse_dns_header() {
+    let message = DnsMessage::from_bytes(&DNS_QUERY_EXAMPLE_COM).unwrap();
+
+    assert_eq!(message.header.id, 0x1234);
+    assert_eq!(message.header.flags, 0x0100);
+    assert_eq!(message.header.question_count, 1);
+    assert_eq!(message.header.answer_count, 0);
+    assert!(!message.is_response());
+    assert_eq!(message.body, DNS_QUERY_EXAMPLE_COM[12..].to_vec());
+    assert_eq!(message.to_bytes(), DNS_QUERY_EXAMPLE_COM.to_vec());
+}
+
+#[test]
+fn decodes_the_question_section() {
+    let message = DnsMessage::from_bytes(&DNS_QUERY_EXAMPLE_COM).unwrap();
+
+    assert_eq!(
+        message.questions,
+        vec![DnsQuestion {
+            name: "example.com".to_string(),
+            qtype: 1,
+            qclass: 1,
+        }]
+    );
+}
+
+#[test]
+fn follows_name_compression_pointers() {
+    // A second question for www.example.com, pointing back to example.com at offset 12.
+    let mut message = DNS_QUERY_EXAMPLE_COM.to_vec();
+    message[5] = 2;
+    message.extend_from_slice(&[3, b'w', b'w', b'w', 0xC0, 12, 0, 28, 0, 1]);
+
+    let message = DnsMessage::from_bytes(&message).unwrap();
+
+    assert_eq!(message.questions[1].name, "www.example.com");
+    assert_eq!(message.questions[1].qtype, 28);
+}
+
+#[test]
+fn fails_on_compression_pointer_loop() {
+    let mut message = DNS_QUERY_EXAMPLE_COM[..12].to_vec();
+    message.extend_from_slice(&[0xC0, 12, 0, 1, 0, 1]);
+
+    let result = DnsMessage::from_bytes(&message);
+
+    assert!(matches!(result, Err(ParserError::MalformedMessage { .. })));
+}
+
+#[test]
+fn udp_dispatches_dns_port_to_dns() {
+    let datagram = dns_datagram(40000, &DNS_QUERY_EXAMPLE_COM);
+    let layered_data = UdpDatagram::from_bytes(&datagram)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    let Some(LayeredData::DnsData(message)) = layered_data.layers().last() else {
+        panic!("expected a DNS message");
+    };
+    assert_eq!(message.questions[0].name, "example.com");
+    assert_eq!(layered_data.to_bytes()[8..], DNS_QUERY_EXAMPLE_COM);
+}
+
+#[test]
+fn fails_if_message_is_shorter_than_header() {
+    let result = DnsMessage::from_bytes(&DNS_QUERY_EXAMPLE_COM[..11]);
+    assert!(
+        matches!(result, Err(ParserError::HeaderTooShort { layer, .. }) if layer == "DNS message")
+    );
+}
+
+#[test]
+fn can_parse_two_messages_in_one_segment() {
+    let mut response = DNS_QUERY_EXAMPLE_COM;
+    response[0..4].copy_from_slice(&[0xAB, 0xCD, 0x81, 0x80]);
+    let payload = generate_dns_over_tcp(&[&DNS_QUERY_EXAMPLE_COM, &response]);
+    let bytes = generate_tcp_segment(53, &payload);
+    let segment = TcpSegment::from_bytes(&bytes).unwrap();
+
+    let layered_data = segment.parse_next_layer().unwrap();
+    assert!(layered_data.contains(LayerKind::Dns));
+
+    let messages = dns_messages(layered_data);
+    let ids: Vec<(u16, bool)> = messages
+        .iter()
+        .map(|message| match message {
+            DnsTcpMessage::Complete(message) => (message.header.id, message.is_response()),
+            other => panic!("Expected a complete message, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(ids, vec![(0x1234, false), (0xABCD, true)]);
+}
+
+#[test]
+fn reports_message_cut_off_by_segment_boundary() {
+    let payload = generate_dns_over_tcp(&[&DNS_QUERY_EXAMPLE_COM, &DNS_QUERY_EXAMPLE_COM]);
+    let cut = &payload[..payload.len() - 10];
+    let bytes = generate_tcp_segment(53, cut);
+    let segment = TcpSegment::from_bytes(&bytes).unwrap();
+
+    let layered_data = segment.parse_next_layer().unwrap();
+    let LayeredData::TcpData(segment) = &layered_data else {
+        panic!("Expected a TCP segment");
+    };
+    // The messages serialize back to the bytes they were parsed from.
+    assert_eq!(segment.payload_len(), cut.len());
+
+    let messages = dns_messages(layered_data);
+    assert_eq!(messages.len(), 2);
+    assert_eq!(
+        messages[1],
+        DnsTcpMessage::Partial {
+            length: Some(29),
+            data: DNS_QUERY_EXAMPLE_COM[..19].to_vec(),
+        }
+    );
+
+    // Even the length prefix may be split.
+    let messages = DnsTcpMessage::parse_all(&payload[..32]);
+    assert_eq!(
+        messages[1],
+        DnsTcpMessage::Partial {
+            length: None,
+            data: vec![0],
+        }
+    );
+}
+
+#[test]
+fn reports_undecodable_payloads_as_partial() {
+    // A complete query followed by a prefix declaring 4 bytes, too few for a DNS header,
+    // as when a segment continues a message begun in an earlier one.
+    let payload = [
+        &generate_dns_over_tcp(&[&DNS_QUERY_EXAMPLE_COM])[..],
+        &[0, 4, 1, 2, 3, 4, 5],
+    ]
+    .concat();
+    let bytes = generate_tcp_segment(53, &payload);
+    let segment = TcpSegment::from_bytes(&bytes).unwrap();
+
+    let messages = dns_messages(segment.parse_next_layer().unwrap());
+    assert_eq!(messages.len(), 2);
+    assert!(matches!(messages[0], DnsTcpMessage::Complete(_)));
+    assert_eq!(
+        messages[1],
+        DnsTcpMessage::Partial {
+            length: Some(4),
+            data: vec![1, 2, 3, 4, 5],
+        }
+    );
+    let bytes: Vec<u8> = messages.iter().flat_map(DnsTcpMessage::to_bytes).collect();
+    assert_eq!(bytes, payload);
+}
+
+#[test]
+fn leaves_other_ports_and_empty_payloads_raw() {
+    let payload = generate_dns_over_tcp(&[&DNS_QUERY_EXAMPLE_COM]);
+
+    for segment in [
+        generate_tcp_segment(80, &payload),
+        generate_tcp_segment(53, &[]),
+    ] {
+        let layered_data = TcpSegment::from_bytes(&segment)
+            .unwrap()
+            .parse_next_layer()
+            .unwrap();
+        assert!(!layered_data.contains(LayerKind::Dns));
+    }
+}
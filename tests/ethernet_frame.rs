@@ -1,15 +1,26 @@
 mod mock_data;
 
 use mock_data::{
-    generate_ethernet_mock_packets, generate_ipv6_mock_packet, DEFAULT_DEST_MAC,
-    DEFAULT_ETHER_TYPE, DEFAULT_Q_TAG, DEFAULT_SRC_MAC, INVALID_ETHER_TYPE, MOCK_MALFORMED_PACKET,
+    generate_8023_mock_frame, generate_arp_mock_packet, generate_ethernet_mock_packets,
+    generate_ipv4_mock_packets, generate_ipv4_packet, generate_ipv4_tcp_with_valid_checksum,
+    generate_ipv6_mock_packet, generate_padded_ethernet_frame, CFM_ETHER_TYPE, DEFAULT_DATA,
+    DEFAULT_DEST_ADDR, DEFAULT_DEST_MAC, DEFAULT_ETHER_TYPE, DEFAULT_Q_TAG, DEFAULT_SRC_ADDR,
+    DEFAULT_SRC_MAC, DEFAULT_TCP_PROTOCOL, INVALID_ETHER_TYPE, IPV4_ETHER_TYPE, MIN_IPV4_LENGTH,
+    MOCK_MALFORMED_PACKET, SNAP_LLC_HEADER, STP_LLC_HEADER, WAKE_ON_LAN_ETHER_TYPE,
 };
 use net_sift::parsers::{
-    definitions::{DeepParser, EtherType, LayeredData},
+    arp::ArpPacket,
+    definitions::{DeepParser, EtherType, LayerKind, LayeredData},
     errors::ParserError,
-    ethernet_frame::EthernetFrame,
+    ethernet_frame::{
+        EthernetFrame, EthernetFrameBuilder, EthernetFrameKind, LlcHeader, MacAddress, SnapHeader,
+        VlanTag,
+    },
+    ipv4::Ipv4Packet,
 };
 
+use std::net::Ipv4Addr;
+
 fn validate_ethernet_frame(frame: EthernetFrame, expected_values: &EthernetFrameValues) {
     assert_eq!(
         frame.header.mac_destination.to_string(),
@@ -34,7 +45,7 @@ fn validate_ethernet_frame(frame: EthernetFrame, expected_values: &EthernetFrame
     assert_eq!(
         frame.data,
         Box::new(LayeredData::Payload(
-            expected_values.expected_payload.to_vec()
+            expected_values.expected_payload.to_vec().into()
         ))
     );
 }
@@ -87,6 +98,85 @@ fn can_parse_ethernet_frame_with_qtag() {
     validate_ethernet_frame(ethernet_frame, &expected_values);
 }
 
+#[test]
+fn decodes_vlan_tag_control_information() {
+    // PCP 5, DEI set, VID 0x123.
+    let frame = generate_ethernet_mock_packets(Some([0x81, 0x00, 0xB1, 0x23]), DEFAULT_ETHER_TYPE);
+
+    let ethernet_frame = EthernetFrame::from_bytes(&frame, true).unwrap();
+
+    assert_eq!(ethernet_frame.header.q_tag, Some(0x8100_B123));
+    assert_eq!(
+        ethernet_frame.header.vlan_tag(),
+        Some(VlanTag {
+            pcp: 5,
+            dei: true,
+            vid: 0x123
+        })
+    );
+    assert_eq!(
+        ethernet_frame.header.ether_type,
+        EtherType::from(u16::from_be_bytes(DEFAULT_ETHER_TYPE))
+    );
+}
+
+#[test]
+fn can_parse_double_tagged_frame() {
+    let packet = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let frame = [
+        &DEFAULT_DEST_MAC[..],
+        &DEFAULT_SRC_MAC,
+        &[0x88, 0xA8, 0x00, 0x64], // S-Tag, VID 100
+        &[0x81, 0x00, 0x20, 0xC8], // C-Tag, PCP 1, VID 200
+        &IPV4_ETHER_TYPE,
+        &packet,
+    ]
+    .concat();
+
+    let ethernet_frame = EthernetFrame::from_bytes(&frame, false).unwrap();
+
+    assert_eq!(ethernet_frame.header.s_tag, Some(0x88A8_0064));
+    assert_eq!(ethernet_frame.header.q_tag, Some(0x8100_20C8));
+    assert_eq!(
+        ethernet_frame.header.service_vlan_tag().map(|tag| tag.vid),
+        Some(100)
+    );
+    assert_eq!(
+        ethernet_frame.header.vlan_tag(),
+        Some(VlanTag {
+            pcp: 1,
+            dei: false,
+            vid: 200
+        })
+    );
+    assert_eq!(ethernet_frame.header.ether_type, EtherType::IPv4);
+    assert_eq!(ethernet_frame.header.span, 0..22);
+    assert_eq!(ethernet_frame.data.payload().unwrap(), packet);
+    assert_eq!(ethernet_frame.to_bytes(), frame);
+
+    let built = EthernetFrameBuilder::new()
+        .dst_mac(MacAddress(DEFAULT_DEST_MAC))
+        .src_mac(MacAddress(DEFAULT_SRC_MAC))
+        .service_vlan(100)
+        .vlan(200)
+        .ether_type(EtherType::IPv4)
+        .payload(LayeredData::Payload(packet.into()))
+        .build();
+    // The builder leaves the priority bits of the C-Tag clear.
+    assert_eq!(built.header.raw_header[..18], frame[..18]);
+    assert_eq!(built.header.vlan_tag().map(|tag| tag.vid), Some(200));
+}
+
+#[test]
+fn untagged_frames_have_no_vlan_tag() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+
+    let ethernet_frame = EthernetFrame::from_bytes(&frame, true).unwrap();
+
+    assert_eq!(ethernet_frame.header.vlan_tag(), None);
+    assert_eq!(ethernet_frame.header.service_vlan_tag(), None);
+}
+
 #[test]
 fn fails_if_bad_ether_type() {
     let frame = generate_ethernet_mock_packets(None, INVALID_ETHER_TYPE);
@@ -98,10 +188,13 @@ fn fails_if_bad_ether_type() {
 
 #[test]
 fn fails_if_frame_is_malformed() {
-    let result = EthernetFrame::from_bytes(&MOCK_MALFORMED_PACKET, true);
+    // Only the header is required, so the frame must end before the header and FCS do.
+    let result = EthernetFrame::from_bytes(&MOCK_MALFORMED_PACKET[..17], true);
 
-    let s = String::from("Ethernet Frame");
-    assert!(matches!(result, Err(ParserError::InvalidLength(s))))
+    assert!(matches!(
+        result,
+        Err(ParserError::HeaderTooShort { layer, got: 17, need: 18 }) if layer == "Ethernet Frame"
+    ))
 }
 
 #[test]
@@ -119,3 +212,458 @@ fn can_parse_layered_data() {
         _ => panic!("Invalid layered data"),
     };
 }
+
+#[test]
+fn passes_through_payload_of_unparsed_ether_type() {
+    let frame = generate_ethernet_mock_packets(None, WAKE_ON_LAN_ETHER_TYPE);
+
+    let ethernet_frame = EthernetFrame::from_bytes(&frame, true).unwrap();
+    let layered_data = ethernet_frame.parse_next_layer().unwrap();
+
+    match layered_data {
+        LayeredData::EthernetFrameData(v) => {
+            assert_eq!(v.header.ether_type, EtherType::Other(0x0842));
+            assert_eq!(
+                v.data,
+                Box::new(LayeredData::Payload(generate_ipv6_mock_packet().into()))
+            );
+        }
+        _ => panic!("Invalid layered data"),
+    };
+}
+
+#[test]
+fn passes_through_payload_of_ether_type_without_a_name() {
+    // Connectivity Fault Management (0x8902) is not in `ACCEPTED_ETHERTYPES`.
+    let frame = generate_ethernet_mock_packets(None, CFM_ETHER_TYPE);
+
+    let layered_data = EthernetFrame::from_bytes(&frame, true)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    match layered_data {
+        LayeredData::EthernetFrameData(v) => {
+            assert_eq!(v.header.ether_type, EtherType::Other(0x8902));
+            assert_eq!(
+                v.data,
+                Box::new(LayeredData::Payload(generate_ipv6_mock_packet().into()))
+            );
+        }
+        _ => panic!("Invalid layered data"),
+    };
+}
+
+#[test]
+fn can_convert_mac_address() {
+    let mac = MacAddress::from([1, 2, 3, 4, 5, 6]);
+
+    assert_eq!(mac.as_ref(), &[1, 2, 3, 4, 5, 6][..]);
+    assert_eq!(mac[0], 1);
+    assert_eq!(mac, MacAddress::from_bytes([1, 2, 3, 4, 5, 6]));
+    assert_eq!(
+        MacAddress::try_from(&DEFAULT_SRC_MAC[..]).unwrap(),
+        MacAddress(DEFAULT_SRC_MAC)
+    );
+}
+
+#[test]
+fn fails_to_convert_mac_address_of_wrong_length() {
+    let result = MacAddress::try_from(&[1, 2, 3][..]);
+
+    assert!(matches!(result, Err(ParserError::InvalidLength(s)) if s == "MAC address"));
+}
+
+#[test]
+fn parses_mac_address_strings() {
+    let mac: MacAddress = "0C:19:3C:FF:58:0C".parse().unwrap();
+
+    assert_eq!(mac, MacAddress(DEFAULT_DEST_MAC));
+    assert_eq!(mac.to_string(), "0C:19:3C:FF:58:0C");
+    assert_eq!("0c:19:3c:ff:58:c".parse::<MacAddress>().unwrap(), mac);
+}
+
+#[test]
+fn rejects_malformed_mac_address_strings() {
+    for input in [
+        "0C:19:3C",
+        "0C:19:3C:FF:58:0C:01",
+        "0C:19:3C:FF:58:0G",
+        "0C:19:3C:FF:58:100",
+        "0C:19:3C:FF::0C",
+        "0C-19-3C-FF-58-0C",
+        "+C:19:3C:FF:58:0C",
+    ] {
+        assert!(
+            matches!(input.parse::<MacAddress>(), Err(ParserError::InvalidMacString(s)) if s == input)
+        );
+    }
+}
+
+#[test]
+fn can_clone_fully_parsed_stack() {
+    let frame = generate_ethernet_mock_packets(Some(DEFAULT_Q_TAG), DEFAULT_ETHER_TYPE);
+    let ethernet_frame = EthernetFrame::from_bytes(&frame, true).unwrap();
+
+    // Keep a copy of the shallow frame while handing ownership to the deep parser.
+    let shallow_copy = ethernet_frame.clone();
+    let layered_data = ethernet_frame.parse_next_layer().unwrap();
+    let deep_copy = layered_data.clone();
+
+    assert_eq!(
+        shallow_copy,
+        EthernetFrame::from_bytes(&frame, true).unwrap()
+    );
+    assert_eq!(deep_copy, layered_data);
+    assert!(matches!(deep_copy.inner(), Some(LayeredData::Ipv6Data(_))));
+}
+
+#[test]
+fn can_classify_layers_of_parsed_stack() {
+    let frame = generate_ethernet_mock_packets(Some(DEFAULT_Q_TAG), DEFAULT_ETHER_TYPE);
+    let layered_data = EthernetFrame::from_bytes(&frame, true)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    let kinds: Vec<LayerKind> = layered_data
+        .layers()
+        .filter_map(LayeredData::layer_kind)
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![LayerKind::Ethernet, LayerKind::Ipv6, LayerKind::Tcp]
+    );
+    assert!(layered_data.contains(LayerKind::Tcp));
+    assert!(!layered_data.contains(LayerKind::Udp));
+    assert!(!layered_data.contains(LayerKind::Ipv4));
+}
+
+#[test]
+fn deepest_returns_the_innermost_parsed_layer() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+    let layered_data = EthernetFrame::from_bytes(&frame, true)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    let LayeredData::TcpData(segment) = layered_data.deepest() else {
+        panic!("expected a TCP segment");
+    };
+    assert_eq!(segment.data.payload(), Some(&DEFAULT_DATA[..]));
+
+    let payload = LayeredData::Payload(vec![1, 2, 3].into());
+    assert_eq!(payload.clone().deepest(), payload);
+}
+
+#[test]
+fn recognises_ethernet_ii_frames() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+
+    let ethernet_frame = EthernetFrame::from_bytes(&frame, true).unwrap();
+
+    assert_eq!(
+        ethernet_frame.header.frame_kind,
+        EthernetFrameKind::EthernetII
+    );
+}
+
+#[test]
+fn can_parse_8023_snap_frame_carrying_ipv4() {
+    let ipv4 = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let llc_pdu = [&SNAP_LLC_HEADER[..], &[0, 0, 0, 8, 0], &ipv4].concat();
+    let frame = generate_8023_mock_frame(&llc_pdu);
+
+    let ethernet_frame = EthernetFrame::from_bytes(&frame, false).unwrap();
+
+    assert_eq!(ethernet_frame.header.ether_type, EtherType::IPv4);
+    assert_eq!(
+        ethernet_frame.header.frame_kind,
+        EthernetFrameKind::Ieee8023 {
+            length: llc_pdu.len() as u16,
+            llc: LlcHeader {
+                dsap: 0xAA,
+                ssap: 0xAA,
+                control: 0x03,
+            },
+            snap: Some(SnapHeader {
+                oui: [0, 0, 0],
+                protocol_id: 0x0800,
+            }),
+        }
+    );
+    assert_eq!(ethernet_frame.header.span, 0..22);
+    assert_eq!(ethernet_frame.header.raw_header, frame[..22]);
+
+    let layered_data = ethernet_frame.parse_next_layer().unwrap();
+    let kinds: Vec<LayerKind> = layered_data
+        .layers()
+        .filter_map(LayeredData::layer_kind)
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![LayerKind::Ethernet, LayerKind::Ipv4, LayerKind::Tcp]
+    );
+}
+
+#[test]
+fn passes_through_8023_llc_payload_without_padding() {
+    let bpdu = [0, 0, 0, 0, 0];
+    let llc_pdu = [&STP_LLC_HEADER[..], &bpdu].concat();
+    let frame = generate_8023_mock_frame(&llc_pdu);
+
+    let layered_data = EthernetFrame::from_bytes(&frame, false)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    match layered_data {
+        LayeredData::EthernetFrameData(v) => {
+            assert_eq!(v.header.ether_type, EtherType::Other(8));
+            assert!(matches!(
+                v.header.frame_kind,
+                EthernetFrameKind::Ieee8023 { snap: None, .. }
+            ));
+            assert_eq!(v.data, Box::new(LayeredData::Payload(bpdu.to_vec().into())));
+        }
+        _ => panic!("Invalid layered data"),
+    };
+}
+
+#[test]
+fn fails_if_8023_length_exceeds_frame() {
+    let mut frame = generate_8023_mock_frame(&STP_LLC_HEADER);
+    frame[12..14].copy_from_slice(&1500u16.to_be_bytes());
+
+    let result = EthernetFrame::from_bytes(&frame, false);
+
+    assert!(matches!(
+        result,
+        Err(ParserError::InconsistentLength {
+            declared: 1500,
+            available: 50,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn fails_if_8023_length_is_shorter_than_llc_header() {
+    let mut frame = generate_8023_mock_frame(&SNAP_LLC_HEADER);
+
+    for length in 0..3u16 {
+        frame[12..14].copy_from_slice(&length.to_be_bytes());
+
+        let result = EthernetFrame::from_bytes(&frame, true);
+        assert!(
+            matches!(result, Err(ParserError::InvalidLength(ref s)) if s == "LLC header"),
+            "length {length}: {result:?}"
+        );
+    }
+}
+
+#[test]
+fn parses_minimum_length_frame_with_fcs() {
+    let frame = generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &[]);
+
+    let ethernet_frame = EthernetFrame::from_bytes(&frame[..64], true).unwrap();
+
+    assert_eq!(
+        ethernet_frame.data.payload().map(<[u8]>::len),
+        Some(64 - 14 - 4)
+    );
+}
+
+#[test]
+fn finalize_checksums_fills_every_checksum_in_the_stack() {
+    let mut ipv4 = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    ipv4[10..12].copy_from_slice(&[0, 0]);
+    ipv4[36..38].copy_from_slice(&[0, 0]);
+    let frame = generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &ipv4);
+
+    let mut layered_data = EthernetFrame::from_bytes(&frame, false)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+    layered_data.finalize_checksums();
+
+    let packet = match layered_data.inner() {
+        Some(LayeredData::Ipv4Data(packet)) => packet,
+        other => panic!("expected IPv4 data, got {:?}", other),
+    };
+    let segment = match &*packet.data {
+        LayeredData::TcpData(segment) => segment,
+        other => panic!("expected TCP data, got {:?}", other),
+    };
+    let bytes = packet.to_bytes();
+
+    assert!(packet.verify_checksum(&bytes[..MIN_IPV4_LENGTH]));
+    assert!(segment.verify_checksum(
+        &bytes[MIN_IPV4_LENGTH..],
+        Ipv4Addr::from(DEFAULT_SRC_ADDR).into(),
+        Ipv4Addr::from(DEFAULT_DEST_ADDR).into(),
+    ));
+    assert_eq!(
+        bytes[MIN_IPV4_LENGTH..],
+        generate_ipv4_tcp_with_valid_checksum()[MIN_IPV4_LENGTH..]
+    );
+}
+
+#[test]
+fn builder_infers_ether_type_from_payload() {
+    let ipv4 = generate_ipv4_packet(DEFAULT_TCP_PROTOCOL[0], &[0; 40]);
+    let packet = Ipv4Packet::from_bytes(&ipv4).unwrap();
+    // `to_bytes` fills in the header checksum the mock leaves blank.
+    let ipv4 = packet.to_bytes();
+
+    let frame = EthernetFrameBuilder::new()
+        .dst_mac(MacAddress(DEFAULT_DEST_MAC))
+        .src_mac(MacAddress(DEFAULT_SRC_MAC))
+        .payload(LayeredData::Ipv4Data(packet))
+        .build();
+    assert_eq!(frame.header.ether_type, EtherType::IPv4);
+    assert_eq!(frame.header.span, 0..14);
+
+    let bytes = frame.to_bytes();
+    assert_eq!(bytes[..14], frame.header.raw_header);
+    assert_eq!(bytes[12..14], IPV4_ETHER_TYPE);
+    assert_eq!(bytes[14..], ipv4);
+
+    let parsed = EthernetFrame::from_bytes(&bytes, false).unwrap();
+    assert_eq!(parsed.header, frame.header);
+    assert_eq!(parsed.data.payload().unwrap(), ipv4);
+}
+
+#[test]
+fn builder_honours_vlan_and_explicit_ether_type() {
+    let payload = generate_ipv6_mock_packet();
+
+    let frame = EthernetFrameBuilder::new()
+        .vlan(0x1216)
+        .ether_type(EtherType::IPv6)
+        .payload(LayeredData::Payload(payload.clone().into()))
+        .build();
+    assert_eq!(frame.header.q_tag, Some(0x8100_0216));
+    assert_eq!(frame.header.vlan_tag().map(|tag| tag.vid), Some(0x216));
+
+    let bytes = frame.to_bytes();
+    assert_eq!(bytes[12..18], [0x81, 0x00, 0x02, 0x16, 0x86, 0xDD]);
+    assert_eq!(bytes[18..], payload);
+
+    let frame = EthernetFrameBuilder::new()
+        .payload(LayeredData::Payload(payload.into()))
+        .build();
+    assert_eq!(frame.header.ether_type, EtherType::Other(0x88B5));
+}
+
+#[test]
+fn builder_output_parses_back() {
+    for (operation, ether_type) in [(1, EtherType::ARP), (3, EtherType::RARP)] {
+        let packet = ArpPacket::from_bytes(&generate_arp_mock_packet(operation)).unwrap();
+        let data = match ether_type {
+            EtherType::ARP => LayeredData::ArpData(packet),
+            _ => LayeredData::RarpData(packet),
+        };
+
+        let frame = EthernetFrameBuilder::new()
+            .dst_mac(MacAddress(DEFAULT_DEST_MAC))
+            .src_mac(MacAddress(DEFAULT_SRC_MAC))
+            .payload(data.clone())
+            .build();
+        assert_eq!(frame.header.ether_type, ether_type);
+
+        // ARP packets used to serialize to nothing, leaving a bare Ethernet header.
+        let bytes = frame.to_bytes();
+        assert_eq!(bytes.len(), 14 + 28);
+
+        let parsed = EthernetFrame::from_bytes(&bytes, false)
+            .unwrap()
+            .parse_next_layer()
+            .unwrap();
+        assert_eq!(parsed.inner(), Some(&data));
+    }
+
+    // Payloads without an EtherType of their own fall back to 0x88B5, which parses too.
+    let payload = DEFAULT_DATA.to_vec();
+    let frame = EthernetFrameBuilder::new()
+        .payload(LayeredData::Payload(payload.clone().into()))
+        .build();
+
+    let bytes = frame.to_bytes();
+    let parsed = EthernetFrame::from_bytes(&bytes, false).unwrap();
+    assert_eq!(parsed.header.ether_type, EtherType::Other(0x88B5));
+    assert_eq!(parsed.data.payload(), Some(&payload[..]));
+}
+
+#[test]
+fn serializes_known_frames_byte_for_byte() {
+    for q_tag in [None, Some(DEFAULT_Q_TAG)] {
+        let frame = generate_ethernet_mock_packets(q_tag, DEFAULT_ETHER_TYPE);
+        let ethernet_frame = EthernetFrame::from_bytes(&frame, true).unwrap();
+
+        // `to_bytes` writes no FCS.
+        let bytes = ethernet_frame.to_bytes();
+        assert_eq!(bytes, frame[..frame.len() - 4], "q_tag {q_tag:?}");
+        assert_eq!(
+            EthernetFrame::from_bytes(&bytes, false).unwrap().header,
+            ethernet_frame.header
+        );
+    }
+
+    let frame = generate_padded_ethernet_frame(WAKE_ON_LAN_ETHER_TYPE, &[0xFF; 6]);
+    let ethernet_frame = EthernetFrame::from_bytes(&frame, false).unwrap();
+    assert_eq!(ethernet_frame.header.ether_type, EtherType::Other(0x0842));
+    assert_eq!(ethernet_frame.to_bytes(), frame);
+}
+
+#[test]
+fn serializes_8023_frame_with_its_llc_header() {
+    let llc_pdu = [&STP_LLC_HEADER[..], &[0; 35]].concat();
+    let frame = generate_8023_mock_frame(&llc_pdu);
+
+    let ethernet_frame = EthernetFrame::from_bytes(&frame, false).unwrap();
+
+    assert_eq!(ethernet_frame.to_bytes(), frame[..14 + llc_pdu.len()]);
+}
+
+#[test]
+fn ipv4_layer_excludes_ethernet_padding_beyond_total_length() {
+    let packet = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let frame = [
+        &DEFAULT_DEST_MAC[..],
+        &DEFAULT_SRC_MAC,
+        &IPV4_ETHER_TYPE,
+        &packet,
+        &[0; 6],
+    ]
+    .concat();
+
+    let ethernet_frame = EthernetFrame::from_bytes(&frame, false).unwrap();
+    assert_eq!(
+        ethernet_frame.data.payload().map(<[u8]>::len),
+        Some(packet.len() + 6)
+    );
+
+    let layered_data = ethernet_frame.parse_next_layer().unwrap();
+    let expected = Ipv4Packet::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+    assert_eq!(layered_data.inner(), Some(&expected));
+    assert_eq!(layered_data.inner().unwrap().to_bytes().len(), packet.len());
+}
+
+#[test]
+fn try_from_parses_a_frame_without_fcs() {
+    let frame = generate_ethernet_mock_packets(None, IPV4_ETHER_TYPE);
+
+    let converted: EthernetFrame = frame.as_slice().try_into().unwrap();
+
+    assert_eq!(converted, EthernetFrame::from_bytes(&frame, false).unwrap());
+    assert!(matches!(
+        EthernetFrame::try_from(&MOCK_MALFORMED_PACKET[..4]),
+        Err(ParserError::HeaderTooShort { .. })
+    ));
+}
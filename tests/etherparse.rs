@@ -0,0 +1,74 @@
+#![cfg(feature = "etherparse")]
+
+mod mock_data;
+
+use etherparse::{Ethernet2Header, Ipv4Header, Ipv6Header, TcpHeader, UdpHeader};
+use mock_data::*;
+use net_sift::parsers::{
+    ethernet_frame::{EthernetFrame, EthernetFrameHeader},
+    ipv4::{Ipv4Packet, Ipv4PacketHeader},
+    ipv6::{Ipv6Packet, Ipv6PacketHeader},
+    tcp::{TcpSegment, TcpSegmentHeader},
+    udp::{UdpDatagram, UdpDatagramHeader},
+};
+
+#[test]
+fn ethernet_header_round_trips() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+    let header = EthernetFrame::from_bytes(&frame, true).unwrap().header;
+
+    let converted = Ethernet2Header::from(header.clone());
+    assert_eq!(converted.source, DEFAULT_SRC_MAC);
+    assert_eq!(converted.destination, DEFAULT_DEST_MAC);
+    assert_eq!(converted.ether_type.0, 0x86DD);
+    assert_eq!(EthernetFrameHeader::from(converted), header);
+}
+
+#[test]
+fn ipv4_header_round_trips() {
+    // The mock uses an invalid version and sets the reserved flag, neither of which
+    // etherparse can represent.
+    let mut packet = generate_ipv4_packet_with_options(&IPV4_ROUTE_OPTIONS);
+    packet[0] = (4 << 4) | (packet[0] & 0x0F);
+    packet[6] &= 0x7F;
+    let header = Ipv4Packet::from_bytes(&packet).unwrap().header;
+
+    let converted = Ipv4Header::from(header.clone());
+    assert_eq!(converted.source, DEFAULT_SRC_ADDR);
+    assert_eq!(converted.options.as_slice(), &IPV4_ROUTE_OPTIONS);
+    assert!(converted.more_fragments);
+    assert_eq!(converted.to_bytes().as_slice(), &header.raw_header[..]);
+    assert_eq!(Ipv4PacketHeader::from(converted), header);
+}
+
+#[test]
+fn ipv6_header_round_trips() {
+    let packet = generate_ipv6_mock_packet();
+    let header = Ipv6Packet::from_bytes(&packet).unwrap().header;
+
+    let converted = Ipv6Header::from(header.clone());
+    assert_eq!(converted.source, DEFAULT_SRC_ADDRESS);
+    assert_eq!(converted.to_bytes().as_slice(), &header.raw_header[..]);
+    assert_eq!(Ipv6PacketHeader::from(converted), header);
+}
+
+#[test]
+fn tcp_header_round_trips() {
+    let segment = generate_tcp_packets_with_options();
+    let header = TcpSegment::from_bytes(&segment).unwrap().header;
+
+    let converted = TcpHeader::from(header.clone());
+    assert!(converted.syn && converted.fin && converted.cwr);
+    assert_eq!(converted.options.as_slice(), &DEFAULT_OPTIONS);
+    assert_eq!(converted.to_bytes().as_slice(), &header.raw_header[..]);
+    assert_eq!(TcpSegmentHeader::from(converted), header);
+}
+
+#[test]
+fn udp_header_round_trips() {
+    let header = UdpDatagram::from_bytes(&UDP_PACKETS).unwrap().header;
+
+    let converted = UdpHeader::from(header.clone());
+    assert_eq!(converted.to_bytes(), UDP_PACKETS[..8]);
+    assert_eq!(UdpDatagramHeader::from(converted), header);
+}
@@ -0,0 +1,122 @@
+mod mock_data;
+
+use mock_data::{
+    generate_arp_mock_packet, generate_ethernet_mock_packets, generate_ipv4_packet,
+    generate_padded_ethernet_frame, generate_tcp_segment, DEFAULT_ETHER_TYPE, ICMP, ICMP_PACKETS,
+    IPV4_ETHER_TYPE, TCP, UDP, UDP_PACKETS,
+};
+use net_sift::parsers::{
+    definitions::{DeepParser, LayerKind, LayeredData},
+    errors::ParserError,
+    ethernet_frame::EthernetFrame,
+    filter::{Endpoint, Filter},
+};
+
+const ARP_ETHER_TYPE: [u8; 2] = [8, 6];
+
+fn parse(frame: &[u8]) -> LayeredData<'static> {
+    EthernetFrame::from_bytes(frame, false)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap()
+        .into_owned()
+}
+
+// From 100.127.60.5:53145 to 30.44.8.50:80.
+fn ipv4_tcp() -> LayeredData<'static> {
+    let packet = generate_ipv4_packet(TCP, &generate_tcp_segment(80, b"GET /"));
+    parse(&generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &packet))
+}
+
+// From port 3327 to port 25615.
+fn ipv4_udp() -> LayeredData<'static> {
+    let packet = generate_ipv4_packet(UDP, &UDP_PACKETS);
+    parse(&generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &packet))
+}
+
+fn matches(expression: &str, layered_data: &LayeredData) -> bool {
+    Filter::parse(expression).unwrap().matches(layered_data)
+}
+
+#[test]
+fn matches_protocols() {
+    let tcp = ipv4_tcp();
+    let udp = ipv4_udp();
+    let icmp = parse(&generate_padded_ethernet_frame(
+        IPV4_ETHER_TYPE,
+        &generate_ipv4_packet(ICMP, &ICMP_PACKETS),
+    ));
+    let ipv6 = parse(&generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE));
+    let arp = parse(&generate_padded_ethernet_frame(
+        ARP_ETHER_TYPE,
+        &generate_arp_mock_packet(1),
+    ));
+
+    assert!(matches("tcp", &tcp) && !matches("tcp", &udp));
+    assert!(matches("udp", &udp) && !matches("udp", &icmp));
+    assert!(matches("icmp", &icmp) && !matches("icmp", &tcp));
+    assert!(matches("ip", &tcp) && !matches("ip", &ipv6));
+    assert!(matches("ip6", &ipv6) && !matches("ip6", &arp));
+    assert!(matches("arp", &arp) && !matches("arp", &tcp));
+}
+
+#[test]
+fn matches_ports_and_hosts() {
+    let tcp = ipv4_tcp();
+
+    assert!(matches("tcp and port 80 and host 100.127.60.5", &tcp));
+    assert!(matches("port 53145", &tcp));
+    assert!(matches("src port 53145 and dst port 80", &tcp));
+    assert!(!matches("src port 80", &tcp));
+    assert!(matches("dst host 30.44.8.50", &tcp));
+    assert!(!matches("src host 30.44.8.50", &tcp));
+    assert!(!matches("host ::1", &tcp));
+}
+
+#[test]
+fn combines_with_and_or_not() {
+    let tcp = ipv4_tcp();
+    let udp = ipv4_udp();
+
+    assert!(matches("udp or port 80", &tcp));
+    assert!(matches("not udp", &tcp) && !matches("not udp", &udp));
+    assert!(matches("!(udp || port 443) && tcp", &tcp));
+    // `and` and `or` associate left to right: this is `(udp and port 1) or tcp`.
+    assert!(matches("udp and port 1 or tcp", &tcp));
+    assert!(!matches("tcp and (port 1 or port 2)", &tcp));
+}
+
+#[test]
+fn parses_into_expression_tree() {
+    let filter = Filter::parse("not src port 80 or ip6").unwrap();
+
+    assert_eq!(
+        filter,
+        Filter::Or(
+            Box::new(Filter::Not(Box::new(Filter::Port(Endpoint::Source, 80)))),
+            Box::new(Filter::Protocol(LayerKind::Ipv6)),
+        )
+    );
+}
+
+#[test]
+fn fails_on_malformed_expressions() {
+    for expression in [
+        "",
+        "tcp and",
+        "port http",
+        "host 300.1.1.1",
+        "(tcp",
+        "tcp)",
+        "src tcp",
+        "sctp",
+    ] {
+        assert!(
+            matches!(
+                Filter::parse(expression),
+                Err(ParserError::InvalidFilter(_))
+            ),
+            "{expression:?} should not parse"
+        );
+    }
+}
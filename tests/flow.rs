@@ -0,0 +1,139 @@
+#![cfg(feature = "std")]
+
+mod mock_data;
+
+use mock_data::{
+    generate_ethernet_mock_packets, generate_ipv4_mock_packets, DEFAULT_ETHER_TYPE,
+    DEFAULT_IPV4_OPTIONS, DEFAULT_TCP_PROTOCOL,
+};
+use net_sift::parsers::{
+    definitions::{DeepParser, IPType, LayeredData},
+    ethernet_frame::EthernetFrame,
+    flow::{FlowKey, FlowTable},
+    frame_reader::TimestampedFrame,
+    ipv4::Ipv4Packet,
+};
+
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    time::{Duration, SystemTime},
+};
+
+fn host(last_octet: u8) -> IpAddr {
+    IpAddr::V4(Ipv4Addr::new(10, 0, 0, last_octet))
+}
+
+#[test]
+fn both_directions_canonicalize_to_the_same_key() {
+    let a_to_b = FlowKey::new(host(2), host(1), 443, 50000, IPType::TCP);
+    let b_to_a = a_to_b.reversed();
+
+    assert_ne!(a_to_b, b_to_a);
+    assert_eq!(a_to_b.canonical(), b_to_a.canonical());
+    assert_eq!(
+        a_to_b.canonical(),
+        FlowKey::new(host(1), host(2), 50000, 443, IPType::TCP)
+    );
+}
+
+#[test]
+fn canonical_orders_by_port_when_addresses_match() {
+    let key = FlowKey::new(host(1), host(1), 8080, 53, IPType::UDP);
+
+    assert_eq!(
+        key.canonical(),
+        FlowKey::new(host(1), host(1), 53, 8080, IPType::UDP)
+    );
+    assert_eq!(key.canonical(), key.canonical().canonical());
+}
+
+#[test]
+fn sorts_flows_by_tuple() {
+    let mut flows = vec![
+        FlowKey::new(host(2), host(1), 80, 1000, IPType::TCP),
+        FlowKey::new(host(1), host(2), 80, 1000, IPType::UDP),
+        FlowKey::new(host(1), host(2), 80, 1000, IPType::TCP),
+        FlowKey::new(host(1), host(2), 22, 1000, IPType::TCP),
+    ];
+    flows.sort();
+
+    assert_eq!(
+        flows,
+        vec![
+            FlowKey::new(host(1), host(2), 22, 1000, IPType::TCP),
+            FlowKey::new(host(1), host(2), 80, 1000, IPType::TCP),
+            FlowKey::new(host(1), host(2), 80, 1000, IPType::UDP),
+            FlowKey::new(host(2), host(1), 80, 1000, IPType::TCP),
+        ]
+    );
+}
+
+#[test]
+fn extracts_key_from_layered_data() {
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, Some(&DEFAULT_IPV4_OPTIONS));
+    let ipv4_packet = Ipv4Packet::from_bytes(&packets).unwrap();
+    let (source, destination) = (
+        ipv4_packet.header.source_address,
+        ipv4_packet.header.destination_address,
+    );
+
+    let layered_data = ipv4_packet.parse_next_layer().unwrap();
+
+    assert_eq!(
+        FlowKey::from_layered_data(&layered_data),
+        Some(FlowKey::new(
+            IpAddr::V4(source),
+            IpAddr::V4(destination),
+            53145,
+            80,
+            IPType::TCP
+        ))
+    );
+    assert_eq!(
+        FlowKey::from_layered_data(&LayeredData::Payload(packets.into())),
+        None
+    );
+}
+
+#[test]
+fn flow_table_tracks_timing_of_timestamped_frames() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+    let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let mut table = FlowTable::new();
+
+    for offset in [0, 2, 5] {
+        let timestamped = TimestampedFrame {
+            timestamp: start + Duration::from_secs(offset),
+            frame: EthernetFrame::from_bytes(&frame, true)
+                .unwrap()
+                .into_owned(),
+        };
+        assert!(table.record_frame(timestamped).unwrap().is_some());
+    }
+
+    assert_eq!(table.len(), 1);
+    let (key, stats) = table.iter().next().unwrap();
+    assert_eq!(table.get(&key.reversed()), Some(stats));
+    assert_eq!(stats.packets, 3);
+    assert_eq!(stats.first_seen, start);
+    assert_eq!(stats.last_seen, start + Duration::from_secs(5));
+    assert_eq!(stats.min_inter_arrival, Some(Duration::from_secs(2)));
+    assert_eq!(stats.max_inter_arrival, Some(Duration::from_secs(3)));
+    assert_eq!(
+        stats.mean_inter_arrival(),
+        Some(Duration::from_millis(2500))
+    );
+}
+
+#[test]
+fn flow_table_ignores_packets_without_ip() {
+    let mut table = FlowTable::new();
+
+    let stats = table.record(
+        SystemTime::UNIX_EPOCH,
+        &LayeredData::Payload(vec![1, 2, 3].into()),
+    );
+
+    assert!(stats.is_none());
+    assert!(table.is_empty());
+}
@@ -0,0 +1,240 @@
+#![cfg(feature = "std")]
+
+mod mock_data;
+
+use mock_data::{
+    generate_ethernet_mock_packets, generate_pcap_capture, DEFAULT_ETHER_TYPE, DEFAULT_Q_TAG,
+    INVALID_ETHER_TYPE, PCAP_GLOBAL_HEADER,
+};
+use net_sift::parsers::{
+    errors::ParserError,
+    ethernet_frame::EthernetFrame,
+    frame_reader::{
+        read_frame, write_pcap_header, write_pcap_record, FrameReader, Framing, LengthFramedReader,
+        TimestampedFrame,
+    },
+};
+
+use std::{
+    io::{self, Cursor, Read},
+    time::{Duration, SystemTime},
+};
+
+#[test]
+fn can_read_frames_from_pcap_capture() {
+    let frames = vec![
+        generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE),
+        generate_ethernet_mock_packets(Some(DEFAULT_Q_TAG), DEFAULT_ETHER_TYPE),
+    ];
+    let capture = generate_pcap_capture(&frames);
+
+    let reader = FrameReader::new(Cursor::new(capture), Framing::Pcap, true);
+    let parsed: Vec<_> = reader.collect();
+
+    assert_eq!(parsed.len(), 2);
+    assert!(parsed[0].as_ref().unwrap().header.q_tag.is_none());
+    assert!(parsed[1].as_ref().unwrap().header.q_tag.is_some());
+}
+
+#[test]
+fn continues_after_malformed_frame() {
+    let frames = vec![
+        generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE),
+        generate_ethernet_mock_packets(None, INVALID_ETHER_TYPE),
+        generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE),
+    ];
+    let capture = generate_pcap_capture(&frames);
+
+    let mut reader = FrameReader::new(Cursor::new(capture), Framing::Pcap, true);
+
+    assert!(reader.next().unwrap().is_ok());
+    assert!(matches!(
+        reader.next().unwrap(),
+        Err(ParserError::InvalidEtherType)
+    ));
+    assert!(reader.next().unwrap().is_ok());
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn can_read_fixed_length_frames() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+    let buffer = [frame.clone(), frame.clone(), frame.clone()].concat();
+
+    let reader = FrameReader::new(Cursor::new(buffer), Framing::FixedLength(frame.len()), true);
+
+    assert_eq!(reader.filter(|f| f.is_ok()).count(), 3);
+}
+
+#[test]
+fn stops_on_truncated_record() {
+    let frames = vec![generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE)];
+    let mut capture = generate_pcap_capture(&frames);
+    capture.truncate(capture.len() - 10);
+
+    let mut reader = FrameReader::new(Cursor::new(capture), Framing::Pcap, true);
+
+    assert!(matches!(
+        reader.next().unwrap(),
+        Err(ParserError::ExtractionError { .. })
+    ));
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn fails_if_pcap_magic_is_unknown() {
+    let mut reader = FrameReader::new(Cursor::new(vec![0u8; 24]), Framing::Pcap, true);
+
+    assert!(matches!(
+        reader.next().unwrap(),
+        Err(ParserError::InvalidPcapMagic(0))
+    ));
+}
+
+#[test]
+fn fails_if_pcap_link_type_is_not_ethernet() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+    let mut capture = generate_pcap_capture(&[frame]);
+    // LINKTYPE_LINUX_SLL, whose records do not start with an Ethernet header.
+    capture[20..24].copy_from_slice(&113u32.to_le_bytes());
+
+    let mut reader = FrameReader::new(Cursor::new(capture), Framing::Pcap, true);
+
+    assert!(matches!(
+        reader.next().unwrap(),
+        Err(ParserError::UnsupportedLinkType(113))
+    ));
+    assert!(reader.next().is_none());
+}
+
+#[test]
+#[should_panic(expected = "at least one byte")]
+fn rejects_zero_length_frames() {
+    FrameReader::new(Cursor::new(Vec::new()), Framing::FixedLength(0), true);
+}
+
+#[test]
+fn can_read_concatenated_frames_by_length() {
+    let first = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+    let second = generate_ethernet_mock_packets(Some(DEFAULT_Q_TAG), DEFAULT_ETHER_TYPE);
+    let mut cursor = Cursor::new([first.clone(), second.clone()].concat());
+
+    let frame = read_frame(&mut cursor, first.len(), true).unwrap();
+    assert!(frame.header.q_tag.is_none());
+    let frame = read_frame(&mut cursor, second.len(), true).unwrap();
+    assert!(frame.header.q_tag.is_some());
+
+    assert!(matches!(
+        read_frame(&mut cursor, first.len(), true),
+        Err(ParserError::ExtractionError { .. })
+    ));
+}
+
+#[test]
+fn can_read_frames_with_custom_record_framing() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+    let length = (frame.len() as u16).to_be_bytes();
+    let buffer = [&length[..], &frame, &length, &frame].concat();
+
+    let reader = LengthFramedReader::new(Cursor::new(buffer), true, |reader| {
+        let mut length = [0u8; 2];
+        match reader.read_exact(&mut length) {
+            Ok(()) => Ok(Some(u16::from_be_bytes(length) as usize)),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e),
+        }
+    });
+
+    assert_eq!(reader.filter(|f| f.is_ok()).count(), 2);
+}
+
+#[test]
+fn maps_framing_callback_errors_to_extraction_errors() {
+    let mut reader = LengthFramedReader::new(Cursor::new(Vec::new()), true, |_| {
+        Err(io::Error::other("corrupt record header"))
+    });
+
+    assert!(matches!(
+        reader.next().unwrap(),
+        Err(ParserError::ExtractionError { .. })
+    ));
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn timestamped_reader_pairs_frames_with_record_timestamps() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+    let second_record = PCAP_GLOBAL_HEADER.len() + 16 + frame.len();
+    let mut capture = generate_pcap_capture(&[frame.clone(), frame]);
+    // The second record is stamped 1 second and 250 microseconds after the epoch.
+    capture[second_record + 4..second_record + 8].copy_from_slice(&250u32.to_le_bytes());
+
+    let reader = FrameReader::new(Cursor::new(capture), Framing::Pcap, true);
+    let timestamps: Vec<_> = reader
+        .timestamped()
+        .map(|frame| frame.unwrap().timestamp)
+        .collect();
+
+    assert_eq!(
+        timestamps,
+        [
+            SystemTime::UNIX_EPOCH,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1) + Duration::from_micros(250),
+        ]
+    );
+}
+
+#[test]
+fn fixed_length_frames_are_stamped_with_the_epoch() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+
+    let reader = FrameReader::new(
+        Cursor::new(frame.clone()),
+        Framing::FixedLength(frame.len()),
+        true,
+    );
+    let frames: Vec<_> = reader.timestamped().collect();
+
+    assert_eq!(frames.len(), 1);
+    assert_eq!(
+        frames[0].as_ref().unwrap().timestamp,
+        SystemTime::UNIX_EPOCH
+    );
+}
+
+#[test]
+fn written_pcap_records_read_back_unchanged() {
+    let frames = [
+        generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE),
+        generate_ethernet_mock_packets(Some(DEFAULT_Q_TAG), DEFAULT_ETHER_TYPE),
+    ]
+    .map(|frame| TimestampedFrame {
+        timestamp: SystemTime::UNIX_EPOCH + Duration::from_micros(1_700_000_000_000_250),
+        frame: EthernetFrame::from_bytes(&frame, false)
+            .unwrap()
+            .into_owned(),
+    });
+
+    let mut capture = Vec::new();
+    write_pcap_header(&mut capture).unwrap();
+    for frame in &frames {
+        write_pcap_record(&mut capture, &frame.frame, frame.timestamp).unwrap();
+    }
+    let read: Vec<_> = FrameReader::new(Cursor::new(capture), Framing::Pcap, false)
+        .timestamped()
+        .map(Result::unwrap)
+        .collect();
+
+    assert_eq!(read, frames);
+}
+
+#[test]
+fn writing_a_record_before_the_epoch_fails() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+    let frame = EthernetFrame::from_bytes(&frame, false).unwrap();
+    let ts = SystemTime::UNIX_EPOCH - Duration::from_secs(1);
+
+    let result = write_pcap_record(&mut Vec::new(), &frame, ts);
+
+    assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,193 @@
+mod mock_data;
+
+use mock_data::*;
+#[cfg(feature = "std")]
+use net_sift::parsers::frame_reader::{FrameReader, Framing};
+use net_sift::parsers::{
+    arp::ArpPacket,
+    definitions::{DeepParser, LayeredData, ParseOptions},
+    dns::{DnsMessage, DnsTcpMessage},
+    ethernet_frame::EthernetFrame,
+    gtp::GtpPacket,
+    icmp::IcmpPacket,
+    ipv4::Ipv4Packet,
+    ipv6::Ipv6Packet,
+    lldp::LldpPacket,
+    mpls::MplsPacket,
+    pppoe::PppoePacket,
+    reassembly::Ipv6FragmentHeader,
+    snmp::SnmpMessage,
+    tcp::TcpSegment,
+    udp::UdpDatagram,
+    view::scan_ethernet,
+    vxlan::VxlanPacket,
+};
+use proptest::prelude::*;
+
+#[cfg(feature = "std")]
+use std::io::Cursor;
+
+// The option sets every input is deep-parsed with.
+fn option_sets() -> Vec<ParseOptions> {
+    let defaults = ParseOptions::default();
+    vec![
+        defaults.clone(),
+        ParseOptions {
+            strict: true,
+            verify_checksums: true,
+            ..defaults.clone()
+        },
+        ParseOptions {
+            best_effort: true,
+            allow_truncated: true,
+            verify_checksums: true,
+            ..defaults
+        },
+    ]
+}
+
+// Serializes and finalizes whatever was parsed, as both walk the parsed layers.
+fn exercise(mut layered_data: LayeredData) {
+    layered_data.to_bytes();
+    layered_data.finalize_checksums();
+    layered_data.to_bytes();
+}
+
+// Runs every parser over `bytes`, which must return an error rather than panic.
+fn parse_everything(bytes: &[u8]) {
+    for fcs_enabled in [false, true] {
+        if let Ok(frame) = EthernetFrame::from_bytes(bytes, fcs_enabled) {
+            for options in option_sets() {
+                if let Ok(layered_data) = frame.clone().parse_next_layer_with(&options, 0) {
+                    exercise(layered_data);
+                }
+            }
+        }
+    }
+
+    if let Ok(packet) = Ipv4Packet::from_bytes(bytes) {
+        packet.options_iter().for_each(drop);
+        packet.to_bytes();
+        if let Ok(layered_data) = packet.parse_next_layer() {
+            exercise(layered_data);
+        }
+    }
+    if let Ok(packet) = Ipv6Packet::from_bytes(bytes) {
+        packet.to_bytes();
+        if let Ok(layered_data) = packet.parse_next_layer() {
+            exercise(layered_data);
+        }
+    }
+    if let Ok(segment) = TcpSegment::from_bytes(bytes) {
+        segment.options_iter().for_each(drop);
+        segment.to_bytes();
+        let _ = segment.parse_next_layer();
+    }
+    if let Ok(datagram) = UdpDatagram::from_bytes(bytes) {
+        datagram.to_bytes();
+        let _ = datagram.parse_next_layer();
+    }
+    if let Ok(packet) = IcmpPacket::from_bytes(bytes) {
+        let _ = packet.parse_next_layer();
+    }
+    if let Ok(packet) = MplsPacket::from_bytes(bytes) {
+        let _ = packet.parse_next_layer();
+    }
+    if let Ok(packet) = PppoePacket::from_bytes(bytes) {
+        let _ = packet.parse_next_layer();
+    }
+    if let Ok(packet) = VxlanPacket::from_bytes(bytes) {
+        let _ = packet.parse_next_layer();
+    }
+    if let Ok(packet) = GtpPacket::from_bytes(bytes) {
+        let _ = packet.parse_next_layer();
+    }
+    if let Ok(packet) = LldpPacket::from_bytes(bytes) {
+        let _ = packet.parse_next_layer();
+    }
+    if let Ok(message) = SnmpMessage::from_bytes(bytes) {
+        let _ = message.parse_next_layer();
+    }
+    if let Ok(message) = DnsMessage::from_bytes(bytes) {
+        message.to_bytes();
+        let _ = message.parse_next_layer();
+    }
+    DnsTcpMessage::parse_all(bytes)
+        .iter()
+        .for_each(|message| drop(message.to_bytes()));
+    let _ = ArpPacket::from_bytes(bytes);
+    if let Ok(view) = scan_ethernet(bytes) {
+        let _ = (view.vlan_id(), view.ip_protocol(), view.source_port());
+        let _ = (view.destination_port(), view.tcp_flags(), view.payload());
+    }
+    let _ = Ipv6FragmentHeader::from_bytes(bytes);
+}
+
+// Well-formed frames for the mutation strategy to start from, so that mutations reach
+// the layers nested deep within them.
+fn seed_frames() -> Vec<Vec<u8>> {
+    vec![
+        generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE),
+        generate_ethernet_mock_packets(Some(DEFAULT_Q_TAG), DEFAULT_ETHER_TYPE),
+        generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &generate_ipv4_tcp_with_valid_checksum()),
+        generate_padded_ethernet_frame(
+            IPV4_ETHER_TYPE,
+            &generate_ipv4_packet_with_options(&IPV4_ROUTE_OPTIONS),
+        ),
+        generate_padded_ethernet_frame(
+            MPLS_UNICAST_ETHER_TYPE,
+            &generate_mpls_mock_packet(&generate_ipv4_mock_packets(DEFAULT_UDP_PROTOCOL, None)),
+        ),
+        generate_padded_ethernet_frame(
+            PPPOE_SESSION_ETHER_TYPE,
+            &generate_pppoe_session_mock_packet(
+                PPP_PROTOCOL_IPV4,
+                &generate_ipv4_mock_packets(DEFAULT_ICMP_PROTOCOL, None),
+            ),
+        ),
+        generate_padded_ethernet_frame(LLDP_ETHER_TYPE, &generate_lldp_mock_packet()),
+        generate_padded_ethernet_frame(RARP_ETHER_TYPE, &generate_arp_mock_packet(3)),
+        generate_8023_mock_frame(&[&SNAP_LLC_HEADER[..], &[0, 0, 0, 8, 0]].concat()),
+        generate_nested_vxlan_mock_frame(2),
+    ]
+}
+
+fn mutated_frame() -> impl Strategy<Value = Vec<u8>> {
+    let seeds = seed_frames();
+    (
+        0..seeds.len(),
+        prop::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 0..8),
+        any::<prop::sample::Index>(),
+    )
+        .prop_map(move |(seed, writes, cut)| {
+            let mut frame = seeds[seed].clone();
+            for (index, byte) in writes {
+                let index = index.index(frame.len());
+                frame[index] = byte;
+            }
+            frame.truncate(cut.index(frame.len() + 1));
+            frame
+        })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2048))]
+
+    #[test]
+    fn never_panics_on_random_bytes(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+        parse_everything(&bytes);
+    }
+
+    #[test]
+    fn never_panics_on_mutated_frames(frame in mutated_frame()) {
+        parse_everything(&frame);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn never_panics_on_random_pcap_records(records in prop::collection::vec(any::<u8>(), 0..512)) {
+        let capture = [&PCAP_GLOBAL_HEADER[..], &records].concat();
+        let reader = FrameReader::new(Cursor::new(capture), Framing::Pcap, false);
+        reader.take(64).for_each(drop);
+    }
+}
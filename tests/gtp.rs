@@ -0,0 +1,196 @@
+mod mock_data;
+
+use mock_data::{
+    generate_gtp_mock_datagram, generate_gtp_mock_packet, generate_ipv4_packet,
+    generate_tcp_packets_without_options, set_ipv4_header_checksum, TCP,
+};
+use net_sift::parsers::{
+    definitions::{DeepParser, LayerKind, LayeredData, ParseOptions},
+    errors::ParserError,
+    gtp::{GtpExtensionHeader, GtpPacket},
+    udp::UdpDatagram,
+};
+
+fn inner_ipv4() -> Vec<u8> {
+    generate_ipv4_packet(TCP, &generate_tcp_packets_without_options())
+}
+
+#[test]
+fn can_parse_gtp_header() {
+    let ipv4 = inner_ipv4();
+    let packet = generate_gtp_mock_packet(&ipv4);
+
+    let gtp = GtpPacket::from_bytes(&packet).unwrap();
+
+    assert_eq!(gtp.header.version, 1);
+    assert_eq!(gtp.header.protocol_type, 1);
+    assert_eq!(gtp.header.flags, 0);
+    assert!(gtp.header.is_g_pdu());
+    assert_eq!(gtp.header.length as usize, ipv4.len());
+    assert_eq!(gtp.header.teid, 0x12345678);
+    assert_eq!(gtp.header.sequence_number, None);
+    assert_eq!(gtp.header.n_pdu_number, None);
+    assert_eq!(gtp.header.span, 0..8);
+    assert_eq!(gtp.data, Box::new(LayeredData::Payload(ipv4.into())));
+}
+
+#[test]
+fn can_parse_optional_fields_and_extension_headers() {
+    let packet = [
+        &[0x36, 0xFF, 0, 9, 0, 0, 0, 1][..], // E and S flags
+        &[0x01, 0x02, 0x07, 0x85],           // Sequence 258, ignored N-PDU number
+        &[0x01, 0x00, 0x09, 0x00],           // PDU session container, QFI 9
+        &[0xAB],
+    ]
+    .concat();
+
+    let header = GtpPacket::from_bytes(&packet).unwrap().header;
+
+    assert_eq!(header.flags, 0x06);
+    assert_eq!(header.sequence_number, Some(258));
+    assert_eq!(header.n_pdu_number, None);
+    assert_eq!(
+        header.extension_headers,
+        [GtpExtensionHeader {
+            extension_type: 0x85,
+            content: vec![0x00, 0x09],
+        }]
+    );
+    assert_eq!(header.span, 0..16);
+}
+
+#[test]
+fn ignores_next_extension_type_without_e_flag() {
+    // The S flag alone, with a nonzero next extension header type.
+    let packet = [0x32, 0xFF, 0, 5, 0, 0, 0, 1, 0, 7, 0, 0x85, 0xAB];
+
+    let gtp = GtpPacket::from_bytes(&packet).unwrap();
+
+    assert!(gtp.header.extension_headers.is_empty());
+    assert_eq!(gtp.header.sequence_number, Some(7));
+    assert_eq!(gtp.data, Box::new(LayeredData::Payload(vec![0xAB].into())));
+}
+
+#[test]
+fn deep_parses_ipv4_carried_by_g_pdu() {
+    let ipv4 = inner_ipv4();
+    let datagram = generate_gtp_mock_datagram(&generate_gtp_mock_packet(&ipv4));
+
+    let layers = UdpDatagram::from_bytes(&datagram)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    let kinds: Vec<_> = layers
+        .layers()
+        .filter_map(LayeredData::layer_kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            LayerKind::Udp,
+            LayerKind::Gtp,
+            LayerKind::Ipv4,
+            LayerKind::Tcp
+        ]
+    );
+}
+
+#[test]
+fn leaves_other_messages_raw() {
+    let mut packet = generate_gtp_mock_packet(&[]);
+    packet[1] = 1; // Echo request
+
+    let LayeredData::GtpData(gtp) = GtpPacket::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap()
+    else {
+        panic!("expected a GTP-U packet");
+    };
+
+    assert_eq!(gtp.data, Box::new(LayeredData::Payload(vec![].into())));
+}
+
+#[test]
+fn tunnelled_packets_count_towards_max_depth() {
+    let ipv4 = inner_ipv4();
+    let datagram = generate_gtp_mock_datagram(&generate_gtp_mock_packet(&ipv4));
+    let options = ParseOptions {
+        max_depth: 3,
+        ..Default::default()
+    };
+
+    let result = UdpDatagram::from_bytes(&datagram)
+        .unwrap()
+        .parse_next_layer_with(&options, 0);
+
+    assert!(matches!(result, Err(ParserError::MaxDepthExceeded(3))));
+}
+
+#[test]
+fn fails_if_length_runs_past_packet() {
+    let packet = generate_gtp_mock_packet(&[1, 2, 3, 4]);
+
+    let result = GtpPacket::from_bytes(&packet[..10]);
+
+    assert!(matches!(
+        result,
+        Err(ParserError::InconsistentLength {
+            declared: 4,
+            available: 2,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn fails_on_unsupported_version() {
+    let mut packet = generate_gtp_mock_packet(&[]);
+    packet[0] = 0x50; // Version 2
+
+    let result = GtpPacket::from_bytes(&packet);
+
+    assert!(matches!(result, Err(ParserError::MalformedMessage { .. })));
+}
+
+#[test]
+fn fails_on_zero_length_extension_header() {
+    let packet = [0x34, 0xFF, 0, 6, 0, 0, 0, 1, 0, 0, 0, 0x85, 0x00, 0x00];
+
+    let result = GtpPacket::from_bytes(&packet);
+
+    assert!(matches!(result, Err(ParserError::MalformedMessage { .. })));
+}
+
+#[test]
+fn serializes_header_and_payload() {
+    let mut ipv4 = inner_ipv4();
+    set_ipv4_header_checksum(&mut ipv4);
+    let datagram = generate_gtp_mock_datagram(&generate_gtp_mock_packet(&ipv4));
+
+    let layered_data = UdpDatagram::from_bytes(&datagram)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert_eq!(layered_data.to_bytes(), datagram);
+}
+
+#[test]
+fn serializes_optional_fields_and_extension_headers() {
+    let packet = [
+        &[0x36, 0xFF, 0, 9, 0, 0, 0, 1][..], // E and S flags
+        &[0x01, 0x02, 0x00, 0x85],           // Sequence 258
+        &[0x01, 0x00, 0x09, 0x00],           // PDU session container, QFI 9
+        &[0xAB],
+    ]
+    .concat();
+
+    let layered_data = GtpPacket::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert_eq!(layered_data.to_bytes(), packet);
+}
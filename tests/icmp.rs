@@ -1,11 +1,21 @@
 mod mock_data;
 
-use mock_data::ICMP_PACKETS;
+use mock_data::{
+    generate_icmp_time_exceeded, generate_ipv4_packet, set_ipv4_header_checksum,
+    ICMPV6_ECHO_REQUEST, ICMP_ECHO_REQUEST, ICMP_PACKETS, QUOTED_IPV4_UDP,
+};
 
 use net_sift::parsers::{
-    definitions::DeepParser, definitions::LayeredData, errors::ParserError, icmp::IcmpPacket,
+    definitions::DeepParser,
+    definitions::IPType,
+    definitions::LayeredData,
+    errors::ParserError,
+    icmp::{IcmpPacket, IcmpType, UnreachableCode},
+    ipv4::Ipv4Packet,
 };
 
+use std::net::{Ipv4Addr, Ipv6Addr};
+
 #[test]
 fn can_decode_icmp_packet() {
     let icmp_packet = IcmpPacket::from_bytes(&ICMP_PACKETS).unwrap();
@@ -14,7 +24,7 @@ fn can_decode_icmp_packet() {
     assert_eq!(icmp_packet.header.checksum, 24068);
     assert_eq!(
         icmp_packet.data,
-        Box::new(LayeredData::Payload([12, 10, 0, 5].to_vec()))
+        Box::new(LayeredData::Payload([12, 10, 0, 5].to_vec().into()))
     )
 }
 
@@ -32,6 +42,158 @@ fn can_parse_layered_data() {
 #[test]
 fn fails_if_packet_is_malformed() {
     let result = IcmpPacket::from_bytes(&[9, 12, 34, 5]);
-    let s = String::from("ICMP packet");
-    assert!(matches!(result, Err(ParserError::InvalidLength(s))))
+    assert!(
+        matches!(result, Err(ParserError::HeaderTooShort { layer, .. }) if layer == "ICMP packet")
+    )
+}
+
+#[test]
+fn can_verify_echo_request_checksum() {
+    let icmp_packet = IcmpPacket::from_bytes(&ICMP_ECHO_REQUEST).unwrap();
+
+    assert!(icmp_packet.verify_checksum(&ICMP_ECHO_REQUEST));
+}
+
+#[test]
+fn verifies_checksum_of_kept_raw_bytes() {
+    let icmp_packet = IcmpPacket::from_bytes_keep_raw(&ICMP_ECHO_REQUEST).unwrap();
+
+    assert_eq!(icmp_packet.raw.as_deref(), Some(&ICMP_ECHO_REQUEST[..]));
+    assert_eq!(icmp_packet.verify_stored_checksum(), Some(true));
+}
+
+#[test]
+fn fails_checksum_verification_if_payload_is_corrupted() {
+    let mut corrupted = ICMP_ECHO_REQUEST;
+    corrupted[20] ^= 0xFF;
+    let icmp_packet = IcmpPacket::from_bytes(&corrupted).unwrap();
+
+    assert!(!icmp_packet.verify_checksum(&corrupted));
+}
+
+#[test]
+fn can_verify_icmpv6_checksum_with_pseudo_header() {
+    let source: Ipv6Addr = "fe80::1".parse().unwrap();
+    let destination: Ipv6Addr = "fe80::2".parse().unwrap();
+    let icmp_packet = IcmpPacket::from_bytes(&ICMPV6_ECHO_REQUEST).unwrap();
+
+    assert!(icmp_packet.verify_checksum_v6(&ICMPV6_ECHO_REQUEST, source, destination));
+    // The same message is not valid between a different pair of hosts.
+    assert!(!icmp_packet.verify_checksum_v6(
+        &ICMPV6_ECHO_REQUEST,
+        destination,
+        "fe80::3".parse().unwrap()
+    ));
+}
+
+#[test]
+fn can_parse_packet_quoted_by_time_exceeded() {
+    let bytes = generate_icmp_time_exceeded(&QUOTED_IPV4_UDP);
+    let icmp_packet = IcmpPacket::from_bytes(&bytes).unwrap();
+    assert!(icmp_packet.is_error());
+
+    let packet = match icmp_packet.embedded_packet() {
+        LayeredData::Ipv4Data(packet) => packet,
+        other => panic!("Expected the quoted IPv4 packet, got {:?}", other),
+    };
+    assert_eq!(packet.header.source_address, Ipv4Addr::new(192, 168, 1, 10));
+    assert_eq!(packet.header.destination_address, Ipv4Addr::new(8, 8, 8, 8));
+    assert_eq!(packet.header.protocol, IPType::UDP);
+    assert_eq!(packet.header.time_to_live, 1);
+    // Only the first 8 bytes of the 40-byte payload are quoted.
+    assert_eq!(packet.header.total_length, 60);
+    assert_eq!(
+        packet.data,
+        Box::new(LayeredData::Payload(QUOTED_IPV4_UDP[20..].to_vec().into()))
+    );
+}
+
+#[test]
+fn embedded_packet_falls_back_to_raw_payload() {
+    let truncated = generate_icmp_time_exceeded(&QUOTED_IPV4_UDP[..12]);
+    let icmp_packet = IcmpPacket::from_bytes(&truncated).unwrap();
+    assert_eq!(
+        icmp_packet.embedded_packet(),
+        LayeredData::Payload(QUOTED_IPV4_UDP[..12].to_vec().into())
+    );
+
+    // Echo requests quote nothing.
+    let icmp_packet = IcmpPacket::from_bytes(&ICMP_ECHO_REQUEST).unwrap();
+    assert!(!icmp_packet.is_error());
+    assert_eq!(
+        icmp_packet.embedded_packet(),
+        LayeredData::Payload(ICMP_ECHO_REQUEST[8..].to_vec().into())
+    );
+}
+
+#[test]
+fn decodes_echo_request_identifier_and_sequence_number() {
+    let icmp_packet = IcmpPacket::from_bytes(&ICMP_ECHO_REQUEST).unwrap();
+
+    assert_eq!(
+        icmp_packet.decoded_type(),
+        IcmpType::EchoRequest {
+            identifier: 1,
+            sequence_number: 44
+        }
+    );
+}
+
+#[test]
+fn decodes_types_and_codes() {
+    let decode = |icmp_type: u8, icmp_code: u8| {
+        IcmpPacket::from_bytes(&[icmp_type, icmp_code, 0, 0, 0x12, 0x34, 0x56, 0x78])
+            .unwrap()
+            .decoded_type()
+    };
+
+    assert_eq!(
+        decode(0, 0),
+        IcmpType::EchoReply {
+            identifier: 0x1234,
+            sequence_number: 0x5678
+        }
+    );
+    assert_eq!(
+        decode(3, 3),
+        IcmpType::DestinationUnreachable(UnreachableCode::PortUnreachable)
+    );
+    assert_eq!(
+        decode(3, 4),
+        IcmpType::DestinationUnreachable(UnreachableCode::FragmentationNeeded)
+    );
+    assert_eq!(
+        decode(3, 9),
+        IcmpType::DestinationUnreachable(UnreachableCode::Other(9))
+    );
+    assert_eq!(decode(5, 1), IcmpType::Redirect { code: 1 });
+    assert_eq!(decode(11, 0), IcmpType::TimeExceeded { in_transit: true });
+    assert_eq!(decode(11, 1), IcmpType::TimeExceeded { in_transit: false });
+    assert_eq!(decode(42, 0), IcmpType::Other(42));
+}
+
+#[test]
+fn try_from_delegates_to_from_bytes() {
+    let converted: IcmpPacket = ICMP_PACKETS.as_slice().try_into().unwrap();
+
+    assert_eq!(converted, IcmpPacket::from_bytes(&ICMP_PACKETS).unwrap());
+    assert!(IcmpPacket::try_from(&ICMP_PACKETS[..4]).is_err());
+}
+
+#[test]
+fn serializes_icmp_carried_by_ipv4() {
+    let mut packet = generate_ipv4_packet(1, &ICMP_ECHO_REQUEST);
+    set_ipv4_header_checksum(&mut packet);
+
+    let layered_data = Ipv4Packet::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+    assert!(matches!(
+        layered_data.inner(),
+        Some(LayeredData::IcmpData(_))
+    ));
+
+    // The ICMP message used to be dropped, leaving the IPv4 header on its own.
+    assert_eq!(layered_data.to_bytes(), packet);
 }
@@ -0,0 +1,92 @@
+mod mock_data;
+
+use mock_data::{
+    generate_icmpv6_packet, ICMPV6_ECHO_REQUEST, LINK_LOCAL_DEST_ADDRESS, LINK_LOCAL_SRC_ADDRESS,
+};
+
+use net_sift::parsers::{
+    definitions::{DeepParser, IPType, LayerKind, LayeredData},
+    errors::ParserError,
+    icmpv6::{Icmpv6Packet, Icmpv6Type},
+    ipv6::Ipv6Packet,
+};
+
+use std::net::Ipv6Addr;
+
+#[test]
+fn can_parse_echo_request_carried_by_ipv6() {
+    let packet = generate_icmpv6_packet(&ICMPV6_ECHO_REQUEST);
+    let ipv6_packet = Ipv6Packet::from_bytes(&packet).unwrap();
+    assert_eq!(ipv6_packet.header.next_header, IPType::ICMPv6);
+
+    let layered_data = ipv6_packet.parse_next_layer().unwrap();
+    let icmpv6_packet = match layered_data.inner() {
+        Some(LayeredData::Icmpv6Data(icmpv6_packet)) => icmpv6_packet,
+        other => panic!("expected an ICMPv6 message, got {:?}", other),
+    };
+
+    assert_eq!(icmpv6_packet.header.icmp_type, 128);
+    assert_eq!(icmpv6_packet.header.icmp_code, 0);
+    assert_eq!(icmpv6_packet.header.checksum, 0xF118);
+    assert_eq!(
+        icmpv6_packet.decoded_type(),
+        Icmpv6Type::EchoRequest {
+            identifier: 1,
+            sequence_number: 1
+        }
+    );
+    assert!(!icmpv6_packet.is_error());
+    assert_eq!(icmpv6_packet.data.payload(), Some(&b"abcdefgh"[..]));
+    assert!(icmpv6_packet.verify_checksum(
+        &ICMPV6_ECHO_REQUEST,
+        Ipv6Addr::from(LINK_LOCAL_SRC_ADDRESS),
+        Ipv6Addr::from(LINK_LOCAL_DEST_ADDRESS)
+    ));
+
+    assert_eq!(
+        layered_data.inner().and_then(LayeredData::layer_kind),
+        Some(LayerKind::Icmpv6)
+    );
+    assert_eq!(layered_data.to_bytes(), packet);
+}
+
+#[test]
+fn recognizes_neighbor_discovery_messages() {
+    let expected = [
+        (133, Icmpv6Type::RouterSolicitation),
+        (134, Icmpv6Type::RouterAdvertisement),
+        (135, Icmpv6Type::NeighborSolicitation),
+        (136, Icmpv6Type::NeighborAdvertisement),
+        (137, Icmpv6Type::Redirect),
+    ];
+
+    for (icmp_type, decoded_type) in expected {
+        let bytes = [icmp_type, 0, 0, 0, 0, 0, 0, 0];
+        let icmpv6_packet = Icmpv6Packet::from_bytes(&bytes).unwrap();
+        assert_eq!(icmpv6_packet.decoded_type(), decoded_type);
+    }
+}
+
+#[test]
+fn decodes_packet_too_big_mtu() {
+    let icmpv6_packet = Icmpv6Packet::from_bytes(&[2, 0, 0, 0, 0, 0, 5, 220]).unwrap();
+
+    assert!(icmpv6_packet.is_error());
+    assert_eq!(
+        icmpv6_packet.decoded_type(),
+        Icmpv6Type::PacketTooBig { mtu: 1500 }
+    );
+    assert_eq!(icmpv6_packet.describe(), "packet too big mtu 1500");
+}
+
+#[test]
+fn fails_if_message_is_shorter_than_its_header() {
+    assert!(matches!(
+        Icmpv6Packet::from_bytes(&ICMPV6_ECHO_REQUEST[..7]),
+        Err(ParserError::HeaderTooShort {
+            got: 7,
+            need: 8,
+            ..
+        })
+    ));
+}
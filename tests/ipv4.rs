@@ -1,16 +1,20 @@
 mod mock_data;
 
 use mock_data::{
-    generate_ipv4_mock_packets, generate_tcp_packets_with_options, DEFAULT_IPV4_OPTIONS,
-    DEFAULT_TCP_PROTOCOL, MOCK_MALFORMED_PACKET,
+    generate_ipv4_mock_packets, generate_ipv4_packet, generate_ipv4_packet_with_options,
+    generate_tcp_packets_with_options, DEFAULT_DEST_ADDR, DEFAULT_IPV4_OPTIONS, DEFAULT_SRC_ADDR,
+    DEFAULT_TCP_PROTOCOL, IPV4_ROUTE_OPTIONS, MOCK_MALFORMED_PACKET,
 };
 use net_sift::parsers::{
-    definitions::{DeepParser, IPType, LayeredData},
+    definitions::{DeepParser, Ecn, IPType, LayeredData, ParseOptions},
     errors::ParserError,
-    ipv4::Ipv4Packet,
+    ipv4::{Ipv4Flags, Ipv4Option, Ipv4Packet, Ipv4PacketHeader},
 };
 
-use std::net::Ipv4Addr;
+use std::{
+    collections::HashSet,
+    net::{IpAddr, Ipv4Addr},
+};
 
 struct IPV4Values {
     expected_version: u8,
@@ -46,7 +50,7 @@ fn validate_ipv4(packet: Ipv4Packet, expected_packet: IPV4Values) {
     assert!(
         packet.data
             == Box::new(LayeredData::Payload(
-                expected_packet.expected_payload.to_vec()
+                expected_packet.expected_payload.to_vec().into()
             ))
     );
 }
@@ -107,8 +111,9 @@ fn can_parse_ipv4_packet_with_options() {
 #[test]
 fn fails_if_packet_is_malformed() {
     let result = Ipv4Packet::from_bytes(&MOCK_MALFORMED_PACKET);
-    let s = String::from("Ipv4 packet");
-    assert!(matches!(result, Err(ParserError::InvalidLength(s))))
+    assert!(
+        matches!(result, Err(ParserError::HeaderTooShort { layer, .. }) if layer == "Ipv4 packet")
+    )
 }
 
 #[test]
@@ -126,3 +131,436 @@ fn can_parse_layered_data() {
         _ => panic!("Invalid layered data"),
     };
 }
+
+#[test]
+fn can_round_trip_ipv4_packet() {
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, Some(&DEFAULT_IPV4_OPTIONS));
+    let mut ipv4 = Ipv4Packet::from_bytes(&packets).unwrap();
+
+    let bytes = ipv4.to_bytes();
+    let reparsed = Ipv4Packet::from_bytes(&bytes).unwrap();
+
+    // Only the checksum is recomputed; the mock packet carries an arbitrary value.
+    ipv4.header.header_checksum = reparsed.header.header_checksum;
+    ipv4.header.raw_header[10..12].copy_from_slice(&reparsed.header.header_checksum.to_be_bytes());
+    assert_eq!(reparsed, ipv4);
+    assert_eq!(bytes.len(), packets.len());
+
+    let header_length = reparsed.header.internet_header_length as usize * 4;
+    assert!(reparsed.verify_checksum(&bytes[..header_length]));
+    assert_eq!(reparsed.to_bytes(), bytes);
+}
+
+#[test]
+fn pads_options_when_serializing() {
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let mut ipv4 = Ipv4Packet::from_bytes(&packets).unwrap();
+    ipv4.header.options = Some(vec![1, 1, 1]);
+
+    let bytes = ipv4.to_bytes();
+    let reparsed = Ipv4Packet::from_bytes(&bytes).unwrap();
+
+    assert_eq!(reparsed.header.internet_header_length, 6);
+    assert_eq!(reparsed.header.options, Some(vec![1, 1, 1, 0]));
+    assert_eq!(reparsed.header.total_length as usize, packets.len() + 4);
+}
+
+#[test]
+fn fails_checksum_verification_if_header_is_corrupted() {
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let mut bytes = Ipv4Packet::from_bytes(&packets).unwrap().to_bytes();
+    bytes[8] ^= 0xFF; // Corrupt the TTL
+
+    let corrupted = Ipv4Packet::from_bytes(&bytes).unwrap();
+
+    assert!(!corrupted.verify_checksum(&bytes[..20]));
+}
+
+#[test]
+fn reports_absolute_spans_of_nested_headers() {
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, Some(&DEFAULT_IPV4_OPTIONS));
+    let layered_data = Ipv4Packet::from_bytes(&packets)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    // A 24 byte IPv4 header with options, followed by a 24 byte TCP header with options.
+    assert_eq!(layered_data.spans(), vec![0..24, 24..48]);
+
+    match layered_data.inner() {
+        Some(LayeredData::TcpData(segment)) => assert_eq!(segment.header.span, 0..24),
+        _ => panic!("Invalid nested layered data"),
+    }
+}
+
+#[test]
+fn exposes_raw_header_including_options() {
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, Some(&DEFAULT_IPV4_OPTIONS));
+    let ipv4 = Ipv4Packet::from_bytes(&packets).unwrap();
+
+    assert_eq!(ipv4.header.raw_header, packets[..24].to_vec());
+
+    // The serialized packet carries a valid checksum that can be checked without re-slicing.
+    let bytes = ipv4.to_bytes();
+    let reparsed = Ipv4Packet::from_bytes(&bytes).unwrap();
+    assert!(reparsed.verify_checksum(&reparsed.header.raw_header));
+}
+
+#[test]
+fn excludes_trailing_padding_from_payload() {
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let padded = [&packets[..], &[0; 6]].concat();
+
+    let ipv4 = Ipv4Packet::from_bytes(&padded).unwrap();
+
+    assert_eq!(ipv4, Ipv4Packet::from_bytes(&packets).unwrap());
+    assert_eq!(
+        ipv4.data,
+        Box::new(LayeredData::Payload(
+            generate_tcp_packets_with_options().into()
+        ))
+    );
+}
+
+#[test]
+fn fails_if_packet_is_shorter_than_total_length() {
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+
+    let result = Ipv4Packet::from_bytes(&packets[..packets.len() - 1]);
+
+    assert!(matches!(
+        result,
+        Err(ParserError::InconsistentLength { layer, declared, available })
+            if layer == "Ipv4 packet" && declared == packets.len() && available == packets.len() - 1
+    ));
+}
+
+#[test]
+fn fails_if_total_length_is_shorter_than_the_header() {
+    let mut packets = generate_ipv4_packet_with_options(&DEFAULT_IPV4_OPTIONS);
+    let header_length = ((packets[0] & 0x0F) * 4) as u16;
+
+    for total_length in 0..header_length {
+        packets[2..4].copy_from_slice(&total_length.to_be_bytes());
+
+        let result = Ipv4Packet::from_bytes(&packets);
+        assert!(
+            matches!(result, Err(ParserError::InvalidLength(ref s)) if s == "Ipv4 total length"),
+            "total length {total_length}: {result:?}"
+        );
+    }
+}
+
+#[test]
+fn matches_source_or_destination_host() {
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let ipv4_packet = Ipv4Packet::from_bytes(&packets).unwrap();
+
+    assert!(ipv4_packet.matches_host(Ipv4Addr::from(DEFAULT_SRC_ADDR)));
+    assert!(ipv4_packet.matches_host(Ipv4Addr::from(DEFAULT_DEST_ADDR)));
+    assert!(!ipv4_packet.matches_host(Ipv4Addr::new(100, 127, 60, 6)));
+}
+
+#[test]
+fn matches_subnet_by_prefix() {
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let ipv4_packet = Ipv4Packet::from_bytes(&packets).unwrap();
+
+    // The source is 100.127.60.5 and the destination 30.44.8.50.
+    assert!(ipv4_packet.matches_subnet(Ipv4Addr::new(100, 64, 0, 0), 10));
+    assert!(ipv4_packet.matches_subnet(Ipv4Addr::new(30, 44, 8, 0), 24));
+    assert!(ipv4_packet.matches_subnet(Ipv4Addr::new(30, 44, 8, 50), 32));
+    assert!(ipv4_packet.matches_subnet(Ipv4Addr::new(192, 168, 0, 0), 0));
+    assert!(!ipv4_packet.matches_subnet(Ipv4Addr::new(100, 128, 0, 0), 10));
+    assert!(!ipv4_packet.matches_subnet(Ipv4Addr::new(30, 44, 8, 51), 40));
+}
+
+#[test]
+fn layered_data_involves_ip_of_any_ipv4_layer() {
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, Some(&DEFAULT_IPV4_OPTIONS));
+    let layered_data = Ipv4Packet::from_bytes(&packets)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert!(layered_data.involves_ip(IpAddr::from(DEFAULT_DEST_ADDR)));
+    assert!(!layered_data.involves_ip(IpAddr::from([10, 0, 0, 1])));
+    // An IPv6 address never matches an IPv4 layer.
+    assert!(!layered_data.involves_ip(IpAddr::V6(
+        Ipv4Addr::from(DEFAULT_SRC_ADDR).to_ipv6_mapped()
+    )));
+}
+
+#[test]
+fn layered_data_exposes_addresses_and_ports() {
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let layered_data = Ipv4Packet::from_bytes(&packets)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert_eq!(
+        layered_data.source_ip(),
+        Some(IpAddr::from(DEFAULT_SRC_ADDR))
+    );
+    assert_eq!(
+        layered_data.destination_ip(),
+        Some(IpAddr::from(DEFAULT_DEST_ADDR))
+    );
+    assert_eq!(layered_data.source_port(), Some(53145));
+    assert_eq!(layered_data.destination_port(), Some(80));
+
+    let payload = LayeredData::Payload(packets.into());
+    assert_eq!(payload.source_ip(), None);
+    assert_eq!(payload.destination_port(), None);
+}
+
+#[test]
+fn strict_mode_checks_version_and_reserved_flag() {
+    let strict = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let strict_parse = |packets: &[u8]| {
+        Ipv4Packet::from_bytes(packets)
+            .unwrap()
+            .parse_next_layer_with(&strict, 0)
+            .map(LayeredData::into_owned)
+    };
+
+    // The mock packet carries version 8 and has the reserved flag set.
+    let mut packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    assert!(Ipv4Packet::from_bytes(&packets)
+        .unwrap()
+        .parse_next_layer()
+        .is_ok());
+    assert!(matches!(
+        strict_parse(&packets),
+        Err(ParserError::ProtocolViolation { field, .. }) if field == "Ipv4 version"
+    ));
+
+    packets[0] = 0x45;
+    assert!(matches!(
+        strict_parse(&packets),
+        Err(ParserError::ProtocolViolation { field, .. }) if field == "Ipv4 flags"
+    ));
+
+    packets[6] &= 0x7F;
+    assert!(strict_parse(&packets).is_ok());
+}
+
+#[test]
+fn best_effort_keeps_ipv4_layer_over_a_short_tcp_segment() {
+    // A valid IPv4 header followed by only 10 bytes of a TCP segment.
+    let mut packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    packets.truncate(30);
+    packets[2..4].copy_from_slice(&30u16.to_be_bytes());
+
+    let result = Ipv4Packet::from_bytes(&packets).unwrap().parse_next_layer();
+    assert!(
+        matches!(result, Err(ParserError::HeaderTooShort { layer, .. }) if layer == "TCP segment")
+    );
+
+    let layered_data = Ipv4Packet::from_bytes(&packets)
+        .unwrap()
+        .parse_best_effort()
+        .unwrap();
+    match layered_data {
+        LayeredData::Ipv4Data(packet) => {
+            assert_eq!(packet.header.protocol, IPType::TCP);
+            assert_eq!(
+                packet.data,
+                Box::new(LayeredData::Payload(packets[20..].to_vec().into()))
+            );
+        }
+        other => panic!("Expected the IPv4 layer, got {:?}", other),
+    }
+}
+
+#[test]
+fn equal_headers_hash_alike() {
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, Some(&DEFAULT_IPV4_OPTIONS));
+    let mut headers = HashSet::new();
+    headers.insert(Ipv4Packet::from_bytes(&packets).unwrap().header);
+    headers.insert(Ipv4Packet::from_bytes(&packets).unwrap().header);
+    assert_eq!(headers.len(), 1);
+
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    headers.insert(Ipv4Packet::from_bytes(&packets).unwrap().header);
+    assert_eq!(headers.len(), 2);
+}
+
+#[test]
+fn iterates_decoded_options() {
+    let packets = generate_ipv4_packet_with_options(&IPV4_ROUTE_OPTIONS);
+    let ipv4 = Ipv4Packet::from_bytes(&packets).unwrap();
+
+    let options: Vec<Ipv4Option> = ipv4.options_iter().map(Result::unwrap).collect();
+    assert_eq!(
+        options,
+        vec![
+            Ipv4Option::RecordRoute {
+                pointer: 8,
+                addresses: vec![Ipv4Addr::new(10, 0, 0, 1)],
+            },
+            Ipv4Option::RouterAlert(0),
+            Ipv4Option::EndOfOptionList,
+        ]
+    );
+    assert_eq!(
+        ipv4.data.payload().unwrap(),
+        generate_tcp_packets_with_options()
+    );
+}
+
+#[test]
+fn options_iter_reports_malformed_options_per_item() {
+    // The mock options declare 44 bytes where only 4 remain.
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, Some(&DEFAULT_IPV4_OPTIONS));
+    let ipv4 = Ipv4Packet::from_bytes(&packets).unwrap();
+    let options: Vec<_> = ipv4.options_iter().collect();
+    assert!(matches!(
+        options[..],
+        [Err(ParserError::OptionsOverrun { declared: 44, .. })]
+    ));
+
+    // A record route option whose address is cut short, followed by a no-operation.
+    let packets = generate_ipv4_packet_with_options(&[7, 6, 4, 10, 0, 0, 1, 0]);
+    let ipv4 = Ipv4Packet::from_bytes(&packets).unwrap();
+    let mut options = ipv4.options_iter();
+    assert!(matches!(
+        options.next(),
+        Some(Err(ParserError::InvalidLength(_)))
+    ));
+    assert_eq!(options.next().unwrap().unwrap(), Ipv4Option::NoOperation);
+}
+
+#[test]
+fn splits_type_of_service_into_dscp_and_ecn() {
+    let mut packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    // Expedited Forwarding (DSCP 46) with ECT(1).
+    packets[1] = 0xB9;
+    let header = Ipv4Packet::from_bytes(&packets).unwrap().header;
+
+    assert_eq!(header.type_of_service, 0xB9);
+    assert_eq!(header.dscp(), 46);
+    assert_eq!(header.ecn(), Ecn::Ect1);
+
+    packets[1] = 0x03;
+    let header = Ipv4Packet::from_bytes(&packets).unwrap().header;
+    assert_eq!((header.dscp(), header.ecn()), (0, Ecn::Ce));
+}
+
+#[test]
+fn default_header_serializes_to_a_minimal_valid_packet() {
+    let packet = Ipv4Packet {
+        header: Ipv4PacketHeader {
+            source_address: Ipv4Addr::from(DEFAULT_SRC_ADDR),
+            destination_address: Ipv4Addr::from(DEFAULT_DEST_ADDR),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let bytes = packet.to_bytes();
+    assert_eq!(bytes.len(), 20);
+    assert_eq!(bytes[0], 0x45);
+
+    let reparsed = Ipv4Packet::from_bytes(&bytes).unwrap();
+    assert!(reparsed.verify_checksum(&reparsed.header.raw_header));
+    assert_eq!(reparsed.header.time_to_live, 64);
+    assert_eq!(reparsed.header.protocol, IPType::TCP);
+    assert_eq!(reparsed.header.total_length, packet.header.total_length);
+    assert_eq!(
+        reparsed.header.source_address,
+        Ipv4Addr::from(DEFAULT_SRC_ADDR)
+    );
+    assert_eq!(
+        reparsed.header.destination_address,
+        Ipv4Addr::from(DEFAULT_DEST_ADDR)
+    );
+}
+
+#[test]
+fn parses_ipv4_tunnelled_in_ipv4() {
+    let inner = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let outer = generate_ipv4_packet(4, &inner);
+
+    let packet = Ipv4Packet::from_bytes(&outer).unwrap();
+    assert_eq!(packet.header.protocol, IPType::IPv4);
+
+    let LayeredData::Ipv4Data(outer_packet) = packet.parse_next_layer().unwrap() else {
+        panic!("expected an IPv4 packet");
+    };
+    let LayeredData::Ipv4Data(inner_packet) = *outer_packet.data else {
+        panic!("expected a tunnelled IPv4 packet");
+    };
+    assert_eq!(inner_packet.header.protocol, IPType::TCP);
+    assert_eq!(inner_packet.header.total_length as usize, inner.len());
+    assert!(matches!(*inner_packet.data, LayeredData::TcpData(_)));
+}
+
+#[test]
+fn tunnelled_packets_count_towards_max_depth() {
+    let inner = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let outer = generate_ipv4_packet(4, &generate_ipv4_packet(4, &inner));
+    let options = ParseOptions {
+        max_depth: 2,
+        ..Default::default()
+    };
+
+    let result = Ipv4Packet::from_bytes(&outer)
+        .unwrap()
+        .parse_next_layer_with(&options, 0);
+
+    assert!(matches!(result, Err(ParserError::MaxDepthExceeded(2))));
+}
+
+#[test]
+fn payload_slice_borrows_the_owned_payload() {
+    // A packet with options, followed by padding beyond its total length.
+    let mut packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, Some(&DEFAULT_IPV4_OPTIONS));
+    packets.extend_from_slice(&[0; 6]);
+    let packet = Ipv4Packet::from_bytes(&packets).unwrap();
+
+    let slice = packet.payload_slice(&packets);
+    assert_eq!(Some(slice), packet.data.payload());
+    assert_eq!(slice, generate_tcp_packets_with_options());
+}
+
+#[test]
+fn try_from_delegates_to_from_bytes() {
+    let packets = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+
+    let converted: Ipv4Packet = packets.as_slice().try_into().unwrap();
+
+    assert_eq!(converted, Ipv4Packet::from_bytes(&packets).unwrap());
+    assert!(Ipv4Packet::try_from(&packets[..10]).is_err());
+}
+
+#[test]
+fn decodes_dont_fragment_flag() {
+    let mut packets = generate_ipv4_packet(6, &[]);
+    packets[6] = 0x40; // DF, fragment offset 0
+
+    let header = Ipv4Packet::from_bytes(&packets).unwrap().header;
+
+    assert_eq!(
+        header.flags_decoded(),
+        Ipv4Flags {
+            reserved: false,
+            dont_fragment: true,
+            more_fragments: false,
+        }
+    );
+    assert_eq!(u8::from(header.flags_decoded()), header.flags);
+}
+
+#[test]
+fn flags_round_trip_through_bits() {
+    for bits in 0..8 {
+        assert_eq!(u8::from(Ipv4Flags::from_bits(bits)), bits);
+    }
+    assert!(Ipv4Flags::from_bits(0b001).more_fragments);
+    assert!(Ipv4Flags::from_bits(0b100).reserved);
+}
@@ -1,16 +1,19 @@
 mod mock_data;
 
 use mock_data::{
-    generate_ipv6_mock_packet, generate_tcp_packets_with_options, DEFAULT_DEST_ADDRESS,
-    DEFAULT_SRC_ADDRESS, MOCK_MALFORMED_PACKET,
+    generate_ipv6_jumbogram, generate_ipv6_mock_packet, generate_tcp_packets_with_options,
+    DEFAULT_DEST_ADDRESS, DEFAULT_SRC_ADDRESS, MOCK_MALFORMED_PACKET,
 };
 use net_sift::parsers::{
-    definitions::{DeepParser, IPType, LayeredData},
+    definitions::{DeepParser, Ecn, IPType, LayeredData, ParseOptions},
     errors::ParserError,
-    ipv6::{Ipv6Packet, Ipv6PacketHeader},
+    ipv6::{self, Ipv6ExtensionHeader, Ipv6MulticastScope, Ipv6Packet, Ipv6PacketHeader},
 };
 
-use std::net::Ipv6Addr;
+use std::{
+    collections::HashSet,
+    net::{IpAddr, Ipv6Addr},
+};
 
 struct IPv6Values {
     expected_version: u8,
@@ -22,10 +25,11 @@ struct IPv6Values {
     expected_source_address: Ipv6Addr,
     expected_destination_address: Ipv6Addr,
     expected_payload: Vec<u8>,
+    expected_raw_header: Vec<u8>,
 }
 
-impl From<IPv6Values> for Ipv6Packet {
-    fn from(value: IPv6Values) -> Ipv6Packet {
+impl From<IPv6Values> for Ipv6Packet<'static> {
+    fn from(value: IPv6Values) -> Ipv6Packet<'static> {
         Ipv6Packet {
             header: Ipv6PacketHeader {
                 version: value.expected_version,
@@ -36,9 +40,13 @@ impl From<IPv6Values> for Ipv6Packet {
                 hop_limit: value.expected_hop_limit,
                 source_address: value.expected_source_address,
                 destination_address: value.expected_destination_address,
+                extension_headers: Vec::new(),
+                span: 0..40,
+                raw_header: value.expected_raw_header,
             },
 
-            data: Box::new(LayeredData::Payload(value.expected_payload)),
+            data: Box::new(LayeredData::Payload(value.expected_payload.into())),
+            truncated: false,
         }
     }
 }
@@ -48,12 +56,13 @@ fn expected_ipv6() -> IPv6Values {
         expected_version: 6,
         expected_traffic_class: 167,
         expected_flow_label: 662527,
-        expected_payload_length: 10,
+        expected_payload_length: 32,
         expected_next_header: IPType::from(6),
         expected_hop_limit: 100,
         expected_source_address: addr(&DEFAULT_SRC_ADDRESS),
         expected_destination_address: addr(&DEFAULT_DEST_ADDRESS),
         expected_payload: generate_tcp_packets_with_options(),
+        expected_raw_header: generate_ipv6_mock_packet()[..40].to_vec(),
     }
 }
 
@@ -86,8 +95,9 @@ fn can_parse_ipv6_packet() {
 fn fail_if_packet_is_too_short() {
     let result = Ipv6Packet::from_bytes(&MOCK_MALFORMED_PACKET);
 
-    let s = String::from("Ipv6 packet");
-    assert!(matches!(result, Err(ParserError::InvalidLength(s))))
+    assert!(
+        matches!(result, Err(ParserError::HeaderTooShort { layer, .. }) if layer == "Ipv6 packet")
+    )
 }
 
 #[test]
@@ -105,3 +115,316 @@ fn can_parse_layered_data() {
         _ => panic!("Invalid layered data"),
     };
 }
+
+#[test]
+fn can_round_trip_ipv6_packet() {
+    let packets = generate_ipv6_mock_packet();
+    let ipv6 = Ipv6Packet::from_bytes(&packets).unwrap();
+
+    let bytes = ipv6.to_bytes();
+
+    assert_eq!(bytes, packets);
+    assert_eq!(Ipv6Packet::from_bytes(&bytes).unwrap(), ipv6);
+}
+
+#[test]
+fn recomputes_payload_length_when_serializing() {
+    let packets = generate_ipv6_mock_packet();
+    let mut ipv6 = Ipv6Packet::from_bytes(&packets).unwrap();
+    *ipv6.data = LayeredData::Payload(vec![1, 2, 3].into());
+
+    let bytes = ipv6.to_bytes();
+    let reparsed = Ipv6Packet::from_bytes(&bytes).unwrap();
+
+    assert_eq!(reparsed.header.payload_length, 3);
+    assert_eq!(reparsed.header.traffic_class, ipv6.header.traffic_class);
+    assert_eq!(reparsed.header.flow_label, ipv6.header.flow_label);
+}
+
+#[test]
+fn matches_source_or_destination_host() {
+    let bytes = generate_ipv6_mock_packet();
+    let ipv6_packet = Ipv6Packet::from_bytes(&bytes).unwrap();
+
+    assert!(ipv6_packet.matches_host(Ipv6Addr::from(DEFAULT_SRC_ADDRESS)));
+    assert!(ipv6_packet.matches_host(Ipv6Addr::from(DEFAULT_DEST_ADDRESS)));
+    assert!(!ipv6_packet.matches_host(Ipv6Addr::LOCALHOST));
+}
+
+#[test]
+fn layered_data_exposes_addresses_and_ports() {
+    let bytes = generate_ipv6_mock_packet();
+    let layered_data = Ipv6Packet::from_bytes(&bytes)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert_eq!(
+        layered_data.source_ip(),
+        Some(IpAddr::from(DEFAULT_SRC_ADDRESS))
+    );
+    assert_eq!(
+        layered_data.destination_ip(),
+        Some(IpAddr::from(DEFAULT_DEST_ADDRESS))
+    );
+    assert_eq!(layered_data.source_port(), Some(53145));
+    assert_eq!(layered_data.destination_port(), Some(80));
+}
+
+#[test]
+fn matches_subnet_by_prefix() {
+    let bytes = generate_ipv6_mock_packet();
+    let ipv6_packet = Ipv6Packet::from_bytes(&bytes).unwrap();
+
+    // The destination is a5a:17b:2b0c:64ff:ffff:ff15:2d64:c0c.
+    let destination_net: Ipv6Addr = "a5a:17b::".parse().unwrap();
+    assert!(ipv6_packet.matches_subnet(destination_net, 32));
+    assert!(ipv6_packet.matches_subnet(Ipv6Addr::from(DEFAULT_SRC_ADDRESS), 128));
+    assert!(ipv6_packet.matches_subnet(Ipv6Addr::LOCALHOST, 0));
+    assert!(!ipv6_packet.matches_subnet(destination_net, 48));
+    assert!(!ipv6_packet.matches_subnet("fe80::".parse().unwrap(), 10));
+}
+
+#[test]
+fn bounds_jumbogram_payload_by_jumbo_option() {
+    let data = vec![7; 70_000];
+    let mut packets = generate_ipv6_jumbogram(&data);
+    // The Hop-by-Hop header carrying the jumbo option is parsed as an extension header.
+    let jumbo_payload = packets[48..].to_vec();
+    packets.extend_from_slice(&[0; 6]);
+
+    let ipv6_packet = Ipv6Packet::from_bytes(&packets).unwrap();
+
+    assert_eq!(ipv6_packet.header.payload_length, 0);
+    assert_eq!(ipv6_packet.header.next_header, IPType::Other(0));
+    assert_eq!(ipv6_packet.header.extension_headers.len(), 1);
+    assert_eq!(
+        *ipv6_packet.data,
+        LayeredData::Payload(jumbo_payload.into())
+    );
+    assert_eq!(ipv6_packet.to_bytes(), packets[..packets.len() - 6]);
+}
+
+#[test]
+fn fails_if_jumbogram_is_truncated() {
+    let packets = generate_ipv6_jumbogram(&[7; 70_000]);
+
+    let result = Ipv6Packet::from_bytes(&packets[..packets.len() - 1]);
+
+    assert!(matches!(
+        result,
+        Err(ParserError::InconsistentLength {
+            declared: 70_008,
+            available: 70_007,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn fails_if_zero_payload_length_has_no_jumbo_option() {
+    let mut packets = generate_ipv6_mock_packet();
+    packets[4..6].copy_from_slice(&[0, 0]);
+
+    let result = Ipv6Packet::from_bytes(&packets);
+
+    assert!(matches!(
+        result,
+        Err(ParserError::InconsistentLength {
+            declared: 0,
+            available: 32,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn excludes_trailing_padding_from_payload() {
+    let packets = generate_ipv6_mock_packet();
+    let padded = [&packets[..], &[0; 4]].concat();
+
+    let ipv6_packet = Ipv6Packet::from_bytes(&padded).unwrap();
+
+    assert_eq!(
+        *ipv6_packet.data,
+        LayeredData::Payload(packets[40..].to_vec().into())
+    );
+}
+
+#[test]
+fn stops_at_payload_length_when_packets_are_concatenated() {
+    let packets = generate_ipv6_mock_packet();
+    let concatenated = [&packets[..], &packets[..]].concat();
+
+    let ipv6_packet = Ipv6Packet::from_bytes(&concatenated).unwrap();
+
+    let payload = ipv6_packet.data.payload().unwrap();
+    assert_eq!(payload.len(), ipv6_packet.header.payload_length as usize);
+    assert_eq!(payload, &packets[40..]);
+}
+
+#[test]
+fn walks_extension_headers_to_the_transport_layer() {
+    let packets = generate_ipv6_mock_packet();
+    // A Hop-by-Hop header holding a PadN option, followed by the original TCP segment.
+    let hop_by_hop = [6, 0, 1, 4, 0, 0, 0, 0];
+    let mut with_extension = [&packets[..40], &hop_by_hop, &packets[40..]].concat();
+    with_extension[4..6].copy_from_slice(&40u16.to_be_bytes());
+    with_extension[6] = 0;
+
+    let ipv6_packet = Ipv6Packet::from_bytes(&with_extension).unwrap();
+
+    assert_eq!(
+        ipv6_packet.header.extension_headers,
+        vec![Ipv6ExtensionHeader {
+            header_type: 0,
+            next_header: 6,
+            length: 8,
+            raw_header: hop_by_hop.to_vec(),
+        }]
+    );
+    assert_eq!(ipv6_packet.header.upper_layer_protocol(), IPType::TCP);
+    assert_eq!(ipv6_packet.header.span, 0..48);
+    assert_eq!(ipv6_packet.to_bytes(), with_extension);
+
+    let layered_data = ipv6_packet.parse_next_layer().unwrap();
+    assert_eq!(layered_data.destination_port(), Some(80));
+}
+
+#[test]
+fn fails_if_extension_header_overruns_payload() {
+    let mut packets = generate_ipv6_mock_packet();
+    // A Destination Options header claiming 256 bytes in a 32-byte payload.
+    packets[6] = 60;
+    packets[41] = 31;
+
+    let result = Ipv6Packet::from_bytes(&packets);
+
+    assert!(matches!(
+        result,
+        Err(ParserError::InconsistentLength {
+            declared: 256,
+            available: 32,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn strict_mode_checks_version() {
+    let strict = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+
+    let mut packets = generate_ipv6_mock_packet();
+    let packet = Ipv6Packet::from_bytes(&packets).unwrap();
+    assert!(packet.parse_next_layer_with(&strict, 0).is_ok());
+
+    packets[0] = (packets[0] & 0x0F) | 0x70;
+    let packet = Ipv6Packet::from_bytes(&packets).unwrap();
+    assert!(packet.clone().parse_next_layer().is_ok());
+    assert!(matches!(
+        packet.parse_next_layer_with(&strict, 0),
+        Err(ParserError::ProtocolViolation { field, .. }) if field == "Ipv6 version"
+    ));
+}
+
+#[test]
+fn equal_headers_hash_alike() {
+    let mut headers = HashSet::new();
+    headers.insert(
+        Ipv6Packet::from_bytes(&generate_ipv6_mock_packet())
+            .unwrap()
+            .header,
+    );
+    headers.insert(
+        Ipv6Packet::from_bytes(&generate_ipv6_mock_packet())
+            .unwrap()
+            .header,
+    );
+
+    assert_eq!(headers.len(), 1);
+}
+
+#[test]
+fn classifies_special_addresses() {
+    let link_local: Ipv6Addr = "fe80::1c2d:3eff:fe4f:5a6b".parse().unwrap();
+    let all_nodes: Ipv6Addr = "ff02::1".parse().unwrap();
+    let solicited_node: Ipv6Addr = "ff02::1:ff4f:5a6b".parse().unwrap();
+    let documentation: Ipv6Addr = "2001:db8::1".parse().unwrap();
+    let unique_local: Ipv6Addr = "fd12:3456::1".parse().unwrap();
+
+    assert!(ipv6::is_link_local(&link_local));
+    assert!(ipv6::is_link_local(&"febf::1".parse().unwrap()));
+    assert!(!ipv6::is_link_local(&"fec0::1".parse().unwrap()));
+    assert!(!ipv6::is_multicast(&link_local));
+
+    assert!(ipv6::is_multicast(&all_nodes));
+    assert!(!ipv6::is_solicited_node_multicast(&all_nodes));
+    assert!(ipv6::is_solicited_node_multicast(&solicited_node));
+    assert!(!ipv6::is_link_local(&all_nodes));
+
+    assert!(ipv6::is_loopback(&Ipv6Addr::LOCALHOST));
+    assert!(ipv6::is_unspecified(&Ipv6Addr::UNSPECIFIED));
+    assert!(!ipv6::is_loopback(&Ipv6Addr::UNSPECIFIED));
+
+    assert!(ipv6::is_documentation(&documentation));
+    assert!(ipv6::is_unique_local(&unique_local));
+    assert!(!ipv6::is_unique_local(&documentation));
+}
+
+#[test]
+fn maps_next_header_through_ip_type() {
+    let mut packets = generate_ipv6_mock_packet();
+    assert_eq!(packets[6], 6);
+
+    let ipv6_packet = Ipv6Packet::from_bytes(&packets).unwrap();
+    assert_eq!(ipv6_packet.header.next_header, IPType::TCP);
+    assert!(matches!(
+        ipv6_packet.parse_next_layer().unwrap(),
+        LayeredData::Ipv6Data(packet) if matches!(*packet.data, LayeredData::TcpData(_))
+    ));
+
+    // Protocols without a variant keep their raw value, which is written back unchanged.
+    packets[6] = 132;
+    let ipv6_packet = Ipv6Packet::from_bytes(&packets).unwrap();
+    assert_eq!(ipv6_packet.header.next_header, IPType::Other(132));
+    assert_eq!(u8::from(&ipv6_packet.header.next_header), 132);
+    assert_eq!(ipv6_packet.to_bytes(), packets);
+}
+
+#[test]
+fn reports_the_scope_of_multicast_addresses() {
+    let scope = |address: &str| ipv6::multicast_scope(&address.parse().unwrap());
+
+    assert_eq!(scope("ff01::1"), Some(Ipv6MulticastScope::InterfaceLocal));
+    assert_eq!(scope("ff02::1"), Some(Ipv6MulticastScope::LinkLocal));
+    assert_eq!(scope("ff05::2"), Some(Ipv6MulticastScope::SiteLocal));
+    assert_eq!(scope("ff0e::101"), Some(Ipv6MulticastScope::Global));
+    assert_eq!(scope("ff1f::1"), Some(Ipv6MulticastScope::Other(0xF)));
+    assert_eq!(scope("fe80::1"), None);
+}
+
+#[test]
+fn splits_traffic_class_into_dscp_and_ecn() {
+    let mut packets = generate_ipv6_mock_packet();
+    // Traffic class 0x2A: Assured Forwarding 11 (DSCP 10) with ECT(0).
+    packets[0] = 0x62;
+    packets[1] = 0xA0 | (packets[1] & 0x0F);
+    let header = Ipv6Packet::from_bytes(&packets).unwrap().header;
+
+    assert_eq!(header.traffic_class, 0x2A);
+    assert_eq!(header.dscp(), 10);
+    assert_eq!(header.ecn(), Ecn::Ect0);
+}
+
+#[test]
+fn try_from_delegates_to_from_bytes() {
+    let packets = generate_ipv6_mock_packet();
+
+    let converted: Ipv6Packet = packets.as_slice().try_into().unwrap();
+
+    assert_eq!(converted, Ipv6Packet::from_bytes(&packets).unwrap());
+    assert!(Ipv6Packet::try_from(&MOCK_MALFORMED_PACKET[..]).is_err());
+}
@@ -0,0 +1,119 @@
+mod mock_data;
+
+use mock_data::{
+    generate_lldp_mock_packet, DEFAULT_DEST_MAC, DEFAULT_SRC_MAC, LLDP_CHASSIS_ID_TLV,
+    LLDP_END_TLV, LLDP_ETHER_TYPE, LLDP_PORT_DESCRIPTION_TLV, LLDP_PORT_ID_TLV,
+    LLDP_SYSTEM_NAME_TLV, LLDP_TTL_TLV,
+};
+use net_sift::parsers::{
+    definitions::{DeepParser, EtherType, LayeredData},
+    errors::ParserError,
+    ethernet_frame::EthernetFrame,
+    lldp::{LldpId, LldpPacket, LldpTlv},
+};
+
+#[test]
+fn can_parse_lldp_packet() {
+    let packet = generate_lldp_mock_packet();
+
+    let lldp = LldpPacket::from_bytes(&packet).unwrap();
+
+    assert_eq!(
+        lldp.chassis_id,
+        LldpId {
+            subtype: 4,
+            id: DEFAULT_DEST_MAC.to_vec(),
+        }
+    );
+    assert_eq!(
+        lldp.port_id,
+        LldpId {
+            subtype: 5,
+            id: b"eth0".to_vec(),
+        }
+    );
+    assert_eq!(lldp.time_to_live, 120);
+    assert_eq!(lldp.system_name.as_deref(), Some("switch1"));
+    assert_eq!(lldp.system_description, None);
+    assert_eq!(
+        lldp.other_tlvs,
+        vec![LldpTlv {
+            tlv_type: 4,
+            value: b"uplink".to_vec(),
+        }]
+    );
+    assert_eq!(lldp.span, 0..packet.len());
+}
+
+#[test]
+fn stops_at_end_of_lldpdu() {
+    let packet = [&generate_lldp_mock_packet()[..], &[10, 2, 0, 0, 0, 0]].concat();
+
+    let lldp = LldpPacket::from_bytes(&packet).unwrap();
+
+    assert_eq!(lldp.span, 0..packet.len() - 6);
+    assert_eq!(lldp.system_name.as_deref(), Some("switch1"));
+}
+
+#[test]
+fn fails_if_tlv_exceeds_buffer() {
+    let packet = [&LLDP_CHASSIS_ID_TLV[..], &LLDP_PORT_ID_TLV, &[6, 9, 0]].concat();
+
+    let result = LldpPacket::from_bytes(&packet);
+
+    assert!(
+        matches!(result, Err(ParserError::InconsistentLength { layer, declared: 9, available: 1 }) if layer == "LLDP TLV")
+    );
+}
+
+#[test]
+fn fails_if_mandatory_tlv_is_missing() {
+    let packet = [&LLDP_CHASSIS_ID_TLV[..], &LLDP_TTL_TLV, &[0, 0]].concat();
+
+    let result = LldpPacket::from_bytes(&packet);
+
+    assert!(matches!(result, Err(ParserError::MissingLldpTlv(s)) if s == "Port ID"));
+}
+
+#[test]
+fn can_parse_lldp_from_ethernet_frame() {
+    let mut frame = [
+        &DEFAULT_DEST_MAC[..],
+        &DEFAULT_SRC_MAC,
+        &LLDP_ETHER_TYPE,
+        &generate_lldp_mock_packet(),
+    ]
+    .concat();
+    frame.resize(64, 0); // Pad to the minimum frame size
+
+    let ethernet_frame = EthernetFrame::from_bytes(&frame, false).unwrap();
+    assert_eq!(ethernet_frame.header.ether_type, EtherType::LLDP);
+
+    match ethernet_frame.parse_next_layer().unwrap() {
+        LayeredData::EthernetFrameData(v) => match *v.data {
+            LayeredData::LldpData(lldp) => assert_eq!(lldp.time_to_live, 120),
+            _ => panic!("Invalid nested layered data"),
+        },
+        _ => panic!("Invalid layered data"),
+    };
+}
+
+#[test]
+fn serializes_optional_tlvs_in_ascending_order() {
+    let packet = [
+        &LLDP_CHASSIS_ID_TLV[..],
+        &LLDP_PORT_ID_TLV,
+        &LLDP_TTL_TLV,
+        &LLDP_PORT_DESCRIPTION_TLV,
+        &LLDP_SYSTEM_NAME_TLV,
+        &LLDP_END_TLV,
+    ]
+    .concat();
+
+    let layered_data = LldpPacket::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert_eq!(layered_data.to_bytes(), packet);
+}
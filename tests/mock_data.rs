@@ -15,7 +15,7 @@ pub const DEFAULT_ACK_NUMBER: [u8; 4] = [0, 0, 5, 220];
 pub const DEFAULT_ZERO_OPTIONS_DATA_OFFSET_RESERVED_FLAGS_WINDOW: [u8; 4] = [80, 255, 19, 136];
 pub const DEFAULT_OPTIONS_DATA_OFFSET_RESERVED_FLAGS_WINDOW: [u8; 4] = [96, 255, 19, 136];
 pub const DEFAULT_CHECKSUM_URGENT_POINTER: [u8; 4] = [72, 27, 5, 65];
-pub const DEFAULT_OPTIONS: [u8; 4] = [12, 5, 0, 255];
+pub const DEFAULT_OPTIONS: [u8; 4] = [12, 4, 0, 255];
 pub const DEFAULT_DATA: [u8; 8] = [120, 5, 0, 55, 0, 255, 12, 100];
 
 fn cap(data_offset_reserved_flags_window: [u8; 4]) -> usize {
@@ -53,6 +53,48 @@ pub fn generate_tcp_packets_with_options() -> Vec<u8> {
     segment
 }
 
+// A TCP segment between ports 53145 and `destination_port` carrying `payload`.
+pub fn generate_tcp_segment(destination_port: u16, payload: &[u8]) -> Vec<u8> {
+    let mut segment = generate_tcp_packets_without_options();
+    segment.truncate(20);
+    segment[2..4].copy_from_slice(&destination_port.to_be_bytes());
+    segment.extend_from_slice(payload);
+    segment
+}
+
+// MSS 1460, SACK permitted, timestamps (1, 0), NOP and window scale 7, as sent in a SYN.
+pub const TCP_SYN_OPTIONS: [u8; 20] = [
+    2, 4, 5, 180, 4, 2, 8, 10, 0, 0, 0, 1, 0, 0, 0, 0, 1, 3, 3, 7,
+];
+
+// A TCP segment carrying DEFAULT_DATA, whose header ends with `options`.
+pub fn generate_tcp_segment_with_options(options: &[u8]) -> Vec<u8> {
+    let mut segment = generate_tcp_packets_without_options();
+    segment.truncate(20);
+    segment[12] = (((20 + options.len()) / 4) << 4) as u8 | (segment[12] & 0xF);
+    segment.extend_from_slice(options);
+    segment.extend_from_slice(&DEFAULT_DATA);
+    segment
+}
+
+// DNS Messages
+
+// A recursive query (id 0x1234) for the A record of example.com.
+pub const DNS_QUERY_EXAMPLE_COM: [u8; 29] = [
+    0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0, 7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 3,
+    b'c', b'o', b'm', 0, 0, 1, 0, 1,
+];
+
+// Prefixes each of `messages` with its 2-byte length, as DNS over TCP does.
+pub fn generate_dns_over_tcp(messages: &[&[u8]]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    for message in messages {
+        payload.extend_from_slice(&(message.len() as u16).to_be_bytes());
+        payload.extend_from_slice(message);
+    }
+    payload
+}
+
 // IPV4 Packets
 pub const ICMP: u8 = 1;
 pub const TCP: u8 = 6;
@@ -78,7 +120,7 @@ pub fn generate_ipv4_mock_packets(protocol: [u8; 1], options: Option<&[u8]>) ->
     let payload = generate_tcp_packets_with_options();
 
     let (options, options_size) = match options {
-        Some(v) => (DEFAULT_VERSION_IHL_WITH_OPTIONS, DEFAULT_IPV4_OPTIONS.len()),
+        Some(_) => (DEFAULT_VERSION_IHL_WITH_OPTIONS, DEFAULT_IPV4_OPTIONS.len()),
         None => (DEFAULT_VERSION_IHL_WITHOUT_OPTIONS, 0),
     };
 
@@ -108,10 +150,90 @@ pub fn generate_ipv4_mock_packets(protocol: [u8; 1], options: Option<&[u8]>) ->
     packets
 }
 
+// The one's complement of the one's complement sum of 16-bit words (RFC 1071).
+pub fn ones_complement_checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|word| u32::from(word[0]) << 8 | u32::from(*word.get(1).unwrap_or(&0)))
+        .sum();
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+// An IPv4 packet carrying a TCP segment whose checksum is correct.
+pub fn generate_ipv4_tcp_with_valid_checksum() -> Vec<u8> {
+    let mut packet = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    packet[36..38].copy_from_slice(&[0, 0]);
+
+    let segment = &packet[MIN_IPV4_LENGTH..];
+    let mut pseudo_header = [&DEFAULT_SRC_ADDR[..], &DEFAULT_DEST_ADDR, &[0, TCP]].concat();
+    pseudo_header.extend_from_slice(&(segment.len() as u16).to_be_bytes());
+    pseudo_header.extend_from_slice(segment);
+
+    let checksum = ones_complement_checksum(&pseudo_header);
+    packet[36..38].copy_from_slice(&checksum.to_be_bytes());
+    set_ipv4_header_checksum(&mut packet);
+    packet
+}
+
+// Overwrites the header checksum of an IPv4 packet without options with a correct one.
+pub fn set_ipv4_header_checksum(packet: &mut [u8]) {
+    packet[10..12].copy_from_slice(&[0, 0]);
+    let checksum = ones_complement_checksum(&packet[..MIN_IPV4_LENGTH]);
+    packet[10..12].copy_from_slice(&checksum.to_be_bytes());
+}
+
+// A version 4 IPv4 packet without options carrying `payload`, from and to the default
+// addresses.
+pub fn generate_ipv4_packet(protocol: u8, payload: &[u8]) -> Vec<u8> {
+    let total_length = ((MIN_IPV4_LENGTH + payload.len()) as u16).to_be_bytes();
+
+    [
+        &[69][..],
+        &DEFAULT_TOS,
+        &total_length,
+        &DEFAULT_IDENTIFICATION,
+        &[0, 0],
+        &DEFAULT_TTL,
+        &[protocol],
+        &[0, 0],
+        &DEFAULT_SRC_ADDR,
+        &DEFAULT_DEST_ADDR,
+        payload,
+    ]
+    .concat()
+}
+
+// Record route with one address (10.0.0.1), router alert and end of option list.
+pub const IPV4_ROUTE_OPTIONS: [u8; 12] = [7, 7, 8, 10, 0, 0, 1, 148, 4, 0, 0, 0];
+
+// A mock IPv4 packet whose header ends with `options`, which must be a multiple of 4 bytes.
+pub fn generate_ipv4_packet_with_options(options: &[u8]) -> Vec<u8> {
+    let mut packet = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let total_length = (packet.len() + options.len()) as u16;
+
+    packet[0] = (packet[0] & 0xF0) | ((MIN_IPV4_LENGTH + options.len()) / 4) as u8;
+    packet[2..4].copy_from_slice(&total_length.to_be_bytes());
+    packet.splice(MIN_IPV4_LENGTH..MIN_IPV4_LENGTH, options.iter().copied());
+    packet
+}
+
+// An IPv4 packet carrying TCP that declares `total_length` bytes, of which only `captured`
+// are present, as in a capture taken with a short snaplen.
+pub fn generate_snaplen_truncated_ipv4_packet(total_length: u16, captured: usize) -> Vec<u8> {
+    let mut packet = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    packet[2..4].copy_from_slice(&total_length.to_be_bytes());
+    set_ipv4_header_checksum(&mut packet);
+    packet.resize(captured, 0xAB);
+    packet
+}
+
 // IPV6 Packets
 pub const MIN_IPV6_LENGTH: usize = 40;
 pub const DEFAULT_VERSION_TRAFFIC_CLASS_FLOW_LABEL: [u8; 4] = [106, 122, 27, 255];
-pub const DEFAULT_PAYLOAD_LENGTH_NEXT_HEADER_HOP_LIMIT: [u8; 4] = [0, 10, 6, 100];
+pub const DEFAULT_PAYLOAD_LENGTH_NEXT_HEADER_HOP_LIMIT: [u8; 4] = [0, 32, 6, 100];
 pub const DEFAULT_SRC_ADDRESS: [u8; 16] = [
     0, 0, 1, 123, 43, 12, 100, 255, 255, 255, 10, 21, 45, 12, 12, 12,
 ];
@@ -133,7 +255,7 @@ pub fn generate_ipv6_mock_packet() -> Vec<u8> {
 
     packets[40..(40 + payload.len())].copy_from_slice(&payload);
 
-    return packets;
+    packets
 }
 
 // ETHERNETFRAME Packets
@@ -142,7 +264,12 @@ pub const MIN_FRAME_SIZE_WITH_QTAG: usize = 22;
 pub const DEFAULT_DEST_MAC: [u8; 6] = [12, 25, 60, 255, 88, 12];
 pub const DEFAULT_SRC_MAC: [u8; 6] = [108, 100, 19, 25, 200, 199];
 pub const DEFAULT_ETHER_TYPE: [u8; 2] = [134, 221];
-pub const INVALID_ETHER_TYPE: [u8; 2] = [99, 0];
+pub const IPV4_ETHER_TYPE: [u8; 2] = [8, 0];
+// A type field between the largest 802.3 length and the smallest EtherType.
+pub const INVALID_ETHER_TYPE: [u8; 2] = [5, 255];
+pub const CFM_ETHER_TYPE: [u8; 2] = [137, 2];
+pub const LLDP_ETHER_TYPE: [u8; 2] = [136, 204];
+pub const WAKE_ON_LAN_ETHER_TYPE: [u8; 2] = [8, 66];
 pub const DEFAULT_FCS: [u8; 4] = [1, 2, 3, 4];
 pub const DEFAULT_Q_TAG: [u8; 4] = [129, 0, 2, 22];
 
@@ -173,3 +300,349 @@ pub fn generate_ethernet_mock_packets(q_tag: Option<[u8; 4]>, ether_type: [u8; 2
 
     frame
 }
+
+// IEEE 802.3 Frames
+pub const SNAP_LLC_HEADER: [u8; 3] = [0xAA, 0xAA, 0x03];
+pub const STP_LLC_HEADER: [u8; 3] = [0x42, 0x42, 0x03];
+
+// An 802.3 frame whose LLC PDU is `llc_pdu`, padded to the minimum frame size.
+pub fn generate_8023_mock_frame(llc_pdu: &[u8]) -> Vec<u8> {
+    let mut frame = [
+        &DEFAULT_DEST_MAC[..],
+        &DEFAULT_SRC_MAC,
+        &(llc_pdu.len() as u16).to_be_bytes(),
+        llc_pdu,
+    ]
+    .concat();
+    frame.resize(frame.len().max(64), 0);
+
+    frame
+}
+
+// PCAP Captures
+pub const PCAP_GLOBAL_HEADER: [u8; 24] = [
+    212, 195, 178, 161, 2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 255, 255, 0, 0, 1, 0, 0, 0,
+];
+
+pub fn generate_pcap_capture(frames: &[Vec<u8>]) -> Vec<u8> {
+    let mut capture = PCAP_GLOBAL_HEADER.to_vec();
+
+    for (i, frame) in frames.iter().enumerate() {
+        let length = (frame.len() as u32).to_le_bytes();
+
+        capture.extend_from_slice(&(i as u32).to_le_bytes()); // Timestamp seconds
+        capture.extend_from_slice(&[0, 0, 0, 0]); // Timestamp microseconds
+        capture.extend_from_slice(&length);
+        capture.extend_from_slice(&length);
+        capture.extend_from_slice(frame);
+    }
+
+    capture
+}
+
+// MPLS Packets
+pub const MPLS_UNICAST_ETHER_TYPE: [u8; 2] = [136, 71];
+pub const DEFAULT_MPLS_LABEL: [u8; 4] = [0, 16, 26, 64]; // Label 257, TC 5, TTL 64
+pub const DEFAULT_MPLS_BOTTOM_LABEL: [u8; 4] = [0, 32, 3, 63]; // Label 512, TC 1, S, TTL 63
+
+pub fn generate_mpls_mock_packet(payload: &[u8]) -> Vec<u8> {
+    [&DEFAULT_MPLS_LABEL[..], &DEFAULT_MPLS_BOTTOM_LABEL, payload].concat()
+}
+
+// PPPoE Packets
+pub const PPPOE_SESSION_ETHER_TYPE: [u8; 2] = [136, 100];
+pub const PPP_PROTOCOL_IPV4: [u8; 2] = [0, 33];
+pub const DEFAULT_PPPOE_VERSION_TYPE_CODE_SESSION: [u8; 4] = [17, 0, 18, 52]; // Session 0x1234
+
+pub fn generate_pppoe_session_mock_packet(ppp_protocol: [u8; 2], payload: &[u8]) -> Vec<u8> {
+    let length = ((payload.len() + ppp_protocol.len()) as u16).to_be_bytes();
+
+    [
+        &DEFAULT_PPPOE_VERSION_TYPE_CODE_SESSION[..],
+        &length,
+        &ppp_protocol,
+        payload,
+    ]
+    .concat()
+}
+
+// SLL Packets
+pub const SLL_PACKET_TYPE_HOST: [u8; 2] = [0, 0];
+pub const SLL_ARPHRD_ETHER: [u8; 2] = [0, 1];
+
+// An SLL header for a packet received from DEFAULT_SRC_MAC over Ethernet, followed by
+// `payload`.
+pub fn generate_sll_mock_packet(protocol: [u8; 2], payload: &[u8]) -> Vec<u8> {
+    [
+        &SLL_PACKET_TYPE_HOST[..],
+        &SLL_ARPHRD_ETHER,
+        &[0, 6],
+        &DEFAULT_SRC_MAC,
+        &[0, 0],
+        &protocol,
+        payload,
+    ]
+    .concat()
+}
+
+// ARP Packets
+pub const ARP_ETHER_TYPE: [u8; 2] = [8, 6];
+pub const RARP_ETHER_TYPE: [u8; 2] = [128, 53];
+pub const ARP_ETHERNET_IPV4_HEADER: [u8; 6] = [0, 1, 8, 0, 6, 4]; // Ethernet, IPv4, 6, 4
+pub const DEFAULT_ARP_SENDER_IP: [u8; 4] = [192, 168, 1, 1];
+pub const DEFAULT_ARP_TARGET_IP: [u8; 4] = [192, 168, 1, 20];
+
+// An Ethernet/IPv4 ARP or RARP packet from DEFAULT_SRC_MAC to DEFAULT_DEST_MAC.
+pub fn generate_arp_mock_packet(operation: u16) -> Vec<u8> {
+    [
+        &ARP_ETHERNET_IPV4_HEADER[..],
+        &operation.to_be_bytes(),
+        &DEFAULT_SRC_MAC,
+        &DEFAULT_ARP_SENDER_IP,
+        &DEFAULT_DEST_MAC,
+        &DEFAULT_ARP_TARGET_IP,
+    ]
+    .concat()
+}
+
+// An Ethernet II frame carrying `payload`, padded to the minimum frame size.
+pub fn generate_padded_ethernet_frame(ether_type: [u8; 2], payload: &[u8]) -> Vec<u8> {
+    let mut frame = [
+        &DEFAULT_DEST_MAC[..],
+        &DEFAULT_SRC_MAC,
+        &ether_type,
+        payload,
+    ]
+    .concat();
+    frame.resize(frame.len().max(64), 0);
+
+    frame
+}
+
+// ICMP Echo Requests
+// An echo request (id 1, sequence 44) carrying the default Windows ping payload.
+pub const ICMP_ECHO_REQUEST: [u8; 40] = [
+    8, 0, 77, 47, 0, 1, 0, 44, 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110,
+    111, 112, 113, 114, 115, 116, 117, 118, 119, 97, 98, 99, 100, 101, 102, 103, 104, 105,
+];
+
+// The IPv4 header of a 60-byte UDP datagram from 192.168.1.10 to 8.8.8.8 with a TTL of 1,
+// followed by the first 8 bytes of its payload: the UDP header, ports 54321 -> 33434.
+pub const QUOTED_IPV4_UDP: [u8; 28] = [
+    69, 0, 0, 60, 28, 70, 0, 0, 1, 17, 0, 0, 192, 168, 1, 10, 8, 8, 8, 8, 212, 49, 130, 154, 0, 40,
+    0, 0,
+];
+
+// An ICMP Time Exceeded (TTL exceeded in transit) message quoting `quoted`.
+pub fn generate_icmp_time_exceeded(quoted: &[u8]) -> Vec<u8> {
+    let mut message = vec![11, 0, 0, 0, 0, 0, 0, 0];
+    message.extend_from_slice(quoted);
+    message
+}
+
+// An ICMPv6 echo request (id 1, sequence 1) sent from fe80::1 to fe80::2.
+pub const ICMPV6_ECHO_REQUEST: [u8; 16] = [
+    128, 0, 241, 24, 0, 1, 0, 1, 97, 98, 99, 100, 101, 102, 103, 104,
+];
+
+pub const ICMPV6_NEXT_HEADER: u8 = 58;
+pub const LINK_LOCAL_SRC_ADDRESS: [u8; 16] = [0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+pub const LINK_LOCAL_DEST_ADDRESS: [u8; 16] =
+    [0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2];
+
+// An IPv6 packet carrying the ICMPv6 `message` from fe80::1 to fe80::2.
+pub fn generate_icmpv6_packet(message: &[u8]) -> Vec<u8> {
+    let mut packets = generate_ipv6_mock_packet();
+    packets.truncate(MIN_IPV6_LENGTH);
+    packets[4..6].copy_from_slice(&(message.len() as u16).to_be_bytes());
+    packets[6] = ICMPV6_NEXT_HEADER;
+    packets[8..24].copy_from_slice(&LINK_LOCAL_SRC_ADDRESS);
+    packets[24..40].copy_from_slice(&LINK_LOCAL_DEST_ADDRESS);
+    packets.extend_from_slice(message);
+    packets
+}
+
+// IPV6 Jumbograms
+pub const HOP_BY_HOP_NEXT_HEADER: u8 = 0;
+pub const NO_NEXT_HEADER: u8 = 59;
+
+// A jumbogram carrying `data` after a Hop-by-Hop header holding only the Jumbo Payload option.
+pub fn generate_ipv6_jumbogram(data: &[u8]) -> Vec<u8> {
+    let jumbo_length = (8 + data.len()) as u32;
+
+    [
+        &DEFAULT_VERSION_TRAFFIC_CLASS_FLOW_LABEL[..],
+        &[0, 0, HOP_BY_HOP_NEXT_HEADER, 100],
+        &DEFAULT_SRC_ADDRESS,
+        &DEFAULT_DEST_ADDRESS,
+        &[NO_NEXT_HEADER, 0, 0xC2, 4],
+        &jumbo_length.to_be_bytes(),
+        data,
+    ]
+    .concat()
+}
+
+// IPV6 Fragments
+pub const FRAGMENT_NEXT_HEADER: u8 = 44;
+
+pub fn generate_ipv6_fragment(
+    fragment_offset: u16,
+    more_fragments: bool,
+    identification: u32,
+    data: &[u8],
+) -> Vec<u8> {
+    let payload_length = (8 + data.len()) as u16;
+    let offset_flags = (fragment_offset << 3) | more_fragments as u16;
+
+    [
+        &DEFAULT_VERSION_TRAFFIC_CLASS_FLOW_LABEL[..],
+        &payload_length.to_be_bytes(),
+        &[FRAGMENT_NEXT_HEADER, 100],
+        &DEFAULT_SRC_ADDRESS,
+        &DEFAULT_DEST_ADDRESS,
+        &[TCP, 0],
+        &offset_flags.to_be_bytes(),
+        &identification.to_be_bytes(),
+        data,
+    ]
+    .concat()
+}
+
+// VXLAN Packets
+pub const VXLAN_PORT: [u8; 2] = [18, 181];
+pub const DEFAULT_VXLAN_HEADER: [u8; 8] = [8, 0, 0, 0, 0, 48, 57, 0]; // I flag, VNI 12345
+
+pub fn generate_vxlan_mock_datagram(inner_frame: &[u8]) -> Vec<u8> {
+    let length = ((8 + DEFAULT_VXLAN_HEADER.len() + inner_frame.len()) as u16).to_be_bytes();
+
+    [
+        &DEFAULT_SRC_DEST_PORT[..2],
+        &VXLAN_PORT,
+        &length,
+        &[0, 0],
+        &DEFAULT_VXLAN_HEADER,
+        inner_frame,
+    ]
+    .concat()
+}
+
+// Wraps the default IPv6 frame in `levels` Ethernet/IPv6/UDP/VXLAN tunnels, so a full parse
+// yields `4 * levels + 3` layers.
+pub fn generate_nested_vxlan_mock_frame(levels: usize) -> Vec<u8> {
+    let mut frame = [
+        &DEFAULT_DEST_MAC[..],
+        &DEFAULT_SRC_MAC,
+        &DEFAULT_ETHER_TYPE,
+        &generate_ipv6_mock_packet(),
+    ]
+    .concat();
+
+    for _ in 0..levels {
+        let datagram = generate_vxlan_mock_datagram(&frame);
+
+        frame = [
+            &DEFAULT_DEST_MAC[..],
+            &DEFAULT_SRC_MAC,
+            &DEFAULT_ETHER_TYPE,
+            &DEFAULT_VERSION_TRAFFIC_CLASS_FLOW_LABEL,
+            &(datagram.len() as u16).to_be_bytes(),
+            &[UDP, 64],
+            &DEFAULT_SRC_ADDRESS,
+            &DEFAULT_DEST_ADDRESS,
+            &datagram,
+        ]
+        .concat();
+    }
+
+    frame
+}
+
+// LLDP Packets
+pub const LLDP_CHASSIS_ID_TLV: [u8; 9] = [2, 7, 4, 12, 25, 60, 255, 88, 12]; // MAC subtype
+pub const LLDP_PORT_ID_TLV: [u8; 7] = [4, 5, 5, 101, 116, 104, 48]; // "eth0"
+pub const LLDP_TTL_TLV: [u8; 4] = [6, 2, 0, 120];
+pub const LLDP_SYSTEM_NAME_TLV: [u8; 9] = [10, 7, 115, 119, 105, 116, 99, 104, 49]; // "switch1"
+pub const LLDP_PORT_DESCRIPTION_TLV: [u8; 8] = [8, 6, 117, 112, 108, 105, 110, 107]; // "uplink"
+pub const LLDP_END_TLV: [u8; 2] = [0, 0];
+
+pub fn generate_lldp_mock_packet() -> Vec<u8> {
+    [
+        &LLDP_CHASSIS_ID_TLV[..],
+        &LLDP_PORT_ID_TLV,
+        &LLDP_TTL_TLV,
+        &LLDP_SYSTEM_NAME_TLV,
+        &LLDP_PORT_DESCRIPTION_TLV,
+        &LLDP_END_TLV,
+    ]
+    .concat()
+}
+
+// SNMP Messages
+// An SNMPv2c GetRequest for sysDescr.0 with the community "public".
+pub const SNMP_GET_REQUEST: [u8; 43] = [
+    48, 41, 2, 1, 1, 4, 6, 112, 117, 98, 108, 105, 99, 160, 28, 2, 4, 0, 0, 48, 57, 2, 1, 0, 2, 1,
+    0, 48, 14, 48, 12, 6, 8, 43, 6, 1, 2, 1, 1, 1, 0, 5, 0,
+];
+pub const SNMP_CLIENT_SERVER_PORTS: [u8; 4] = [195, 80, 0, 161]; // 50000 -> 161
+
+pub fn generate_snmp_mock_datagram(message: &[u8]) -> Vec<u8> {
+    let length = ((8 + message.len()) as u16).to_be_bytes();
+
+    [&SNMP_CLIENT_SERVER_PORTS[..], &length, &[0, 0], message].concat()
+}
+
+// A masked WebSocket text frame carrying "Hello", from RFC 6455, section 5.7.
+pub const WEBSOCKET_MASKED_HELLO: [u8; 11] = [
+    0x81, 0x85, 0x37, 0xFA, 0x21, 0x3D, 0x7F, 0x9F, 0x4D, 0x51, 0x58,
+];
+
+// An unmasked WebSocket binary frame carrying `payload`, using the extended length
+// encodings for payloads of 126 bytes or more.
+pub fn generate_websocket_binary_frame(payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x82];
+    match payload.len() {
+        length if length < 126 => frame.push(length as u8),
+        length if length <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+
+    frame
+}
+
+// A minimal INVITE, adapted from RFC 3665, section 3.1, carrying a 5-byte SDP body.
+pub const SIP_INVITE: &[u8] = b"INVITE sip:bob@biloxi.example.com SIP/2.0\r\n\
+Via: SIP/2.0/UDP pc33.atlanta.example.com;branch=z9hG4bK776asdhds\r\n\
+From: Alice <sip:alice@atlanta.example.com>;tag=1928301774\r\n\
+To: Bob <sip:bob@biloxi.example.com>\r\n\
+Call-ID: a84b4c76e66710@pc33.atlanta.example.com\r\n\
+CSeq: 314159 INVITE\r\n\
+Content-Type: application/sdp\r\n\
+Content-Length: 5\r\n\
+\r\n\
+v=0\r\n";
+
+// A GTP-U G-PDU header with no optional fields, carrying `payload` on TEID 0x12345678.
+pub fn generate_gtp_mock_packet(payload: &[u8]) -> Vec<u8> {
+    let length = (payload.len() as u16).to_be_bytes();
+
+    [
+        &[0x30, 0xFF][..],
+        &length,
+        &[0x12, 0x34, 0x56, 0x78],
+        payload,
+    ]
+    .concat()
+}
+
+pub fn generate_gtp_mock_datagram(gtp_packet: &[u8]) -> Vec<u8> {
+    let length = ((8 + gtp_packet.len()) as u16).to_be_bytes();
+
+    [&[0x08, 0x68, 0x08, 0x68][..], &length, &[0, 0], gtp_packet].concat()
+}
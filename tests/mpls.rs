@@ -0,0 +1,121 @@
+mod mock_data;
+
+use mock_data::{
+    generate_ipv4_mock_packets, generate_ipv4_packet, generate_mpls_mock_packet,
+    generate_tcp_packets_without_options, set_ipv4_header_checksum, DEFAULT_DEST_MAC,
+    DEFAULT_MPLS_LABEL, DEFAULT_SRC_MAC, DEFAULT_TCP_PROTOCOL, MPLS_UNICAST_ETHER_TYPE, TCP,
+};
+use net_sift::parsers::{
+    definitions::{DeepParser, EtherType, LayeredData},
+    errors::ParserError,
+    ethernet_frame::EthernetFrame,
+    mpls::{MplsLabel, MplsPacket},
+};
+
+#[test]
+fn can_parse_mpls_label_stack() {
+    let ipv4 = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let packet = generate_mpls_mock_packet(&ipv4);
+
+    let mpls = MplsPacket::from_bytes(&packet).unwrap();
+
+    assert_eq!(
+        mpls.header.labels,
+        vec![
+            MplsLabel {
+                label: 257,
+                traffic_class: 5,
+                bottom_of_stack: false,
+                time_to_live: 64,
+            },
+            MplsLabel {
+                label: 512,
+                traffic_class: 1,
+                bottom_of_stack: true,
+                time_to_live: 63,
+            },
+        ]
+    );
+    assert_eq!(mpls.data, Box::new(LayeredData::Payload(ipv4.into())));
+}
+
+#[test]
+fn can_parse_layered_data() {
+    let mut ipv4 = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    ipv4[0] = 0x45; // The payload type is inferred from the IP version nibble
+    let bytes = generate_mpls_mock_packet(&ipv4);
+    let mpls = MplsPacket::from_bytes(&bytes).unwrap();
+
+    match mpls.parse_next_layer().unwrap() {
+        LayeredData::MplsData(v) => match *v.data {
+            LayeredData::Ipv4Data(_) => {}
+            _ => panic!("Invalid nested layered data"),
+        },
+        _ => panic!("Invalid layered data"),
+    };
+}
+
+#[test]
+fn can_parse_mpls_from_ethernet_frame() {
+    let mut ipv4 = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    ipv4[0] = 0x45;
+    let frame = [
+        &DEFAULT_DEST_MAC[..],
+        &DEFAULT_SRC_MAC,
+        &MPLS_UNICAST_ETHER_TYPE,
+        &generate_mpls_mock_packet(&ipv4),
+    ]
+    .concat();
+
+    let ethernet_frame = EthernetFrame::from_bytes(&frame, false).unwrap();
+    assert_eq!(ethernet_frame.header.ether_type, EtherType::MPLSUnicast);
+
+    match ethernet_frame.parse_next_layer().unwrap() {
+        LayeredData::EthernetFrameData(v) => match *v.data {
+            LayeredData::MplsData(mpls) => {
+                assert!(matches!(*mpls.data, LayeredData::Ipv4Data(_)))
+            }
+            _ => panic!("Invalid nested layered data"),
+        },
+        _ => panic!("Invalid layered data"),
+    };
+}
+
+#[test]
+fn fails_if_stack_has_no_bottom_of_stack() {
+    let packet = [&DEFAULT_MPLS_LABEL[..], &DEFAULT_MPLS_LABEL, &[0, 0]].concat();
+
+    let result = MplsPacket::from_bytes(&packet);
+
+    assert!(matches!(result, Err(ParserError::UnterminatedLabelStack)));
+}
+
+#[test]
+fn leaves_unrecognised_payload_raw() {
+    let packet = generate_mpls_mock_packet(&[0, 1, 2, 3]);
+    let mpls = MplsPacket::from_bytes(&packet).unwrap();
+
+    match mpls.parse_next_layer().unwrap() {
+        LayeredData::MplsData(v) => {
+            assert_eq!(
+                v.data,
+                Box::new(LayeredData::Payload(vec![0, 1, 2, 3].into()))
+            )
+        }
+        _ => panic!("Invalid layered data"),
+    };
+}
+
+#[test]
+fn serializes_label_stack_and_payload() {
+    let mut ipv4 = generate_ipv4_packet(TCP, &generate_tcp_packets_without_options());
+    set_ipv4_header_checksum(&mut ipv4);
+    let packet = generate_mpls_mock_packet(&ipv4);
+
+    let layered_data = MplsPacket::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert_eq!(layered_data.to_bytes(), packet);
+}
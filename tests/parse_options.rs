@@ -0,0 +1,270 @@
+mod mock_data;
+
+use mock_data::{
+    generate_ethernet_mock_packets, generate_ipv4_packet, generate_ipv4_tcp_with_valid_checksum,
+    generate_nested_vxlan_mock_frame, generate_padded_ethernet_frame,
+    generate_snaplen_truncated_ipv4_packet, generate_tcp_segment, DEFAULT_ETHER_TYPE,
+    IPV4_ETHER_TYPE, TCP,
+};
+use net_sift::parsers::{
+    definitions::{DeepParser, LayerKind, LayeredData, ParseOptions, DEFAULT_MAX_DEPTH},
+    errors::ParserError,
+    ethernet_frame::EthernetFrame,
+    ipv4::Ipv4Packet,
+};
+
+// Layer timings are only recorded with the `std` feature, which provides the clock.
+#[cfg(feature = "std")]
+use net_sift::parsers::definitions::LayerHook;
+
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn defaults_to_a_max_depth_of_sixteen() {
+    assert_eq!(ParseOptions::default().max_depth, DEFAULT_MAX_DEPTH);
+    assert_eq!(DEFAULT_MAX_DEPTH, 16);
+}
+
+#[test]
+fn parses_nested_tunnels_within_the_limit() {
+    // 3 tunnels nest 15 layers.
+    let frame = generate_nested_vxlan_mock_frame(3);
+    let options = ParseOptions {
+        max_depth: 15,
+        ..Default::default()
+    };
+
+    let layered_data = EthernetFrame::from_bytes(&frame, false)
+        .unwrap()
+        .parse_next_layer_with(&options, 0)
+        .unwrap();
+
+    let kinds: Vec<LayerKind> = layered_data
+        .layers()
+        .filter_map(|layer| layer.layer_kind())
+        .collect();
+    assert_eq!(kinds.len(), 15);
+    assert_eq!(kinds.last(), Some(&LayerKind::Tcp));
+}
+
+#[test]
+fn stops_at_max_depth() {
+    let frame = generate_nested_vxlan_mock_frame(3);
+    let options = ParseOptions {
+        max_depth: 14,
+        ..Default::default()
+    };
+
+    let result = EthernetFrame::from_bytes(&frame, false)
+        .unwrap()
+        .parse_next_layer_with(&options, 0);
+
+    assert!(matches!(result, Err(ParserError::MaxDepthExceeded(14))));
+}
+
+#[test]
+fn default_options_bound_deeply_nested_packets() {
+    // 4 tunnels nest 19 layers, more than the default allows.
+    let frame = generate_nested_vxlan_mock_frame(4);
+
+    let result = EthernetFrame::from_bytes(&frame, false)
+        .unwrap()
+        .parse_next_layer();
+
+    assert!(matches!(
+        result,
+        Err(ParserError::MaxDepthExceeded(DEFAULT_MAX_DEPTH))
+    ));
+}
+
+#[test]
+fn best_effort_keeps_the_layers_around_a_failure() {
+    let mut frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+    // Replace the IPv6 next header with a protocol net-sift cannot parse.
+    frame[14 + 6] = 99;
+    let options = ParseOptions {
+        best_effort: true,
+        ..Default::default()
+    };
+
+    let result = EthernetFrame::from_bytes(&frame, true)
+        .unwrap()
+        .parse_next_layer();
+    assert!(matches!(result, Err(ParserError::UnknownIPType(99))));
+
+    let layered_data = EthernetFrame::from_bytes(&frame, true)
+        .unwrap()
+        .parse_next_layer_with(&options, 0)
+        .unwrap();
+    let kinds: Vec<LayerKind> = layered_data
+        .layers()
+        .filter_map(|layer| layer.layer_kind())
+        .collect();
+    assert_eq!(kinds, vec![LayerKind::Ethernet, LayerKind::Ipv6]);
+    assert!(layered_data.hexdump_innermost().is_some());
+
+    assert_eq!(
+        EthernetFrame::from_bytes(&frame, true)
+            .unwrap()
+            .parse_best_effort()
+            .unwrap(),
+        layered_data
+    );
+}
+
+#[test]
+fn lossy_parsing_annotates_a_truncated_ipv4_packet() {
+    // A valid Ethernet header wrapping an IPv4 packet cut short of its total length.
+    let mut packet = generate_ipv4_packet(TCP, &generate_tcp_segment(80, &[0; 100]));
+    packet.truncate(60);
+    let frame = generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &packet);
+
+    let strict_error = EthernetFrame::from_bytes(&frame, false)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap_err();
+    assert!(matches!(
+        strict_error,
+        ParserError::InconsistentLength { ref layer, .. } if layer == "Ipv4 packet"
+    ));
+
+    let layered_data = EthernetFrame::from_bytes(&frame, false)
+        .unwrap()
+        .parse_next_layer_lossy()
+        .unwrap();
+    match &layered_data {
+        LayeredData::EthernetFrameData(frame) => assert_eq!(
+            *frame.data,
+            LayeredData::Unparsed {
+                reason: strict_error.to_string(),
+                bytes: packet.clone(),
+            }
+        ),
+        other => panic!("Expected the Ethernet layer, got {:?}", other),
+    }
+    assert_eq!(layered_data.layer_kind(), Some(LayerKind::Ethernet));
+    assert!(layered_data.hexdump_innermost().is_some());
+    assert_eq!(layered_data.to_bytes(), frame);
+}
+
+#[test]
+fn verifies_transport_checksums_against_the_enclosing_packet() {
+    let options = ParseOptions {
+        verify_checksums: true,
+        ..ParseOptions::default()
+    };
+    let packet = generate_ipv4_tcp_with_valid_checksum();
+
+    let layered_data = Ipv4Packet::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer_with(&options, 0)
+        .unwrap();
+    assert!(layered_data.contains(LayerKind::Tcp));
+
+    let mut corrupted = packet.clone();
+    *corrupted.last_mut().unwrap() ^= 0xFF;
+    let result = Ipv4Packet::from_bytes(&corrupted)
+        .unwrap()
+        .parse_next_layer_with(&options, 0);
+    assert!(matches!(
+        result,
+        Err(ParserError::ChecksumMismatch { expected, computed }) if expected != computed
+    ));
+
+    // Checksums are only verified on request.
+    assert!(Ipv4Packet::from_bytes(&corrupted)
+        .unwrap()
+        .parse_next_layer()
+        .is_ok());
+}
+
+#[test]
+fn verifies_ipv4_header_checksums() {
+    let options = ParseOptions {
+        verify_checksums: true,
+        ..ParseOptions::default()
+    };
+    let mut packet = generate_ipv4_tcp_with_valid_checksum();
+    packet[8] ^= 0xFF; // TTL
+
+    let result = Ipv4Packet::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer_with(&options, 0);
+
+    assert!(matches!(
+        result,
+        Err(ParserError::ChecksumMismatch { expected, computed }) if expected != computed
+    ));
+}
+
+#[test]
+fn clamps_packets_truncated_by_the_snaplen() {
+    let packet = generate_snaplen_truncated_ipv4_packet(1500, 200);
+    let frame = generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &packet);
+
+    let result = EthernetFrame::from_bytes(&frame, false)
+        .unwrap()
+        .parse_next_layer();
+    assert!(matches!(
+        result,
+        Err(ParserError::InconsistentLength {
+            declared: 1500,
+            available: 200,
+            ..
+        })
+    ));
+
+    let options = ParseOptions {
+        allow_truncated: true,
+        verify_checksums: true,
+        ..Default::default()
+    };
+    let layered_data = EthernetFrame::from_bytes(&frame, false)
+        .unwrap()
+        .parse_next_layer_with(&options, 0)
+        .unwrap();
+
+    let LayeredData::EthernetFrameData(frame) = layered_data else {
+        panic!("expected an Ethernet frame, got {:?}", layered_data);
+    };
+    let LayeredData::Ipv4Data(ipv4_packet) = *frame.data else {
+        panic!("expected an IPv4 packet");
+    };
+    assert!(ipv4_packet.truncated);
+    assert_eq!(ipv4_packet.header.total_length, 1500);
+
+    // The checksum of the truncated segment is not verified.
+    let LayeredData::TcpData(segment) = *ipv4_packet.data else {
+        panic!("expected a TCP segment");
+    };
+    assert!(segment.truncated);
+    assert_eq!(segment.payload_len(), 200 - 20 - 24);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn reports_the_time_taken_by_each_nested_layer() {
+    let timings = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&timings);
+    let options = ParseOptions {
+        on_layer: Some(LayerHook::new(move |kind, elapsed| {
+            recorded.lock().unwrap().push((kind, elapsed));
+        })),
+        ..ParseOptions::default()
+    };
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+
+    EthernetFrame::from_bytes(&frame, true)
+        .unwrap()
+        .parse_next_layer_with(&options, 0)
+        .unwrap();
+
+    let kinds: Vec<LayerKind> = timings
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(kind, _)| *kind)
+        .collect();
+    assert_eq!(kinds, vec![LayerKind::Ipv6, LayerKind::Tcp]);
+}
@@ -0,0 +1,120 @@
+mod mock_data;
+
+use mock_data::{
+    generate_ipv4_mock_packets, generate_ipv4_packet, generate_pppoe_session_mock_packet,
+    generate_tcp_packets_without_options, set_ipv4_header_checksum, DEFAULT_DEST_MAC,
+    DEFAULT_SRC_MAC, DEFAULT_TCP_PROTOCOL, PPPOE_SESSION_ETHER_TYPE, PPP_PROTOCOL_IPV4, TCP,
+};
+use net_sift::parsers::{
+    definitions::{DeepParser, EtherType, LayeredData},
+    errors::ParserError,
+    ethernet_frame::EthernetFrame,
+    pppoe::{PppoeHeader, PppoePacket},
+};
+
+#[test]
+fn can_parse_pppoe_session_packet() {
+    let ipv4 = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let packet = generate_pppoe_session_mock_packet(PPP_PROTOCOL_IPV4, &ipv4);
+
+    let pppoe = PppoePacket::from_bytes(&packet).unwrap();
+
+    assert_eq!(
+        pppoe.header,
+        PppoeHeader {
+            version: 1,
+            packet_type: 1,
+            code: 0,
+            session_id: 0x1234,
+            length: ipv4.len() as u16 + 2,
+            ppp_protocol: Some(0x0021),
+            span: 0..8,
+            raw_header: packet[..8].to_vec(),
+        }
+    );
+    assert_eq!(pppoe.data, Box::new(LayeredData::Payload(ipv4.into())));
+}
+
+#[test]
+fn can_parse_discovery_packet_without_ppp_protocol() {
+    // A PADI carrying a single End-Of-List tag.
+    let packet = [17, 9, 0, 0, 0, 4, 0, 0, 0, 0];
+
+    let pppoe = PppoePacket::from_bytes(&packet).unwrap();
+
+    assert_eq!(pppoe.header.code, 0x09);
+    assert_eq!(pppoe.header.ppp_protocol, None);
+    assert_eq!(
+        pppoe.data,
+        Box::new(LayeredData::Payload(vec![0, 0, 0, 0].into()))
+    );
+}
+
+#[test]
+fn payload_is_bounded_by_declared_length() {
+    let mut packet = generate_pppoe_session_mock_packet([0xC0, 0x21], &[1, 2, 3, 4]);
+    packet.extend_from_slice(&[0; 10]); // Ethernet padding
+
+    let pppoe = PppoePacket::from_bytes(&packet).unwrap();
+
+    assert_eq!(
+        pppoe.data,
+        Box::new(LayeredData::Payload(vec![1, 2, 3, 4].into()))
+    );
+}
+
+#[test]
+fn fails_if_declared_length_exceeds_packet() {
+    let mut packet = generate_pppoe_session_mock_packet(PPP_PROTOCOL_IPV4, &[1, 2, 3, 4]);
+    packet.truncate(packet.len() - 1);
+
+    let result = PppoePacket::from_bytes(&packet);
+
+    assert!(
+        matches!(result, Err(ParserError::InconsistentLength { layer, .. }) if layer == "PPPoE packet")
+    );
+}
+
+#[test]
+fn can_parse_pppoe_from_ethernet_frame() {
+    let mut ipv4 = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    ipv4[0] = 0x45;
+    let frame = [
+        &DEFAULT_DEST_MAC[..],
+        &DEFAULT_SRC_MAC,
+        &PPPOE_SESSION_ETHER_TYPE,
+        &generate_pppoe_session_mock_packet(PPP_PROTOCOL_IPV4, &ipv4),
+    ]
+    .concat();
+
+    let ethernet_frame = EthernetFrame::from_bytes(&frame, false).unwrap();
+    assert_eq!(ethernet_frame.header.ether_type, EtherType::PPPoESession);
+
+    match ethernet_frame.parse_next_layer().unwrap() {
+        LayeredData::EthernetFrameData(v) => match *v.data {
+            LayeredData::PppoeData(pppoe) => {
+                assert!(matches!(*pppoe.data, LayeredData::Ipv4Data(_)))
+            }
+            _ => panic!("Invalid nested layered data"),
+        },
+        _ => panic!("Invalid layered data"),
+    };
+}
+
+#[test]
+fn serializes_session_header_and_payload() {
+    let mut ipv4 = generate_ipv4_packet(TCP, &generate_tcp_packets_without_options());
+    set_ipv4_header_checksum(&mut ipv4);
+    let packet = generate_pppoe_session_mock_packet(PPP_PROTOCOL_IPV4, &ipv4);
+
+    let layered_data = PppoePacket::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert!(matches!(
+        &layered_data,
+        LayeredData::PppoeData(pppoe) if matches!(*pppoe.data, LayeredData::Ipv4Data(_))
+    ));
+    assert_eq!(layered_data.to_bytes(), packet);
+}
@@ -0,0 +1,111 @@
+mod mock_data;
+
+use mock_data::{
+    generate_ipv6_fragment, generate_ipv6_jumbogram, generate_tcp_packets_with_options,
+    DEFAULT_PAYLOAD, NO_NEXT_HEADER,
+};
+use net_sift::parsers::{
+    definitions::IPType,
+    errors::ParserError,
+    ipv6::Ipv6Packet,
+    reassembly::{Ipv6FragmentHeader, Ipv6Reassembler},
+};
+
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+fn fragment(offset: u16, more: bool, id: u32, data: &[u8]) -> Ipv6Packet<'static> {
+    Ipv6Packet::from_bytes(&generate_ipv6_fragment(offset, more, id, data))
+        .unwrap()
+        .into_owned()
+}
+
+#[test]
+fn can_parse_fragment_header() {
+    let header = Ipv6FragmentHeader::from_bytes(&[6, 0, 0, 25, 0, 0, 48, 57]).unwrap();
+
+    assert_eq!(
+        header,
+        Ipv6FragmentHeader {
+            next_header: 6,
+            fragment_offset: 3,
+            more_fragments: true,
+            identification: 12345,
+        }
+    );
+    assert_eq!(header.byte_offset(), 24);
+}
+
+#[test]
+fn can_reassemble_payload_split_across_two_fragments() {
+    let payload = generate_tcp_packets_with_options();
+    let (first, second) = payload.split_at(16);
+    let mut reassembler = Ipv6Reassembler::new(TIMEOUT);
+
+    // Fragments may arrive out of order.
+    let result = reassembler.process(&fragment(2, false, 7, second), Duration::ZERO);
+    assert_eq!(result.unwrap(), None);
+    assert_eq!(reassembler.pending(), 1);
+
+    let reassembled = reassembler
+        .process(&fragment(0, true, 7, first), Duration::from_secs(1))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(reassembled.next_header, IPType::TCP);
+    assert_eq!(reassembled.data, payload);
+    assert_eq!(reassembler.pending(), 0);
+}
+
+#[test]
+fn discards_packet_with_overlapping_fragments() {
+    let mut reassembler = Ipv6Reassembler::new(TIMEOUT);
+
+    reassembler
+        .process(&fragment(0, true, 7, &[1; 16]), Duration::ZERO)
+        .unwrap();
+    let result = reassembler.process(&fragment(1, false, 7, &[2; 16]), Duration::ZERO);
+
+    assert_eq!(result.unwrap(), None);
+    assert_eq!(reassembler.pending(), 0);
+}
+
+#[test]
+fn evicts_incomplete_packets_after_timeout() {
+    let mut reassembler = Ipv6Reassembler::new(TIMEOUT);
+
+    reassembler
+        .process(&fragment(0, true, 7, &[1; 16]), Duration::ZERO)
+        .unwrap();
+
+    assert_eq!(reassembler.evict_expired(Duration::from_secs(30)), 0);
+    assert_eq!(reassembler.evict_expired(Duration::from_secs(61)), 1);
+    assert_eq!(reassembler.pending(), 0);
+}
+
+#[test]
+fn fails_if_non_final_fragment_is_not_a_multiple_of_eight() {
+    let mut reassembler = Ipv6Reassembler::new(TIMEOUT);
+
+    let result = reassembler.process(&fragment(0, true, 7, &[1; 10]), Duration::ZERO);
+
+    assert!(matches!(result, Err(ParserError::InvalidLength(s)) if s == "Ipv6 fragment"));
+}
+
+#[test]
+fn returns_the_whole_payload_of_a_jumbogram() {
+    // A jumbogram's payload length field is zero, its length being in a Hop-by-Hop option.
+    let packet = Ipv6Packet::from_bytes(&generate_ipv6_jumbogram(&DEFAULT_PAYLOAD))
+        .unwrap()
+        .into_owned();
+    let mut reassembler = Ipv6Reassembler::new(TIMEOUT);
+
+    let reassembled = reassembler
+        .process(&packet, Duration::ZERO)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(reassembled.next_header, IPType::from(NO_NEXT_HEADER));
+    assert_eq!(reassembled.data, DEFAULT_PAYLOAD);
+}
@@ -0,0 +1,203 @@
+mod mock_data;
+
+use mock_data::{
+    generate_ipv4_mock_packets, generate_tcp_packets_with_options, generate_vxlan_mock_datagram,
+    DEFAULT_DATA, DEFAULT_DEST_ADDR, DEFAULT_SRC_ADDR, DEFAULT_TCP_PROTOCOL, UDP_PACKETS,
+};
+use net_sift::parsers::{
+    definitions::{DeepParser, LayerKind, LayeredData, ParseOptions},
+    ipv4::Ipv4Packet,
+    registry::{CustomLayer, ParseContext, ParserRegistry},
+    tcp::TcpSegment,
+    udp::UdpDatagram,
+};
+
+use std::net::Ipv4Addr;
+
+#[derive(Debug, PartialEq)]
+struct Sip(String);
+
+fn sip_parser(_: &ParseContext, payload: &[u8]) -> Option<LayeredData<'static>> {
+    let text = std::str::from_utf8(payload).ok()?;
+    Some(LayeredData::Custom(CustomLayer::new(
+        "sip",
+        Sip(text.to_string()),
+    )))
+}
+
+fn udp_datagram(destination_port: u16, payload: &[u8]) -> Vec<u8> {
+    [
+        &[19, 196][..],
+        &destination_port.to_be_bytes(),
+        &((8 + payload.len()) as u16).to_be_bytes(),
+        &[0, 0],
+        payload,
+    ]
+    .concat()
+}
+
+fn custom_layer<'a>(layered_data: &'a LayeredData) -> &'a CustomLayer {
+    match layered_data.layers().last() {
+        Some(LayeredData::Custom(layer)) => layer,
+        _ => panic!("Missing custom layer"),
+    }
+}
+
+#[test]
+fn dissects_udp_payload_on_registered_port() {
+    let mut options = ParseOptions::default();
+    options.parsers.register(
+        |ctx: &ParseContext| ctx.layer == LayerKind::Udp && ctx.has_port(5060),
+        sip_parser,
+    );
+    let datagram = udp_datagram(5060, b"INVITE sip:bob@example.com SIP/2.0");
+
+    let layered_data = UdpDatagram::from_bytes(&datagram)
+        .unwrap()
+        .parse_next_layer_with(&options, 0)
+        .unwrap();
+
+    assert!(layered_data.contains(LayerKind::Custom));
+    let layer = custom_layer(&layered_data);
+    assert_eq!(layer.name, "sip");
+    assert_eq!(
+        layer.downcast_ref::<Sip>(),
+        Some(&Sip("INVITE sip:bob@example.com SIP/2.0".to_string()))
+    );
+    assert_eq!(layer.downcast_ref::<String>(), None);
+}
+
+#[test]
+fn dissects_tcp_payload() {
+    let mut options = ParseOptions::default();
+    options.parsers.register(
+        |ctx: &ParseContext| ctx.layer == LayerKind::Tcp && ctx.has_port(80),
+        |_: &ParseContext, payload: &[u8]| {
+            Some(LayeredData::Custom(CustomLayer::new(
+                "length",
+                payload.len(),
+            )))
+        },
+    );
+
+    let segment = generate_tcp_packets_with_options();
+    let layered_data = TcpSegment::from_bytes(&segment)
+        .unwrap()
+        .parse_next_layer_with(&options, 0)
+        .unwrap();
+
+    let layer = custom_layer(&layered_data);
+    assert_eq!(layer.downcast_ref::<usize>(), Some(&DEFAULT_DATA.len()));
+}
+
+#[test]
+fn leaves_payload_raw_if_no_parser_matches() {
+    let mut options = ParseOptions::default();
+    options
+        .parsers
+        .register(|ctx: &ParseContext| ctx.has_port(5060), sip_parser);
+
+    let unmatched = UdpDatagram::from_bytes(&UDP_PACKETS)
+        .unwrap()
+        .parse_next_layer_with(&options, 0)
+        .unwrap();
+    // Sent to the SIP port, but not valid UTF-8, so the built-in SIP dissector rejects it
+    // too and leaves it raw.
+    let datagram = udp_datagram(5060, &[0xFF, 0xFE]);
+    let rejected = UdpDatagram::from_bytes(&datagram)
+        .unwrap()
+        .parse_next_layer_with(&options, 0)
+        .unwrap();
+
+    assert!(!unmatched.contains(LayerKind::Custom));
+    assert!(!rejected.contains(LayerKind::Custom));
+    assert_eq!(
+        rejected.layers().last(),
+        Some(&LayeredData::Payload(vec![0xFF, 0xFE].into()))
+    );
+}
+
+#[test]
+fn custom_parsers_take_precedence_over_builtin_dissectors() {
+    let mut options = ParseOptions::default();
+    options.parsers.register(
+        |ctx: &ParseContext| ctx.has_port(4789),
+        |_: &ParseContext, _: &[u8]| Some(LayeredData::Custom(CustomLayer::new("first", ()))),
+    );
+    options.parsers.register(
+        |_: &ParseContext| true,
+        |_: &ParseContext, _: &[u8]| Some(LayeredData::Custom(CustomLayer::new("second", ()))),
+    );
+    let datagram = generate_vxlan_mock_datagram(&[0; 64]);
+
+    let layered_data = UdpDatagram::from_bytes(&datagram)
+        .unwrap()
+        .parse_next_layer_with(&options, 0)
+        .unwrap();
+
+    assert!(!layered_data.contains(LayerKind::Vxlan));
+    assert_eq!(custom_layer(&layered_data).name, "first");
+}
+
+#[test]
+fn registry_clones_share_parsers() {
+    let mut registry = ParserRegistry::new();
+    assert!(registry.is_empty());
+
+    registry.register(|_: &ParseContext| true, sip_parser);
+    let clone = registry.clone();
+    let ctx = ParseContext::new(LayerKind::Udp, 5060, 5060);
+
+    assert_eq!(clone.len(), 1);
+    let layer = clone.dissect(&ctx, b"ACK").unwrap();
+    assert_eq!(layer.clone(), layer);
+}
+
+#[test]
+fn context_carries_the_enclosing_ip_packet() {
+    let mut options = ParseOptions::default();
+    options.parsers.register(
+        |ctx: &ParseContext| ctx.layer == LayerKind::Tcp,
+        |ctx: &ParseContext, _: &[u8]| {
+            Some(LayeredData::Custom(CustomLayer::new(
+                "context",
+                ctx.clone(),
+            )))
+        },
+    );
+    let packet = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+
+    let layered_data = Ipv4Packet::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer_with(&options, 0)
+        .unwrap();
+    let ctx = custom_layer(&layered_data)
+        .downcast_ref::<ParseContext>()
+        .unwrap();
+
+    assert_eq!(ctx.src_ip, Some(Ipv4Addr::from(DEFAULT_SRC_ADDR).into()));
+    assert_eq!(ctx.dst_ip, Some(Ipv4Addr::from(DEFAULT_DEST_ADDR).into()));
+    assert_eq!(ctx.l3_payload_len, Some(packet.len() - 20));
+    assert_eq!(ctx.destination_port, 80);
+}
+
+#[test]
+fn serializes_custom_layer_as_the_payload_it_was_decoded_from() {
+    let mut options = ParseOptions::default();
+    options.parsers.register(
+        |ctx: &ParseContext| ctx.layer == LayerKind::Udp && ctx.has_port(5060),
+        sip_parser,
+    );
+    let datagram = udp_datagram(5060, b"INVITE sip:bob@example.com SIP/2.0");
+
+    let layered_data = UdpDatagram::from_bytes(&datagram)
+        .unwrap()
+        .parse_next_layer_with(&options, 0)
+        .unwrap();
+
+    assert_eq!(
+        custom_layer(&layered_data).bytes(),
+        b"INVITE sip:bob@example.com SIP/2.0"
+    );
+    assert_eq!(layered_data.to_bytes(), datagram);
+}
@@ -0,0 +1,312 @@
+// Serializes arbitrary valid layers with `to_bytes`, parses them back with `from_bytes` and
+// checks that every field survives. Fields that `to_bytes` recomputes, such as lengths and
+// checksums, are taken from the parsed layer before comparing.
+
+use net_sift::parsers::{
+    constants,
+    definitions::{EtherType, IPType, LayeredData},
+    ethernet_frame::{EthernetFrame, EthernetFrameBuilder, MacAddress},
+    ipv4::{Ipv4Packet, Ipv4PacketHeader},
+    ipv6::{Ipv6Packet, Ipv6PacketHeader},
+    tcp::{Flags, TcpOption, TcpSegment, TcpSegmentHeader},
+    udp::{UdpDatagram, UdpDatagramHeader},
+    websocket::WebSocketFrame,
+};
+use proptest::prelude::*;
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+const TPID_VLAN: u32 = 0x8100;
+const TPID_SERVICE_VLAN: u32 = 0x88A8;
+const EXTENSION_HEADERS: [u8; 3] = [0, 43, 60];
+
+fn payload(max: usize) -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(any::<u8>(), 0..max)
+}
+
+// Options padded to whole 32-bit words, as both IPv4 and TCP require.
+fn options() -> impl Strategy<Value = Vec<u8>> {
+    (0..=10usize).prop_flat_map(|words| prop::collection::vec(any::<u8>(), words * 4))
+}
+
+fn ethernet_frame() -> impl Strategy<Value = EthernetFrame<'static>> {
+    (
+        any::<[u8; 6]>(),
+        any::<[u8; 6]>(),
+        prop::option::of(any::<u16>()),
+        prop::option::of(any::<u16>()),
+        prop::sample::select(&constants::ACCEPTED_ETHERTYPES[..]),
+        // The parser requires frames of at least 64 bytes.
+        prop::collection::vec(any::<u8>(), 50..128),
+    )
+        .prop_map(|(destination, source, s_tci, tci, ether_type, payload)| {
+            let mut frame = EthernetFrameBuilder::new()
+                .dst_mac(MacAddress::from(destination))
+                .src_mac(MacAddress::from(source))
+                .ether_type(EtherType::from(u16::from_be_bytes(ether_type)))
+                .payload(LayeredData::Payload(payload.into()))
+                .build();
+            frame.header.s_tag = s_tci.map(|tci| (TPID_SERVICE_VLAN << 16) | tci as u32);
+            frame.header.q_tag = tci.map(|tci| (TPID_VLAN << 16) | tci as u32);
+            frame
+        })
+}
+
+fn ipv4_packet() -> impl Strategy<Value = Ipv4Packet<'static>> {
+    (
+        (any::<u8>(), any::<u16>(), 0..8u8, 0..8192u16, any::<u8>()),
+        (any::<u8>(), any::<[u8; 4]>(), any::<[u8; 4]>()),
+        options(),
+        payload(64),
+    )
+        .prop_map(
+            |(
+                (type_of_service, identification, flags, fragment_offset, time_to_live),
+                (protocol, source, destination),
+                options,
+                payload,
+            )| Ipv4Packet {
+                header: Ipv4PacketHeader {
+                    type_of_service,
+                    identification,
+                    flags,
+                    fragment_offset,
+                    time_to_live,
+                    protocol: IPType::from(protocol),
+                    source_address: Ipv4Addr::from(source),
+                    destination_address: Ipv4Addr::from(destination),
+                    options: (!options.is_empty()).then_some(options),
+                    ..Default::default()
+                },
+                data: Box::new(LayeredData::Payload(payload.into())),
+                ..Default::default()
+            },
+        )
+}
+
+fn ipv6_packet() -> impl Strategy<Value = Ipv6Packet<'static>> {
+    (
+        any::<u8>(),
+        0..(1u32 << 20),
+        // An extension header would be parsed out of the payload.
+        any::<u8>().prop_filter("not an extension header", |next| {
+            !EXTENSION_HEADERS.contains(next)
+        }),
+        any::<u8>(),
+        any::<[u8; 16]>(),
+        any::<[u8; 16]>(),
+        payload(64),
+    )
+        .prop_map(
+            |(traffic_class, flow_label, next_header, hop_limit, source, destination, payload)| {
+                Ipv6Packet {
+                    header: Ipv6PacketHeader {
+                        traffic_class,
+                        flow_label,
+                        next_header: IPType::from(next_header),
+                        hop_limit,
+                        source_address: Ipv6Addr::from(source),
+                        destination_address: Ipv6Addr::from(destination),
+                        ..Default::default()
+                    },
+                    data: Box::new(LayeredData::Payload(payload.into())),
+                    ..Default::default()
+                }
+            },
+        )
+}
+
+// Every option but the End of Option List, after which nothing more is decoded.
+fn tcp_option() -> impl Strategy<Value = TcpOption> {
+    prop_oneof![
+        Just(TcpOption::NoOp),
+        any::<u16>().prop_map(TcpOption::Mss),
+        any::<u8>().prop_map(TcpOption::WindowScale),
+        Just(TcpOption::SackPermitted),
+        prop::collection::vec(any::<(u32, u32)>(), 1..=4).prop_map(TcpOption::Sack),
+        (any::<u32>(), any::<u32>())
+            .prop_map(|(tsval, tsecr)| TcpOption::Timestamp { tsval, tsecr }),
+        (
+            (6..=255u8).prop_filter("decoded kind", |kind| *kind != 8),
+            payload(8)
+        )
+            .prop_map(|(kind, data)| TcpOption::Unknown { kind, data }),
+    ]
+}
+
+// The length of `option` in its wire format.
+fn encoded_len(option: &TcpOption) -> usize {
+    match option {
+        TcpOption::EndOfOptions | TcpOption::NoOp => 1,
+        TcpOption::Mss(_) => 4,
+        TcpOption::WindowScale(_) => 3,
+        TcpOption::SackPermitted => 2,
+        TcpOption::Sack(blocks) => 2 + blocks.len() * 8,
+        TcpOption::Timestamp { .. } => 10,
+        TcpOption::Unknown { data, .. } => 2 + data.len(),
+    }
+}
+
+// Options fitting within the 40 bytes a header can hold.
+fn tcp_options() -> impl Strategy<Value = Vec<TcpOption>> {
+    prop::collection::vec(tcp_option(), 0..8).prop_filter("options too long", |options| {
+        options.iter().map(encoded_len).sum::<usize>() <= 40
+    })
+}
+
+fn tcp_segment() -> impl Strategy<Value = TcpSegment<'static>> {
+    (
+        (any::<u16>(), any::<u16>(), any::<u32>(), any::<u32>()),
+        (
+            0..16u8,
+            any::<u8>(),
+            any::<u16>(),
+            any::<u16>(),
+            any::<u16>(),
+        ),
+        tcp_options(),
+        payload(64),
+    )
+        .prop_map(
+            |(
+                (source_port, destination_port, sequence_number, acknowledgment_value),
+                (reserved, flags, window_size, checksum, urg_pointer),
+                options,
+                payload,
+            )| TcpSegment {
+                header: TcpSegmentHeader {
+                    source_port,
+                    destination_port,
+                    sequence_number,
+                    acknowledgment_value,
+                    reserved,
+                    flags: Flags::new(flags),
+                    window_size,
+                    checksum,
+                    urg_pointer,
+                    options,
+                    ..Default::default()
+                },
+                data: Box::new(LayeredData::Payload(payload.into())),
+                ..Default::default()
+            },
+        )
+}
+
+fn udp_datagram() -> impl Strategy<Value = UdpDatagram<'static>> {
+    (any::<u16>(), any::<u16>(), any::<u16>(), payload(64)).prop_map(
+        |(source_port, destination_port, checksum, payload)| UdpDatagram {
+            header: UdpDatagramHeader {
+                source_port,
+                destination_port,
+                checksum,
+                ..Default::default()
+            },
+            data: Box::new(LayeredData::Payload(payload.into())),
+            ..Default::default()
+        },
+    )
+}
+
+fn websocket_frame() -> impl Strategy<Value = WebSocketFrame> {
+    (
+        any::<bool>(),
+        0..8u8,
+        0..16u8,
+        prop::option::of(any::<[u8; 4]>()),
+        // Long enough to need each of the three length encodings.
+        prop_oneof![payload(126), payload(70_000)],
+    )
+        .prop_map(|(fin, rsv, opcode, masking_key, payload)| WebSocketFrame {
+            fin,
+            rsv,
+            opcode,
+            masking_key,
+            payload,
+            span: 0..0,
+            raw_header: Vec::new(),
+        })
+}
+
+proptest! {
+    #[test]
+    fn ethernet_frame_round_trips(frame in ethernet_frame()) {
+        let bytes = frame.to_bytes();
+        let parsed = EthernetFrame::from_bytes(&bytes, false).unwrap();
+
+        let mut expected = frame;
+        expected.header.span = 0..parsed.header.raw_header.len();
+        expected.header.raw_header = bytes[expected.header.span.clone()].to_vec();
+        prop_assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn ipv4_packet_round_trips(packet in ipv4_packet()) {
+        let bytes = packet.to_bytes();
+        let parsed = Ipv4Packet::from_bytes(&bytes).unwrap();
+
+        let options_len = packet.header.options.as_ref().map_or(0, Vec::len);
+        let mut expected = packet;
+        expected.header.internet_header_length = (5 + options_len / 4) as u8;
+        expected.header.total_length = bytes.len() as u16;
+        expected.header.header_checksum = parsed.header.header_checksum;
+        expected.header.span = 0..20 + options_len;
+        expected.header.raw_header = bytes[..20 + options_len].to_vec();
+        prop_assert!(parsed.verify_checksum(&parsed.header.raw_header));
+        prop_assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn ipv6_packet_round_trips(packet in ipv6_packet()) {
+        let bytes = packet.to_bytes();
+        let parsed = Ipv6Packet::from_bytes(&bytes).unwrap();
+
+        let mut expected = packet;
+        expected.header.payload_length = (bytes.len() - 40) as u16;
+        expected.header.span = 0..40;
+        expected.header.raw_header = bytes[..40].to_vec();
+        prop_assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn tcp_segment_round_trips(segment in tcp_segment()) {
+        let bytes = segment.to_bytes();
+        let parsed = TcpSegment::from_bytes(&bytes).unwrap();
+
+        let options_len: usize = segment.header.options.iter().map(encoded_len).sum();
+        let header_length = 20 + options_len.div_ceil(4) * 4;
+        let mut expected = segment;
+        // Padding parses back as an End of Option List.
+        if !options_len.is_multiple_of(4) {
+            expected.header.options.push(TcpOption::EndOfOptions);
+        }
+        expected.header.data_offset = (header_length / 4) as u8;
+        expected.header.span = 0..header_length;
+        expected.header.raw_header = bytes[..header_length].to_vec();
+        prop_assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn udp_datagram_round_trips(datagram in udp_datagram()) {
+        let bytes = datagram.to_bytes();
+        let parsed = UdpDatagram::from_bytes(&bytes).unwrap();
+
+        let mut expected = datagram;
+        expected.header.length = bytes.len() as u16;
+        expected.header.span = 0..8;
+        expected.header.raw_header = bytes[..8].to_vec();
+        prop_assert_eq!(parsed, expected);
+    }
+
+    #[test]
+    fn websocket_frame_round_trips(frame in websocket_frame()) {
+        let bytes = frame.to_bytes();
+        let parsed = WebSocketFrame::from_bytes(&bytes).unwrap();
+
+        let header_length = bytes.len() - frame.payload.len();
+        let mut expected = frame;
+        expected.span = 0..header_length;
+        expected.raw_header = bytes[..header_length].to_vec();
+        prop_assert_eq!(parsed, expected);
+    }
+}
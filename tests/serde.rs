@@ -0,0 +1,60 @@
+#![cfg(feature = "serde")]
+
+mod mock_data;
+
+use mock_data::*;
+use net_sift::parsers::{
+    definitions::{DeepParser, EtherType, IPType, LayeredData},
+    ethernet_frame::{EthernetFrame, MacAddress},
+};
+use serde_json::json;
+use std::net::Ipv4Addr;
+
+fn tcp_over_ipv4_frame() -> LayeredData<'static> {
+    let segment = generate_tcp_segment(80, b"hi");
+    let packet = generate_ipv4_packet(DEFAULT_TCP_PROTOCOL[0], &segment);
+    let frame = generate_padded_ethernet_frame([8, 0], &packet);
+
+    EthernetFrame::from_bytes(&frame, false)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap()
+        .into_owned()
+}
+
+#[test]
+fn serializes_tcp_over_ipv4_frame() {
+    let json = serde_json::to_value(tcp_over_ipv4_frame()).unwrap();
+
+    let frame = &json["EthernetFrameData"];
+    assert_eq!(
+        frame["header"]["mac_destination"],
+        MacAddress(DEFAULT_DEST_MAC).to_string()
+    );
+    assert_eq!(frame["header"]["ether_type"], "IPv4");
+
+    let packet = &frame["data"]["Ipv4Data"];
+    assert_eq!(packet["header"]["protocol"], "TCP");
+    assert_eq!(
+        packet["header"]["source_address"],
+        Ipv4Addr::from(DEFAULT_SRC_ADDR).to_string()
+    );
+    assert_eq!(packet["header"]["raw_header"].as_str().unwrap().len(), 40);
+
+    let segment = &packet["data"]["TcpData"];
+    assert_eq!(segment["header"]["destination_port"], 80);
+    assert_eq!(segment["data"], json!({ "Payload": "6869" }));
+}
+
+#[test]
+fn serializes_unknown_types_with_their_value() {
+    assert_eq!(serde_json::to_value(IPType::UDP).unwrap(), "UDP");
+    assert_eq!(
+        serde_json::to_value(IPType::Other(99)).unwrap(),
+        json!({ "Other": 99 })
+    );
+    assert_eq!(
+        serde_json::to_value(EtherType::Other(0x88B5)).unwrap(),
+        json!({ "Other": 0x88B5 })
+    );
+}
@@ -0,0 +1,189 @@
+mod mock_data;
+
+use mock_data::{generate_tcp_segment, SIP_INVITE};
+use net_sift::parsers::{
+    definitions::{DeepParser, LayerKind, LayeredData},
+    errors::ParserError,
+    sip::{SipMessage, SipMethod, SipStartLine},
+    tcp::TcpSegment,
+    udp::UdpDatagram,
+};
+
+const SIP_BODY: &[u8] = b"v=0\r\n";
+
+fn sip_datagram(message: &[u8]) -> Vec<u8> {
+    [
+        &[19, 196, 19, 196][..],
+        &((8 + message.len()) as u16).to_be_bytes(),
+        &[0, 0],
+        message,
+    ]
+    .concat()
+}
+
+#[test]
+fn can_parse_minimal_invite() {
+    let message = SipMessage::from_bytes(SIP_INVITE).unwrap();
+
+    assert_eq!(
+        message.start_line,
+        SipStartLine::Request {
+            method: SipMethod::Invite,
+            uri: "sip:bob@biloxi.example.com".to_string(),
+            version: "SIP/2.0".to_string(),
+        }
+    );
+    assert_eq!(
+        message.header("Via"),
+        Some("SIP/2.0/UDP pc33.atlanta.example.com;branch=z9hG4bK776asdhds")
+    );
+    assert_eq!(
+        message.header("from"),
+        Some("Alice <sip:alice@atlanta.example.com>;tag=1928301774")
+    );
+    assert_eq!(
+        message.header("To"),
+        Some("Bob <sip:bob@biloxi.example.com>")
+    );
+    assert_eq!(
+        message.call_id(),
+        Some("a84b4c76e66710@pc33.atlanta.example.com")
+    );
+    assert_eq!(message.cseq(), Some((314159, SipMethod::Invite)));
+    assert_eq!(message.header("Content-Type"), Some("application/sdp"));
+    assert_eq!(message.body, SIP_BODY.to_vec());
+    assert_eq!(message.span, 0..SIP_INVITE.len() - SIP_BODY.len());
+}
+
+#[test]
+fn can_parse_status_line() {
+    let message = SipMessage::from_bytes(b"SIP/2.0 180 Ringing\r\nCall-ID: abc\r\n\r\n").unwrap();
+
+    assert_eq!(
+        message.start_line,
+        SipStartLine::Response {
+            version: "SIP/2.0".to_string(),
+            status_code: 180,
+            reason: "Ringing".to_string(),
+        }
+    );
+    assert_eq!(message.call_id(), Some("abc"));
+}
+
+#[test]
+fn joins_folded_headers() {
+    let message = SipMessage::from_bytes(
+        b"BYE sip:bob@b.example SIP/2.0\r\nSubject: Lunch\r\n  plans\r\n\r\n",
+    )
+    .unwrap();
+
+    assert_eq!(message.header("Subject"), Some("Lunch plans"));
+}
+
+#[test]
+fn finds_compact_headers_by_full_name() {
+    let message = SipMessage::from_bytes(
+        b"ACK sip:bob@b.example SIP/2.0\r\ni: abc\r\nv: SIP/2.0/UDP a\r\n\r\n",
+    )
+    .unwrap();
+
+    assert_eq!(message.call_id(), Some("abc"));
+    assert_eq!(message.header("Via"), Some("SIP/2.0/UDP a"));
+    assert_eq!(message.headers[0].0, "i");
+}
+
+#[test]
+fn can_parse_message_without_body() {
+    let with_blank_line = SipMessage::from_bytes(b"OPTIONS sip:b.example SIP/2.0\r\n\r\n").unwrap();
+    let without_blank_line =
+        SipMessage::from_bytes(b"REGISTER sip:b.example SIP/2.0\r\nCall-ID: abc").unwrap();
+
+    assert!(with_blank_line.body.is_empty());
+    assert!(without_blank_line.body.is_empty());
+    assert_eq!(without_blank_line.call_id(), Some("abc"));
+}
+
+#[test]
+fn fails_if_body_is_shorter_than_content_length() {
+    let result = SipMessage::from_bytes(&SIP_INVITE[..SIP_INVITE.len() - 1]);
+
+    assert!(matches!(
+        result,
+        Err(ParserError::InconsistentLength {
+            declared: 5,
+            available: 4,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn fails_on_malformed_start_line() {
+    let result = SipMessage::from_bytes(b"HELLO\r\n\r\n");
+
+    assert!(matches!(result, Err(ParserError::MalformedMessage { .. })));
+}
+
+#[test]
+fn serializes_back_into_the_original_message() {
+    let message = SipMessage::from_bytes(SIP_INVITE).unwrap();
+
+    assert_eq!(message.to_bytes(), SIP_INVITE.to_vec());
+}
+
+#[test]
+fn udp_dispatches_sip_port_to_sip() {
+    let datagram = sip_datagram(SIP_INVITE);
+    let layered_data = UdpDatagram::from_bytes(&datagram)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert!(layered_data.contains(LayerKind::Sip));
+    let Some(LayeredData::SipData(message)) = layered_data.layers().last() else {
+        panic!("expected a SIP message");
+    };
+    assert_eq!(message.cseq(), Some((314159, SipMethod::Invite)));
+}
+
+#[test]
+fn leaves_keepalives_raw() {
+    let datagram = sip_datagram(b"\r\n\r\n");
+    let layered_data = UdpDatagram::from_bytes(&datagram)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert!(!layered_data.contains(LayerKind::Sip));
+}
+
+#[test]
+fn tcp_dispatches_sip_port_to_sip() {
+    let bytes = generate_tcp_segment(5060, SIP_INVITE);
+    let layered_data = TcpSegment::from_bytes(&bytes)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert!(layered_data.contains(LayerKind::Sip));
+}
+
+#[test]
+fn tcp_leaves_continuation_segments_raw() {
+    // The rest of a body whose headers came in an earlier segment.
+    let continuation = b"o=alice 2890844526 2890844526 IN IP4 host.example.com\r\n";
+
+    for payload in [&continuation[..], &SIP_INVITE[..SIP_INVITE.len() - 1]] {
+        let bytes = generate_tcp_segment(5060, payload);
+        let layered_data = TcpSegment::from_bytes(&bytes)
+            .unwrap()
+            .parse_next_layer()
+            .unwrap();
+
+        assert!(!layered_data.contains(LayerKind::Sip));
+        let LayeredData::TcpData(segment) = layered_data else {
+            panic!("expected a TCP segment");
+        };
+        assert_eq!(segment.data.payload(), Some(payload));
+    }
+}
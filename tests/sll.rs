@@ -0,0 +1,125 @@
+mod mock_data;
+
+use mock_data::{
+    generate_arp_mock_packet, generate_ipv4_mock_packets, generate_ipv4_packet,
+    generate_sll_mock_packet, generate_tcp_packets_without_options, set_ipv4_header_checksum,
+    ARP_ETHER_TYPE, DEFAULT_SRC_MAC, DEFAULT_TCP_PROTOCOL, IPV4_ETHER_TYPE, TCP,
+};
+use net_sift::parsers::{
+    definitions::{DeepParser, EtherType, LayerKind, LayeredData},
+    errors::ParserError,
+    sll::{SllHeader, SllPacket, SllPacketType},
+};
+
+#[test]
+fn can_parse_sll_header() {
+    let ipv4 = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let packet = generate_sll_mock_packet(IPV4_ETHER_TYPE, &ipv4);
+
+    let sll = SllPacket::from_bytes(&packet).unwrap();
+
+    assert_eq!(
+        sll.header,
+        SllHeader {
+            packet_type: SllPacketType::Host,
+            link_layer_type: 1,
+            address_length: 6,
+            address: DEFAULT_SRC_MAC.to_vec(),
+            protocol: EtherType::IPv4,
+            span: 0..16,
+            raw_header: packet[..16].to_vec(),
+        }
+    );
+    assert_eq!(sll.data, Box::new(LayeredData::Payload(ipv4.into())));
+}
+
+#[test]
+fn deep_parses_ipv4_carried_by_sll() {
+    let ipv4 = generate_ipv4_mock_packets(DEFAULT_TCP_PROTOCOL, None);
+    let packet = generate_sll_mock_packet(IPV4_ETHER_TYPE, &ipv4);
+
+    let layers = SllPacket::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    let kinds: Vec<_> = layers
+        .layers()
+        .filter_map(LayeredData::layer_kind)
+        .collect();
+    assert_eq!(kinds, [LayerKind::Sll, LayerKind::Ipv4, LayerKind::Tcp]);
+    assert_eq!(layers.spans()[..2], [0..16, 16..36]);
+}
+
+#[test]
+fn deep_parses_arp_carried_by_sll() {
+    let packet = generate_sll_mock_packet(ARP_ETHER_TYPE, &generate_arp_mock_packet(1));
+
+    let layers = SllPacket::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert!(matches!(layers.inner(), Some(LayeredData::ArpData(_))));
+}
+
+#[test]
+fn leaves_unknown_protocols_raw() {
+    // 802.2 LLC frames are flagged with protocol 4 rather than an EtherType.
+    let packet = generate_sll_mock_packet([0, 4], &[0x42, 0x42, 0x03]);
+
+    let LayeredData::SllData(sll) = SllPacket::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap()
+    else {
+        panic!("expected an SLL packet");
+    };
+
+    assert_eq!(sll.header.protocol, EtherType::Other(4));
+    assert_eq!(
+        sll.data,
+        Box::new(LayeredData::Payload(vec![0x42, 0x42, 0x03].into()))
+    );
+}
+
+#[test]
+fn reads_packet_type_and_caps_address_length() {
+    let mut packet = generate_sll_mock_packet(IPV4_ETHER_TYPE, &[]);
+    packet[1] = 4; // Outgoing
+    packet[5] = 20; // Longer than the address field
+
+    let header = SllPacket::from_bytes(&packet).unwrap().header;
+
+    assert_eq!(header.packet_type, SllPacketType::Outgoing);
+    assert_eq!(header.address_length, 20);
+    assert_eq!(header.address.len(), 8);
+}
+
+#[test]
+fn fails_if_header_is_too_short() {
+    let packet = generate_sll_mock_packet(IPV4_ETHER_TYPE, &[]);
+
+    assert!(matches!(
+        SllPacket::from_bytes(&packet[..15]),
+        Err(ParserError::HeaderTooShort {
+            got: 15,
+            need: 16,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn serializes_header_and_payload() {
+    let mut ipv4 = generate_ipv4_packet(TCP, &generate_tcp_packets_without_options());
+    set_ipv4_header_checksum(&mut ipv4);
+    let packet = generate_sll_mock_packet(IPV4_ETHER_TYPE, &ipv4);
+
+    let layered_data = SllPacket::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert_eq!(layered_data.to_bytes(), packet);
+}
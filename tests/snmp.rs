@@ -0,0 +1,93 @@
+mod mock_data;
+
+use mock_data::{generate_snmp_mock_datagram, SNMP_GET_REQUEST};
+use net_sift::parsers::{
+    definitions::{DeepParser, LayerKind, LayeredData},
+    errors::ParserError,
+    snmp::{SnmpMessage, SnmpPduType, SnmpVersion},
+    udp::UdpDatagram,
+};
+
+#[test]
+fn can_parse_snmp_envelope() {
+    let message = SnmpMessage::from_bytes(&SNMP_GET_REQUEST).unwrap();
+
+    assert_eq!(message.version, SnmpVersion::V2c);
+    assert_eq!(message.community, b"public".to_vec());
+    assert_eq!(message.pdu_type, SnmpPduType::GetRequest);
+    assert_eq!(message.pdu, SNMP_GET_REQUEST[15..].to_vec());
+    assert_eq!(message.span, 0..SNMP_GET_REQUEST.len());
+}
+
+#[test]
+fn can_parse_long_form_length() {
+    // The same message, with the outer SEQUENCE length encoded as 0x81 0x29.
+    let message = [&[48, 129, 41][..], &SNMP_GET_REQUEST[2..]].concat();
+
+    let snmp = SnmpMessage::from_bytes(&message).unwrap();
+
+    assert_eq!(snmp.community, b"public".to_vec());
+    assert_eq!(snmp.span, 0..message.len());
+}
+
+#[test]
+fn fails_if_length_runs_past_buffer() {
+    let message = [&[48, 130, 255, 255][..], &SNMP_GET_REQUEST[2..]].concat();
+
+    let result = SnmpMessage::from_bytes(&message);
+
+    assert!(
+        matches!(result, Err(ParserError::InconsistentLength { layer, .. }) if layer == "BER element")
+    );
+}
+
+#[test]
+fn fails_if_long_form_length_is_truncated() {
+    let result = SnmpMessage::from_bytes(&[48, 132, 0, 0]);
+
+    assert!(matches!(result, Err(ParserError::ExtractionError { .. })));
+}
+
+#[test]
+fn fails_on_unsupported_version() {
+    let mut message = SNMP_GET_REQUEST;
+    message[4] = 3; // SNMPv3
+
+    let result = SnmpMessage::from_bytes(&message);
+
+    assert!(matches!(
+        result,
+        Err(ParserError::UnsupportedSnmpVersion(3))
+    ));
+}
+
+#[test]
+fn can_parse_snmp_from_udp_datagram() {
+    let datagram = generate_snmp_mock_datagram(&SNMP_GET_REQUEST);
+
+    let layered_data = UdpDatagram::from_bytes(&datagram)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert!(layered_data.contains(LayerKind::Snmp));
+    match layered_data.inner() {
+        Some(LayeredData::SnmpData(message)) => {
+            assert_eq!(message.pdu_type, SnmpPduType::GetRequest)
+        }
+        _ => panic!("Invalid nested layered data"),
+    }
+}
+
+#[test]
+fn serializes_message_carried_by_udp() {
+    let datagram = generate_snmp_mock_datagram(&SNMP_GET_REQUEST);
+
+    let layered_data = UdpDatagram::from_bytes(&datagram)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert_eq!(layered_data.inner().unwrap().to_bytes(), SNMP_GET_REQUEST);
+    assert_eq!(layered_data.to_bytes(), datagram);
+}
@@ -0,0 +1,79 @@
+mod mock_data;
+
+use mock_data::{
+    generate_arp_mock_packet, generate_ipv4_packet, generate_lldp_mock_packet,
+    generate_padded_ethernet_frame, generate_tcp_packets_with_options, ICMP, ICMP_ECHO_REQUEST,
+    IPV4_ETHER_TYPE, LLDP_ETHER_TYPE, RARP_ETHER_TYPE, TCP, UDP, UDP_PACKETS,
+};
+use net_sift::parsers::{definitions::DeepParser, ethernet_frame::EthernetFrame};
+
+// Deep-parses an Ethernet frame, without an FCS, carrying `payload`.
+fn summarize(ether_type: [u8; 2], payload: &[u8]) -> String {
+    let frame = generate_padded_ethernet_frame(ether_type, payload);
+
+    EthernetFrame::from_bytes(&frame, false)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap()
+        .summary()
+}
+
+#[test]
+fn summarizes_a_tcp_segment() {
+    let mut segment = generate_tcp_packets_with_options();
+    segment[13] = 0x02; // SYN
+
+    assert_eq!(
+        summarize(IPV4_ETHER_TYPE, &generate_ipv4_packet(TCP, &segment)),
+        "100.127.60.5:53145 -> 30.44.8.50:80 TCP SYN 8B"
+    );
+
+    segment[13] = 0x12; // SYN, ACK
+    assert_eq!(
+        summarize(IPV4_ETHER_TYPE, &generate_ipv4_packet(TCP, &segment)),
+        "100.127.60.5:53145 -> 30.44.8.50:80 TCP SYN ACK 8B"
+    );
+}
+
+#[test]
+fn summarizes_a_udp_datagram() {
+    assert_eq!(
+        summarize(IPV4_ETHER_TYPE, &generate_ipv4_packet(UDP, &UDP_PACKETS)),
+        "100.127.60.5:3327 -> 30.44.8.50:25615 UDP 6B"
+    );
+}
+
+#[test]
+fn summarizes_an_icmp_message() {
+    assert_eq!(
+        summarize(
+            IPV4_ETHER_TYPE,
+            &generate_ipv4_packet(ICMP, &ICMP_ECHO_REQUEST)
+        ),
+        "100.127.60.5 -> 30.44.8.50 ICMP echo request id 1 seq 44"
+    );
+}
+
+#[test]
+fn falls_back_to_the_layers_present() {
+    // GRE is not parsed, so its packet is left as raw payload.
+    let frame = generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &generate_ipv4_packet(47, &[0; 4]));
+    let layered_data = EthernetFrame::from_bytes(&frame, false)
+        .unwrap()
+        .parse_best_effort()
+        .unwrap();
+    assert_eq!(
+        layered_data.summary(),
+        "100.127.60.5 -> 30.44.8.50 IPv4 proto 47 4B"
+    );
+
+    assert_eq!(
+        summarize(RARP_ETHER_TYPE, &generate_arp_mock_packet(3)),
+        "RARP who-is 0C:19:3C:FF:58:0C tell 6C:64:13:19:C8:C7"
+    );
+
+    assert_eq!(
+        summarize(LLDP_ETHER_TYPE, &generate_lldp_mock_packet()),
+        "6C:64:13:19:C8:C7 -> 0C:19:3C:FF:58:0C LLDP"
+    );
+}
@@ -1,12 +1,23 @@
 mod mock_data;
 
 use mock_data::{
-    generate_tcp_packets_with_options, generate_tcp_packets_without_options, DEFAULT_DATA,
-    DEFAULT_OPTIONS_DATA_OFFSET_RESERVED_FLAGS_WINDOW,
-    DEFAULT_ZERO_OPTIONS_DATA_OFFSET_RESERVED_FLAGS_WINDOW, MOCK_MALFORMED_PACKET,
+    generate_ipv4_packet, generate_ipv4_tcp_with_valid_checksum, generate_padded_ethernet_frame,
+    generate_tcp_packets_with_options, generate_tcp_packets_without_options, generate_tcp_segment,
+    generate_tcp_segment_with_options, ones_complement_checksum, DEFAULT_DATA, DEFAULT_DEST_ADDR,
+    DEFAULT_DEST_ADDRESS, DEFAULT_OPTIONS_DATA_OFFSET_RESERVED_FLAGS_WINDOW, DEFAULT_SRC_ADDR,
+    DEFAULT_SRC_ADDRESS, DEFAULT_ZERO_OPTIONS_DATA_OFFSET_RESERVED_FLAGS_WINDOW, IPV4_ETHER_TYPE,
+    MIN_IPV4_LENGTH, MOCK_MALFORMED_PACKET, TCP, TCP_SYN_OPTIONS,
 };
 use net_sift::parsers::{
-    definitions::DeepParser, definitions::LayeredData, errors::ParserError, tcp,
+    definitions::{DeepParser, LayeredData, ParseOptions},
+    errors::ParserError,
+    ethernet_frame::EthernetFrame,
+    tcp::{self, Flags, TcpOption, TcpSegment, TcpSegmentHeader},
+};
+
+use std::{
+    collections::HashSet,
+    net::{Ipv4Addr, Ipv6Addr},
 };
 
 // fn generate_mock_segment(data_offset_reserved_flags_window: [u8; 4]) -> Vec<u8> {
@@ -43,11 +54,13 @@ struct TcpValues {
     expected_window_size: u16,
     expected_checksum: u16,
     expected_urg_pointer: u16,
+    expected_options: Vec<TcpOption>,
     expected_data: Vec<u8>,
+    expected_raw_header: Vec<u8>,
 }
 
-impl From<TcpValues> for tcp::TcpSegment {
-    fn from(value: TcpValues) -> tcp::TcpSegment {
+impl From<TcpValues> for tcp::TcpSegment<'static> {
+    fn from(value: TcpValues) -> tcp::TcpSegment<'static> {
         tcp::TcpSegment {
             header: tcp::TcpSegmentHeader {
                 source_port: value.expected_src_port,
@@ -60,14 +73,23 @@ impl From<TcpValues> for tcp::TcpSegment {
                 window_size: value.expected_window_size,
                 checksum: value.expected_checksum,
                 urg_pointer: value.expected_urg_pointer,
+                options: value.expected_options,
+                span: 0..value.expected_data_offset as usize * 4,
+                raw_header: value.expected_raw_header,
             },
 
-            data: Box::new(LayeredData::Payload(value.expected_data)),
+            data: Box::new(LayeredData::Payload(value.expected_data.into())),
+            truncated: false,
+            raw: None,
         }
     }
 }
 
-fn expected_tcp_values(expected_data_offset: u8) -> TcpValues {
+fn expected_tcp_values(
+    expected_data_offset: u8,
+    expected_options: Vec<TcpOption>,
+    expected_raw_header: &[u8],
+) -> TcpValues {
     TcpValues {
         expected_src_port: 53145,
         expected_dest_port: 80,
@@ -79,7 +101,9 @@ fn expected_tcp_values(expected_data_offset: u8) -> TcpValues {
         expected_window_size: 5000,
         expected_checksum: 18459,
         expected_urg_pointer: 1345,
+        expected_options,
         expected_data: DEFAULT_DATA.to_vec(),
+        expected_raw_header: expected_raw_header.to_vec(),
     }
 }
 
@@ -97,7 +121,10 @@ fn can_parse_tcp_packet_without_options() {
     let data_offset =
         (u32::from_be_bytes(DEFAULT_ZERO_OPTIONS_DATA_OFFSET_RESERVED_FLAGS_WINDOW) >> 28) as u8;
 
-    validate_tcp(tcp_segment, expected_tcp_values(data_offset));
+    validate_tcp(
+        tcp_segment,
+        expected_tcp_values(data_offset, vec![], &segment[..data_offset as usize * 4]),
+    );
 }
 
 #[test]
@@ -107,15 +134,62 @@ fn can_parse_tcp_packet_with_options() {
     let data_offset =
         (u32::from_be_bytes(DEFAULT_OPTIONS_DATA_OFFSET_RESERVED_FLAGS_WINDOW) >> 28) as u8;
 
-    validate_tcp(tcp_segment, expected_tcp_values(data_offset));
+    validate_tcp(
+        tcp_segment,
+        expected_tcp_values(
+            data_offset,
+            vec![TcpOption::Unknown {
+                kind: 12,
+                data: vec![0, 255],
+            }],
+            &segment[..data_offset as usize * 4],
+        ),
+    );
 }
 
 #[test]
 fn fail_if_segment_is_too_short() {
     let result = tcp::TcpSegment::from_bytes(&MOCK_MALFORMED_PACKET);
 
-    let s = String::from("TCP segment");
-    assert!(matches!(result, Err(ParserError::InvalidLength(s))))
+    assert!(
+        matches!(result, Err(ParserError::HeaderTooShort { layer, .. }) if layer == "TCP segment")
+    )
+}
+
+#[test]
+fn fails_if_data_offset_is_out_of_range() {
+    let mut segment = generate_tcp_packets_without_options();
+
+    segment[12] = 4 << 4;
+    let result = tcp::TcpSegment::from_bytes(&segment);
+    assert!(matches!(result, Err(ParserError::InvalidLength(s)) if s == "TCP data offset"));
+
+    // 15 words is 60 bytes, more than the segment holds.
+    segment[12] = 15 << 4;
+    let result = tcp::TcpSegment::from_bytes(&segment);
+    assert!(matches!(
+        result,
+        Err(ParserError::OptionsOverrun {
+            declared: 40,
+            available: 8,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn fails_without_panicking_for_every_short_data_offset() {
+    let mut segment = generate_tcp_packets_with_options();
+
+    for data_offset in 0..5 {
+        segment[12] = data_offset << 4;
+
+        let result = tcp::TcpSegment::from_bytes(&segment);
+        assert!(
+            matches!(result, Err(ParserError::InvalidLength(ref s)) if s == "TCP data offset"),
+            "data offset {data_offset}: {result:?}"
+        );
+    }
 }
 
 #[test]
@@ -130,3 +204,356 @@ fn can_parse_layered_data() {
         _ => panic!("Invalid layered data"),
     };
 }
+
+// Returns the default segment with its sequence number and flags replaced.
+fn segment_with(sequence_number: u32, flags: u8) -> tcp::TcpSegment<'static> {
+    let mut segment = generate_tcp_packets_with_options();
+    segment[4..8].copy_from_slice(&sequence_number.to_be_bytes());
+    segment[13] = flags;
+
+    tcp::TcpSegment::from_bytes(&segment).unwrap().into_owned()
+}
+
+const ACK: u8 = 0x10;
+const SYN: u8 = 0x02;
+const FIN: u8 = 0x01;
+const URG: u8 = 0x20;
+
+#[test]
+fn payload_len_counts_data_bytes() {
+    assert_eq!(segment_with(1000, ACK).payload_len(), DEFAULT_DATA.len());
+
+    let bytes = generate_tcp_packets_without_options();
+    let header_only = tcp::TcpSegment::from_bytes(&bytes[..20]);
+    assert_eq!(header_only.unwrap().payload_len(), 0);
+}
+
+#[test]
+fn seq_len_accounts_for_syn_and_fin() {
+    let payload_len = DEFAULT_DATA.len() as u32;
+
+    assert_eq!(segment_with(1000, ACK).seq_len(), payload_len);
+    assert_eq!(segment_with(1000, SYN).seq_len(), payload_len + 1);
+    assert_eq!(segment_with(1000, FIN | ACK).seq_len(), payload_len + 1);
+    assert_eq!(segment_with(1000, SYN | FIN).seq_len(), payload_len + 2);
+}
+
+#[test]
+fn seq_range_spans_sequence_space() {
+    assert_eq!(segment_with(1000, ACK).seq_range(), 1000..1008);
+    assert_eq!(segment_with(1000, SYN).seq_range(), 1000..1009);
+}
+
+#[test]
+fn seq_range_wraps_around_near_u32_max() {
+    let segment = segment_with(u32::MAX - 3, FIN | ACK);
+
+    let range = segment.seq_range();
+
+    // 4 bytes before the wrap, 4 after it and 1 for the FIN.
+    assert_eq!((range.start, range.end), (u32::MAX - 3, 5));
+    assert_eq!(range.end.wrapping_sub(range.start), segment.seq_len());
+}
+
+#[test]
+fn urgent_pointer_is_only_significant_with_urg() {
+    let segment = segment_with(1000, ACK);
+    assert_eq!(segment.urgent_pointer(), None);
+    assert_eq!(segment.header.urg_pointer, 1345);
+
+    assert_eq!(segment_with(1000, URG | ACK).urgent_pointer(), Some(1345));
+}
+
+#[test]
+fn strict_mode_rejects_nonzero_reserved_bits() {
+    let mut segment = generate_tcp_packets_with_options();
+    segment[12] |= 0x05;
+    let strict = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+
+    let lenient = tcp::TcpSegment::from_bytes(&segment).unwrap();
+    assert_eq!(lenient.header.reserved, 5);
+    assert!(lenient.parse_next_layer().is_ok());
+
+    let result = tcp::TcpSegment::from_bytes(&segment)
+        .unwrap()
+        .parse_next_layer_with(&strict, 0);
+    assert!(matches!(
+        result,
+        Err(ParserError::ProtocolViolation { field, .. }) if field == "TCP reserved"
+    ));
+
+    let bytes = generate_tcp_packets_with_options();
+    let conforming = tcp::TcpSegment::from_bytes(&bytes).unwrap();
+    assert!(conforming.parse_next_layer_with(&strict, 0).is_ok());
+}
+
+#[test]
+fn equal_headers_hash_alike() {
+    let headers: HashSet<_> = [1000, 1000, 2000]
+        .into_iter()
+        .map(|sequence_number| segment_with(sequence_number, ACK).header)
+        .collect();
+
+    assert_eq!(headers.len(), 2);
+}
+
+#[test]
+fn verify_checksum_uses_the_pseudo_header() {
+    let packet = generate_ipv4_tcp_with_valid_checksum();
+    let raw = &packet[MIN_IPV4_LENGTH..];
+    let segment = tcp::TcpSegment::from_bytes(raw).unwrap();
+    let source = Ipv4Addr::from(DEFAULT_SRC_ADDR).into();
+    let destination = Ipv4Addr::from(DEFAULT_DEST_ADDR).into();
+
+    assert!(segment.verify_checksum(raw, source, destination));
+    assert!(!segment.verify_checksum(raw, Ipv4Addr::LOCALHOST.into(), destination));
+    assert!(!segment.verify_checksum(&raw[..12], source, destination));
+}
+
+#[test]
+fn keeps_raw_bytes_only_when_asked() {
+    let packet = generate_ipv4_tcp_with_valid_checksum();
+    let raw = &packet[MIN_IPV4_LENGTH..];
+    let source = Ipv4Addr::from(DEFAULT_SRC_ADDR).into();
+    let destination = Ipv4Addr::from(DEFAULT_DEST_ADDR).into();
+
+    let segment = TcpSegment::from_bytes_keep_raw(raw).unwrap();
+    assert_eq!(segment.raw.as_deref(), Some(raw));
+    assert_eq!(
+        segment.verify_stored_checksum(source, destination),
+        Some(true)
+    );
+
+    let segment = TcpSegment::from_bytes(raw).unwrap();
+    assert_eq!(segment.raw, None);
+    assert_eq!(segment.verify_stored_checksum(source, destination), None);
+}
+
+#[test]
+fn verify_checksum_pads_odd_length_segments_under_ipv6() {
+    let mut raw = generate_tcp_segment(80, b"odd");
+    raw[16..18].copy_from_slice(&[0, 0]);
+    let mut pseudo_header = [&DEFAULT_SRC_ADDRESS[..], &DEFAULT_DEST_ADDRESS].concat();
+    pseudo_header.extend_from_slice(&(raw.len() as u32).to_be_bytes());
+    pseudo_header.extend_from_slice(&[0, 0, 0, 6]);
+    pseudo_header.extend_from_slice(&raw);
+    raw[16..18].copy_from_slice(&ones_complement_checksum(&pseudo_header).to_be_bytes());
+
+    let segment = tcp::TcpSegment::from_bytes(&raw).unwrap();
+    let source = Ipv6Addr::from(DEFAULT_SRC_ADDRESS).into();
+    let destination = Ipv6Addr::from(DEFAULT_DEST_ADDRESS).into();
+
+    assert!(segment.verify_checksum(&raw, source, destination));
+
+    let mut corrupted = raw.clone();
+    *corrupted.last_mut().unwrap() ^= 0x01;
+    assert!(!segment.verify_checksum(&corrupted, source, destination));
+    assert!(!segment.verify_checksum(&raw, Ipv4Addr::from(DEFAULT_SRC_ADDR).into(), destination));
+}
+
+#[test]
+fn serializes_back_into_the_original_segment() {
+    for packets in [
+        generate_tcp_packets_without_options(),
+        generate_tcp_packets_with_options(),
+    ] {
+        let tcp_segment = tcp::TcpSegment::from_bytes(&packets).unwrap();
+        assert_eq!(tcp_segment.to_bytes(), packets);
+        assert_eq!(u8::from(&tcp_segment.header.flags), packets[13]);
+    }
+}
+
+#[test]
+fn iterates_decoded_options() {
+    let packets = generate_tcp_segment_with_options(&TCP_SYN_OPTIONS);
+    let tcp_segment = tcp::TcpSegment::from_bytes(&packets).unwrap();
+
+    let options: Result<Vec<TcpOption>, ParserError> = tcp_segment.options_iter().collect();
+    assert_eq!(
+        options.unwrap(),
+        vec![
+            TcpOption::Mss(1460),
+            TcpOption::SackPermitted,
+            TcpOption::Timestamp { tsval: 1, tsecr: 0 },
+            TcpOption::NoOp,
+            TcpOption::WindowScale(7),
+        ]
+    );
+    assert_eq!(tcp_segment.data.payload().unwrap(), DEFAULT_DATA);
+
+    let bytes = generate_tcp_packets_without_options();
+    let without_options = tcp::TcpSegment::from_bytes(&bytes);
+    assert_eq!(without_options.unwrap().options_iter().count(), 0);
+}
+
+#[test]
+fn decodes_mss_and_timestamp_options() {
+    // MSS 1400, then timestamps (0x01020304, 0x0A0B0C0D) padded with two NOPs.
+    let packets =
+        generate_tcp_segment_with_options(&[2, 4, 5, 120, 1, 1, 8, 10, 1, 2, 3, 4, 10, 11, 12, 13]);
+    let tcp_segment = tcp::TcpSegment::from_bytes(&packets).unwrap();
+
+    let expected = vec![
+        TcpOption::Mss(1400),
+        TcpOption::NoOp,
+        TcpOption::NoOp,
+        TcpOption::Timestamp {
+            tsval: 0x01020304,
+            tsecr: 0x0A0B0C0D,
+        },
+    ];
+    assert_eq!(tcp_segment.header.options, expected);
+
+    let options: Vec<TcpOption> = tcp_segment.options_iter().map(Result::unwrap).collect();
+    assert_eq!(options, expected);
+    assert_eq!(tcp_segment.to_bytes(), packets);
+}
+
+#[test]
+fn stops_decoding_options_at_the_end_of_option_list() {
+    let packets = generate_tcp_segment_with_options(&[1, 0, 2, 4]);
+    let tcp_segment = tcp::TcpSegment::from_bytes(&packets).unwrap();
+
+    let expected = vec![TcpOption::NoOp, TcpOption::EndOfOptions];
+    assert_eq!(tcp_segment.header.options, expected);
+
+    let options: Vec<TcpOption> = tcp_segment.options_iter().map(Result::unwrap).collect();
+    assert_eq!(options, expected);
+}
+
+#[test]
+fn keeps_segments_with_malformed_options() {
+    // A length below 2, a length running past the options, and a window scale option
+    // with two bytes of data, each after a valid MSS option.
+    for malformed in [[2, 1, 0, 0], [12, 5, 0, 255], [3, 4, 7, 0]] {
+        let options = [&[2, 4, 5, 180][..], &malformed].concat();
+        let segment = generate_tcp_segment_with_options(&options);
+
+        let tcp_segment = tcp::TcpSegment::from_bytes(&segment).unwrap();
+
+        // The options stop before the malformed one, which the iterator reports.
+        assert_eq!(tcp_segment.header.options, [TcpOption::Mss(1460)]);
+        let mut options = tcp_segment.options_iter();
+        assert_eq!(options.next().unwrap().unwrap(), TcpOption::Mss(1460));
+        assert!(options.next().unwrap().is_err());
+
+        // The payload is still reached.
+        assert_eq!(tcp_segment.data.payload(), Some(&DEFAULT_DATA[..]));
+        assert_eq!(
+            tcp_segment.parse_next_layer().unwrap(),
+            LayeredData::TcpData(tcp::TcpSegment::from_bytes(&segment).unwrap())
+        );
+    }
+}
+
+// A segment whose header holds `options`, built without going through `from_bytes`.
+fn segment_with_unchecked_options(options: &[u8]) -> TcpSegment<'static> {
+    TcpSegment {
+        header: TcpSegmentHeader {
+            raw_header: [&[0; 20][..], options].concat(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn options_iter_reports_malformed_options_per_item() {
+    // 5 bytes are declared where only 4 remain.
+    let tcp_segment = segment_with_unchecked_options(&[12, 5, 0, 255]);
+    let options: Vec<_> = tcp_segment.options_iter().collect();
+    assert_eq!(options.len(), 1);
+    assert!(matches!(
+        options[0],
+        Err(ParserError::OptionsOverrun {
+            declared: 5,
+            available: 4,
+            ..
+        })
+    ));
+
+    // A window scale option with two bytes of data, followed by a valid option.
+    let tcp_segment = segment_with_unchecked_options(&[3, 4, 7, 0, 4, 2, 1, 1]);
+    let mut options = tcp_segment.options_iter();
+    assert!(matches!(
+        options.next(),
+        Some(Err(ParserError::InvalidLength(_)))
+    ));
+    assert_eq!(options.next().unwrap().unwrap(), TcpOption::SackPermitted);
+
+    // A length below 2 cannot cover the kind and length bytes themselves.
+    let tcp_segment = segment_with_unchecked_options(&[2, 1, 0, 0]);
+    let options: Vec<_> = tcp_segment.options_iter().collect();
+    assert!(matches!(options[..], [Err(ParserError::InvalidLength(_))]));
+}
+
+#[test]
+fn drops_options_cut_off_by_a_truncated_header() {
+    // MSS 1460, then timestamps cut off after their length byte.
+    let mut segment = generate_tcp_segment_with_options(&[2, 4, 5, 180, 8, 10, 0, 0, 0, 0, 0, 0]);
+    segment.truncate(26);
+    let frame =
+        generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &generate_ipv4_packet(TCP, &segment));
+
+    let options = ParseOptions {
+        allow_truncated: true,
+        ..Default::default()
+    };
+    let layered_data = EthernetFrame::from_bytes(&frame, false)
+        .unwrap()
+        .parse_next_layer_with(&options, 0)
+        .unwrap();
+
+    let Some(tcp_segment) = layered_data.layers().find_map(|layer| match layer {
+        LayeredData::TcpData(segment) => Some(segment),
+        _ => None,
+    }) else {
+        panic!("expected a TCP segment, got {:?}", layered_data);
+    };
+    assert!(tcp_segment.truncated);
+    assert_eq!(tcp_segment.header.options, [TcpOption::Mss(1460)]);
+}
+
+#[test]
+fn default_header_overrides_only_the_given_fields() {
+    let segment = TcpSegment {
+        header: TcpSegmentHeader {
+            source_port: 53145,
+            destination_port: 443,
+            flags: Flags {
+                syn: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let bytes = segment.to_bytes();
+    let reparsed = TcpSegment::from_bytes(&bytes).unwrap();
+
+    assert_eq!(reparsed.header.data_offset, 5);
+    assert_eq!(reparsed.header.window_size, u16::MAX);
+    assert_eq!(reparsed.header.flags, segment.header.flags);
+    assert_eq!(
+        (
+            reparsed.header.source_port,
+            reparsed.header.destination_port
+        ),
+        (53145, 443)
+    );
+    assert_eq!(reparsed.payload_len(), 0);
+}
+
+#[test]
+fn try_from_delegates_to_from_bytes() {
+    let segment = generate_tcp_packets_with_options();
+
+    let converted: TcpSegment = segment.as_slice().try_into().unwrap();
+
+    assert_eq!(converted, TcpSegment::from_bytes(&segment).unwrap());
+    assert!(TcpSegment::try_from(&MOCK_MALFORMED_PACKET[..]).is_err());
+}
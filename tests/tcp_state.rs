@@ -0,0 +1,166 @@
+mod mock_data;
+
+use mock_data::generate_tcp_packets_without_options;
+use net_sift::parsers::{
+    tcp::TcpSegment,
+    tcp_state::{Direction, TcpState, TcpStateMachine, TcpTransition},
+};
+
+const ACK: u8 = 0x10;
+const RST: u8 = 0x04;
+const SYN: u8 = 0x02;
+const FIN: u8 = 0x01;
+const CLIENT_ISN: u32 = 1000;
+const SERVER_ISN: u32 = 5000;
+
+fn segment(
+    sequence_number: u32,
+    acknowledgment: u32,
+    flags: u8,
+    payload: &[u8],
+) -> TcpSegment<'static> {
+    let mut header = generate_tcp_packets_without_options()[..20].to_vec();
+    header[4..8].copy_from_slice(&sequence_number.to_be_bytes());
+    header[8..12].copy_from_slice(&acknowledgment.to_be_bytes());
+    header[13] = flags;
+
+    TcpSegment::from_bytes(&[&header[..], payload].concat())
+        .unwrap()
+        .into_owned()
+}
+
+#[test]
+fn tracks_handshake_and_teardown() {
+    use Direction::{Initiator, Responder};
+
+    let mut machine = TcpStateMachine::new();
+    assert_eq!(machine.state(), TcpState::Closed);
+
+    let steps = [
+        (
+            segment(CLIENT_ISN, 0, SYN, &[]),
+            Initiator,
+            TcpState::SynSent,
+        ),
+        (
+            segment(SERVER_ISN, CLIENT_ISN + 1, SYN | ACK, &[]),
+            Responder,
+            TcpState::SynReceived,
+        ),
+        (
+            segment(CLIENT_ISN + 1, SERVER_ISN + 1, ACK, &[]),
+            Initiator,
+            TcpState::Established,
+        ),
+        (
+            segment(CLIENT_ISN + 1, SERVER_ISN + 1, ACK, b"hello"),
+            Initiator,
+            TcpState::Established,
+        ),
+        (
+            segment(CLIENT_ISN + 6, SERVER_ISN + 1, FIN | ACK, &[]),
+            Initiator,
+            TcpState::FinWait1,
+        ),
+        (
+            segment(SERVER_ISN + 1, CLIENT_ISN + 7, ACK, &[]),
+            Responder,
+            TcpState::FinWait2,
+        ),
+        (
+            segment(SERVER_ISN + 1, CLIENT_ISN + 7, FIN | ACK, b"bye"),
+            Responder,
+            TcpState::LastAck,
+        ),
+        (
+            segment(CLIENT_ISN + 7, SERVER_ISN + 5, ACK, &[]),
+            Initiator,
+            TcpState::TimeWait,
+        ),
+    ];
+
+    let mut previous = TcpState::Closed;
+    for (segment, direction, expected) in steps {
+        let transition = machine.push(&segment, direction);
+
+        assert_eq!(machine.state(), expected);
+        let expected_transition = (previous != expected).then_some(TcpTransition {
+            from: previous,
+            to: expected,
+        });
+        assert_eq!(transition, expected_transition);
+        previous = expected;
+    }
+}
+
+#[test]
+fn reports_half_open_connections() {
+    let mut machine = TcpStateMachine::new();
+    machine.push(&segment(CLIENT_ISN, 0, SYN, &[]), Direction::Initiator);
+    assert!(machine.is_half_open());
+
+    // A SYN-ACK acknowledging the wrong sequence number is ignored.
+    let transition = machine.push(
+        &segment(SERVER_ISN, CLIENT_ISN + 2, SYN | ACK, &[]),
+        Direction::Responder,
+    );
+    assert_eq!(transition, None);
+    assert_eq!(machine.state(), TcpState::SynSent);
+
+    machine.push(
+        &segment(SERVER_ISN, CLIENT_ISN + 1, SYN | ACK, &[]),
+        Direction::Responder,
+    );
+    assert!(machine.is_half_open());
+}
+
+#[test]
+fn tracks_simultaneous_close_and_reset() {
+    use Direction::{Initiator, Responder};
+
+    let mut machine = TcpStateMachine::new();
+    machine.push(&segment(CLIENT_ISN, 0, SYN, &[]), Initiator);
+    machine.push(
+        &segment(SERVER_ISN, CLIENT_ISN + 1, SYN | ACK, &[]),
+        Responder,
+    );
+    machine.push(
+        &segment(CLIENT_ISN + 1, SERVER_ISN + 1, ACK, &[]),
+        Initiator,
+    );
+
+    machine.push(
+        &segment(CLIENT_ISN + 1, SERVER_ISN + 1, FIN | ACK, &[]),
+        Initiator,
+    );
+    machine.push(
+        &segment(SERVER_ISN + 1, CLIENT_ISN + 1, FIN | ACK, &[]),
+        Responder,
+    );
+    assert_eq!(machine.state(), TcpState::Closing);
+
+    let transition = machine.push(
+        &segment(SERVER_ISN + 2, CLIENT_ISN + 1, RST, &[]),
+        Responder,
+    );
+    assert_eq!(
+        transition,
+        Some(TcpTransition {
+            from: TcpState::Closing,
+            to: TcpState::Closed,
+        })
+    );
+}
+
+#[test]
+fn ignores_connections_picked_up_mid_stream() {
+    let mut machine = TcpStateMachine::new();
+
+    let transition = machine.push(
+        &segment(CLIENT_ISN, SERVER_ISN, ACK, b"data"),
+        Direction::Initiator,
+    );
+
+    assert_eq!(transition, None);
+    assert_eq!(machine.state(), TcpState::Closed);
+}
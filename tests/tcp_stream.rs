@@ -0,0 +1,94 @@
+mod mock_data;
+
+use mock_data::generate_tcp_packets_without_options;
+use net_sift::parsers::{tcp::TcpSegment, tcp_stream::TcpStreamReassembler};
+
+const ACK: u8 = 0x10;
+const SYN: u8 = 0x02;
+const FIN: u8 = 0x01;
+const ISN: u32 = 1000;
+
+fn segment(sequence_number: u32, flags: u8, payload: &[u8]) -> TcpSegment<'static> {
+    let mut header = generate_tcp_packets_without_options()[..20].to_vec();
+    header[4..8].copy_from_slice(&sequence_number.to_be_bytes());
+    header[13] = flags;
+
+    TcpSegment::from_bytes(&[&header[..], payload].concat())
+        .unwrap()
+        .into_owned()
+}
+
+#[test]
+fn reassembles_out_of_order_segments() {
+    let mut reassembler = TcpStreamReassembler::new();
+
+    assert!(reassembler.push(&segment(ISN, SYN, &[])).is_empty());
+    assert!(reassembler
+        .push(&segment(ISN + 8, ACK, b"world"))
+        .is_empty());
+    assert!(reassembler
+        .push(&segment(ISN + 4, ACK, b"lo, wo"))
+        .is_empty());
+    assert_eq!(reassembler.pending_bytes(), 9);
+
+    let released = reassembler.push(&segment(ISN + 1, ACK, b"Hello, "));
+
+    // The segments overlap by "lo, " and "wo"; each byte is released once.
+    assert_eq!(released, b"Hello, world");
+    assert_eq!(reassembler.pending_bytes(), 0);
+}
+
+#[test]
+fn keeps_first_seen_bytes_of_overlapping_retransmissions() {
+    let mut reassembler = TcpStreamReassembler::new();
+    reassembler.push(&segment(ISN, SYN, &[]));
+
+    reassembler.push(&segment(ISN + 4, ACK, b"EFGH"));
+    reassembler.push(&segment(ISN + 2, ACK, b"xxxxxxJ"));
+    let released = reassembler.push(&segment(ISN + 1, ACK, b"A"));
+
+    assert_eq!(released, b"AxxEFGHJ");
+    // A full retransmission releases nothing new.
+    assert!(reassembler.push(&segment(ISN + 1, ACK, b"ABCD")).is_empty());
+}
+
+#[test]
+fn closes_stream_at_fin() {
+    let mut reassembler = TcpStreamReassembler::new();
+    reassembler.push(&segment(ISN, SYN, &[]));
+
+    assert!(reassembler
+        .push(&segment(ISN + 4, FIN | ACK, b"def"))
+        .is_empty());
+    assert!(!reassembler.is_closed());
+
+    assert_eq!(reassembler.push(&segment(ISN + 1, ACK, b"abc")), b"abcdef");
+    assert!(reassembler.is_closed());
+    assert!(reassembler.push(&segment(ISN + 8, ACK, b"late")).is_empty());
+}
+
+#[test]
+fn picks_up_stream_without_syn() {
+    let mut reassembler = TcpStreamReassembler::new();
+
+    assert_eq!(reassembler.push(&segment(5000, ACK, b"mid")), b"mid");
+    assert_eq!(
+        reassembler.push(&segment(5003, ACK, b"-stream")),
+        b"-stream"
+    );
+    // Data from before the pickup point is ignored.
+    assert!(reassembler.push(&segment(4990, ACK, b"early")).is_empty());
+}
+
+#[test]
+fn follows_sequence_numbers_across_wraparound() {
+    let mut reassembler = TcpStreamReassembler::new();
+    let isn = u32::MAX - 2;
+    reassembler.push(&segment(isn, SYN, &[]));
+
+    assert!(reassembler.push(&segment(1, ACK, b"3456")).is_empty());
+    assert_eq!(
+        reassembler.push(&segment(isn.wrapping_add(1), ACK, b"012")),
+        b"0123456"
+    );
+}
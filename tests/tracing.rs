@@ -0,0 +1,162 @@
+#![cfg(feature = "tracing")]
+
+mod mock_data;
+
+use mock_data::{
+    generate_ethernet_mock_packets, generate_ipv4_tcp_with_valid_checksum,
+    generate_padded_ethernet_frame, DEFAULT_ETHER_TYPE, DEFAULT_Q_TAG, IPV4_ETHER_TYPE,
+    WAKE_ON_LAN_ETHER_TYPE,
+};
+use net_sift::parsers::{
+    definitions::{DeepParser, ParseOptions},
+    ethernet_frame::EthernetFrame,
+};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Metadata, Subscriber,
+};
+
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+// Records the fields of every span and event as `name key=value ...` lines.
+#[derive(Default)]
+struct Recorder {
+    lines: Arc<Mutex<Vec<String>>>,
+    next_id: AtomicU64,
+}
+
+struct LineVisitor(String);
+
+impl Visit for LineVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut visitor = LineVisitor(span.metadata().name().to_string());
+        span.record(&mut visitor);
+        self.lines.lock().unwrap().push(visitor.0);
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = LineVisitor("event".to_string());
+        event.record(&mut visitor);
+        self.lines.lock().unwrap().push(visitor.0);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+fn record(parse: impl FnOnce()) -> Vec<String> {
+    let recorder = Recorder::default();
+    let lines = Arc::clone(&recorder.lines);
+
+    tracing::subscriber::with_default(recorder, parse);
+
+    let lines = lines.lock().unwrap().clone();
+    lines
+}
+
+#[test]
+fn opens_a_span_for_each_layer() {
+    let frame =
+        generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &generate_ipv4_tcp_with_valid_checksum());
+
+    let lines = record(|| {
+        EthernetFrame::from_bytes(&frame, false)
+            .unwrap()
+            .parse_next_layer()
+            .unwrap();
+    });
+
+    let spans: Vec<_> = lines
+        .iter()
+        .filter(|line| line.starts_with("parse_layer"))
+        .collect();
+    assert_eq!(
+        spans,
+        [
+            "parse_layer layer=Ethernet depth=1",
+            "parse_layer layer=Ipv4 depth=2",
+            "parse_layer layer=Tcp depth=3",
+        ]
+    );
+}
+
+#[test]
+fn reports_vlan_tags_and_unparsed_ether_types() {
+    let frame = generate_ethernet_mock_packets(Some(DEFAULT_Q_TAG), WAKE_ON_LAN_ETHER_TYPE);
+
+    let lines = record(|| {
+        EthernetFrame::from_bytes(&frame, true)
+            .unwrap()
+            .parse_next_layer()
+            .unwrap();
+    });
+
+    assert!(lines
+        .iter()
+        .any(|line| line.contains("802.1Q tag found") && line.contains("vlan_id=534")));
+    assert!(lines
+        .iter()
+        .any(|line| line.contains("no parser for EtherType")));
+}
+
+#[test]
+fn reports_checksum_mismatches_and_failed_layers() {
+    let mut packet = generate_ipv4_tcp_with_valid_checksum();
+    let last = packet.len() - 1;
+    packet[last] ^= 0xFF;
+    let frame = generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &packet);
+    let options = ParseOptions {
+        verify_checksums: true,
+        best_effort: true,
+        ..Default::default()
+    };
+
+    let lines = record(|| {
+        EthernetFrame::from_bytes(&frame, false)
+            .unwrap()
+            .parse_next_layer_with(&options, 0)
+            .unwrap();
+    });
+
+    assert!(lines.iter().any(|line| line.contains("checksum mismatch")));
+    assert!(lines
+        .iter()
+        .any(|line| line.contains("payload left raw") && line.contains("Checksum")));
+}
+
+#[test]
+fn emits_nothing_for_a_frame_parsed_without_surprises() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+
+    let lines = record(|| {
+        EthernetFrame::from_bytes(&frame, true)
+            .unwrap()
+            .parse_next_layer()
+            .unwrap();
+    });
+
+    assert!(lines.iter().all(|line| line.starts_with("parse_layer")));
+}
@@ -1,8 +1,17 @@
 mod mock_data;
 
-use mock_data::UDP_PACKETS;
+use mock_data::{
+    generate_ipv6_mock_packet, ones_complement_checksum, DEFAULT_DEST_ADDR, DEFAULT_DEST_ADDRESS,
+    DEFAULT_SRC_ADDR, DEFAULT_SRC_ADDRESS, MIN_IPV6_LENGTH, UDP, UDP_PACKETS,
+};
 use net_sift::parsers::{
-    definitions::DeepParser, definitions::LayeredData, errors::ParserError, udp::UdpDatagram,
+    definitions::DeepParser, definitions::LayeredData, definitions::ParseOptions,
+    errors::ParserError, ipv6::Ipv6Packet, udp::UdpDatagram,
+};
+
+use std::{
+    collections::HashSet,
+    net::{Ipv4Addr, Ipv6Addr},
 };
 
 #[test]
@@ -14,15 +23,59 @@ fn can_create_udp() {
     assert_eq!(udp.header.checksum, 2053);
     assert_eq!(
         udp.data,
-        Box::new(LayeredData::Payload([7, 90, 100, 100, 255, 9].to_vec()))
+        Box::new(LayeredData::Payload(
+            [7, 90, 100, 100, 255, 9].to_vec().into()
+        ))
+    );
+}
+
+#[test]
+fn keeps_raw_bytes_up_to_length() {
+    let padded = [&UDP_PACKETS[..], &[0; 4]].concat();
+
+    let udp = UdpDatagram::from_bytes_keep_raw(&padded).unwrap();
+
+    assert_eq!(udp.raw.as_deref(), Some(&UDP_PACKETS[..]));
+    assert_eq!(UdpDatagram::from_bytes(&padded).unwrap().raw, None);
+}
+
+#[test]
+fn leaves_payload_raw_if_port_protocol_fails_to_decode() {
+    // Sent to the DNS port, but too short to hold a DNS header.
+    let mut datagram = UDP_PACKETS;
+    datagram[2..4].copy_from_slice(&53u16.to_be_bytes());
+
+    let layered_data = UdpDatagram::from_bytes(&datagram)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert_eq!(
+        layered_data.layers().last(),
+        Some(&LayeredData::Payload(UDP_PACKETS[8..].to_vec().into()))
+    );
+}
+
+#[test]
+fn leaves_payload_raw_on_unknown_ports() {
+    let layered_data = UdpDatagram::from_bytes(&UDP_PACKETS)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert_eq!(layered_data.layers().count(), 2);
+    assert_eq!(
+        layered_data.layers().last(),
+        Some(&LayeredData::Payload(UDP_PACKETS[8..].to_vec().into()))
     );
 }
 
 #[test]
 fn fails_if_packet_is_malformed() {
     let result = UdpDatagram::from_bytes(&[9, 12, 34, 5]);
-    let s = String::from("UDP datagram");
-    assert!(matches!(result, Err(ParserError::InvalidLength(s))))
+    assert!(
+        matches!(result, Err(ParserError::HeaderTooShort { layer, .. }) if layer == "UDP datagram")
+    )
 }
 
 #[test]
@@ -35,3 +88,139 @@ fn can_parse_layered_data() {
         _ => panic!("Invalid layered data"),
     };
 }
+
+#[test]
+fn excludes_trailing_bytes_beyond_length() {
+    let padded = [&UDP_PACKETS[..], &[0; 6]].concat();
+
+    let udp = UdpDatagram::from_bytes(&padded).unwrap();
+
+    assert_eq!(udp, UdpDatagram::from_bytes(&UDP_PACKETS).unwrap());
+    assert_eq!(
+        udp.data,
+        Box::new(LayeredData::Payload(
+            [7, 90, 100, 100, 255, 9].to_vec().into()
+        ))
+    );
+}
+
+#[test]
+fn can_parse_datagram_without_payload() {
+    let udp = UdpDatagram::from_bytes(&[12, 255, 100, 15, 0, 8, 8, 5]).unwrap();
+
+    assert_eq!(udp.header.length, 8);
+    assert_eq!(udp.data, Box::new(LayeredData::Payload(vec![].into())));
+}
+
+#[test]
+fn fails_if_length_exceeds_datagram() {
+    let result = UdpDatagram::from_bytes(&UDP_PACKETS[..13]);
+
+    assert!(matches!(
+        result,
+        Err(ParserError::InconsistentLength { layer, declared: 14, available: 13 })
+            if layer == "UDP datagram"
+    ));
+}
+
+#[test]
+fn fails_if_length_is_smaller_than_header() {
+    let result = UdpDatagram::from_bytes(&[12, 255, 100, 15, 0, 4, 8, 5]);
+
+    assert!(matches!(
+        result,
+        Err(ParserError::InconsistentLength { declared: 4, .. })
+    ));
+}
+
+#[test]
+fn equal_headers_hash_alike() {
+    let mut headers = HashSet::new();
+    headers.insert(UdpDatagram::from_bytes(&UDP_PACKETS).unwrap().header);
+    headers.insert(UdpDatagram::from_bytes(&UDP_PACKETS).unwrap().header);
+
+    assert_eq!(headers.len(), 1);
+}
+
+#[test]
+fn serializes_back_into_the_original_datagram() {
+    let udp = UdpDatagram::from_bytes(&UDP_PACKETS).unwrap();
+    assert_eq!(udp.to_bytes(), UDP_PACKETS);
+}
+
+#[test]
+fn finalize_checksums_uses_the_ipv6_pseudo_header() {
+    let mut packet = generate_ipv6_mock_packet();
+    packet.truncate(MIN_IPV6_LENGTH);
+    packet[4..6].copy_from_slice(&(UDP_PACKETS.len() as u16).to_be_bytes());
+    packet[6] = UDP;
+    packet.extend_from_slice(&UDP_PACKETS);
+
+    let mut layered_data = Ipv6Packet::from_bytes(&packet)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+    layered_data.finalize_checksums();
+
+    let options = ParseOptions {
+        verify_checksums: true,
+        ..ParseOptions::default()
+    };
+    let bytes = layered_data.to_bytes();
+    assert_ne!(bytes, packet);
+    assert!(Ipv6Packet::from_bytes(&bytes)
+        .unwrap()
+        .parse_next_layer_with(&options, 0)
+        .is_ok());
+}
+
+#[test]
+fn verify_checksum_accepts_a_zero_checksum_only_over_ipv4() {
+    let mut raw = UDP_PACKETS;
+    raw[6..8].copy_from_slice(&[0, 0]);
+    let udp = UdpDatagram::from_bytes(&raw).unwrap();
+
+    assert!(udp.verify_checksum(
+        &raw,
+        Ipv4Addr::from(DEFAULT_SRC_ADDR).into(),
+        Ipv4Addr::from(DEFAULT_DEST_ADDR).into()
+    ));
+    assert!(!udp.verify_checksum(
+        &raw,
+        Ipv6Addr::from(DEFAULT_SRC_ADDRESS).into(),
+        Ipv6Addr::from(DEFAULT_DEST_ADDRESS).into()
+    ));
+}
+
+#[test]
+fn verify_checksum_uses_the_ipv6_pseudo_header() {
+    // The payload has an odd length, so the last byte is padded before summing.
+    let mut raw = [&[0, 53, 0, 53, 0, 11, 0, 0][..], b"odd"].concat();
+    let mut pseudo_header = [&DEFAULT_SRC_ADDRESS[..], &DEFAULT_DEST_ADDRESS].concat();
+    pseudo_header.extend_from_slice(&(raw.len() as u32).to_be_bytes());
+    pseudo_header.extend_from_slice(&[0, 0, 0, UDP]);
+    pseudo_header.extend_from_slice(&raw);
+    raw[6..8].copy_from_slice(&ones_complement_checksum(&pseudo_header).to_be_bytes());
+
+    let udp = UdpDatagram::from_bytes(&raw).unwrap();
+    let source = Ipv6Addr::from(DEFAULT_SRC_ADDRESS).into();
+    let destination = Ipv6Addr::from(DEFAULT_DEST_ADDRESS).into();
+    assert!(udp.verify_checksum(&raw, source, destination));
+
+    let mut corrupted = raw.clone();
+    corrupted[10] ^= 0x01;
+    assert!(!udp.verify_checksum(&corrupted, source, destination));
+    assert!(!udp.verify_checksum(
+        &raw,
+        Ipv4Addr::from(DEFAULT_SRC_ADDR).into(),
+        Ipv4Addr::from(DEFAULT_DEST_ADDR).into()
+    ));
+}
+
+#[test]
+fn try_from_delegates_to_from_bytes() {
+    let converted: UdpDatagram = UDP_PACKETS.as_slice().try_into().unwrap();
+
+    assert_eq!(converted, UdpDatagram::from_bytes(&UDP_PACKETS).unwrap());
+    assert!(UdpDatagram::try_from(&UDP_PACKETS[..4]).is_err());
+}
@@ -0,0 +1,147 @@
+mod mock_data;
+
+use mock_data::{
+    generate_ethernet_mock_packets, generate_ipv4_packet, generate_lldp_mock_packet,
+    generate_tcp_packets_with_options, generate_tcp_segment, DEFAULT_DATA, DEFAULT_ETHER_TYPE, TCP,
+};
+use net_sift::parsers::{
+    definitions::{DeepParser, LayeredData},
+    errors::{ErrorSource, ParserError},
+    ethernet_frame::EthernetFrame,
+    lldp::LldpPacket,
+    tcp::TcpSegment,
+    utils::{hexdump, parse_ip, ByteReader},
+};
+
+#[test]
+fn can_hexdump_known_buffer() {
+    let bytes: Vec<u8> = (b'A'..=b'T').chain([0x00, 0x7F, 0x0A]).collect();
+
+    let expected = concat!(
+        "00000000  41 42 43 44 45 46 47 48  49 4a 4b 4c 4d 4e 4f 50  |ABCDEFGHIJKLMNOP|\n",
+        "00000010  51 52 53 54 00 7f 0a                              |QRST...|\n",
+    );
+
+    assert_eq!(hexdump(&bytes), expected);
+}
+
+#[test]
+fn hexdump_of_empty_buffer_is_empty() {
+    assert_eq!(hexdump(&[]), "");
+}
+
+#[test]
+fn can_hexdump_innermost_payload() {
+    let segment = generate_tcp_packets_with_options();
+    let layered_data = TcpSegment::from_bytes(&segment)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert_eq!(
+        layered_data.hexdump_innermost(),
+        Some(hexdump(&DEFAULT_DATA))
+    );
+}
+
+#[test]
+fn fully_parsed_stack_has_no_innermost_payload() {
+    let layered_data = LldpPacket::from_bytes(&generate_lldp_mock_packet())
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert!(matches!(layered_data, LayeredData::LldpData(_)));
+    assert_eq!(layered_data.hexdump_innermost(), None);
+}
+
+#[test]
+fn can_read_24_bit_field_ending_at_buffer_boundary() {
+    let mut cursor = ByteReader::new(&[0xAB, 0x12, 0x34, 0x56]);
+
+    assert_eq!(cursor.read_u8("Flags").unwrap(), 0xAB);
+    assert_eq!(cursor.read_u24_be("VNI").unwrap(), 0x123456);
+    assert_eq!(cursor.remaining(), 0);
+}
+
+#[test]
+fn reading_past_buffer_boundary_is_an_extraction_error() {
+    let mut cursor = ByteReader::new(&[0xAB, 0x12, 0x34]);
+    cursor.read_u8("Flags").unwrap();
+
+    let result = cursor.read_u24_be("VNI");
+    match result {
+        Err(ParserError::ExtractionError { string, source }) => {
+            assert_eq!(string, "VNI");
+            #[cfg(feature = "std")]
+            assert!(matches!(
+                source,
+                ErrorSource::Io(e) if e.kind() == std::io::ErrorKind::UnexpectedEof
+            ));
+            #[cfg(not(feature = "std"))]
+            assert!(matches!(source, ErrorSource::UnexpectedEof));
+        }
+        other => panic!("Expected an extraction error, got {:?}", other),
+    }
+    // A failed read does not consume the remaining bytes.
+    assert_eq!(cursor.position(), 1);
+
+    let mut cursor = ByteReader::new(&[0; 15]);
+    assert!(matches!(
+        cursor.read_u128_be("Address"),
+        Err(ParserError::ExtractionError { string, .. }) if string == "Address"
+    ));
+}
+
+#[test]
+fn parse_ip_dispatches_ipv4_by_version() {
+    let packet = generate_ipv4_packet(TCP, &generate_tcp_segment(80, b"GET /"));
+
+    let layered_data = parse_ip(&packet).unwrap();
+
+    assert!(matches!(layered_data, LayeredData::Ipv4Data(_)));
+    match layered_data.inner() {
+        Some(LayeredData::TcpData(segment)) => assert_eq!(segment.header.destination_port, 80),
+        other => panic!("Expected a TCP segment, got {:?}", other),
+    }
+}
+
+#[test]
+fn parse_ip_dispatches_ipv6_by_version() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+    // Strip the Ethernet header and FCS, leaving the IPv6 packet.
+    let packet = &frame[14..frame.len() - 4];
+
+    let layered_data = parse_ip(packet).unwrap();
+
+    let expected = EthernetFrame::from_bytes(&frame, true)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+    assert!(matches!(layered_data, LayeredData::Ipv6Data(_)));
+    assert_eq!(Some(&layered_data), expected.inner());
+}
+
+#[test]
+fn parse_ip_follows_ipv6_tunnelled_in_ipv4() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+    let inner = &frame[14..frame.len() - 4];
+    let packet = generate_ipv4_packet(41, inner);
+
+    let layered_data = parse_ip(&packet).unwrap();
+
+    assert!(matches!(layered_data, LayeredData::Ipv4Data(_)));
+    assert_eq!(layered_data.inner(), Some(&parse_ip(inner).unwrap()));
+}
+
+#[test]
+fn parse_ip_rejects_other_versions() {
+    assert!(matches!(
+        parse_ip(&[0x50; 40]),
+        Err(ParserError::UnknownIpVersion(5))
+    ));
+    assert!(matches!(
+        parse_ip(&[]),
+        Err(ParserError::HeaderTooShort { got: 0, .. })
+    ));
+}
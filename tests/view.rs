@@ -0,0 +1,102 @@
+mod mock_data;
+
+use mock_data::{
+    generate_ethernet_mock_packets, generate_ipv4_packet, generate_padded_ethernet_frame,
+    DEFAULT_ETHER_TYPE, DEFAULT_Q_TAG, IPV4_ETHER_TYPE, MIN_IPV6_LENGTH, UDP_PACKETS,
+};
+use net_sift::parsers::{
+    definitions::{DeepParser, LayeredData},
+    errors::ParserError,
+    ethernet_frame::EthernetFrame,
+    tcp,
+    view::scan_ethernet,
+};
+
+#[test]
+fn locates_headers_of_tcp_over_ipv6() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+
+    let view = scan_ethernet(&frame).unwrap();
+
+    assert_eq!(view.ether_type(), 0x86DD);
+    assert_eq!(view.vlan_id(), None);
+    assert_eq!(view.ip_protocol(), Some(6));
+    assert!(view.is_tcp() && !view.is_udp());
+    assert_eq!(view.network_offset(), 14);
+    assert_eq!(view.transport_offset(), Some(14 + MIN_IPV6_LENGTH));
+    assert_eq!(view.payload_offset(), Some(14 + MIN_IPV6_LENGTH + 24));
+    assert_eq!(view.source_port(), Some(53145));
+    assert_eq!(view.destination_port(), Some(80));
+    assert_eq!(view.tcp_flags(), Some(tcp::Flags::new(255)));
+}
+
+#[test]
+fn matches_the_full_parse() {
+    let frame = generate_ethernet_mock_packets(Some(DEFAULT_Q_TAG), DEFAULT_ETHER_TYPE);
+
+    let view = scan_ethernet(&frame).unwrap();
+    let layered_data = EthernetFrame::from_bytes(&frame, false)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert_eq!(view.vlan_id(), Some(0x0216));
+    let spans = layered_data.spans();
+    assert_eq!(view.network_offset(), spans[1].start);
+    assert_eq!(view.transport_offset(), Some(spans[2].start));
+    assert_eq!(view.payload_offset(), Some(spans[2].end));
+    assert_eq!(view.source_port(), layered_data.source_port());
+    assert_eq!(view.destination_port(), layered_data.destination_port());
+    // The view's payload runs on into the Ethernet padding.
+    assert!(matches!(
+        layered_data.layers().last(),
+        Some(LayeredData::Payload(payload)) if view.payload().unwrap().starts_with(payload)
+    ));
+}
+
+#[test]
+fn locates_headers_of_udp_over_ipv4() {
+    let packet = generate_ipv4_packet(17, &UDP_PACKETS);
+    let frame = generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &packet);
+
+    let view = scan_ethernet(&frame).unwrap();
+
+    assert!(view.is_udp());
+    assert_eq!(view.transport_offset(), Some(34));
+    assert_eq!(view.payload_offset(), Some(42));
+    assert_eq!(view.source_port(), Some(3327));
+    assert_eq!(view.tcp_flags(), None);
+}
+
+#[test]
+fn stops_at_non_initial_fragments_and_other_protocols() {
+    let mut packet = generate_ipv4_packet(17, &UDP_PACKETS);
+    packet[6..8].copy_from_slice(&[0x00, 0x10]);
+    let frame = generate_padded_ethernet_frame(IPV4_ETHER_TYPE, &packet);
+    let view = scan_ethernet(&frame).unwrap();
+    assert_eq!(view.ip_protocol(), Some(17));
+    assert_eq!(view.transport_offset(), None);
+
+    let frame = generate_padded_ethernet_frame([0x08, 0x06], &[0; 28]);
+    let view = scan_ethernet(&frame).unwrap();
+    assert_eq!(view.ether_type(), 0x0806);
+    assert_eq!((view.ip_protocol(), view.source_port()), (None, None));
+}
+
+#[test]
+fn fails_if_a_header_is_cut_short() {
+    let frame = generate_ethernet_mock_packets(None, DEFAULT_ETHER_TYPE);
+
+    assert!(matches!(
+        scan_ethernet(&frame[..13]),
+        Err(ParserError::HeaderTooShort {
+            got: 13,
+            need: 14,
+            ..
+        })
+    ));
+    assert!(matches!(
+        scan_ethernet(&frame[..14 + MIN_IPV6_LENGTH + 10]),
+        Err(ParserError::HeaderTooShort { layer, got: 10, need: 20 }) if layer == "TCP segment"
+    ));
+}
@@ -0,0 +1,121 @@
+mod mock_data;
+
+use mock_data::{
+    generate_ipv6_mock_packet, generate_vxlan_mock_datagram, DEFAULT_DEST_ADDRESS,
+    DEFAULT_DEST_MAC, DEFAULT_ETHER_TYPE, DEFAULT_SRC_MAC, DEFAULT_VXLAN_HEADER,
+};
+use net_sift::parsers::{
+    definitions::{DeepParser, LayerKind, LayeredData},
+    errors::ParserError,
+    ethernet_frame::{EthernetFrame, MacAddress},
+    udp::UdpDatagram,
+    vxlan::VxlanPacket,
+};
+
+use std::net::IpAddr;
+
+fn inner_frame() -> Vec<u8> {
+    [
+        &DEFAULT_DEST_MAC[..],
+        &DEFAULT_SRC_MAC,
+        &DEFAULT_ETHER_TYPE,
+        &generate_ipv6_mock_packet(),
+    ]
+    .concat()
+}
+
+#[test]
+fn can_parse_vxlan_header() {
+    let packet = [&DEFAULT_VXLAN_HEADER[..], &inner_frame()].concat();
+
+    let vxlan = VxlanPacket::from_bytes(&packet).unwrap();
+
+    assert_eq!(vxlan.header.flags, 8);
+    assert!(vxlan.header.has_valid_vni());
+    assert_eq!(vxlan.header.vni, 12345);
+    assert_eq!(
+        vxlan.data,
+        Box::new(LayeredData::Payload(inner_frame().into()))
+    );
+}
+
+#[test]
+fn fails_if_packet_is_too_short() {
+    let result = VxlanPacket::from_bytes(&DEFAULT_VXLAN_HEADER[..4]);
+
+    assert!(
+        matches!(result, Err(ParserError::HeaderTooShort { layer, .. }) if layer == "VXLAN packet")
+    );
+}
+
+#[test]
+fn can_parse_inner_frame_from_udp_datagram() {
+    let datagram = generate_vxlan_mock_datagram(&inner_frame());
+    let udp = UdpDatagram::from_bytes(&datagram).unwrap();
+
+    let inner = inner_frame();
+    let expected_inner = EthernetFrame::from_bytes(&inner, false)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    match udp.parse_next_layer().unwrap() {
+        LayeredData::UdpData(v) => match *v.data {
+            LayeredData::VxlanData(vxlan) => {
+                assert_eq!(vxlan.header.vni, 12345);
+                assert_eq!(*vxlan.data, expected_inner);
+
+                match *vxlan.data {
+                    LayeredData::EthernetFrameData(frame) => {
+                        assert_eq!(frame.header.mac_source, MacAddress::from(DEFAULT_SRC_MAC));
+                        assert!(matches!(*frame.data, LayeredData::Ipv6Data(_)));
+                    }
+                    _ => panic!("Invalid inner frame"),
+                }
+            }
+            _ => panic!("Invalid nested layered data"),
+        },
+        _ => panic!("Invalid layered data"),
+    };
+}
+
+#[test]
+fn can_classify_layers_of_overlay_stack() {
+    let datagram = generate_vxlan_mock_datagram(&inner_frame());
+    let udp = UdpDatagram::from_bytes(&datagram).unwrap();
+    assert_eq!(udp.layer_kind(), LayerKind::Udp);
+
+    let layered_data = udp.parse_next_layer().unwrap();
+
+    assert_eq!(layered_data.layer_kind(), Some(LayerKind::Udp));
+    assert!(layered_data.contains(LayerKind::Vxlan));
+    assert!(layered_data.contains(LayerKind::Ethernet));
+    assert!(layered_data.contains(LayerKind::Tcp));
+    assert!(!layered_data.contains(LayerKind::Icmp));
+}
+
+#[test]
+fn involves_ip_of_inner_packet() {
+    let datagram = generate_vxlan_mock_datagram(&inner_frame());
+    let layered_data = UdpDatagram::from_bytes(&datagram)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert!(layered_data.involves_ip(IpAddr::from(DEFAULT_DEST_ADDRESS)));
+    assert!(!layered_data.involves_ip(IpAddr::from([0u8; 16])));
+}
+
+#[test]
+fn serializes_overlay_stack() {
+    let datagram = generate_vxlan_mock_datagram(&inner_frame());
+
+    let layered_data = UdpDatagram::from_bytes(&datagram)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+    assert!(layered_data.contains(LayerKind::Vxlan));
+
+    // UDP used to report a zero-length payload, as the VXLAN layer serialized to nothing.
+    assert_eq!(layered_data.to_bytes(), datagram);
+}
@@ -0,0 +1,123 @@
+mod mock_data;
+
+use mock_data::{generate_tcp_segment, generate_websocket_binary_frame, WEBSOCKET_MASKED_HELLO};
+use net_sift::parsers::{
+    constants::WEBSOCKET_PORTS,
+    definitions::{DeepParser, LayerKind, LayeredData},
+    errors::ParserError,
+    tcp::TcpSegment,
+    websocket::{self, WebSocketFrame, WebSocketOpcode},
+};
+
+#[test]
+fn can_parse_masked_text_frame() {
+    let frame = WebSocketFrame::from_bytes(&WEBSOCKET_MASKED_HELLO).unwrap();
+
+    assert!(frame.fin);
+    assert_eq!(frame.rsv, 0);
+    assert_eq!(frame.kind(), WebSocketOpcode::Text);
+    assert_eq!(frame.masking_key, Some([0x37, 0xFA, 0x21, 0x3D]));
+    assert_eq!(frame.text(), Some("Hello"));
+    assert_eq!(frame.span, 0..6);
+    assert_eq!(frame.raw_header, WEBSOCKET_MASKED_HELLO[..6]);
+    assert_eq!(frame.to_bytes(), WEBSOCKET_MASKED_HELLO);
+}
+
+#[test]
+fn can_parse_extended_payload_lengths() {
+    for length in [126, 300, 70_000] {
+        let payload: Vec<u8> = (0..length).map(|i| i as u8).collect();
+        let bytes = generate_websocket_binary_frame(&payload);
+
+        let frame = WebSocketFrame::from_bytes(&bytes).unwrap();
+
+        assert_eq!(frame.kind(), WebSocketOpcode::Binary);
+        assert!(!frame.is_masked());
+        assert_eq!(frame.payload, payload);
+        assert_eq!(frame.to_bytes(), bytes);
+    }
+}
+
+#[test]
+fn fails_if_payload_runs_past_the_end() {
+    let bytes = generate_websocket_binary_frame(&[0; 300]);
+
+    let result = WebSocketFrame::from_bytes(&bytes[..200]);
+    assert!(matches!(
+        result,
+        Err(ParserError::InconsistentLength {
+            declared: 300,
+            available: 196,
+            ..
+        })
+    ));
+
+    let result = WebSocketFrame::from_bytes(&bytes[..3]);
+    assert!(matches!(result, Err(ParserError::ExtractionError { .. })));
+}
+
+#[test]
+fn deep_parses_frames_carried_by_tcp() {
+    let payload = [
+        &WEBSOCKET_MASKED_HELLO[..],
+        &generate_websocket_binary_frame(&[1, 2, 3]),
+    ]
+    .concat();
+    let bytes = generate_tcp_segment(8080, &payload);
+    let segment = TcpSegment::from_bytes(&bytes).unwrap();
+
+    let layered_data = segment.parse_next_layer().unwrap();
+    assert!(layered_data.contains(LayerKind::WebSocket));
+
+    let frames = match layered_data.inner() {
+        Some(LayeredData::WebSocketData(frames)) => frames,
+        other => panic!("Expected WebSocket frames, got {:?}", other),
+    };
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].text(), Some("Hello"));
+    assert_eq!(frames[1].payload, [1, 2, 3]);
+    assert_eq!(layered_data.inner().unwrap().to_bytes(), payload);
+}
+
+#[test]
+fn does_not_mistake_other_payloads_for_frames() {
+    assert!(!websocket::is_websocket(b"GET /chat HTTP/1.1\r\n"));
+    assert!(!websocket::is_websocket(b""));
+    // A fragmented ping, and a frame with RSV1 set.
+    assert!(!websocket::is_websocket(&[0x09, 0x00]));
+    assert!(!websocket::is_websocket(&[0xC1, 0x00]));
+    // A frame followed by the start of another.
+    assert!(!websocket::is_websocket(&[0x82, 0x01, 0xFF, 0x82]));
+
+    let segment = generate_tcp_segment(80, b"HTTP/1.1 101 Switching Protocols\r\n");
+    let layered_data = TcpSegment::from_bytes(&segment)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+    assert!(!layered_data.contains(LayerKind::WebSocket));
+}
+
+#[test]
+fn leaves_frames_on_other_ports_raw() {
+    // A frame sent on port 22 is far more likely to be SSH traffic that happens to match.
+    let segment = generate_tcp_segment(22, b"\x81\x05hello");
+    let layered_data = TcpSegment::from_bytes(&segment)
+        .unwrap()
+        .parse_next_layer()
+        .unwrap();
+
+    assert!(!layered_data.contains(LayerKind::WebSocket));
+    assert_eq!(
+        layered_data.inner().unwrap().payload(),
+        Some(&b"\x81\x05hello"[..])
+    );
+
+    for port in WEBSOCKET_PORTS {
+        let segment = generate_tcp_segment(port, b"\x81\x05hello");
+        let layered_data = TcpSegment::from_bytes(&segment)
+            .unwrap()
+            .parse_next_layer()
+            .unwrap();
+        assert!(layered_data.contains(LayerKind::WebSocket));
+    }
+}